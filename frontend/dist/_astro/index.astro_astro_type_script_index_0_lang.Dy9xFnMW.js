@@ -75,4 +75,4 @@ async function d(){const o=document.getElementById("xkcd-container"),r=document.
 								Try Again
 							</button>
 						</div>
-					`;const n=document.getElementById("try-again-btn");n&&n.addEventListener("click",d),r.textContent="Error"}}document.addEventListener("DOMContentLoaded",()=>{d();const o=new URLSearchParams(window.location.search);if(o.get("subscribed")==="true"){const t=document.getElementById("subscription-success");t&&(t.classList.remove("hidden"),t.scrollIntoView({behavior:"smooth",block:"center"}),window.history.replaceState({},"","/"))}const r=o.get("error");if(r){const t=document.getElementById("subscription-error"),n=document.getElementById("error-message");if(t&&n){const i={validation:"Invalid name or email. Please check your input.",captcha:"Captcha verification failed. Please try again.",server:"Server error. Please try again later."};n.textContent=i[r]||"Something went wrong. Please try again.",t.classList.remove("hidden"),t.scrollIntoView({behavior:"smooth",block:"center"}),window.history.replaceState({},"","/")}}});
+					`;const n=document.getElementById("try-again-btn");n&&n.addEventListener("click",d),r.textContent="Error"}}document.addEventListener("DOMContentLoaded",()=>{d();const o=new URLSearchParams(window.location.search);const s=document.getElementById("subscription-form"),u=document.getElementById("source_url");if(s&&u){u.value=document.referrer;const c=new URLSearchParams;for(const a of["utm_source","utm_medium","utm_campaign"]){const l=o.get(a);l&&c.set(a,l)}c.toString()&&(s.action="/subscriptions?"+c.toString())}if(o.get("subscribed")==="true"){const t=document.getElementById("subscription-success");t&&(t.classList.remove("hidden"),t.scrollIntoView({behavior:"smooth",block:"center"}),window.history.replaceState({},"","/"))}const r=o.get("error");if(r){const t=document.getElementById("subscription-error"),n=document.getElementById("error-message");if(t&&n){const i={validation:"Invalid name or email. Please check your input.",captcha:"Captcha verification failed. Please try again.",server:"Server error. Please try again later.",not_found:"That page does not exist.",bad_request:"That request was invalid."};n.textContent=(r==="validation"&&o.get("reason"))||i[r]||"Something went wrong. Please try again.",t.classList.remove("hidden"),t.scrollIntoView({behavior:"smooth",block:"center"}),window.history.replaceState({},"","/")}}});
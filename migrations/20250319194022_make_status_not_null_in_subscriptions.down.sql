@@ -0,0 +1,14 @@
+-- Back to the uuid as the primary key, the statuses stay filled in.
+CREATE TABLE subscriptions_old (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL UNIQUE,
+    subscribed_at TEXT NOT NULL,
+    status TEXT NULL
+);
+
+INSERT INTO subscriptions_old (id, name, email, subscribed_at, status)
+SELECT uuid, name, email, subscribed_at, status FROM subscriptions;
+
+DROP TABLE subscriptions;
+ALTER TABLE subscriptions_old RENAME TO subscriptions;
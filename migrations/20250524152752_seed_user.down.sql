@@ -0,0 +1 @@
+DELETE FROM users WHERE uuid = 'ddf8994f-d522-4659-8d02-c1d479057be6';
@@ -0,0 +1,19 @@
+-- Keys still being processed have no response yet, they can't be kept.
+CREATE TABLE idempotency_old (
+    user_uuid TEXT NOT NULL REFERENCES users(uuid),
+    idempotency_key TEXT NOT NULL,
+    response_status_code INT NOT NULL,
+    response_headers TEXT NOT NULL,
+    response_body BLOB NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY(user_uuid, idempotency_key)
+);
+
+INSERT INTO idempotency_old
+SELECT * FROM idempotency
+WHERE response_status_code IS NOT NULL
+    AND response_headers IS NOT NULL
+    AND response_body IS NOT NULL;
+
+DROP TABLE idempotency;
+ALTER TABLE idempotency_old RENAME TO idempotency;
@@ -0,0 +1,70 @@
+-- Titles are unique again and an issue can only be queued for one
+-- subscriber: the later issues reusing a title, and all but the first
+-- queued delivery of every issue, are dropped.
+
+CREATE TABLE issue_delivery_queue_backup (
+    newsletter_issue_uuid TEXT NOT NULL,
+    subscriber_email TEXT NOT NULL
+);
+
+INSERT INTO issue_delivery_queue_backup (
+    newsletter_issue_uuid,
+    subscriber_email
+)
+SELECT
+    newsletter_issue_uuid,
+    subscriber_email
+FROM issue_delivery_queue;
+
+DROP TABLE issue_delivery_queue;
+
+CREATE TABLE newsletter_issues_old (
+    id INTEGER,
+    newsletter_issue_uuid TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL UNIQUE,
+    text_content TEXT NOT NULL,
+    html_content TEXT NOT NULL,
+    published_at TEXT NOT NULL,
+    PRIMARY KEY(id)
+);
+
+INSERT OR IGNORE INTO newsletter_issues_old (
+    id,
+    newsletter_issue_uuid,
+    title,
+    text_content,
+    html_content,
+    published_at
+)
+SELECT
+    id,
+    newsletter_issue_uuid,
+    title,
+    text_content,
+    html_content,
+    published_at
+FROM newsletter_issues
+ORDER BY id;
+
+DROP TABLE newsletter_issues;
+ALTER TABLE newsletter_issues_old RENAME TO newsletter_issues;
+
+CREATE TABLE issue_delivery_queue (
+    id INTEGER,
+    newsletter_issue_uuid TEXT NOT NULL UNIQUE
+        REFERENCES newsletter_issues(newsletter_issue_uuid),
+    subscriber_email TEXT NOT NULL,
+    PRIMARY KEY(id, subscriber_email)
+);
+
+INSERT OR IGNORE INTO issue_delivery_queue (
+    newsletter_issue_uuid,
+    subscriber_email
+)
+SELECT
+    newsletter_issue_uuid,
+    subscriber_email
+FROM issue_delivery_queue_backup
+WHERE newsletter_issue_uuid IN (SELECT newsletter_issue_uuid FROM newsletter_issues);
+
+DROP TABLE issue_delivery_queue_backup;
@@ -0,0 +1,4 @@
+DROP TABLE issue_delivery_dead_letter;
+
+ALTER TABLE issue_delivery_queue DROP COLUMN next_attempt_at;
+ALTER TABLE issue_delivery_queue DROP COLUMN n_retries;
@@ -0,0 +1,17 @@
+-- Track how many times a delivery has been attempted and when it becomes
+-- eligible for the next attempt, so failed deliveries can back off.
+ALTER TABLE issue_delivery_queue ADD COLUMN n_retries INTEGER NOT NULL DEFAULT 0;
+-- timestamptz, NULL means the task can be picked up right away
+ALTER TABLE issue_delivery_queue ADD COLUMN next_attempt_at TEXT NULL;
+
+-- Deliveries that exhausted their retries end up here instead of being dropped.
+CREATE TABLE issue_delivery_dead_letter (
+    id INTEGER PRIMARY KEY,
+    newsletter_issue_uuid TEXT NOT NULL
+        REFERENCES newsletter_issues(newsletter_issue_uuid),
+    subscriber_email TEXT NOT NULL,
+    n_retries INTEGER NOT NULL,
+    failure_reason TEXT NOT NULL,
+    -- timestamptz
+    failed_at TEXT NOT NULL
+);
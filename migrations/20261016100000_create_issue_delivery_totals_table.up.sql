@@ -0,0 +1,7 @@
+-- How many deliveries were enqueued for each newsletter issue, so we can
+-- report progress once the queue starts draining.
+CREATE TABLE issue_delivery_totals (
+    newsletter_issue_uuid TEXT PRIMARY KEY
+        REFERENCES newsletter_issues(newsletter_issue_uuid),
+    total_queued INTEGER NOT NULL
+);
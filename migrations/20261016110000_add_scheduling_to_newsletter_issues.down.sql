@@ -0,0 +1,3 @@
+-- Scheduled issues that haven't gone out yet are left without subscribers.
+ALTER TABLE newsletter_issues DROP COLUMN scheduled_for;
+ALTER TABLE newsletter_issues DROP COLUMN status;
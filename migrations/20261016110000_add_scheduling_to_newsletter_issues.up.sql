@@ -0,0 +1,4 @@
+-- Newsletter issues can be scheduled for a later delivery.
+-- Issues published before this migration have already been enqueued.
+ALTER TABLE newsletter_issues ADD COLUMN status TEXT NOT NULL DEFAULT 'queued';
+ALTER TABLE newsletter_issues ADD COLUMN scheduled_for TEXT NULL;
@@ -0,0 +1 @@
+DROP TABLE newsletter_deliveries;
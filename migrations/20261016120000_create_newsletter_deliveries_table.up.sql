@@ -0,0 +1,14 @@
+-- The delivery queue forgets about a task once it has been processed, so we keep
+-- a record of the outcome for every subscriber of every issue.
+CREATE TABLE newsletter_deliveries (
+    newsletter_issue_uuid TEXT NOT NULL
+        REFERENCES newsletter_issues(newsletter_issue_uuid),
+    subscriber_email TEXT NOT NULL,
+    status TEXT NOT NULL,
+    delivered_at TEXT NULL,
+    failure_reason TEXT NULL,
+    PRIMARY KEY (newsletter_issue_uuid, subscriber_email)
+);
+
+CREATE INDEX newsletter_deliveries_issue_status_idx
+    ON newsletter_deliveries (newsletter_issue_uuid, status);
@@ -0,0 +1,2 @@
+-- Keep the Markdown source of an issue around so that it can be edited later on.
+ALTER TABLE newsletter_issues ADD COLUMN markdown_content TEXT NULL;
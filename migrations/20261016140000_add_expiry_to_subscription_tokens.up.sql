@@ -0,0 +1,6 @@
+-- Confirmation tokens are only valid for 24 hours.
+-- Tokens issued before this migration get a fresh 24 hours from now.
+ALTER TABLE subscription_tokens ADD COLUMN token_expires_at TEXT NULL;
+
+UPDATE subscription_tokens
+SET token_expires_at = strftime('%Y-%m-%d %H:%M:%S UTC', 'now', '+1 day');
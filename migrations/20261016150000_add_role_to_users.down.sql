@@ -0,0 +1,2 @@
+-- Editors get full access back.
+ALTER TABLE users DROP COLUMN role;
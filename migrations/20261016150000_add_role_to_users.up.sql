@@ -0,0 +1,3 @@
+-- Users are either `admin`s with full access, or `editor`s who can only publish.
+-- Existing users keep full access.
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'admin';
@@ -0,0 +1,12 @@
+-- Who did what in the admin area, and from where.
+CREATE TABLE admin_audit_log (
+    id INTEGER PRIMARY KEY,
+    user_uuid TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id TEXT NULL,
+    occurred_at TEXT NOT NULL,
+    ip_address TEXT NULL
+);
+
+CREATE INDEX admin_audit_log_occurred_at_idx ON admin_audit_log (occurred_at);
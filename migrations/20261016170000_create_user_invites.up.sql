@@ -0,0 +1,7 @@
+-- One-time tokens admins hand out so that new users can register themselves.
+CREATE TABLE user_invites (
+    token TEXT NOT NULL PRIMARY KEY,
+    invited_by TEXT NOT NULL REFERENCES users(uuid),
+    expires_at TEXT NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT FALSE
+);
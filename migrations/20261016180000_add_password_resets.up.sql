@@ -0,0 +1,13 @@
+-- Users can recover their account through a link sent to their email address.
+ALTER TABLE users ADD COLUMN email TEXT NULL;
+CREATE UNIQUE INDEX users_email_idx ON users (email);
+
+-- Sessions started before this point in time are no longer valid.
+ALTER TABLE users ADD COLUMN sessions_invalidated_at TEXT NULL;
+
+CREATE TABLE password_reset_tokens (
+    token TEXT NOT NULL PRIMARY KEY,
+    user_uuid TEXT NOT NULL REFERENCES users(uuid),
+    expires_at TEXT NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT FALSE
+);
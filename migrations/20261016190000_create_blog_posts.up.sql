@@ -0,0 +1,12 @@
+-- Posts written from the admin area, on top of the ones built by astro.
+-- New posts start out as drafts and are hidden from the public blog.
+CREATE TABLE blog_posts (
+    slug TEXT NOT NULL PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    markdown_content TEXT NOT NULL,
+    draft BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    published_at TEXT
+);
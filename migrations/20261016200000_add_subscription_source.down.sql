@@ -0,0 +1,4 @@
+ALTER TABLE subscriptions DROP COLUMN utm_campaign;
+ALTER TABLE subscriptions DROP COLUMN utm_medium;
+ALTER TABLE subscriptions DROP COLUMN utm_source;
+ALTER TABLE subscriptions DROP COLUMN source_url;
@@ -0,0 +1,5 @@
+-- Where subscribers came from, to see which campaigns bring them in.
+ALTER TABLE subscriptions ADD COLUMN source_url TEXT;
+ALTER TABLE subscriptions ADD COLUMN utm_source TEXT;
+ALTER TABLE subscriptions ADD COLUMN utm_medium TEXT;
+ALTER TABLE subscriptions ADD COLUMN utm_campaign TEXT;
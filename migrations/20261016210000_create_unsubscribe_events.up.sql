@@ -0,0 +1,12 @@
+-- Why subscribers leave, from the optional survey of the unsubscribe page.
+CREATE TABLE unsubscribe_events (
+    id INTEGER PRIMARY KEY,
+    subscriber_uuid TEXT NOT NULL,
+    -- NULL when the survey was left blank or skipped by a one-click unsubscribe
+    reason TEXT NULL CHECK (
+        reason IN ('too_frequent', 'not_relevant', 'never_subscribed', 'other')
+    ),
+    other_text TEXT NULL,
+    occurred_at TEXT NOT NULL,
+    ip_address TEXT NULL
+);
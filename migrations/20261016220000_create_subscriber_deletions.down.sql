@@ -0,0 +1,36 @@
+DROP TABLE subscriber_deletions;
+
+-- The email is unique per issue again, only the first '[redacted]' delivery
+-- of every issue is kept.
+CREATE TABLE newsletter_deliveries_old (
+    newsletter_issue_uuid TEXT NOT NULL
+        REFERENCES newsletter_issues(newsletter_issue_uuid),
+    subscriber_email TEXT NOT NULL,
+    status TEXT NOT NULL,
+    delivered_at TEXT NULL,
+    failure_reason TEXT NULL,
+    PRIMARY KEY (newsletter_issue_uuid, subscriber_email)
+);
+
+INSERT OR IGNORE INTO newsletter_deliveries_old (
+    newsletter_issue_uuid,
+    subscriber_email,
+    status,
+    delivered_at,
+    failure_reason
+)
+SELECT
+    newsletter_issue_uuid,
+    subscriber_email,
+    status,
+    delivered_at,
+    failure_reason
+FROM newsletter_deliveries
+ORDER BY id;
+
+DROP TABLE newsletter_deliveries;
+
+ALTER TABLE newsletter_deliveries_old RENAME TO newsletter_deliveries;
+
+CREATE INDEX newsletter_deliveries_issue_status_idx
+    ON newsletter_deliveries (newsletter_issue_uuid, status);
@@ -0,0 +1,46 @@
+-- Who was erased and by whom, without keeping anything about the subscriber.
+CREATE TABLE subscriber_deletions (
+    id INTEGER PRIMARY KEY,
+    uuid TEXT NOT NULL,
+    deleted_at TEXT NOT NULL,
+    deleted_by_user_uuid TEXT NOT NULL
+);
+
+-- Erased subscribers leave their deliveries behind as '[redacted]', so an
+-- issue can have several of those: the email is only unique per issue for
+-- the subscribers we still know about.
+CREATE TABLE newsletter_deliveries_new (
+    id INTEGER PRIMARY KEY,
+    newsletter_issue_uuid TEXT NOT NULL
+        REFERENCES newsletter_issues(newsletter_issue_uuid),
+    subscriber_email TEXT NOT NULL,
+    status TEXT NOT NULL,
+    delivered_at TEXT NULL,
+    failure_reason TEXT NULL
+);
+
+INSERT INTO newsletter_deliveries_new (
+    newsletter_issue_uuid,
+    subscriber_email,
+    status,
+    delivered_at,
+    failure_reason
+)
+SELECT
+    newsletter_issue_uuid,
+    subscriber_email,
+    status,
+    delivered_at,
+    failure_reason
+FROM newsletter_deliveries;
+
+DROP TABLE newsletter_deliveries;
+
+ALTER TABLE newsletter_deliveries_new RENAME TO newsletter_deliveries;
+
+CREATE UNIQUE INDEX newsletter_deliveries_issue_email_idx
+    ON newsletter_deliveries (newsletter_issue_uuid, subscriber_email)
+    WHERE subscriber_email != '[redacted]';
+
+CREATE INDEX newsletter_deliveries_issue_status_idx
+    ON newsletter_deliveries (newsletter_issue_uuid, status);
@@ -0,0 +1,2 @@
+DROP INDEX newsletter_issues_slug_idx;
+ALTER TABLE newsletter_issues DROP COLUMN slug;
@@ -0,0 +1,7 @@
+-- Where an issue can be read on the blog, linked from the Atom feed.
+ALTER TABLE newsletter_issues ADD COLUMN slug TEXT NULL;
+
+-- the issues from before slugs existed are only reachable by their uuid
+UPDATE newsletter_issues SET slug = newsletter_issue_uuid;
+
+CREATE UNIQUE INDEX newsletter_issues_slug_idx ON newsletter_issues (slug);
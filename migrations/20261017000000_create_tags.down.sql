@@ -0,0 +1,3 @@
+DROP TABLE subscriber_tags;
+DROP TABLE newsletter_issue_tags;
+DROP TABLE tags;
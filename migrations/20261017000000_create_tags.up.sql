@@ -0,0 +1,21 @@
+-- Topics subscribers opt into, an issue with tags only goes to the
+-- subscribers who share at least one of them.
+CREATE TABLE tags (
+    name TEXT NOT NULL PRIMARY KEY,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE newsletter_issue_tags (
+    newsletter_issue_uuid TEXT NOT NULL
+        REFERENCES newsletter_issues(newsletter_issue_uuid) ON DELETE CASCADE,
+    tag TEXT NOT NULL REFERENCES tags(name) ON DELETE CASCADE,
+    PRIMARY KEY (newsletter_issue_uuid, tag)
+);
+
+CREATE TABLE subscriber_tags (
+    subscriber_uuid TEXT NOT NULL REFERENCES subscriptions(uuid) ON DELETE CASCADE,
+    tag TEXT NOT NULL REFERENCES tags(name) ON DELETE CASCADE,
+    PRIMARY KEY (subscriber_uuid, tag)
+);
+
+CREATE INDEX subscriber_tags_tag_idx ON subscriber_tags (tag);
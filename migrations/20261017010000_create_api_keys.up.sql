@@ -0,0 +1,12 @@
+-- Keys for machine-to-machine access to the admin API, only the SHA-256 hash
+-- of a key is kept, the key itself is shown once when it is created.
+CREATE TABLE api_keys (
+    uuid TEXT NOT NULL PRIMARY KEY,
+    user_uuid TEXT NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    key_hash TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    last_used_at TEXT NULL,
+    -- NULL for keys that never expire
+    expires_at TEXT NULL
+);
@@ -0,0 +1,2 @@
+-- Why an admin did something, for the actions that ask for it.
+ALTER TABLE admin_audit_log ADD COLUMN reason TEXT NULL;
@@ -0,0 +1 @@
+DROP TABLE blog_post_views;
@@ -0,0 +1,11 @@
+-- One row per read of a blog post, the reader's IP is only kept as a hash
+-- salted with the day, so readers can be told apart within a day but not
+-- followed across days.
+CREATE TABLE blog_post_views (
+    slug TEXT NOT NULL,
+    viewed_at TEXT NOT NULL,
+    -- NULL when the client address is unknown
+    ip_hash TEXT NULL
+);
+
+CREATE INDEX blog_post_views_slug_viewed_at ON blog_post_views (slug, viewed_at);
@@ -0,0 +1,5 @@
+DROP TRIGGER subscriptions_fts_update;
+DROP TRIGGER subscriptions_fts_delete;
+DROP TRIGGER subscriptions_fts_insert;
+
+DROP TABLE subscriptions_fts;
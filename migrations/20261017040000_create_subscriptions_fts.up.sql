@@ -0,0 +1,31 @@
+-- Full-text index over the subscriber names and emails, for the admin
+-- search. The trigram tokenizer lets any part of a name or address of at
+-- least three characters match, not only whole words.
+CREATE VIRTUAL TABLE subscriptions_fts USING fts5(
+    uuid UNINDEXED,
+    name,
+    email,
+    tokenize = 'trigram'
+);
+
+INSERT INTO subscriptions_fts (rowid, uuid, name, email)
+SELECT id, uuid, name, email FROM subscriptions;
+
+-- the rows share their rowid with `subscriptions`, which keeps them in sync
+CREATE TRIGGER subscriptions_fts_insert AFTER INSERT ON subscriptions
+BEGIN
+    INSERT INTO subscriptions_fts (rowid, uuid, name, email)
+    VALUES (new.id, new.uuid, new.name, new.email);
+END;
+
+CREATE TRIGGER subscriptions_fts_delete AFTER DELETE ON subscriptions
+BEGIN
+    DELETE FROM subscriptions_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER subscriptions_fts_update AFTER UPDATE OF uuid, name, email ON subscriptions
+BEGIN
+    UPDATE subscriptions_fts
+    SET uuid = new.uuid, name = new.name, email = new.email
+    WHERE rowid = old.id;
+END;
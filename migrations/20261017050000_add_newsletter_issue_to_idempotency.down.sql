@@ -0,0 +1 @@
+ALTER TABLE idempotency DROP COLUMN newsletter_issue_uuid;
@@ -0,0 +1,3 @@
+-- The issue published under an idempotency key, so that submitting the form
+-- again can enqueue the deliveries the worker lost track of.
+ALTER TABLE idempotency ADD COLUMN newsletter_issue_uuid TEXT NULL;
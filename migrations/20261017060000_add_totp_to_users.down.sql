@@ -0,0 +1,3 @@
+-- Two-factor authentication is turned off for everyone.
+ALTER TABLE users DROP COLUMN totp_enabled;
+ALTER TABLE users DROP COLUMN totp_secret;
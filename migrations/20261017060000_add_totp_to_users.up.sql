@@ -0,0 +1,4 @@
+-- encrypted with a key derived from `application.hmac_secret`
+ALTER TABLE users ADD COLUMN totp_secret TEXT;
+-- the secret is only used at login once a code has been verified with it
+ALTER TABLE users ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT FALSE;
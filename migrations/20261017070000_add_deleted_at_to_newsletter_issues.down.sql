@@ -0,0 +1,3 @@
+-- Deleted issues show up again.
+DROP VIEW active_newsletter_issues;
+ALTER TABLE newsletter_issues DROP COLUMN deleted_at;
@@ -0,0 +1,7 @@
+-- Deleted issues are kept, the deliveries and the queue still refer to them.
+ALTER TABLE newsletter_issues ADD COLUMN deleted_at TEXT NULL;
+
+-- What every read outside of the slug allocation should go through.
+CREATE VIEW active_newsletter_issues AS
+SELECT * FROM newsletter_issues
+WHERE deleted_at IS NULL;
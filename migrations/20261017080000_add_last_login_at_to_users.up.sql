@@ -0,0 +1,2 @@
+-- when the user last got through `/login`, null until their next login
+ALTER TABLE users ADD COLUMN last_login_at TEXT NULL;
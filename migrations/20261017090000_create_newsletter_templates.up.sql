@@ -0,0 +1,8 @@
+-- Reusable header and footer HTML wrapped around the body of new issues
+CREATE TABLE newsletter_templates (
+    uuid TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    header_html TEXT NOT NULL,
+    footer_html TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
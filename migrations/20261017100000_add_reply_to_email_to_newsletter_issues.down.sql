@@ -0,0 +1,5 @@
+DROP VIEW active_newsletter_issues;
+ALTER TABLE newsletter_issues DROP COLUMN reply_to_email;
+CREATE VIEW active_newsletter_issues AS
+SELECT * FROM newsletter_issues
+WHERE deleted_at IS NULL;
@@ -0,0 +1,7 @@
+-- Where the replies to an issue go, null for the issues sent before it
+-- could be chosen. The view is recreated to pick up the new column.
+DROP VIEW active_newsletter_issues;
+ALTER TABLE newsletter_issues ADD COLUMN reply_to_email TEXT NULL;
+CREATE VIEW active_newsletter_issues AS
+SELECT * FROM newsletter_issues
+WHERE deleted_at IS NULL;
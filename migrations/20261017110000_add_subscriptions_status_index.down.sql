@@ -0,0 +1 @@
+DROP INDEX idx_subscriptions_status;
@@ -0,0 +1,4 @@
+-- Every enqueueing looks for the confirmed subscribers. The queue needs no
+-- index of its own, UNIQUE(newsletter_issue_uuid, subscriber_email) already
+-- serves the lookups by issue.
+CREATE INDEX idx_subscriptions_status ON subscriptions (status);
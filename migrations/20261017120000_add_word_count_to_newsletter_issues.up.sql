@@ -0,0 +1,7 @@
+-- Words of the plain text body, null for the issues published before it
+-- was counted. The view is recreated to pick up the new column.
+DROP VIEW active_newsletter_issues;
+ALTER TABLE newsletter_issues ADD COLUMN word_count INTEGER NULL;
+CREATE VIEW active_newsletter_issues AS
+SELECT * FROM newsletter_issues
+WHERE deleted_at IS NULL;
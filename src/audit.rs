@@ -0,0 +1,91 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::{request::Parts, Extensions};
+use chrono::Utc;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::db::SqliteInstrumentedPool;
+
+/// The address of the client, the socket peer or, behind a trusted proxy, the
+/// address it forwarded (see [`crate::middleware::ForwardedForLayer`]).
+#[derive(Copy, Clone, Debug)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::resolve(&parts.extensions))
+    }
+}
+
+impl ClientIp {
+    pub fn resolve(extensions: &Extensions) -> Self {
+        Self(
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    pub user_id: Uuid,
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: Option<String>,
+    pub ip_address: Option<IpAddr>,
+    /// What the admin wrote down about why, if the action asks for it.
+    pub reason: Option<String>,
+}
+
+#[tracing::instrument(name = "Record audit event", skip(pool))]
+pub async fn record_audit_event(
+    pool: &SqliteInstrumentedPool,
+    event: &AuditEvent,
+) -> Result<(), sqlx::Error> {
+    let user_uuid = event.user_id.to_string();
+    let occurred_at = Utc::now().to_string();
+    let ip_address = event.ip_address.map(|ip| ip.to_string());
+    sqlx::query!(
+        r#"
+        INSERT INTO admin_audit_log
+            (user_uuid, action, target_type, target_id, occurred_at, ip_address, reason)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        user_uuid,
+        event.action,
+        event.target_type,
+        event.target_id,
+        occurred_at,
+        ip_address,
+        event.reason,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record the event in the background: a failure to write the audit trail is
+/// logged, but never fails the request that triggered it.
+pub fn spawn_audit_event(pool: SqliteInstrumentedPool, event: AuditEvent) {
+    tokio::spawn(
+        async move {
+            if let Err(e) = record_audit_event(&pool, &event).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to record an audit event",
+                );
+            }
+        }
+        .in_current_span(),
+    );
+}
@@ -0,0 +1,176 @@
+use anyhow::Context;
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    RequestPartsExt,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use chrono::Utc;
+use rand::Rng;
+use secrecy::{ExposeSecret, SecretString};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::UserId;
+use crate::db::SqliteInstrumentedPool;
+use crate::{routes::error_chain_fmt, startup::AppState};
+
+/// Tells the keys apart from other secrets when they leak into logs or code.
+const API_KEY_PREFIX: &str = "nzl_";
+
+/// A key for the admin API, sent as `Authorization: Bearer <key>`.
+pub struct ApiKey(SecretString);
+
+impl ApiKey {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::rng().fill(&mut bytes);
+        Self(format!("{}{}", API_KEY_PREFIX, hex::encode(bytes)).into())
+    }
+
+    /// What gets stored, keys are random enough not to need a slow hash.
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.0.expose_secret().as_bytes()))
+    }
+}
+
+impl ExposeSecret<str> for ApiKey {
+    fn expose_secret(&self) -> &str {
+        self.0.expose_secret()
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self(key.into())
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum ApiKeyError {
+    #[error("The Authorization header is not a bearer token")]
+    MalformedHeader,
+    #[error("Invalid or expired API key")]
+    InvalidKey,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ApiKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl IntoResponse for ApiKeyError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::MalformedHeader | Self::InvalidKey => {
+                tracing::warn!(cause_chain = ?self);
+                (StatusCode::UNAUTHORIZED, self.to_string()).into_response()
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// The user of the API key in the `Authorization` header.
+///
+/// As an `Option`, requests without the header are `None`, while a header
+/// with an unknown or expired key is still rejected.
+pub struct ApiKeyExtractor(pub UserId);
+
+impl FromRequestParts<Arc<AppState>> for ApiKeyExtractor {
+    type Rejection = ApiKeyError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let TypedHeader(Authorization(bearer)) = parts
+            .extract::<TypedHeader<Authorization<Bearer>>>()
+            .await
+            .map_err(|_| ApiKeyError::MalformedHeader)?;
+        let key = ApiKey::from(bearer.token().to_string());
+        let user_id = authenticate(state.pool(), &key)
+            .await?
+            .ok_or(ApiKeyError::InvalidKey)?;
+        Ok(Self(user_id))
+    }
+}
+
+impl OptionalFromRequestParts<Arc<AppState>> for ApiKeyExtractor {
+    type Rejection = ApiKeyError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if !parts.headers.contains_key(AUTHORIZATION) {
+            return Ok(None);
+        }
+        <Self as FromRequestParts<_>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
+/// The owner of the key if it exists and hasn't expired, recording that it
+/// has just been used.
+#[tracing::instrument(name = "Authenticate an API key", skip(pool, key))]
+async fn authenticate(
+    pool: &SqliteInstrumentedPool,
+    key: &ApiKey,
+) -> Result<Option<UserId>, anyhow::Error> {
+    let key_hash = key.hash();
+    // timestamps are stored as `Utc::now().to_string()`, so they compare as strings
+    let now = Utc::now().to_string();
+    let row = sqlx::query!(
+        r#"
+        UPDATE api_keys
+        SET last_used_at = $2
+        WHERE key_hash = $1 AND (expires_at IS NULL OR expires_at > $2)
+        RETURNING user_uuid
+        "#,
+        key_hash,
+        now,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to look up the API key.")?;
+    row.map(|row| {
+        Uuid::parse_str(&row.user_uuid)
+            .map(UserId::from)
+            .context("The stored user id is not a uuid.")
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiKey;
+    use secrecy::ExposeSecret;
+
+    #[test]
+    fn generated_keys_are_prefixed_and_unique() {
+        let key = ApiKey::generate();
+        assert!(key.expose_secret().starts_with("nzl_"));
+        assert_ne!(key.expose_secret(), ApiKey::generate().expose_secret());
+    }
+
+    #[test]
+    fn the_hash_is_the_hex_sha256_of_the_key() {
+        let key = ApiKey::from("nzl_key".to_string());
+        assert_eq!(key.hash().len(), 64);
+        assert_eq!(key.hash(), ApiKey::from("nzl_key".to_string()).hash());
+        assert_ne!(key.hash(), ApiKey::from("nzl_other".to_string()).hash());
+    }
+}
@@ -1,13 +1,15 @@
 use axum::{
     body::Body,
-    http::Request,
+    extract::State,
+    http::{Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Redirect, Response},
 };
-use std::ops::Deref;
+use std::{ops::Deref, sync::Arc};
 use uuid::Uuid;
 
-use crate::{routes::error_chain_fmt, session_state::TypedSession};
+use super::{get_user_role, session_is_purged, ApiKeyExtractor, AuthorizedUser, UserRole};
+use crate::{routes::error_chain_fmt, session_state::TypedSession, startup::AppState};
 
 #[derive(Copy, Clone, Debug)]
 pub struct UserId(Uuid);
@@ -18,6 +20,12 @@ impl std::fmt::Display for UserId {
     }
 }
 
+impl From<Uuid> for UserId {
+    fn from(user_id: Uuid) -> Self {
+        Self(user_id)
+    }
+}
+
 impl Deref for UserId {
     type Target = Uuid;
 
@@ -30,6 +38,8 @@ impl Deref for UserId {
 pub enum AuthMiddlewareError {
     #[error("Authentication failed")]
     AuthError(#[source] anyhow::Error),
+    #[error("Not allowed")]
+    Forbidden(#[source] anyhow::Error),
 }
 
 impl std::fmt::Debug for AuthMiddlewareError {
@@ -45,21 +55,58 @@ impl IntoResponse for AuthMiddlewareError {
                 tracing::error!(cause_chain = ?e);
                 Redirect::to("/login").into_response()
             }
+            AuthMiddlewareError::Forbidden(e) => {
+                tracing::warn!(cause_chain = ?e);
+                StatusCode::FORBIDDEN.into_response()
+            }
         }
     }
 }
 
+/// Lets through users with a session, or machines with an API key in the
+/// `Authorization` header, which then doesn't fall back to the session.
 pub async fn reject_anonymous_users(
+    State(app_state): State<Arc<AppState>>,
+    api_key: Option<ApiKeyExtractor>,
     session: TypedSession,
     request: Request<Body>,
     next: Next,
 ) -> Result<Response, AuthMiddlewareError> {
+    if let Some(ApiKeyExtractor(user_id)) = api_key {
+        // the role would otherwise be read from the session
+        let role = get_user_role(app_state.pool(), *user_id)
+            .await
+            .map_err(AuthMiddlewareError::AuthError)?;
+        let mut request = request;
+        request.extensions_mut().insert(user_id);
+        request
+            .extensions_mut()
+            .insert(AuthorizedUser { user_id, role });
+        return Ok(next.run(request).await);
+    }
     match session
         .get_user_id()
         .await
         .map_err(|e| AuthMiddlewareError::AuthError(e.into()))?
     {
         Some(user_id) => {
+            let logged_in_at = session
+                .get_logged_in_at()
+                .await
+                .map_err(|e| AuthMiddlewareError::AuthError(e.into()))?;
+            if session_is_purged(app_state.pool(), user_id, logged_in_at.as_deref())
+                .await
+                .map_err(AuthMiddlewareError::AuthError)?
+            {
+                session
+                    .log_out()
+                    .await
+                    .map_err(|e| AuthMiddlewareError::AuthError(e.into()))?;
+                return Err(AuthMiddlewareError::AuthError(anyhow::anyhow!(
+                    "The session of user {} has been purged",
+                    user_id
+                )));
+            }
             let mut request = request;
             request.extensions_mut().insert(UserId(user_id));
             Ok(next.run(request).await)
@@ -69,3 +116,17 @@ pub async fn reject_anonymous_users(
         ))),
     }
 }
+
+pub async fn reject_non_admin(
+    user: AuthorizedUser,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, AuthMiddlewareError> {
+    if user.role != UserRole::Admin {
+        return Err(AuthMiddlewareError::Forbidden(anyhow::anyhow!(
+            "The user {} is not an admin",
+            user.user_id
+        )));
+    }
+    Ok(next.run(request).await)
+}
@@ -1,5 +1,16 @@
+mod api_key;
 mod middleware;
 mod password;
-pub use middleware::reject_anonymous_users;
+mod role;
+mod sessions;
+mod totp;
+pub use api_key::{ApiKey, ApiKeyError, ApiKeyExtractor};
 pub use middleware::UserId;
-pub use password::{change_password, validate_credentials, AuthError, Credentials};
+pub use middleware::{reject_anonymous_users, reject_non_admin};
+pub use password::{change_password, create_user, validate_credentials, AuthError, Credentials};
+pub use role::{get_user_role, AuthorizedUser, UserRole};
+pub use sessions::{purge_user_sessions, session_is_purged};
+pub use totp::{
+    disable_totp, enable_totp, encrypt_totp_secret, generate_totp_secret, get_totp_settings,
+    store_totp_secret, totp, verify_totp_code,
+};
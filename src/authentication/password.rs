@@ -1,9 +1,11 @@
+use super::UserRole;
+use crate::db::{SqliteInstrumentedPool, SqliteInstrumentedTransaction};
 use crate::telemetry::spawn_blocking_with_tracing;
 use anyhow::Context;
 use argon2::password_hash::{rand_core, SaltString};
 use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
 use secrecy::{ExposeSecret, SecretString};
-use sqlx::SqlitePool;
+use sqlx::SqliteExecutor;
 
 #[derive(thiserror::Error, Debug)]
 pub enum AuthError {
@@ -21,7 +23,7 @@ pub struct Credentials {
 #[tracing::instrument(name = "Get stored credentials", skip(username, pool))]
 async fn get_stored_credentials(
     username: &str,
-    pool: &SqlitePool,
+    pool: &SqliteInstrumentedPool,
 ) -> Result<Option<(uuid::Uuid, SecretString)>, anyhow::Error> {
     let row = sqlx::query!(
         r#"
@@ -46,7 +48,7 @@ async fn get_stored_credentials(
 #[tracing::instrument(name = "Validate credentials", skip(credentials, pool))]
 pub async fn validate_credentials(
     credentials: Credentials,
-    pool: &SqlitePool,
+    pool: &SqliteInstrumentedPool,
 ) -> Result<uuid::Uuid, AuthError> {
     let mut user_id = None;
     let mut expected_password_hash = SecretString::from(
@@ -93,11 +95,11 @@ fn verify_password_hash(
         .map_err(AuthError::InvalidCredentials)
 }
 
-#[tracing::instrument(name = "Change password", skip(password, pool))]
-pub async fn change_password(
+#[tracing::instrument(name = "Change password", skip(password, executor))]
+pub async fn change_password<'e>(
     user_id: uuid::Uuid,
     password: SecretString,
-    pool: &SqlitePool,
+    executor: impl SqliteExecutor<'e>,
 ) -> Result<(), anyhow::Error> {
     let user_id = user_id.to_string();
     let password_hash = spawn_blocking_with_tracing(move || compute_password_hash(password))
@@ -113,12 +115,44 @@ pub async fn change_password(
         password_hash,
         user_id
     )
-    .execute(pool)
+    .execute(executor)
     .await
     .context("Failed to change user's password in the database.")?;
     Ok(())
 }
 
+#[tracing::instrument(name = "Create user", skip(transaction, password))]
+pub async fn create_user(
+    transaction: &mut SqliteInstrumentedTransaction,
+    username: &str,
+    email: Option<&str>,
+    password: SecretString,
+    role: UserRole,
+) -> Result<uuid::Uuid, anyhow::Error> {
+    let user_id = uuid::Uuid::new_v4();
+    let user_id_string = user_id.to_string();
+    let password_hash = spawn_blocking_with_tracing(move || compute_password_hash(password))
+        .await?
+        .context("Failed to hash password")?;
+    let password_hash = password_hash.expose_secret();
+    let role = role.as_str();
+    sqlx::query!(
+        r#"
+        INSERT INTO users (uuid, username, email, password_hash, role)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        user_id_string,
+        username,
+        email,
+        password_hash,
+        role,
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to store the new user in the database.")?;
+    Ok(user_id)
+}
+
 fn compute_password_hash(password: SecretString) -> Result<SecretString, anyhow::Error> {
     let salt = SaltString::generate(&mut rand_core::OsRng);
     let password_hash = Argon2::new(
@@ -0,0 +1,91 @@
+use anyhow::Context;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::{Deserialize, Serialize};
+
+use super::{middleware::AuthMiddlewareError, UserId};
+use crate::db::SqliteInstrumentedPool;
+use crate::session_state::TypedSession;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Full access to the admin area.
+    Admin,
+    /// Can publish newsletters, but can't manage accounts or export subscribers.
+    Editor,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Editor => "editor",
+        }
+    }
+}
+
+impl TryFrom<String> for UserRole {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "admin" => Ok(Self::Admin),
+            "editor" => Ok(Self::Editor),
+            other => Err(format!("{} is not a supported user role.", other)),
+        }
+    }
+}
+
+/// The logged in user along with their role, both read from the session,
+/// or put in the request extensions when authenticated with an API key.
+#[derive(Copy, Clone, Debug)]
+pub struct AuthorizedUser {
+    pub user_id: UserId,
+    pub role: UserRole,
+}
+
+impl<S> FromRequestParts<S> for AuthorizedUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AuthMiddlewareError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(user) = parts.extensions.get::<AuthorizedUser>() {
+            return Ok(*user);
+        }
+        let session = TypedSession::from_request_parts(parts, state)
+            .await
+            .map_err(|(_, e)| AuthMiddlewareError::AuthError(anyhow::anyhow!(e)))?;
+        let user_id = session
+            .get_user_id()
+            .await
+            .map_err(|e| AuthMiddlewareError::AuthError(e.into()))?;
+        let role = session
+            .get_user_role()
+            .await
+            .map_err(|e| AuthMiddlewareError::AuthError(e.into()))?;
+        match (user_id, role) {
+            (Some(user_id), Some(role)) => Ok(Self {
+                user_id: UserId::from(user_id),
+                role,
+            }),
+            _ => Err(AuthMiddlewareError::AuthError(anyhow::anyhow!(
+                "The user has not logged in"
+            ))),
+        }
+    }
+}
+
+#[tracing::instrument(name = "Get user role", skip(pool))]
+pub async fn get_user_role(
+    pool: &SqliteInstrumentedPool,
+    user_id: uuid::Uuid,
+) -> Result<UserRole, anyhow::Error> {
+    let user_id = user_id.to_string();
+    let row = sqlx::query!(r#"SELECT role FROM users WHERE uuid = $1"#, user_id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to perform a query to retrieve the user role.")?;
+    UserRole::try_from(row.role).map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,51 @@
+use crate::db::SqliteInstrumentedPool;
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::SqliteExecutor;
+
+/// Sessions live in Redis and can't be looked up by user, so instead of
+/// deleting them we record the moment they stopped being valid: any session
+/// started before that is rejected by `reject_anonymous_users`.
+#[tracing::instrument(name = "Purge user sessions", skip(executor))]
+pub async fn purge_user_sessions<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: uuid::Uuid,
+) -> Result<(), anyhow::Error> {
+    let user_id = user_id.to_string();
+    let now = Utc::now().to_string();
+    sqlx::query!(
+        r#"UPDATE users SET sessions_invalidated_at = $1 WHERE uuid = $2"#,
+        now,
+        user_id
+    )
+    .execute(executor)
+    .await
+    .context("Failed to invalidate the sessions of the user.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Check if the session was purged", skip(pool))]
+pub async fn session_is_purged(
+    pool: &SqliteInstrumentedPool,
+    user_id: uuid::Uuid,
+    logged_in_at: Option<&str>,
+) -> Result<bool, anyhow::Error> {
+    let user_id = user_id.to_string();
+    let row = sqlx::query!(
+        r#"SELECT sessions_invalidated_at FROM users WHERE uuid = $1"#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to retrieve the session invalidation time of the user.")?;
+    // a user that has been deleted has no business being logged in either
+    let Some(row) = row else {
+        return Ok(true);
+    };
+    // timestamps are stored as `Utc::now().to_string()`, so they compare as strings
+    Ok(match (row.sessions_invalidated_at, logged_in_at) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(invalidated_at), Some(logged_in_at)) => logged_in_at <= invalidated_at.as_str(),
+    })
+}
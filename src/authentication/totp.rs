@@ -0,0 +1,196 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::Context;
+use secrecy::ExposeSecret;
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::HmacSecret;
+
+/// Shown next to the account in authenticator apps.
+const TOTP_ISSUER: &str = "Newzletter";
+
+/// The length of the AES-GCM nonce stored in front of the ciphertext.
+const NONCE_LENGTH: usize = 12;
+
+pub struct TotpSettings {
+    pub encrypted_secret: Option<String>,
+    pub enabled: bool,
+}
+
+/// A fresh 160 bit secret, the size RFC 4226 recommends.
+pub fn generate_totp_secret() -> Vec<u8> {
+    Secret::generate_secret()
+        .to_bytes()
+        .expect("A generated secret is always valid")
+}
+
+/// Six digit codes that change every 30 seconds, the one step of clock drift
+/// either way is accepted. These are the settings authenticator apps expect.
+pub fn totp(secret: Vec<u8>, username: &str) -> Result<TOTP, anyhow::Error> {
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        username.to_string(),
+    )
+    .context("Failed to build the TOTP generator.")
+}
+
+/// Encrypts the secret with AES-256-GCM, the key is the SHA-256 hash of the
+/// HMAC secret. The nonce is stored in front of the ciphertext, hex encoded.
+pub fn encrypt_totp_secret(secret: &[u8], key: &HmacSecret) -> Result<String, anyhow::Error> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(key)
+        .encrypt(&nonce, secret)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt the TOTP secret."))?;
+    Ok(hex::encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+pub fn decrypt_totp_secret(encrypted: &str, key: &HmacSecret) -> Result<Vec<u8>, anyhow::Error> {
+    let bytes = hex::decode(encrypted).context("The stored TOTP secret is not hex encoded.")?;
+    if bytes.len() <= NONCE_LENGTH {
+        anyhow::bail!("The stored TOTP secret is too short.");
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+    cipher(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt the TOTP secret."))
+}
+
+fn cipher(key: &HmacSecret) -> Aes256Gcm {
+    let key = Sha256::digest(key.0.expose_secret().as_bytes());
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+}
+
+#[tracing::instrument(name = "Get TOTP settings", skip(pool))]
+pub async fn get_totp_settings(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+) -> Result<TotpSettings, anyhow::Error> {
+    let user_id = user_id.to_string();
+    let row = sqlx::query!(
+        r#"
+        SELECT totp_secret, totp_enabled AS "totp_enabled: bool"
+        FROM users
+        WHERE uuid = $1
+        "#,
+        user_id,
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to retrieve the TOTP settings.")?;
+    Ok(TotpSettings {
+        encrypted_secret: row.totp_secret,
+        enabled: row.totp_enabled,
+    })
+}
+
+/// Replaces any secret the user had, it's only used at login once
+/// [`enable_totp`] has been called.
+#[tracing::instrument(name = "Store TOTP secret", skip(pool, encrypted_secret))]
+pub async fn store_totp_secret(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+    encrypted_secret: &str,
+) -> Result<(), anyhow::Error> {
+    let user_id = user_id.to_string();
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_secret = $1, totp_enabled = FALSE
+        WHERE uuid = $2
+        "#,
+        encrypted_secret,
+        user_id,
+    )
+    .execute(pool)
+    .await
+    .context("Failed to store the TOTP secret.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Enable TOTP", skip(pool))]
+pub async fn enable_totp(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    let user_id = user_id.to_string();
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_enabled = TRUE
+        WHERE uuid = $1 AND totp_secret IS NOT NULL
+        "#,
+        user_id,
+    )
+    .execute(pool)
+    .await
+    .context("Failed to enable TOTP.")?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "Disable TOTP", skip(pool))]
+pub async fn disable_totp(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    let user_id = user_id.to_string();
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_secret = NULL, totp_enabled = FALSE
+        WHERE uuid = $1
+        "#,
+        user_id,
+    )
+    .execute(pool)
+    .await
+    .context("Failed to disable TOTP.")?;
+    Ok(())
+}
+
+/// Checks `code` against the user's secret, `false` when they have none.
+pub async fn verify_totp_code(
+    pool: &SqliteInstrumentedPool,
+    hmac_secret: &HmacSecret,
+    user_id: Uuid,
+    username: &str,
+    code: &str,
+) -> Result<bool, anyhow::Error> {
+    let Some(encrypted_secret) = get_totp_settings(pool, user_id).await?.encrypted_secret else {
+        return Ok(false);
+    };
+    let secret = decrypt_totp_secret(&encrypted_secret, hmac_secret)?;
+    totp(secret, username)?
+        .check_current(code.trim())
+        .context("The system clock is set before the UNIX epoch.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_totp_secret, encrypt_totp_secret, generate_totp_secret};
+    use crate::startup::HmacSecret;
+    use secrecy::SecretString;
+
+    #[test]
+    fn an_encrypted_secret_can_be_decrypted_with_the_same_key() {
+        let key = HmacSecret(SecretString::from("secret"));
+        let secret = generate_totp_secret();
+        let encrypted = encrypt_totp_secret(&secret, &key).unwrap();
+        assert_eq!(decrypt_totp_secret(&encrypted, &key).unwrap(), secret);
+    }
+
+    #[test]
+    fn an_encrypted_secret_cannot_be_decrypted_with_another_key() {
+        let secret = generate_totp_secret();
+        let encrypted =
+            encrypt_totp_secret(&secret, &HmacSecret(SecretString::from("secret"))).unwrap();
+        assert!(decrypt_totp_secret(&encrypted, &HmacSecret(SecretString::from("other"))).is_err());
+    }
+}
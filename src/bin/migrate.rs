@@ -0,0 +1,58 @@
+use clap::{Parser, Subcommand};
+use newzletter::{
+    configuration::{get_configuration, Environment},
+    migrations::{migrate_down, MIGRATOR},
+};
+
+/// Moves the local database between migrations, e.g. to rework the latest
+/// one. The application applies every migration on startup anyway.
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Applies the pending migrations.
+    Up,
+    /// Reverts the most recently applied migrations with their `.down.sql`.
+    #[command(alias = "migrate_down")]
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let configuration = get_configuration()?;
+    // reverting loses data, production databases are left alone
+    if configuration.application.environment != Environment::Local {
+        anyhow::bail!("Migrations can only be run by hand with APP_ENVIRONMENT=local.");
+    }
+    let options = configuration.database.connect_options()?;
+    let pool = configuration
+        .database
+        .pool_options()
+        .connect_with(options)
+        .await?;
+
+    match cli.command {
+        Command::Up => {
+            MIGRATOR.run(&pool).await?;
+            println!("The database is up to date.");
+        }
+        Command::Down { steps } => {
+            let reverted = migrate_down(&pool, steps).await?;
+            if reverted.is_empty() {
+                println!("No migration to revert.");
+            }
+            for version in reverted {
+                println!("Reverted {}", version);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,25 @@
-use std::{str::FromStr, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use config::{Config, ConfigError};
+use rand::Rng;
 use secrecy::SecretString;
 use serde::Deserialize;
+use tower_sessions::cookie::SameSite;
 // use serde_aux::field_attributes::deserialize_number_from_string;
 use crate::email_client::EmailClient;
+use crate::migrations::MIGRATOR;
+use crate::routes::error_chain_fmt;
+use crate::turnstile::TurnstileClient;
 use sqlx::{
-    sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous},
+    sqlite::{
+        SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions,
+        SqliteSynchronous,
+    },
     SqlitePool,
 };
 
@@ -18,6 +31,14 @@ pub struct Settings {
     pub application: ApplicationSettings,
     pub email_client: EmailClientSettings,
     pub redis_uri: SecretString,
+    pub issue_delivery: IssueDeliverySettings,
+    pub turnstile: TurnstileSettings,
+    pub rate_limit: RateLimitSettings,
+    pub http_client: HttpClientSettings,
+    pub session: SessionSettings,
+    /// Touched by the delivery worker every 30 seconds while it runs, so a
+    /// container orchestrator can tell when it gets stuck.
+    pub worker_health_check_path: Option<PathBuf>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -29,8 +50,39 @@ pub struct ApplicationSettings {
     pub port: u16,
     pub host: String,
     pub base_url: String,
+    /// Requests for any other `Host` get a `421`, the links emailed in
+    /// response to a request point at the host it was sent to.
+    pub allowed_hosts: Vec<String>,
     pub hmac_secret: SecretString,
-    pub turnstile_secret_key: SecretString,
+    pub idempotency_ttl_hours: u64,
+    /// Subscribers still unconfirmed after this many days are deleted.
+    pub pending_ttl_days: u64,
+    pub metrics_allowed_cidr: Option<String>,
+    /// CIDR ranges of the proxies whose `X-Forwarded-For` is believed, the
+    /// header of anyone else is ignored. Only the entry the proxy appended
+    /// is read.
+    pub trusted_proxies: Vec<String>,
+    pub shutdown_timeout_seconds: u64,
+    /// Taken from `APP_ENVIRONMENT`.
+    pub environment: Environment,
+    /// Brotli or gzip for responses over 1 KB, when the client accepts them.
+    pub compress_responses: bool,
+    /// Larger request bodies are rejected with a `413`, outside of `/admin`.
+    pub max_request_body_bytes: usize,
+    /// Requests still unanswered by then get a `408`.
+    pub request_timeout_seconds: u64,
+    /// `max-age` of the `Strict-Transport-Security` header, only sent in production.
+    pub hsts_max_age_seconds: u64,
+    /// Accepts every Turnstile token without asking Cloudflare, for tests
+    /// that run without internet access. Never turn it on in production.
+    pub turnstile_test_mode: bool,
+    /// Failed logins allowed per client IP within the window, further
+    /// attempts are refused with a `429` until the window ends.
+    pub login_max_attempts: u32,
+    pub login_window_minutes: u64,
+    /// Topics subscribers can opt into, offered on the signup form and the
+    /// subscription management page. Tagging is off while it is empty.
+    pub newsletter_tags: Vec<String>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -48,18 +100,46 @@ pub struct DatabaseSettings {
     pub cache_size: String,
     pub mmap_size: String,
     pub temp_store: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+    pub max_lifetime_secs: u64,
+    /// Runs `PRAGMA incremental_vacuum` in the background, tests turn it off.
+    pub vacuum_enabled: bool,
+    pub vacuum_interval_minutes: u64,
+    pub vacuum_pages_per_run: u32,
+    /// Only used with `journal_mode: "WAL"`.
+    pub wal_checkpoint_interval_minutes: u64,
 }
 
 pub async fn configure_database(config: &DatabaseSettings) -> anyhow::Result<SqlitePool> {
     // options -> pool -> migrate
     let options = config.connect_options()?;
-    let pool = SqlitePool::connect_with(options).await?;
+    tracing::info!(
+        max_connections = config.max_connections,
+        min_connections = config.min_connections,
+        acquire_timeout_secs = config.acquire_timeout_secs,
+        idle_timeout_secs = config.idle_timeout_secs,
+        max_lifetime_secs = config.max_lifetime_secs,
+        "Configuring the SQLite connection pool"
+    );
+    let pool = config.pool_options().connect_with(options).await?;
     // Run migrations automatically
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    MIGRATOR.run(&pool).await?;
     Ok(pool)
 }
 
 impl DatabaseSettings {
+    pub fn pool_options(&self) -> SqlitePoolOptions {
+        SqlitePoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_secs))
+            .idle_timeout(Duration::from_secs(self.idle_timeout_secs))
+            .max_lifetime(Duration::from_secs(self.max_lifetime_secs))
+    }
+
     pub fn connect_options(&self) -> anyhow::Result<SqliteConnectOptions> {
         let options =
             SqliteConnectOptions::from_str(&format!("sqlite://{}.db", self.database_path))?
@@ -96,6 +176,9 @@ pub struct EmailClientSettings {
     pub authorization_token: SecretString,
     // #[serde(deserialize_with = "deserialize_number_from_string")]
     pub timeout_milliseconds: u64,
+    /// Upper bound for the delivery worker to hand one email over to the
+    /// provider, a timed out delivery is retried like any other failure.
+    pub email_send_timeout_seconds: u64,
 }
 
 impl EmailClientSettings {
@@ -106,9 +189,140 @@ impl EmailClientSettings {
     pub fn timeout(&self) -> std::time::Duration {
         std::time::Duration::from_millis(self.timeout_milliseconds)
     }
+
+    pub fn send_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.email_send_timeout_seconds)
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct TurnstileSettings {
+    pub base_url: String,
+    pub secret_key: SecretString,
+    pub timeout_milliseconds: u64,
+}
+
+impl TurnstileSettings {
+    pub fn client(self, http_client: Arc<reqwest::Client>, test_mode: bool) -> TurnstileClient {
+        let timeout = std::time::Duration::from_millis(self.timeout_milliseconds);
+        TurnstileClient::new(
+            http_client,
+            self.base_url,
+            self.secret_key,
+            timeout,
+            test_mode,
+        )
+    }
+}
+
+/// The outgoing HTTP client shared by the email and Turnstile clients.
+#[derive(Deserialize, Clone)]
+pub struct HttpClientSettings {
+    /// Upper bound for any request, the API clients set tighter ones of their own.
+    pub timeout_milliseconds: u64,
+    pub connect_timeout_milliseconds: u64,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+}
+
+impl HttpClientSettings {
+    pub fn client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .timeout(Duration::from_millis(self.timeout_milliseconds))
+            .connect_timeout(Duration::from_millis(self.connect_timeout_milliseconds))
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(self.pool_idle_timeout_secs))
+            .build()
+            .expect("Failed to build the HTTP client.")
+    }
+}
+
+/// Attributes of the session cookie.
+#[derive(Deserialize, Clone)]
+pub struct SessionSettings {
+    /// Only send the cookie over HTTPS.
+    pub secure: bool,
+    /// `strict`, `lax` or `none`, the latter needs `secure`.
+    pub same_site: String,
+    /// Unset ties the cookie to the exact host that set it.
+    pub domain: Option<String>,
+}
+
+impl SessionSettings {
+    pub fn same_site(&self) -> Result<SameSite, String> {
+        match self.same_site.to_lowercase().as_str() {
+            "strict" => Ok(SameSite::Strict),
+            "lax" => Ok(SameSite::Lax),
+            "none" => Ok(SameSite::None),
+            other => Err(format!(
+                "{} is not a supported `SameSite` value. Use either `strict`, `lax` or `none`.",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct IssueDeliverySettings {
+    /// How many times a failed delivery is retried before it is moved to the
+    /// dead letter table.
+    pub max_retries: u8,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RateLimitSettings {
+    /// Applied to `POST /login` and `POST /subscriptions`.
+    pub strict: TokenBucketSettings,
+    /// Applied to every request.
+    pub default: TokenBucketSettings,
+    /// Applied to `GET /subscriptions/check-email`.
+    pub check_email: TokenBucketSettings,
 }
 
-pub fn get_configuration() -> Result<Settings, ConfigError> {
+#[derive(Deserialize, Clone)]
+pub struct TokenBucketSettings {
+    /// How many requests a client can burst.
+    pub capacity: u32,
+    pub refill_per_minute: u32,
+}
+
+#[derive(thiserror::Error)]
+pub enum ConfigurationError {
+    #[error("Failed to read {}", path.display())]
+    File {
+        path: PathBuf,
+        #[source]
+        source: ConfigError,
+    },
+    #[error("The configuration doesn't fit the settings")]
+    Deserialize(#[source] ConfigError),
+    #[error("`{key}` {reason}")]
+    Invalid { key: &'static str, reason: String },
+    #[error("Failed to parse APP_ENVIRONMENT: {0}")]
+    Environment(String),
+}
+
+impl std::fmt::Debug for ConfigurationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ConfigurationError {
+    fn invalid(key: &'static str, reason: impl Into<String>) -> Self {
+        Self::Invalid {
+            key,
+            reason: reason.into(),
+        }
+    }
+}
+
+pub fn get_configuration() -> Result<Settings, ConfigurationError> {
     let base_path = std::env::current_dir().expect("Failed to determine the current directory");
     let configuration_directory = base_path.join("configuration");
 
@@ -117,15 +331,53 @@ pub fn get_configuration() -> Result<Settings, ConfigError> {
     let environment: Environment = std::env::var("APP_ENVIRONMENT")
         .unwrap_or_else(|_| "local".into())
         .try_into()
-        .expect("Failed to parse APP_ENVIRONMENT.");
-    let environment_filename = format!("{}.yaml", environment.as_str());
-    let settings = Config::builder()
-        .add_source(config::File::from(
-            configuration_directory.join("base.yaml"),
-        ))
-        .add_source(config::File::from(
-            configuration_directory.join(environment_filename),
-        ))
+        .map_err(ConfigurationError::Environment)?;
+    load_configuration(&configuration_directory, environment)
+}
+
+/// Layers `<environment>.yaml` and the `APP_` environment variables over
+/// `base.yaml`, both files are looked up in `configuration_directory`.
+///
+/// A relative `database.database_path` is taken relative to the directory
+/// holding `configuration_directory`, not to wherever the app was started.
+fn load_configuration(
+    configuration_directory: &Path,
+    environment: Environment,
+) -> Result<Settings, ConfigurationError> {
+    let files = [
+        configuration_directory.join("base.yaml"),
+        configuration_directory.join(format!("{}.yaml", environment.as_str())),
+    ];
+    let environment_file = &files[1];
+    if environment == Environment::Local && !environment_file.exists() {
+        write_default_local_config(configuration_directory, environment_file).map_err(|e| {
+            ConfigurationError::File {
+                path: environment_file.clone(),
+                source: ConfigError::Foreign(Box::new(e)),
+            }
+        })?;
+        // the subscriber is set up from these settings, so it isn't there yet
+        eprintln!(
+            "Wrote a default local configuration to {}",
+            environment_file.display()
+        );
+    }
+    // parsed on their own first, so a syntax error names the file it is in
+    for path in &files {
+        Config::builder()
+            .add_source(config::File::from(path.as_path()))
+            .build()
+            .map_err(|source| ConfigurationError::File {
+                path: path.clone(),
+                source,
+            })?;
+    }
+
+    let settings = files
+        .iter()
+        .fold(Config::builder(), |builder, path| {
+            builder.add_source(config::File::from(path.as_path()))
+        })
         // Add in settings from environment variables (with a prefix of APP and '__' as separator)
         // E.g. `APP_APPLICATION__PORT=5001 would set `Settings.application.port`
         .add_source(
@@ -133,12 +385,137 @@ pub fn get_configuration() -> Result<Settings, ConfigError> {
                 .prefix_separator("_")
                 .separator("__"),
         )
-        .build()?;
+        .set_override("application.environment", environment.as_str())
+        .and_then(|builder| builder.build())
+        .map_err(ConfigurationError::Deserialize)?;
+
+    let mut settings = settings
+        .try_deserialize::<Settings>()
+        .map_err(ConfigurationError::Deserialize)?;
+    let database_path = Path::new(&settings.database.database_path);
+    if let Some(root) = configuration_directory.parent() {
+        if database_path.is_relative() {
+            settings.database.database_path = root.join(database_path).display().to_string();
+        }
+    }
+    Ok(settings)
+}
 
-    settings.try_deserialize::<Settings>()
+/// Settings for running the app on a fresh clone: SQLite under `data/`
+/// next to the configuration directory, Redis on its default port and
+/// Postmark's sandbox, which accepts emails without delivering them. The
+/// HMAC secret is generated, so every clone has its own.
+///
+/// The database path is written as is, relative, so that the file still
+/// works once the clone is moved.
+fn write_default_local_config(
+    configuration_directory: &Path,
+    path: &Path,
+) -> Result<(), std::io::Error> {
+    let data_directory = configuration_directory
+        .parent()
+        .unwrap_or(configuration_directory)
+        .join("data");
+    let hmac_secret: String = rand::rng()
+        .sample_iter(rand::distr::Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect();
+    let defaults = serde_json::json!({
+        "application": {
+            "host": "127.0.0.1",
+            "base_url": "http://127.0.0.1",
+            "hmac_secret": hmac_secret,
+        },
+        "database": {
+            "database_path": "data/newzletter",
+            "create_if_missing": true,
+        },
+        "redis_uri": "redis://localhost:6379",
+        "email_client": {
+            "base_url": "https://api.postmarkapp.com",
+            "authorization_token": "POSTMARK_API_TEST",
+        },
+        "session": {
+            "secure": false,
+            "same_site": "lax",
+        },
+    });
+    let yaml = serde_yaml::to_string(&defaults).map_err(std::io::Error::other)?;
+    // SQLite creates the database file, not the directory it goes in
+    std::fs::create_dir_all(&data_directory)?;
+    std::fs::write(
+        path,
+        format!(
+            "# Written because the file was missing, edit away.\n{}",
+            yaml
+        ),
+    )
+}
+
+impl Settings {
+    /// Values that deserialize fine but can't work together, reported all at
+    /// once so a broken deployment is fixed in one go.
+    pub fn validate(&self) -> Vec<ConfigurationError> {
+        let mut errors = Vec::new();
+        // port 0 picks any free port, fine for tests but not behind a proxy
+        if self.application.port == 0 && self.application.environment == Environment::Production {
+            errors.push(ConfigurationError::invalid(
+                "application.port",
+                "can't be 0 in production",
+            ));
+        }
+        if !(512..=65536).contains(&self.database.page_size)
+            || !self.database.page_size.is_power_of_two()
+        {
+            errors.push(ConfigurationError::invalid(
+                "database.page_size",
+                format!(
+                    "must be a power of 2 between 512 and 65536, got {}",
+                    self.database.page_size
+                ),
+            ));
+        }
+        if self.database.min_connections > self.database.max_connections {
+            errors.push(ConfigurationError::invalid(
+                "database.min_connections",
+                format!(
+                    "can't be more than `database.max_connections` ({})",
+                    self.database.max_connections
+                ),
+            ));
+        }
+        if self.application.pending_ttl_days == 0 {
+            errors.push(ConfigurationError::invalid(
+                "application.pending_ttl_days",
+                "must be more than 0",
+            ));
+        }
+        if self.email_client.timeout_milliseconds == 0 {
+            errors.push(ConfigurationError::invalid(
+                "email_client.timeout_milliseconds",
+                "must be more than 0",
+            ));
+        }
+        if let Err(e) = self.email_client.sender() {
+            errors.push(ConfigurationError::invalid("email_client.sender_email", e));
+        }
+        if self.turnstile.timeout_milliseconds == 0 {
+            errors.push(ConfigurationError::invalid(
+                "turnstile.timeout_milliseconds",
+                "must be more than 0",
+            ));
+        }
+        if let Err(e) = self.session.same_site() {
+            errors.push(ConfigurationError::invalid("session.same_site", e));
+        }
+        errors
+    }
 }
 
 /// The possible runtime environment for our application.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String")]
 pub enum Environment {
     Local,
     Production,
@@ -169,9 +546,208 @@ impl TryFrom<String> for Environment {
 }
 
 impl EmailClientSettings {
-    pub fn client(self) -> EmailClient {
+    pub fn client(self, http_client: Arc<reqwest::Client>) -> EmailClient {
         let sender = self.sender().expect("Invalid sender email address.");
         let timeout = self.timeout();
-        EmailClient::new(sender, self.base_url, self.authorization_token, timeout)
+        EmailClient::new(
+            http_client,
+            sender,
+            self.base_url,
+            self.authorization_token,
+            timeout,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_configuration, Environment, SessionSettings, Settings};
+    use secrecy::ExposeSecret;
+    use std::path::{Path, PathBuf};
+    use tower_sessions::cookie::SameSite;
+
+    fn settings() -> Settings {
+        load_configuration(Path::new("configuration"), Environment::Local)
+            .expect("Failed to read the configuration")
+    }
+
+    /// A configuration directory holding a copy of `local.yaml` and the
+    /// given `base.yaml`.
+    fn configuration_directory(base: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("base.yaml"), base).unwrap();
+        std::fs::copy("configuration/local.yaml", directory.join("local.yaml")).unwrap();
+        directory
+    }
+
+    fn error_keys(settings: &Settings) -> Vec<String> {
+        settings
+            .validate()
+            .iter()
+            .map(|e| e.to_string().split('`').nth(1).unwrap().to_string())
+            .collect()
+    }
+
+    fn session(same_site: &str) -> SessionSettings {
+        SessionSettings {
+            secure: true,
+            same_site: same_site.to_string(),
+            domain: None,
+        }
+    }
+
+    #[test]
+    fn same_site_values_are_parsed_ignoring_case() {
+        assert_eq!(session("strict").same_site(), Ok(SameSite::Strict));
+        assert_eq!(session("Lax").same_site(), Ok(SameSite::Lax));
+        assert_eq!(session("NONE").same_site(), Ok(SameSite::None));
+    }
+
+    #[test]
+    fn unknown_same_site_values_are_rejected() {
+        let error = session("sometimes").same_site().unwrap_err();
+        assert!(error.contains("sometimes"));
+    }
+
+    #[test]
+    fn the_checked_in_configuration_is_valid() {
+        assert!(settings().validate().is_empty());
+    }
+
+    #[test]
+    fn port_0_is_only_accepted_outside_production() {
+        let mut settings = settings();
+        settings.application.port = 0;
+        assert!(settings.validate().is_empty());
+
+        settings.application.environment = Environment::Production;
+        assert_eq!(error_keys(&settings), vec!["application.port"]);
+    }
+
+    #[test]
+    fn page_sizes_must_be_a_power_of_2() {
+        let mut settings = settings();
+        for page_size in [0, 256, 1000, 131072] {
+            settings.database.page_size = page_size;
+            assert_eq!(error_keys(&settings), vec!["database.page_size"]);
+        }
+        settings.database.page_size = 8192;
+        assert!(settings.validate().is_empty());
+    }
+
+    #[test]
+    fn the_pool_cannot_keep_more_connections_than_it_allows() {
+        let mut settings = settings();
+        settings.database.min_connections = settings.database.max_connections + 1;
+        assert_eq!(error_keys(&settings), vec!["database.min_connections"]);
+    }
+
+    #[test]
+    fn timeouts_must_be_more_than_0() {
+        let mut settings = settings();
+        settings.email_client.timeout_milliseconds = 0;
+        settings.turnstile.timeout_milliseconds = 0;
+        assert_eq!(
+            error_keys(&settings),
+            vec![
+                "email_client.timeout_milliseconds",
+                "turnstile.timeout_milliseconds"
+            ]
+        );
+    }
+
+    #[test]
+    fn the_sender_and_same_site_values_are_checked() {
+        let mut settings = settings();
+        settings.email_client.sender_email = "not-an-email".to_string();
+        settings.session.same_site = "sometimes".to_string();
+        let errors = settings.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].to_string().contains("sometimes"));
+    }
+
+    #[test]
+    fn invalid_yaml_names_the_file_it_is_in() {
+        let directory = configuration_directory("application: [port: 8080");
+
+        let Err(error) = load_configuration(&directory, Environment::Local) else {
+            panic!("The configuration was loaded");
+        };
+
+        let message = error.to_string();
+        assert!(message.starts_with("Failed to read"));
+        assert!(message.contains(&directory.join("base.yaml").display().to_string()));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn values_of_the_wrong_type_name_their_key() {
+        let base = std::fs::read_to_string("configuration/base.yaml")
+            .unwrap()
+            .replace("port: 8080", "port: eighty");
+        let directory = configuration_directory(&base);
+
+        let Err(error) = load_configuration(&directory, Environment::Local) else {
+            panic!("The configuration was loaded");
+        };
+
+        // the whole chain, as `main` prints it
+        let report = format!("{:?}", error);
+        assert!(report.contains("application.port"));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn a_missing_local_configuration_is_written_with_defaults() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let directory = root.join("configuration");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::copy("configuration/base.yaml", directory.join("base.yaml")).unwrap();
+
+        let settings = load_configuration(&directory, Environment::Local).unwrap();
+
+        assert!(directory.join("local.yaml").exists());
+        assert!(root.join("data").is_dir());
+        let written = std::fs::read_to_string(directory.join("local.yaml")).unwrap();
+        assert!(
+            written.contains("database_path: data/newzletter\n"),
+            "{}",
+            written
+        );
+        assert_eq!(
+            Path::new(&settings.database.database_path),
+            root.join("data").join("newzletter")
+        );
+        assert!(settings.database.create_if_missing);
+        assert_eq!(
+            settings.email_client.base_url,
+            "https://api.postmarkapp.com"
+        );
+        assert!(!settings.session.secure);
+        assert_eq!(settings.application.hmac_secret.expose_secret().len(), 64);
+        assert!(settings.validate().is_empty());
+        // written once, then left alone
+        load_configuration(&directory, Environment::Local).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(directory.join("local.yaml")).unwrap(),
+            written
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn a_missing_production_configuration_is_an_error() {
+        let directory = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::copy("configuration/base.yaml", directory.join("base.yaml")).unwrap();
+
+        let Err(error) = load_configuration(&directory, Environment::Production) else {
+            panic!("The configuration was loaded");
+        };
+
+        assert!(error.to_string().contains("production.yaml"));
+        assert!(!directory.join("production.yaml").exists());
+        std::fs::remove_dir_all(directory).unwrap();
     }
 }
@@ -0,0 +1,233 @@
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Instant;
+
+use futures_core::Stream;
+use sqlx::pool::PoolOptions;
+use sqlx::sqlite::{SqliteQueryResult, SqliteRow, SqliteStatement, SqliteTypeInfo};
+use sqlx::{Describe, Either, Execute, Executor, Sqlite, SqlitePool, Transaction};
+use tracing::Span;
+
+type BoxFuture<'a, T> = Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+type BoxStream<'a, T> = Pin<Box<dyn Stream<Item = T> + Send + 'a>>;
+type QueryItem = Result<Either<SqliteQueryResult, SqliteRow>, sqlx::Error>;
+
+/// A `SqlitePool` that records how long each query it runs took, and how many
+/// rows it touched, as `db_query_duration_ms`, `db_rows_affected` and
+/// `db_rows_returned` on the active span.
+///
+/// SQLite has no query plans to look at, so this is what we get. Spans only
+/// keep the fields they declare, the same numbers are also logged at `debug`.
+#[derive(Clone, Debug)]
+pub struct SqliteInstrumentedPool(SqlitePool);
+
+impl SqliteInstrumentedPool {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn begin(&self) -> Result<SqliteInstrumentedTransaction, sqlx::Error> {
+        self.0.begin().await.map(SqliteInstrumentedTransaction)
+    }
+
+    pub fn size(&self) -> u32 {
+        self.0.size()
+    }
+
+    pub fn num_idle(&self) -> usize {
+        self.0.num_idle()
+    }
+
+    pub fn options(&self) -> &PoolOptions<Sqlite> {
+        self.0.options()
+    }
+
+    pub async fn close(&self) {
+        self.0.close().await
+    }
+}
+
+impl<'c> Executor<'c> for &'c SqliteInstrumentedPool {
+    type Database = Sqlite;
+
+    // `execute` and `fetch_all` go through here too
+    fn fetch_many<'e, 'q: 'e, E>(self, query: E) -> BoxStream<'e, QueryItem>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        instrument_stream(self.0.fetch_many(query))
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<SqliteRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        instrument_row(self.0.fetch_optional(query))
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [SqliteTypeInfo],
+    ) -> BoxFuture<'e, Result<SqliteStatement<'q>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        self.0.prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, Result<Describe<Sqlite>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        self.0.describe(sql)
+    }
+}
+
+/// A transaction from [`SqliteInstrumentedPool::begin`], its queries are
+/// recorded the same way.
+#[derive(Debug)]
+pub struct SqliteInstrumentedTransaction(Transaction<'static, Sqlite>);
+
+impl SqliteInstrumentedTransaction {
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        self.0.commit().await
+    }
+
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        self.0.rollback().await
+    }
+}
+
+impl<'c> Executor<'c> for &'c mut SqliteInstrumentedTransaction {
+    type Database = Sqlite;
+
+    fn fetch_many<'e, 'q: 'e, E>(self, query: E) -> BoxStream<'e, QueryItem>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        instrument_stream((&mut *self.0).fetch_many(query))
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<SqliteRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Sqlite>,
+    {
+        instrument_row((&mut *self.0).fetch_optional(query))
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [SqliteTypeInfo],
+    ) -> BoxFuture<'e, Result<SqliteStatement<'q>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        (&mut *self.0).prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, Result<Describe<Sqlite>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        (&mut *self.0).describe(sql)
+    }
+}
+
+fn instrument_stream(inner: BoxStream<'_, QueryItem>) -> BoxStream<'_, QueryItem> {
+    Box::pin(InstrumentedStream {
+        inner,
+        recorder: QueryRecorder::start(),
+    })
+}
+
+fn instrument_row(
+    row: BoxFuture<'_, Result<Option<SqliteRow>, sqlx::Error>>,
+) -> BoxFuture<'_, Result<Option<SqliteRow>, sqlx::Error>> {
+    let recorder = QueryRecorder::start();
+    Box::pin(async move {
+        // moved in whole, so that it only records once the query is done
+        let mut recorder = recorder;
+        let row = row.await?;
+        recorder.rows_returned = u64::from(row.is_some());
+        Ok(row)
+    })
+}
+
+/// Records the query on the span that was active when it started, once it
+/// has been read to the end, has failed or has been dropped.
+struct QueryRecorder {
+    span: Span,
+    started_at: Instant,
+    rows_affected: u64,
+    rows_returned: u64,
+}
+
+impl QueryRecorder {
+    fn start() -> Self {
+        Self {
+            span: Span::current(),
+            started_at: Instant::now(),
+            rows_affected: 0,
+            rows_returned: 0,
+        }
+    }
+
+    fn observe(&mut self, item: &Either<SqliteQueryResult, SqliteRow>) {
+        match item {
+            Either::Left(result) => self.rows_affected += result.rows_affected(),
+            Either::Right(_) => self.rows_returned += 1,
+        }
+    }
+}
+
+impl Drop for QueryRecorder {
+    fn drop(&mut self) {
+        let duration_ms = self.started_at.elapsed().as_millis() as u64;
+        self.span
+            .record("db_query_duration_ms", duration_ms)
+            .record("db_rows_affected", self.rows_affected)
+            .record("db_rows_returned", self.rows_returned);
+        tracing::debug!(
+            parent: &self.span,
+            db_query_duration_ms = duration_ms,
+            db_rows_affected = self.rows_affected,
+            db_rows_returned = self.rows_returned,
+            "Ran a database query"
+        );
+    }
+}
+
+struct InstrumentedStream<'e> {
+    inner: BoxStream<'e, QueryItem>,
+    recorder: QueryRecorder,
+}
+
+impl Stream for InstrumentedStream<'_> {
+    type Item = QueryItem;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.inner.as_mut().poll_next(cx));
+        if let Some(Ok(item)) = &item {
+            self.recorder.observe(item);
+        }
+        Poll::Ready(item)
+    }
+}
@@ -1,7 +1,14 @@
 mod new_subscriber;
 mod subscriber_email;
 mod subscriber_name;
+mod unsubscribe_token;
+mod validation_error;
 
-pub use new_subscriber::NewSubscriber;
+pub use new_subscriber::{NewSubscriber, NewSubscriberBuilder};
 pub use subscriber_email::SubscriberEmail;
 pub use subscriber_name::SubscriberName;
+pub use unsubscribe_token::{
+    generate_unsubscribe_token, verify_unsubscribe_token, UnsubscribeTokenError,
+    UNSUBSCRIBE_URL_PLACEHOLDER,
+};
+pub use validation_error::ValidationError;
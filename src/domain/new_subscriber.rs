@@ -1,6 +1,84 @@
-use super::{subscriber_email::SubscriberEmail, subscriber_name::SubscriberName};
+use super::{
+    subscriber_email::SubscriberEmail, subscriber_name::SubscriberName,
+    validation_error::ValidationError,
+};
 
+#[derive(Debug)]
 pub struct NewSubscriber {
     pub name: SubscriberName,
     pub email: SubscriberEmail,
 }
+
+impl NewSubscriber {
+    pub fn builder() -> NewSubscriberBuilder {
+        NewSubscriberBuilder::default()
+    }
+}
+
+/// Parses every field before giving up, so that all of the problems can be
+/// shown at once. A missing field is treated as an empty one.
+#[derive(Default)]
+pub struct NewSubscriberBuilder {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+impl NewSubscriberBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<NewSubscriber, Vec<String>> {
+        let name = SubscriberName::parse(self.name.unwrap_or_default());
+        let email = SubscriberEmail::parse(self.email.unwrap_or_default())
+            .map_err(|e| ValidationError::new("email", e));
+        match (name, email) {
+            (Ok(name), Ok(email)) => Ok(NewSubscriber { name, email }),
+            (name, email) => Err([name.err(), email.err()]
+                .into_iter()
+                .flatten()
+                .map(|e| e.to_string())
+                .collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::NewSubscriber;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn a_valid_name_and_email_are_accepted() {
+        let subscriber = assert_ok!(NewSubscriber::builder()
+            .name("abood")
+            .email("abood@example.com")
+            .build());
+        assert_eq!(subscriber.name.as_ref(), "abood");
+        assert_eq!(subscriber.email.as_ref(), "abood@example.com");
+    }
+
+    #[test]
+    fn a_bad_name_and_a_bad_email_are_both_reported() {
+        let errors = assert_err!(NewSubscriber::builder()
+            .name("")
+            .email("not-an-email")
+            .build());
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("name: "));
+        assert!(errors[1].starts_with("email: "));
+    }
+
+    #[test]
+    fn missing_fields_are_reported() {
+        let errors = assert_err!(NewSubscriber::builder().email("abood@example.com").build());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("name: "));
+    }
+}
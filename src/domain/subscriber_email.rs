@@ -1,6 +1,7 @@
-use validator::ValidateEmail;
+use email_address::{EmailAddress, Options};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SubscriberEmail(String);
 
 impl std::fmt::Display for SubscriberEmail {
@@ -10,11 +11,16 @@ impl std::fmt::Display for SubscriberEmail {
 }
 
 impl SubscriberEmail {
+    /// Validates the address against RFC 5321, internationalized domains,
+    /// quoted local parts and IP literals included.
+    ///
+    /// Display names (`Ursula <ursula@domain.com>`) are rejected, we only
+    /// store the address itself.
     pub fn parse(s: String) -> Result<SubscriberEmail, String> {
-        if s.validate_email() {
-            Ok(Self(s))
-        } else {
-            Err(format!("{} is not a valid subscriber email.", s))
+        let options = Options::default().without_display_text();
+        match EmailAddress::parse_with_options(&s, options) {
+            Ok(_) => Ok(Self(s)),
+            Err(_) => Err(format!("{} is not a valid subscriber email.", s)),
         }
     }
 }
@@ -25,10 +31,23 @@ impl AsRef<str> for SubscriberEmail {
     }
 }
 
+impl Serialize for SubscriberEmail {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SubscriberEmail {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        SubscriberEmail::parse(s).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::SubscriberEmail;
-    use claims::assert_err;
+    use claims::{assert_err, assert_ok};
     use fake::faker::internet::en::SafeEmail;
     use fake::Fake;
     use rand::rngs::StdRng;
@@ -52,6 +71,52 @@ mod tests {
         assert_err!(SubscriberEmail::parse(email));
     }
 
+    #[test]
+    fn email_with_display_name_is_rejected() {
+        let email = "Ursula <ursula@domain.com>".to_string();
+        assert_err!(SubscriberEmail::parse(email));
+    }
+
+    #[test]
+    fn internationalized_domain_is_accepted() {
+        let email = "user@münchen.de".to_string();
+        assert_ok!(SubscriberEmail::parse(email));
+    }
+
+    #[test]
+    fn quoted_local_part_is_accepted() {
+        let email = r#""user name"@example.com"#.to_string();
+        assert_ok!(SubscriberEmail::parse(email));
+    }
+
+    #[test]
+    fn ip_literal_host_is_accepted() {
+        let email = "user@[127.0.0.1]".to_string();
+        assert_ok!(SubscriberEmail::parse(email));
+    }
+
+    #[test]
+    fn emails_round_trip_through_json() {
+        let email = SubscriberEmail::parse("ursula@domain.com".to_string()).unwrap();
+        let json = serde_json::to_string(&email).unwrap();
+        assert_eq!(json, r#""ursula@domain.com""#);
+        let parsed: SubscriberEmail = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.as_ref(), "ursula@domain.com");
+    }
+
+    #[test]
+    fn invalid_emails_are_rejected_when_deserializing() {
+        assert_err!(serde_json::from_str::<SubscriberEmail>(
+            r#""ursuladomain.com""#
+        ));
+    }
+
+    #[test]
+    fn empty_emails_are_rejected_when_deserializing() {
+        let error = serde_json::from_str::<SubscriberEmail>(r#""""#).unwrap_err();
+        assert_eq!(error.to_string(), " is not a valid subscriber email.");
+    }
+
     #[derive(Debug, Clone)]
     struct ValidEmailFixture(pub String);
 
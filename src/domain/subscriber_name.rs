@@ -1,27 +1,41 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use unicode_segmentation::UnicodeSegmentation;
 
+use super::ValidationError;
+
+/// Names end up in HTML emails, so characters that could break out of the
+/// markup are rejected.
+const FORBIDDEN_CHARACTERS: [char; 11] = ['<', '>', '"', '\'', ';', '(', ')', '{', '}', '\\', '/'];
+
 #[derive(Debug)]
 pub struct SubscriberName(String);
 
 impl SubscriberName {
-    pub fn parse(s: String) -> Result<SubscriberName, String> {
-        let is_empty_or_whitespace = s.trim().is_empty();
+    pub fn parse(s: String) -> Result<SubscriberName, ValidationError> {
+        let invalid = |reason: &str| Err(ValidationError::new("name", reason));
+
+        if s.trim().is_empty() {
+            return invalid("The name can't be empty.");
+        }
 
         // A grapheme is defined by the Unicode standard as a "user-perceived"
         // character: `å` is a single grapheme, but it is composed of two characters
         // (`a` and `̊`).
-        let is_too_long = s.graphemes(true).count() > 256;
+        let length = s.trim().graphemes(true).count();
+        if length < 2 {
+            return invalid("The name must be at least 2 characters long.");
+        }
+        if s.graphemes(true).count() > 256 {
+            return invalid("The name must be at most 256 characters long.");
+        }
 
         // it's better to use array rather than dealing with the overhead of
         // hashing in a hashset for a small size
-        let forbidden_characters = ['/', '(', ')', '"', '<', '>', '\\', '{', '}'];
-        let contains_forbidden_characters = s.chars().any(|g| forbidden_characters.contains(&g));
-
-        if is_empty_or_whitespace || is_too_long || contains_forbidden_characters {
-            Err(format!("{} is not a valid subscriber name.", s))
-        } else {
-            Ok(Self(s))
+        if s.chars().any(|c| FORBIDDEN_CHARACTERS.contains(&c)) {
+            return invalid("The name can't contain any of < > \" ' ; ( ) { } \\ /");
         }
+
+        Ok(Self(s))
     }
 }
 
@@ -31,6 +45,19 @@ impl AsRef<str> for SubscriberName {
     }
 }
 
+impl Serialize for SubscriberName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SubscriberName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        SubscriberName::parse(s).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::domain::subscriber_name::SubscriberName;
@@ -60,14 +87,50 @@ mod tests {
         assert_err!(SubscriberName::parse(name));
     }
 
+    #[test]
+    fn a_single_character_name_is_rejected() {
+        assert_err!(SubscriberName::parse("a".to_string()));
+        assert_err!(SubscriberName::parse("  å  ".to_string()));
+    }
+
+    #[test]
+    fn a_two_character_name_is_valid() {
+        assert_ok!(SubscriberName::parse("Al".to_string()));
+    }
+
     #[test]
     fn names_containing_an_invalid_character_are_rejected() {
-        for name in &['/', '(', ')', '"', '<', '>', '\\', '{', '}'] {
-            let name = name.to_string();
+        for name in &['/', '(', ')', '"', '\'', ';', '<', '>', '\\', '{', '}'] {
+            let name = format!("Ursula {}", name);
             assert_err!(SubscriberName::parse(name));
         }
     }
 
+    #[test]
+    fn the_error_names_the_field_and_the_reason() {
+        let error = SubscriberName::parse("Le <Guin>".to_string()).unwrap_err();
+        assert_eq!(error.field, "name");
+        assert!(error.reason.contains("can't contain"));
+
+        let error = SubscriberName::parse("U".to_string()).unwrap_err();
+        assert_eq!(error.reason, "The name must be at least 2 characters long.");
+    }
+
+    #[test]
+    fn names_round_trip_through_json() {
+        let name = SubscriberName::parse("Ursula Le Guin".to_string()).unwrap();
+        let json = serde_json::to_string(&name).unwrap();
+        assert_eq!(json, r#""Ursula Le Guin""#);
+        let parsed: SubscriberName = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.as_ref(), "Ursula Le Guin");
+    }
+
+    #[test]
+    fn empty_names_are_rejected_when_deserializing() {
+        let error = serde_json::from_str::<SubscriberName>(r#""""#).unwrap_err();
+        assert_eq!(error.to_string(), "name: The name can't be empty.");
+    }
+
     #[test]
     fn a_valid_name_is_parsed_successfully() {
         let name = "Shekohex".to_string();
@@ -0,0 +1,127 @@
+use anyhow::Context;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::startup::HmacSecret;
+
+/// How long an unsubscribe link stays valid after it has been sent out.
+const UNSUBSCRIBE_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Replaced in the newsletter templates with the link of each subscriber when
+/// the issue is delivered.
+pub const UNSUBSCRIBE_URL_PLACEHOLDER: &str = "{UNSUBSCRIBE_URL}";
+
+#[derive(thiserror::Error, Debug)]
+pub enum UnsubscribeTokenError {
+    #[error("The unsubscribe token is invalid.")]
+    Invalid(#[source] anyhow::Error),
+    #[error("The unsubscribe token has expired.")]
+    Expired,
+}
+
+/// Build a signed token of the form `<subscriber_id>.<expires_at>.<hex(hmac)>`,
+/// valid for 30 days.
+pub fn generate_unsubscribe_token(subscriber_id: Uuid, secret: &HmacSecret) -> String {
+    let expires_at = Utc::now() + chrono::Duration::days(UNSUBSCRIBE_TOKEN_TTL_DAYS);
+    build_token(subscriber_id, expires_at.timestamp(), secret)
+}
+
+fn build_token(subscriber_id: Uuid, expires_at: i64, secret: &HmacSecret) -> String {
+    let payload = format!("{}.{}", subscriber_id, expires_at);
+    let tag = hex::encode(hmac_for(&payload, secret).finalize().into_bytes());
+    format!("{}.{}", payload, tag)
+}
+
+fn hmac_for(payload: &str, secret: &HmacSecret) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.0.expose_secret().as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Check the signature and the expiry of a token, returning the subscriber
+/// it was generated for.
+pub fn verify_unsubscribe_token(
+    token: &str,
+    secret: &HmacSecret,
+) -> Result<Uuid, UnsubscribeTokenError> {
+    let (payload, tag) = token
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow::anyhow!("The token is missing its signature."))
+        .map_err(UnsubscribeTokenError::Invalid)?;
+    let tag = hex::decode(tag)
+        .context("The token signature is not valid hex.")
+        .map_err(UnsubscribeTokenError::Invalid)?;
+    hmac_for(payload, secret)
+        .verify_slice(&tag)
+        .context("The token signature does not match.")
+        .map_err(UnsubscribeTokenError::Invalid)?;
+
+    let (subscriber_id, expires_at) = payload
+        .split_once('.')
+        .ok_or_else(|| anyhow::anyhow!("The token is missing its expiry."))
+        .map_err(UnsubscribeTokenError::Invalid)?;
+    let subscriber_id = Uuid::try_parse(subscriber_id)
+        .context("The token does not contain a valid subscriber id.")
+        .map_err(UnsubscribeTokenError::Invalid)?;
+    let expires_at: i64 = expires_at
+        .parse()
+        .context("The token does not contain a valid expiry.")
+        .map_err(UnsubscribeTokenError::Invalid)?;
+
+    if Utc::now().timestamp() > expires_at {
+        return Err(UnsubscribeTokenError::Expired);
+    }
+    Ok(subscriber_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_token, generate_unsubscribe_token, verify_unsubscribe_token, UnsubscribeTokenError,
+    };
+    use crate::startup::HmacSecret;
+    use chrono::Utc;
+    use claims::{assert_err, assert_ok_eq};
+    use secrecy::SecretString;
+    use uuid::Uuid;
+
+    fn secret() -> HmacSecret {
+        HmacSecret(SecretString::from("super-secret-test-key"))
+    }
+
+    #[test]
+    fn a_freshly_generated_token_is_valid() {
+        let subscriber_id = Uuid::new_v4();
+        let token = generate_unsubscribe_token(subscriber_id, &secret());
+        assert_ok_eq!(verify_unsubscribe_token(&token, &secret()), subscriber_id);
+    }
+
+    #[test]
+    fn a_token_signed_with_another_secret_is_rejected() {
+        let other_secret = HmacSecret(SecretString::from("another-secret"));
+        let token = generate_unsubscribe_token(Uuid::new_v4(), &other_secret);
+        assert_err!(verify_unsubscribe_token(&token, &secret()));
+    }
+
+    #[test]
+    fn a_tampered_token_is_rejected() {
+        let token = generate_unsubscribe_token(Uuid::new_v4(), &secret());
+        let (_, rest) = token.split_once('.').unwrap();
+        let tampered = format!("{}.{}", Uuid::new_v4(), rest);
+        assert_err!(verify_unsubscribe_token(&tampered, &secret()));
+    }
+
+    #[test]
+    fn an_expired_token_is_rejected_as_expired() {
+        let expires_at = Utc::now().timestamp() - 1;
+        let token = build_token(Uuid::new_v4(), expires_at, &secret());
+        assert!(matches!(
+            verify_unsubscribe_token(&token, &secret()),
+            Err(UnsubscribeTokenError::Expired)
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use reqwest::{Client, Url};
 use secrecy::{ExposeSecret, SecretString};
 use serde::Serialize;
@@ -5,10 +7,11 @@ use serde::Serialize;
 use crate::domain::SubscriberEmail;
 
 pub struct EmailClient {
-    http_client: reqwest::Client,
+    http_client: Arc<Client>,
     sender: SubscriberEmail,
     base_url: String,
     authorization_token: SecretString,
+    timeout: std::time::Duration,
 }
 
 #[derive(Serialize)]
@@ -19,29 +22,66 @@ struct SendEmailRequest<'a> {
     subject: &'a str,
     html_body: &'a str,
     text_body: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_to: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<EmailHeader>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EmailHeader {
+    pub name: &'static str,
+    pub value: String,
+}
+
+/// RFC 8058 one-click unsubscribe, required by the big providers for bulk senders.
+pub fn list_unsubscribe_headers(unsubscribe_url: &str) -> Vec<EmailHeader> {
+    vec![
+        EmailHeader {
+            name: "List-Unsubscribe",
+            value: format!("<{}>", unsubscribe_url),
+        },
+        EmailHeader {
+            name: "List-Unsubscribe-Post",
+            value: "List-Unsubscribe=One-Click".to_string(),
+        },
+    ]
 }
 
 impl EmailClient {
+    /// The `http_client` is shared with the rest of the application so that
+    /// connections to the email API are pooled, `timeout` applies to each
+    /// email sent.
     pub fn new(
+        http_client: Arc<Client>,
         sender: SubscriberEmail,
         base_url: String,
         authorization_token: SecretString,
         timeout: std::time::Duration,
     ) -> Self {
         Self {
-            http_client: Client::builder().timeout(timeout).build().unwrap(),
+            http_client,
             sender,
             base_url,
             authorization_token,
+            timeout,
         }
     }
 
+    pub fn sender(&self) -> &SubscriberEmail {
+        &self.sender
+    }
+
+    /// Replies go to the sender when `reply_to` is missing.
     pub async fn send_email(
         &self,
         recipient: &SubscriberEmail,
         subject: &str,
         html_content: &str,
         text_content: &str,
+        unsubscribe_url: Option<&str>,
+        reply_to: Option<&SubscriberEmail>,
     ) -> Result<(), reqwest::Error> {
         let base = Url::parse(&self.base_url).expect("url from config is wrong");
         let url = base
@@ -53,9 +93,14 @@ impl EmailClient {
             subject,
             html_body: html_content,
             text_body: text_content,
+            reply_to: reply_to.map(AsRef::as_ref),
+            headers: unsubscribe_url
+                .map(list_unsubscribe_headers)
+                .unwrap_or_default(),
         };
         self.http_client
             .post(url)
+            .timeout(self.timeout)
             .header(
                 "X-Postmark-Server-Token",
                 self.authorization_token.expose_secret(),
@@ -79,6 +124,7 @@ mod tests {
         Fake, Faker,
     };
     use secrecy::SecretString;
+    use std::sync::Arc;
     use wiremock::{
         matchers::{any, header, header_exists, method, path},
         Mock, MockServer, ResponseTemplate,
@@ -104,6 +150,7 @@ mod tests {
     /// Get a test instance of `EmailClient`.
     fn email_client(base_url: String) -> EmailClient {
         EmailClient::new(
+            Arc::new(reqwest::Client::new()),
             email(),
             base_url,
             SecretString::from(Faker.fake::<String>()),
@@ -149,9 +196,143 @@ mod tests {
             .await;
         // Act
         let _ = email_client
-            .send_email(&email(), &subject(), &content(), &content())
+            .send_email(&email(), &subject(), &content(), &content(), None, None)
+            .await;
+        // Assert
+    }
+
+    struct UnsubscribeHeadersMatcher(Option<String>);
+
+    impl wiremock::Match for UnsubscribeHeadersMatcher {
+        fn matches(&self, request: &wiremock::Request) -> bool {
+            let Ok(body) = request.body_json::<serde_json::Value>() else {
+                return false;
+            };
+            match &self.0 {
+                Some(url) => {
+                    body["Headers"]
+                        == serde_json::json!([
+                            {"Name": "List-Unsubscribe", "Value": format!("<{}>", url)},
+                            {"Name": "List-Unsubscribe-Post", "Value": "List-Unsubscribe=One-Click"},
+                        ])
+                }
+                None => body.get("Headers").is_none(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn send_email_adds_list_unsubscribe_headers_when_given_an_unsubscribe_url() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        let unsubscribe_url = "https://example.com/subscriptions/unsubscribe?token=abc";
+
+        Mock::given(UnsubscribeHeadersMatcher(Some(unsubscribe_url.to_string())))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = email_client
+            .send_email(
+                &email(),
+                &subject(),
+                &content(),
+                &content(),
+                Some(unsubscribe_url),
+                None,
+            )
+            .await;
+
+        // Assert
+        assert_ok!(outcome);
+    }
+
+    #[tokio::test]
+    async fn send_email_omits_list_unsubscribe_headers_without_an_unsubscribe_url() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(UnsubscribeHeadersMatcher(None))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
             .await;
+
+        // Act
+        let outcome = email_client
+            .send_email(&email(), &subject(), &content(), &content(), None, None)
+            .await;
+
         // Assert
+        assert_ok!(outcome);
+    }
+
+    struct ReplyToMatcher(Option<String>);
+
+    impl wiremock::Match for ReplyToMatcher {
+        fn matches(&self, request: &wiremock::Request) -> bool {
+            let Ok(body) = request.body_json::<serde_json::Value>() else {
+                return false;
+            };
+            match &self.0 {
+                Some(reply_to) => body["ReplyTo"] == reply_to.as_str(),
+                None => body.get("ReplyTo").is_none(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn send_email_sets_the_reply_to_address_when_given_one() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        let reply_to = email();
+
+        Mock::given(ReplyToMatcher(Some(reply_to.as_ref().to_string())))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = email_client
+            .send_email(
+                &email(),
+                &subject(),
+                &content(),
+                &content(),
+                None,
+                Some(&reply_to),
+            )
+            .await;
+
+        // Assert
+        assert_ok!(outcome);
+    }
+
+    #[tokio::test]
+    async fn send_email_omits_the_reply_to_address_without_one() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(ReplyToMatcher(None))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = email_client
+            .send_email(&email(), &subject(), &content(), &content(), None, None)
+            .await;
+
+        // Assert
+        assert_ok!(outcome);
     }
 
     #[tokio::test]
@@ -168,7 +349,7 @@ mod tests {
 
         // Act
         let outcome = email_client
-            .send_email(&email(), &subject(), &content(), &content())
+            .send_email(&email(), &subject(), &content(), &content(), None, None)
             .await;
 
         // Assert
@@ -190,7 +371,7 @@ mod tests {
 
         // Act
         let outcome = email_client
-            .send_email(&email(), &subject(), &content(), &content())
+            .send_email(&email(), &subject(), &content(), &content(), None, None)
             .await;
 
         // Assert
@@ -212,7 +393,7 @@ mod tests {
 
         // Act
         let outcome = email_client
-            .send_email(&email(), &subject(), &content(), &content())
+            .send_email(&email(), &subject(), &content(), &content(), None, None)
             .await;
 
         // Assert
@@ -0,0 +1,137 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+
+use crate::routes::error_chain_fmt;
+
+/// What a handler can fail with when there is nothing more specific to say.
+///
+/// Rendered as `{ "error": "<code>", "message": "..." }`, browsers are
+/// redirected instead by [`crate::middleware::negotiate_error_format`].
+#[derive(thiserror::Error)]
+pub enum AppError {
+    #[error("Not found.")]
+    NotFound,
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Authentication required.")]
+    Unauthorized,
+    // the cause stays in the logs, clients only get the generic message
+    #[error("Something went wrong on our side.")]
+    InternalError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// Attached to every response rendered from an [`AppError`], so middleware
+/// can tell them apart from other error responses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorCode(pub &'static str);
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: &'static str,
+    message: String,
+}
+
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound => "not_found",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Unauthorized => "unauthorized",
+            AppError::InternalError(_) => "server",
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match self {
+            AppError::InternalError(_) => tracing::error!(cause_chain = ?self),
+            _ => tracing::warn!(cause_chain = ?self),
+        }
+        let code = self.code();
+        let mut response = (
+            self.status_code(),
+            Json(ErrorBody {
+                error: code,
+                message: self.to_string(),
+            }),
+        )
+            .into_response();
+        response.extensions_mut().insert(ErrorCode(code));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppError, ErrorCode};
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    async fn render(error: AppError) -> (StatusCode, Option<ErrorCode>, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let code = response.extensions().get::<ErrorCode>().copied();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, code, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn not_found_is_a_404() {
+        let (status, code, body) = render(AppError::NotFound).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(code, Some(ErrorCode("not_found")));
+        assert_eq!(body["error"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn bad_request_is_a_400_carrying_its_message() {
+        let (status, code, body) =
+            render(AppError::BadRequest("`page` must be positive.".into())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(code, Some(ErrorCode("bad_request")));
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "error": "bad_request",
+                "message": "`page` must be positive.",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn unauthorized_is_a_401() {
+        let (status, code, body) = render(AppError::Unauthorized).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(code, Some(ErrorCode("unauthorized")));
+        assert_eq!(body["error"], "unauthorized");
+    }
+
+    #[tokio::test]
+    async fn internal_error_is_a_500_hiding_its_cause() {
+        let error = AppError::InternalError(anyhow::anyhow!("database is locked"));
+        let (status, code, body) = render(error).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(code, Some(ErrorCode("server")));
+        assert_eq!(body["error"], "server");
+        assert!(!body["message"].as_str().unwrap().contains("locked"));
+    }
+}
@@ -1,6 +1,7 @@
 mod key;
 mod persistence;
 pub use key::IdempotencyKey;
+pub use persistence::cleanup_expired_idempotency_keys;
 pub use persistence::get_saved_response;
 pub use persistence::save_response;
 pub use persistence::{try_processing, NextAction};
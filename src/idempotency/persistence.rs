@@ -2,13 +2,21 @@ use axum::{body::to_bytes, http, response::Response};
 use chrono::Utc;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use sqlx::{Sqlite, SqlitePool, Transaction};
 use uuid::Uuid;
 
 use super::IdempotencyKey;
+use crate::db::{SqliteInstrumentedPool, SqliteInstrumentedTransaction};
 
+#[tracing::instrument(
+    skip_all,
+    fields(
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_affected = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty
+    )
+)]
 pub async fn get_saved_response(
-    pool: &SqlitePool,
+    pool: &SqliteInstrumentedPool,
     idempotency_key: &IdempotencyKey,
     user_id: Uuid,
 ) -> Result<Option<Response<Vec<u8>>>, anyhow::Error> {
@@ -59,10 +67,18 @@ struct HeaderPair {
     value: Vec<u8>,
 }
 
+#[tracing::instrument(
+    skip_all,
+    fields(
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_affected = tracing::field::Empty
+    )
+)]
 pub async fn save_response(
-    mut transaction: Transaction<'static, Sqlite>,
+    mut transaction: SqliteInstrumentedTransaction,
     idempotency_key: &IdempotencyKey,
     user_id: Uuid,
+    newsletter_issue_uuid: Option<Uuid>,
     http_response: axum::response::Response,
 ) -> Result<Response, anyhow::Error> {
     let (response_head, body) = http_response.into_parts();
@@ -85,6 +101,7 @@ pub async fn save_response(
     let idempotency_key = idempotency_key.as_ref().to_string();
     let headers = serde_json::to_string(&headers)?;
     let body = body.to_vec();
+    let newsletter_issue_uuid = newsletter_issue_uuid.map(|uuid| uuid.to_string());
 
     sqlx::query!(
         r#"
@@ -92,7 +109,8 @@ pub async fn save_response(
             SET
                 response_status_code = $3,
                 response_headers = $4,
-                response_body = $5
+                response_body = $5,
+                newsletter_issue_uuid = $6
             WHERE
                 user_uuid = $1 AND
                 idempotency_key = $2
@@ -101,9 +119,10 @@ pub async fn save_response(
         idempotency_key,
         status_code,
         headers,
-        body
+        body,
+        newsletter_issue_uuid
     )
-    .execute(&mut *transaction)
+    .execute(&mut transaction)
     .await?;
 
     transaction.commit().await?;
@@ -113,19 +132,43 @@ pub async fn save_response(
 }
 
 pub enum NextAction {
-    ReturnSavedResponse(Response),
-    StartProcessing(Transaction<'static, Sqlite>),
+    ReturnSavedResponse {
+        saved_response: Response,
+        /// The issue published the first time around, its deliveries may
+        /// have to be resumed.
+        newsletter_issue_uuid: Option<Uuid>,
+    },
+    StartProcessing(SqliteInstrumentedTransaction),
 }
 
 pub async fn try_processing(
-    pool: &SqlitePool,
+    pool: &SqliteInstrumentedPool,
     idempotency_key: &IdempotencyKey,
     user_id: Uuid,
+    ttl_hours: u64,
 ) -> Result<NextAction, anyhow::Error> {
     let mut transaction = pool.begin().await?;
     let user_id_string = user_id.to_string();
     let idempotency_key_string = idempotency_key.as_ref().to_owned();
     let now = Utc::now().to_string();
+    let expired_before = expiry_cutoff(ttl_hours)?;
+
+    // an expired key is treated as if it was never used
+    sqlx::query!(
+        r#"
+            DELETE FROM idempotency
+            WHERE
+                user_uuid = $1 AND
+                idempotency_key = $2 AND
+                created_at < $3
+        "#,
+        user_id_string,
+        idempotency_key_string,
+        expired_before
+    )
+    .execute(&mut transaction)
+    .await?;
+
     let n_inserted_rows = sqlx::query!(
         r#"
             INSERT INTO idempotency (
@@ -140,7 +183,7 @@ pub async fn try_processing(
         idempotency_key_string,
         now
     )
-    .execute(&mut *transaction)
+    .execute(&mut transaction)
     .await?
     .rows_affected();
 
@@ -150,9 +193,66 @@ pub async fn try_processing(
         let saved_response = get_saved_response(pool, &idempotency_key, user_id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("We expected a saved response, we didn't find it"))?;
+        let newsletter_issue_uuid =
+            get_newsletter_issue_uuid(pool, idempotency_key, user_id).await?;
 
-        Ok(NextAction::ReturnSavedResponse(
-            saved_response.map(axum::body::Body::from),
-        ))
+        Ok(NextAction::ReturnSavedResponse {
+            saved_response: saved_response.map(axum::body::Body::from),
+            newsletter_issue_uuid,
+        })
     }
 }
+
+#[tracing::instrument(skip_all)]
+async fn get_newsletter_issue_uuid(
+    pool: &SqliteInstrumentedPool,
+    idempotency_key: &IdempotencyKey,
+    user_id: Uuid,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let user_id = user_id.to_string();
+    let idempotency_key = idempotency_key.as_ref().to_string();
+    let newsletter_issue_uuid = sqlx::query_scalar!(
+        r#"
+            SELECT newsletter_issue_uuid
+            FROM idempotency
+            WHERE
+                user_uuid = $1 AND
+                idempotency_key = $2
+        "#,
+        user_id,
+        idempotency_key,
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+    Ok(newsletter_issue_uuid
+        .map(|uuid| Uuid::parse_str(&uuid))
+        .transpose()?)
+}
+
+fn expiry_cutoff(ttl_hours: u64) -> Result<String, anyhow::Error> {
+    let ttl = chrono::Duration::try_hours(ttl_hours.try_into()?)
+        .ok_or_else(|| anyhow::anyhow!("The idempotency TTL is too large"))?;
+    Ok((Utc::now() - ttl).to_string())
+}
+
+/// Delete the idempotency keys that are older than `ttl_hours`, returning how many
+/// of them were removed.
+#[tracing::instrument(skip(pool))]
+pub async fn cleanup_expired_idempotency_keys(
+    pool: &SqliteInstrumentedPool,
+    ttl_hours: u64,
+) -> Result<u64, anyhow::Error> {
+    let expired_before = expiry_cutoff(ttl_hours)?;
+    let n_deleted_rows = sqlx::query!(
+        r#"
+            DELETE FROM idempotency
+            WHERE created_at < $1
+        "#,
+        expired_before
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(n_deleted_rows)
+}
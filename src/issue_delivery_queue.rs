@@ -0,0 +1,117 @@
+use crate::db::{SqliteInstrumentedPool, SqliteInstrumentedTransaction};
+use anyhow::Context;
+use chrono::Utc;
+use uuid::Uuid;
+
+/// Queues the issue for every matching subscriber it hasn't been delivered to
+/// yet, returning how many deliveries have been queued.
+///
+/// Deliveries already in the queue are left alone, so calling it again for an
+/// issue whose queue rows have been lost only sends to the subscribers left.
+#[tracing::instrument(skip_all)]
+pub async fn enqueue_delivery_tasks(
+    transaction: &mut SqliteInstrumentedTransaction,
+    newsletter_issue_uuid: Uuid,
+) -> Result<i64, sqlx::Error> {
+    let newsletter_issue_uuid_string = newsletter_issue_uuid.to_string();
+
+    let total_queued = sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO issue_delivery_queue (
+            newsletter_issue_uuid, 
+            subscriber_email
+        )
+        SELECT $1, s.email
+        FROM subscriptions s
+        WHERE s.status = 'confirmed'
+        AND NOT EXISTS (
+            SELECT 1
+            FROM newsletter_deliveries d
+            WHERE d.newsletter_issue_uuid = $1
+            AND d.subscriber_email = s.email
+            AND d.status = 'delivered'
+        )
+        AND (
+            -- untagged issues go to everyone
+            NOT EXISTS (
+                SELECT 1 FROM newsletter_issue_tags WHERE newsletter_issue_uuid = $1
+            )
+            OR EXISTS (
+                SELECT 1
+                FROM subscriber_tags st
+                JOIN newsletter_issue_tags it ON it.tag = st.tag
+                WHERE it.newsletter_issue_uuid = $1 AND st.subscriber_uuid = s.uuid
+            )
+        )
+        "#,
+        newsletter_issue_uuid_string,
+    )
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected() as i64;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_delivery_totals (
+            newsletter_issue_uuid,
+            total_queued
+        )
+        VALUES ($1, $2)
+        -- the progress is reported against the first enqueueing
+        ON CONFLICT (newsletter_issue_uuid) DO NOTHING
+        "#,
+        newsletter_issue_uuid_string,
+        total_queued,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    Ok(total_queued)
+}
+
+/// Enqueue the delivery tasks of every scheduled issue that is due, returning
+/// how many issues have been enqueued.
+#[tracing::instrument(skip_all)]
+pub async fn enqueue_due_scheduled_issues(
+    pool: &SqliteInstrumentedPool,
+) -> Result<u64, anyhow::Error> {
+    let now = Utc::now().to_string();
+    let due_issues = sqlx::query!(
+        r#"
+        SELECT newsletter_issue_uuid
+        FROM active_newsletter_issues
+        WHERE status = 'scheduled' AND scheduled_for <= $1
+        "#,
+        now
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to retrieve the due scheduled newsletter issues.")?;
+
+    let mut n_enqueued = 0;
+    for issue in due_issues {
+        let mut transaction = pool.begin().await?;
+        // the status check guards against the issue being cancelled in the meantime
+        let n_updated_rows = sqlx::query!(
+            r#"
+            UPDATE newsletter_issues
+            SET status = 'queued'
+            WHERE newsletter_issue_uuid = $1 AND status = 'scheduled' AND deleted_at IS NULL
+            "#,
+            issue.newsletter_issue_uuid
+        )
+        .execute(&mut transaction)
+        .await?
+        .rows_affected();
+        if n_updated_rows == 0 {
+            continue;
+        }
+        let issue_id = Uuid::parse_str(&issue.newsletter_issue_uuid)?;
+        enqueue_delivery_tasks(&mut transaction, issue_id)
+            .await
+            .context("Failed to enqueue the delivery tasks of a scheduled issue.")?;
+        transaction.commit().await?;
+        n_enqueued += 1;
+    }
+    Ok(n_enqueued)
+}
@@ -1,33 +1,192 @@
 use crate::configuration::{configure_database, Settings};
-use crate::domain::SubscriberEmail;
+use crate::db::SqliteInstrumentedPool;
+use crate::domain::{generate_unsubscribe_token, SubscriberEmail, UNSUBSCRIBE_URL_PLACEHOLDER};
 use crate::email_client::EmailClient;
-use sqlx::SqlitePool;
-use std::time::Duration;
+use crate::issue_delivery_queue::enqueue_due_scheduled_issues;
+use crate::routes::error_chain_fmt;
+use crate::startup::HmacSecret;
+use chrono::Utc;
+use rand::Rng;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
 use tracing::{field::display, Span};
 use uuid::Uuid;
 
-pub async fn run_worker_until_stopped(configuration: Settings) -> Result<(), anyhow::Error> {
+/// Lets the admins hold the deliveries back, e.g. during a deployment or an
+/// incident at the email provider. Clones share the same flag.
+#[derive(Clone, Default)]
+pub struct DeliveryPause {
+    paused: Arc<AtomicBool>,
+    resumed: Arc<Notify>,
+}
+
+impl DeliveryPause {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// A sleeping worker picks the queue up again right away.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        self.resumed.notify_waiters();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+pub async fn run_worker_until_stopped(
+    configuration: Settings,
+    shutdown_token: CancellationToken,
+    delivery_pause: DeliveryPause,
+) -> Result<(), anyhow::Error> {
     let connection_pool = configure_database(&configuration.database).await?;
-    let email_client = configuration.email_client.client();
-    worker_loop(connection_pool, email_client).await
+    let http_client = std::sync::Arc::new(configuration.http_client.client());
+    let send_timeout = configuration.email_client.send_timeout();
+    let email_client = configuration.email_client.client(http_client);
+    let hmac_secret = HmacSecret(configuration.application.hmac_secret);
+    let config = WorkerConfig {
+        base_url: configuration.application.base_url,
+        hmac_secret,
+        max_retries: configuration.issue_delivery.max_retries,
+        send_timeout,
+        health_check_path: configuration.worker_health_check_path,
+        pause: delivery_pause,
+    };
+    worker_loop(
+        SqliteInstrumentedPool::new(connection_pool),
+        email_client,
+        config,
+        shutdown_token,
+    )
+    .await
 }
 
-async fn worker_loop(pool: SqlitePool, email_client: EmailClient) -> Result<(), anyhow::Error> {
-    loop {
-        match try_execute_task(&pool, &email_client).await {
+/// Everything the worker loop needs besides its connections.
+struct WorkerConfig {
+    base_url: String,
+    hmac_secret: HmacSecret,
+    max_retries: u8,
+    send_timeout: Duration,
+    health_check_path: Option<PathBuf>,
+    pause: DeliveryPause,
+}
+
+/// How often a running worker touches its health check file, orchestrators
+/// should consider it stuck once the file is about twice as old.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a paused worker looks at the flag again, in case it missed the
+/// wake up from [`DeliveryPause::resume`].
+const PAUSED_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+async fn worker_loop(
+    pool: SqliteInstrumentedPool,
+    email_client: EmailClient,
+    config: WorkerConfig,
+    shutdown_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut last_health_check: Option<Instant> = None;
+    let dead_letter_interval = Duration::from_secs(60 * 60);
+    let mut last_dead_letter_check = Instant::now();
+    let scheduled_issues_interval = Duration::from_secs(10);
+    let mut last_scheduled_issues_check: Option<Instant> = None;
+    // we only check for shutdown between tasks, so a delivery is never cut in half
+    while !shutdown_token.is_cancelled() {
+        if let Some(path) = &config.health_check_path {
+            if last_health_check.is_none_or(|last| last.elapsed() >= HEALTH_CHECK_INTERVAL) {
+                if let Err(e) = tokio::fs::write(path, Utc::now().to_rfc3339()).await {
+                    tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        path = %path.display(),
+                        "Failed to write the worker health check file",
+                    );
+                }
+                last_health_check = Some(Instant::now());
+            }
+        }
+        // nothing leaves the queue while paused, scheduled issues included
+        if config.pause.is_paused() {
+            tokio::select! {
+                _ = tokio::time::sleep(PAUSED_CHECK_INTERVAL) => {}
+                _ = config.pause.resumed.notified() => {}
+                _ = shutdown_token.cancelled() => {}
+            }
+            continue;
+        }
+        if last_scheduled_issues_check
+            .is_none_or(|last| last.elapsed() >= scheduled_issues_interval)
+        {
+            if let Err(e) = enqueue_due_scheduled_issues(&pool).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to enqueue the due scheduled newsletter issues",
+                );
+            }
+            last_scheduled_issues_check = Some(Instant::now());
+        }
+        if last_dead_letter_check.elapsed() >= dead_letter_interval {
+            if let Err(e) = process_dead_letter_queue(&pool).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to process the dead letter queue",
+                );
+            }
+            last_dead_letter_check = Instant::now();
+        }
+        match try_execute_task(
+            &pool,
+            &email_client,
+            &config.base_url,
+            &config.hmac_secret,
+            config.max_retries,
+            config.send_timeout,
+        )
+        .await
+        {
             Ok(ExecutionOutcome::EmptyQueue) => {
-                tokio::time::sleep(Duration::from_secs(10)).await;
+                sleep_until_cancelled(Duration::from_secs(10), &shutdown_token).await;
             }
             Err(_) => {
-                tokio::time::sleep(Duration::from_secs(1)).await;
+                sleep_until_cancelled(Duration::from_secs(1), &shutdown_token).await;
             }
+            // the failed task has been rescheduled (or dead lettered), so we can
+            // move on to the next one right away
+            Ok(ExecutionOutcome::TaskFailed { .. }) => {}
             Ok(ExecutionOutcome::TaskCompleted) => {}
+            Ok(ExecutionOutcome::TaskSkipped) => {}
         }
     }
+    tracing::info!("Issue delivery worker has been shut down");
+    Ok(())
+}
+
+async fn sleep_until_cancelled(duration: Duration, shutdown_token: &CancellationToken) {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => {}
+        _ = shutdown_token.cancelled() => {}
+    }
 }
 
 pub enum ExecutionOutcome {
     TaskCompleted,
+    /// The delivery failed. With `retries_remaining == 0` the task has been moved
+    /// to the dead letter table, otherwise it will be retried later on.
+    TaskFailed {
+        retries_remaining: u8,
+    },
+    /// The issue was deleted after the task had been enqueued, nothing was sent.
+    TaskSkipped,
     EmptyQueue,
 }
 
@@ -35,40 +194,80 @@ pub enum ExecutionOutcome {
     skip_all,
     fields(
         newsletter_issue_id=tracing::field::Empty,
-        subscriber_email=tracing::field::Empty
+        subscriber_email=tracing::field::Empty,
+        n_retries=tracing::field::Empty
     ),
     err
 )]
 pub async fn try_execute_task(
-    pool: &SqlitePool,
+    pool: &SqliteInstrumentedPool,
     email_client: &EmailClient,
+    base_url: &str,
+    hmac_secret: &HmacSecret,
+    max_retries: u8,
+    send_timeout: Duration,
 ) -> Result<ExecutionOutcome, anyhow::Error> {
     let task = dequeue_task(pool).await?;
     if task.is_none() {
         return Ok(ExecutionOutcome::EmptyQueue);
     }
-    let (issue_id, email) = task.unwrap();
+    let task = task.unwrap();
     Span::current()
-        .record("newsletter_issue_id", display(issue_id))
-        .record("subscriber_email", display(&email));
-    match SubscriberEmail::parse(email.clone()) {
+        .record("newsletter_issue_id", display(task.issue_id))
+        .record("subscriber_email", display(&task.subscriber_email))
+        .record("n_retries", task.n_retries);
+    match SubscriberEmail::parse(task.subscriber_email.clone()) {
         Ok(email) => {
-            let issue = get_issue(pool, &issue_id).await?;
-            if let Err(e) = email_client
-                .send_email(
+            let Some(issue) = get_issue(pool, &task.issue_id).await? else {
+                tracing::info!("Skipping a delivery of a deleted newsletter issue");
+                return Ok(ExecutionOutcome::TaskSkipped);
+            };
+            let subscriber_id = get_subscriber_id(pool, &task.subscriber_email).await?;
+            let unsubscribe_link = unsubscribe_link(base_url, subscriber_id, hmac_secret);
+            let html_content = issue.html_content_with_footer(&unsubscribe_link);
+            let text_content = issue.text_content_with_footer(&unsubscribe_link);
+            let sent = tokio::time::timeout(
+                send_timeout,
+                email_client.send_email(
                     &email,
                     &issue.title,
-                    &issue.html_content,
-                    &issue.text_content,
-                )
-                .await
-            {
-                tracing::error!(
+                    &html_content,
+                    &text_content,
+                    Some(&unsubscribe_link),
+                    issue.reply_to().as_ref(),
+                ),
+            )
+            .await;
+            let result = match sent {
+                Ok(result) => result.map_err(SendError::Provider),
+                Err(_) => Err(SendError::TimedOut(send_timeout)),
+            };
+            if let Err(e) = result {
+                metrics::counter!("newzletter_emails_failed_total").increment(1);
+                let n_retries = task.n_retries + 1;
+                if is_permanent_failure(&e) || n_retries > max_retries {
+                    tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        "Failed to deliver issue to a confirmed subscriber. \
+                            Moving it to the dead letter queue.",
+                    );
+                    move_to_dead_letter(pool, &task, n_retries, &e).await?;
+                    record_delivery(pool, &task, DeliveryStatus::Failed(&e)).await?;
+                    return Ok(ExecutionOutcome::TaskFailed {
+                        retries_remaining: 0,
+                    });
+                }
+                tracing::warn!(
                     error.cause_chain = ?e,
                     error.message = %e,
                     "Failed to deliver issue to a confirmed subscriber. \
-                        Skipping.",
+                        Retrying later.",
                 );
+                reschedule_task(pool, &task, n_retries).await?;
+                return Ok(ExecutionOutcome::TaskFailed {
+                    retries_remaining: max_retries - n_retries,
+                });
             }
         }
         Err(e) => {
@@ -78,54 +277,522 @@ pub async fn try_execute_task(
                 "Skipping a confirmed subscriber. \
                     Their stored contact details are invalid",
             );
+            metrics::counter!("newzletter_emails_failed_total").increment(1);
+            move_to_dead_letter(pool, &task, task.n_retries, &e).await?;
+            record_delivery(pool, &task, DeliveryStatus::Failed(&e)).await?;
+            return Ok(ExecutionOutcome::TaskFailed {
+                retries_remaining: 0,
+            });
         }
     }
+    metrics::counter!("newzletter_emails_sent_total").increment(1);
+    record_delivery(pool, &task, DeliveryStatus::Delivered).await?;
     Ok(ExecutionOutcome::TaskCompleted)
 }
 
-#[tracing::instrument(skip_all)]
-async fn dequeue_task(pool: &SqlitePool) -> Result<Option<(Uuid, String)>, anyhow::Error> {
+#[derive(thiserror::Error)]
+enum SendError {
+    #[error(transparent)]
+    Provider(reqwest::Error),
+    // a provider hanging on to the connection shouldn't hold up the queue
+    #[error("The email provider didn't answer within {0:?}.")]
+    TimedOut(Duration),
+}
+
+impl std::fmt::Debug for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+/// A 4xx from the email provider (e.g. a hard bounce or an inactive recipient)
+/// won't go away by retrying, rate limiting being the exception. Timeouts are
+/// always worth another try.
+fn is_permanent_failure(e: &SendError) -> bool {
+    match e {
+        SendError::Provider(e) => e
+            .status()
+            .is_some_and(|s| s.is_client_error() && s != StatusCode::TOO_MANY_REQUESTS),
+        SendError::TimedOut(_) => false,
+    }
+}
+
+/// Exponential backoff (base 2, capped at 5 minutes) with ±10% jitter, so that
+/// failed deliveries don't all retry at the same time once the email provider
+/// recovers.
+fn backoff_delay(n_retries: u8) -> Duration {
+    let max_delay = Duration::from_secs(5 * 60);
+    let delay = 2u64
+        .checked_pow(n_retries.into())
+        .map(Duration::from_secs)
+        .unwrap_or(max_delay)
+        .min(max_delay);
+    let jitter = rand::rng().random_range(-0.1..=0.1);
+    delay.mul_f64(1.0 + jitter)
+}
+
+pub fn unsubscribe_link(base_url: &str, subscriber_id: Uuid, hmac_secret: &HmacSecret) -> String {
+    format!(
+        "{}/subscriptions/unsubscribe?token={}",
+        base_url,
+        generate_unsubscribe_token(subscriber_id, hmac_secret)
+    )
+}
+
+struct DeliveryTask {
+    issue_id: Uuid,
+    subscriber_email: String,
+    n_retries: u8,
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_affected = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty
+    )
+)]
+async fn dequeue_task(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Option<DeliveryTask>, anyhow::Error> {
+    let now = Utc::now().to_string();
     let r = sqlx::query!(
         r#"
         DELETE FROM issue_delivery_queue
         WHERE rowid IN (
             SELECT rowid
             FROM issue_delivery_queue
+            WHERE next_attempt_at IS NULL OR next_attempt_at <= $1
             LIMIT 1
         )
-        RETURNING newsletter_issue_uuid, subscriber_email
+        RETURNING newsletter_issue_uuid, subscriber_email, n_retries
         "#,
+        now
     )
     .fetch_optional(pool)
     .await?;
     if let Some(r) = r {
         let issue_id = Uuid::parse_str(&r.newsletter_issue_uuid)?;
-        Ok(Some((issue_id, r.subscriber_email)))
+        Ok(Some(DeliveryTask {
+            issue_id,
+            subscriber_email: r.subscriber_email,
+            n_retries: r.n_retries.try_into()?,
+        }))
     } else {
         Ok(None)
     }
 }
 
-struct NewsletterIssue {
-    title: String,
-    text_content: String,
-    html_content: String,
+#[tracing::instrument(skip_all)]
+async fn reschedule_task(
+    pool: &SqliteInstrumentedPool,
+    task: &DeliveryTask,
+    n_retries: u8,
+) -> Result<(), anyhow::Error> {
+    let issue_id = task.issue_id.to_string();
+    let next_attempt_at = (Utc::now() + backoff_delay(n_retries)).to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_delivery_queue (
+            newsletter_issue_uuid,
+            subscriber_email,
+            n_retries,
+            next_attempt_at
+        )
+        VALUES ($1, $2, $3, $4)
+        "#,
+        issue_id,
+        task.subscriber_email,
+        n_retries,
+        next_attempt_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
 }
 
 #[tracing::instrument(skip_all)]
-async fn get_issue(pool: &SqlitePool, issue_id: &Uuid) -> Result<NewsletterIssue, anyhow::Error> {
+async fn move_to_dead_letter(
+    pool: &SqliteInstrumentedPool,
+    task: &DeliveryTask,
+    n_retries: u8,
+    failure_reason: &(dyn std::fmt::Display + Sync),
+) -> Result<(), anyhow::Error> {
+    let issue_id = task.issue_id.to_string();
+    let failure_reason = failure_reason.to_string();
+    let now = Utc::now().to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_delivery_dead_letter (
+            newsletter_issue_uuid,
+            subscriber_email,
+            n_retries,
+            failure_reason,
+            failed_at
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        issue_id,
+        task.subscriber_email,
+        n_retries,
+        failure_reason,
+        now
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+enum DeliveryStatus<'a> {
+    Delivered,
+    Failed(&'a (dyn std::fmt::Display + Sync)),
+}
+
+/// Keep track of the final outcome of a delivery, the queue row is gone by now.
+#[tracing::instrument(skip_all)]
+async fn record_delivery(
+    pool: &SqliteInstrumentedPool,
+    task: &DeliveryTask,
+    status: DeliveryStatus<'_>,
+) -> Result<(), anyhow::Error> {
+    let issue_id = task.issue_id.to_string();
+    let (status, delivered_at, failure_reason) = match status {
+        DeliveryStatus::Delivered => ("delivered", Some(Utc::now().to_string()), None),
+        DeliveryStatus::Failed(reason) => ("failed", None, Some(reason.to_string())),
+    };
+    sqlx::query!(
+        r#"
+        INSERT OR REPLACE INTO newsletter_deliveries (
+            newsletter_issue_uuid,
+            subscriber_email,
+            status,
+            delivered_at,
+            failure_reason
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        issue_id,
+        task.subscriber_email,
+        status,
+        delivered_at,
+        failure_reason
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub struct NewsletterIssue {
+    pub title: String,
+    pub text_content: String,
+    pub html_content: String,
+    pub reply_to_email: Option<String>,
+}
+
+impl NewsletterIssue {
+    /// Validated when the issue was published, the sender gets the replies to
+    /// the issues that don't have one.
+    pub fn reply_to(&self) -> Option<SubscriberEmail> {
+        self.reply_to_email
+            .clone()
+            .and_then(|email| SubscriberEmail::parse(email).ok())
+    }
+
+    /// The `{UNSUBSCRIBE_URL}` placeholders of the template the issue was
+    /// wrapped in point at the link of the subscriber too.
+    pub fn html_content_with_footer(&self, unsubscribe_link: &str) -> String {
+        format!(
+            r#"{}
+<p style="margin-top:32px;font-size:12px;color:#6b7280;">
+  Don't want these emails anymore? <a href="{}">Unsubscribe</a>.
+</p>"#,
+            self.html_content
+                .replace(UNSUBSCRIBE_URL_PLACEHOLDER, unsubscribe_link),
+            unsubscribe_link
+        )
+    }
+
+    pub fn text_content_with_footer(&self, unsubscribe_link: &str) -> String {
+        format!(
+            "{}\n\n--\nDon't want these emails anymore? Unsubscribe here:\n{}",
+            self.text_content, unsubscribe_link
+        )
+    }
+}
+
+/// Read for every delivery, so edits made while the issue is being delivered
+/// reach the subscribers still in the queue. `None` once it has been deleted.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_affected = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty
+    )
+)]
+async fn get_issue(
+    pool: &SqliteInstrumentedPool,
+    issue_id: &Uuid,
+) -> Result<Option<NewsletterIssue>, anyhow::Error> {
     let issue_id_string = issue_id.to_string();
     let issue = sqlx::query_as!(
         NewsletterIssue,
         r#"
-        SELECT title, text_content, html_content
-        FROM newsletter_issues
+        SELECT title, text_content, html_content, reply_to_email
+        FROM active_newsletter_issues
         WHERE
             newsletter_issue_uuid = $1
         "#,
         issue_id_string
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await?;
     Ok(issue)
 }
+
+/// Deliveries waiting in the queue, retries included.
+#[tracing::instrument(skip_all)]
+pub async fn get_queue_depth(pool: &SqliteInstrumentedPool) -> Result<u64, anyhow::Error> {
+    let queue_depth = sqlx::query_scalar!("SELECT COUNT(*) FROM issue_delivery_queue")
+        .fetch_one(pool)
+        .await?;
+    Ok(queue_depth.try_into().unwrap_or_default())
+}
+
+#[derive(Serialize)]
+pub struct DeadLetterEntry {
+    pub id: i64,
+    pub newsletter_issue_id: String,
+    pub subscriber_email: String,
+    pub failure_reason: String,
+    pub attempt_count: i64,
+    pub failed_at: String,
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn get_dead_letter_entries(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<DeadLetterEntry>, anyhow::Error> {
+    let entries = sqlx::query_as!(
+        DeadLetterEntry,
+        r#"
+        SELECT
+            id,
+            newsletter_issue_uuid as newsletter_issue_id,
+            subscriber_email,
+            failure_reason,
+            n_retries as attempt_count,
+            failed_at
+        FROM issue_delivery_dead_letter
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(entries)
+}
+
+/// Returns `false` if there was no entry with the given id.
+#[tracing::instrument(skip(pool))]
+pub async fn delete_dead_letter_entry(
+    pool: &SqliteInstrumentedPool,
+    id: i64,
+) -> Result<bool, anyhow::Error> {
+    let n_deleted_rows = sqlx::query!(
+        r#"
+        DELETE FROM issue_delivery_dead_letter
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(n_deleted_rows > 0)
+}
+
+/// Report every permanently failed delivery, so they show up in the logs until
+/// an admin acknowledges them.
+#[tracing::instrument(skip_all)]
+pub async fn process_dead_letter_queue(pool: &SqliteInstrumentedPool) -> Result<(), anyhow::Error> {
+    for entry in get_dead_letter_entries(pool).await? {
+        tracing::warn!(
+            subscriber_email = %entry.subscriber_email,
+            newsletter_issue_id = %entry.newsletter_issue_id,
+            failure_reason = %entry.failure_reason,
+            attempt_count = entry.attempt_count,
+            "Undeliverable newsletter issue in the dead letter queue",
+        );
+    }
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+async fn get_subscriber_id(
+    pool: &SqliteInstrumentedPool,
+    email: &str,
+) -> Result<Uuid, anyhow::Error> {
+    let r = sqlx::query!(
+        r#"
+        SELECT uuid
+        FROM subscriptions
+        WHERE email = $1
+        "#,
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(Uuid::parse_str(&r.uuid)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backoff_delay, worker_loop, DeliveryPause, WorkerConfig};
+    use crate::db::SqliteInstrumentedPool;
+    use crate::domain::SubscriberEmail;
+    use crate::email_client::EmailClient;
+    use crate::startup::HmacSecret;
+    use secrecy::SecretString;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+    use uuid::Uuid;
+
+    fn config(health_check_path: Option<PathBuf>, pause: DeliveryPause) -> WorkerConfig {
+        WorkerConfig {
+            base_url: "http://127.0.0.1".to_string(),
+            hmac_secret: HmacSecret(SecretString::from("secret")),
+            max_retries: 5,
+            send_timeout: Duration::from_secs(30),
+            health_check_path,
+            pause,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_within_jitter_bounds() {
+        for n_retries in 0..8 {
+            let expected = 2f64.powi(n_retries.into());
+            let delay = backoff_delay(n_retries).as_secs_f64();
+            assert!(delay >= expected * 0.9 && delay <= expected * 1.1);
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped_at_five_minutes() {
+        let cap = Duration::from_secs(5 * 60).as_secs_f64();
+        for n_retries in [9, 20, 64, u8::MAX] {
+            let delay = backoff_delay(n_retries).as_secs_f64();
+            assert!(delay >= cap * 0.9 && delay <= cap * 1.1);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_running_worker_writes_its_health_check_file() {
+        // an in-memory database only lives as long as its single connection
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let email_client = EmailClient::new(
+            Arc::new(reqwest::Client::new()),
+            SubscriberEmail::parse("sender@example.com".to_string()).unwrap(),
+            "http://127.0.0.1:1".to_string(),
+            SecretString::from("token"),
+            Duration::from_millis(100),
+        );
+        let path = std::env::temp_dir().join(format!("worker-healthy-{}", Uuid::new_v4()));
+        let shutdown_token = CancellationToken::new();
+        let worker = tokio::spawn(worker_loop(
+            SqliteInstrumentedPool::new(pool),
+            email_client,
+            config(Some(path.clone()), DeliveryPause::default()),
+            shutdown_token.clone(),
+        ));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        shutdown_token.cancel();
+        worker.await.unwrap().unwrap();
+
+        let written_at = std::fs::read_to_string(&path).unwrap();
+        let written_at = chrono::DateTime::parse_from_rfc3339(&written_at).unwrap();
+        assert!(
+            chrono::Utc::now().signed_duration_since(written_at) < chrono::Duration::seconds(5)
+        );
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert!(modified.elapsed().unwrap_or_default() < Duration::from_secs(5));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_paused_worker_leaves_the_queue_alone_until_resumed() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let issue_id = Uuid::new_v4().to_string();
+        sqlx::query!(
+            r#"
+            INSERT INTO newsletter_issues (
+                newsletter_issue_uuid, title, text_content, html_content, published_at
+            )
+            VALUES ($1, 'title', 'text', '<p>html</p>', 'now')
+            "#,
+            issue_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        // an invalid address leaves the queue without a call to the provider
+        sqlx::query!(
+            r#"
+            INSERT INTO issue_delivery_queue (newsletter_issue_uuid, subscriber_email)
+            VALUES ($1, 'not-an-email')
+            "#,
+            issue_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let pool = SqliteInstrumentedPool::new(pool);
+        let email_client = EmailClient::new(
+            Arc::new(reqwest::Client::new()),
+            SubscriberEmail::parse("sender@example.com".to_string()).unwrap(),
+            "http://127.0.0.1:1".to_string(),
+            SecretString::from("token"),
+            Duration::from_millis(100),
+        );
+        let shutdown_token = CancellationToken::new();
+        let delivery_pause = DeliveryPause::default();
+        delivery_pause.pause();
+        let worker = tokio::spawn(worker_loop(
+            pool.clone(),
+            email_client,
+            config(None, delivery_pause.clone()),
+            shutdown_token.clone(),
+        ));
+        let queue_depth = || async {
+            sqlx::query_scalar!("SELECT COUNT(*) FROM issue_delivery_queue")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(queue_depth().await, 1);
+
+        delivery_pause.resume();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(queue_depth().await, 0);
+
+        shutdown_token.cancel();
+        worker.await.unwrap().unwrap();
+    }
+}
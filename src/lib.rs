@@ -1,14 +1,24 @@
+pub mod audit;
 pub mod authentication;
 pub mod configuration;
+pub mod db;
 pub mod domain;
 pub mod email_client;
+pub mod error;
 pub mod idempotency;
+pub mod issue_delivery_queue;
 pub mod issue_delivery_worker;
+pub mod middleware;
+pub mod migrations;
 pub mod routes;
+pub mod scheduler;
 pub mod session_state;
 pub mod startup;
+pub mod tags;
 pub mod telemetry;
+pub mod turnstile;
 pub mod utils;
+pub mod vacuum_worker;
 
 /*
 // https://github.com/tokio-rs/axum/blob/main/examples/customize-extractor-error/src/with_rejection.rs
@@ -3,7 +3,7 @@ use newzletter::{
     configuration::get_configuration,
     issue_delivery_worker::run_worker_until_stopped,
     startup::Application,
-    telemetry::{get_subscriber, init_subscriber},
+    telemetry::{get_otel_subscriber, init_subscriber},
 };
 use std::fmt::{Debug, Display};
 use tokio::task::JoinError;
@@ -12,18 +12,43 @@ use tokio::task::JoinError;
 async fn main() -> anyhow::Result<()> {
     println!("Hello, world!");
 
-    let subscriber = get_subscriber("newzletter".into(), "info".into(), std::io::stdout);
+    // spans are also exported over OTLP when `APP_OTEL_ENDPOINT` is set
+    let otel_endpoint = std::env::var("APP_OTEL_ENDPOINT").ok();
+    let (subscriber, log_filter, tracer_provider) = get_otel_subscriber(
+        "newzletter".into(),
+        "info".into(),
+        std::io::stdout,
+        otel_endpoint.as_deref(),
+    )?;
     init_subscriber(subscriber);
 
     let configuration = get_configuration()?;
-    let application = Application::build(configuration.clone()).await?;
+    let application =
+        Application::build(configuration.clone(), Some(log_filter), tracer_provider).await?;
+    let shutdown_token = application.shutdown_token();
+    let delivery_pause = application.delivery_pause();
     let application_task = tokio::spawn(application.run_until_stopped());
-    let worker_task = tokio::spawn(run_worker_until_stopped(configuration));
+    let worker_task = tokio::spawn(run_worker_until_stopped(
+        configuration,
+        shutdown_token.clone(),
+        delivery_pause,
+    ));
 
-    tokio::select! {
-        o = application_task => {report_exit("API", o)}
-        o = worker_task => {report_exit("Background worker", o)}
-    };
+    // whichever task stops first takes the other one down with it
+    let (application_outcome, worker_outcome) = tokio::join!(
+        async {
+            let outcome = application_task.await;
+            shutdown_token.cancel();
+            outcome
+        },
+        async {
+            let outcome = worker_task.await;
+            shutdown_token.cancel();
+            outcome
+        },
+    );
+    report_exit("API", application_outcome);
+    report_exit("Background worker", worker_outcome);
 
     Ok(())
 }
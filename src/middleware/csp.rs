@@ -0,0 +1,151 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{header::CONTENT_SECURITY_POLICY, request::Parts, HeaderValue, Request, StatusCode},
+    response::Response,
+};
+use rand::Rng;
+use tower::{Layer, Service};
+
+use crate::configuration::Environment;
+
+/// The nonce of the current response's policy, inline `<script>` tags of
+/// templates must carry it as their `nonce` attribute to run in production.
+#[derive(Clone, Debug)]
+pub struct CspNonce(pub String);
+
+impl<S> FromRequestParts<S> for CspNonce
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<CspNonce>().cloned().ok_or_else(|| {
+            tracing::error!("The CSP layer is missing");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+    }
+}
+
+/// Marks a response as meant to be embedded in `<iframe>`s of other sites,
+/// its policy then allows any `frame-ancestors`.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddableAnywhere;
+
+/// Sets the `Content-Security-Policy` header of every response.
+///
+/// Locally the policy lets dev tools inject whatever they need, in production
+/// scripts only run from our own origin or with the request's nonce. Turnstile
+/// is the one third party allowed, the subscription form doesn't work without it.
+#[derive(Clone, Copy)]
+pub struct CspLayer {
+    environment: Environment,
+}
+
+impl CspLayer {
+    pub fn new(environment: Environment) -> Self {
+        Self { environment }
+    }
+}
+
+impl<S> Layer<S> for CspLayer {
+    type Service = Csp<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Csp {
+            inner,
+            environment: self.environment,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Csp<S> {
+    inner: S,
+    environment: Environment,
+}
+
+impl<S> Service<Request<Body>> for Csp<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let nonce = generate_nonce();
+        let mut policy = policy(self.environment, &nonce);
+        request.extensions_mut().insert(CspNonce(nonce));
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let Ok(mut response) = response.await;
+            if response.extensions().get::<EmbeddableAnywhere>().is_some() {
+                policy.push_str("; frame-ancestors *");
+            }
+            response.headers_mut().insert(
+                CONTENT_SECURITY_POLICY,
+                HeaderValue::from_str(&policy).expect("The policy is valid ASCII"),
+            );
+            Ok(response)
+        })
+    }
+}
+
+fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rng().fill(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn policy(environment: Environment, nonce: &str) -> String {
+    match environment {
+        Environment::Local => "default-src 'self' 'unsafe-inline'".to_string(),
+        Environment::Production => format!(
+            "default-src 'self'; \
+             script-src 'self' 'nonce-{nonce}' https://challenges.cloudflare.com; \
+             style-src 'self'; \
+             img-src 'self' data:; \
+             frame-src https://challenges.cloudflare.com"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_nonce, policy};
+    use crate::configuration::Environment;
+
+    #[test]
+    fn the_production_policy_allows_scripts_with_the_nonce() {
+        let policy = policy(Environment::Production, "abc123");
+        assert!(policy.contains("script-src 'self' 'nonce-abc123'"));
+        assert!(!policy.contains("unsafe-inline"));
+    }
+
+    #[test]
+    fn the_local_policy_allows_inline_scripts() {
+        assert_eq!(
+            policy(Environment::Local, "abc123"),
+            "default-src 'self' 'unsafe-inline'"
+        );
+    }
+
+    #[test]
+    fn every_request_gets_its_own_nonce() {
+        assert_ne!(generate_nonce(), generate_nonce());
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::FromRequestParts,
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, COOKIE, SET_COOKIE},
+        request::Parts,
+        HeaderMap, HeaderValue, Method, Request, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use rand::Rng;
+use tower::{Layer, Service};
+use tower_sessions::Session;
+
+const SESSION_KEY: &str = "csrf_token";
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+pub const CSRF_FORM_FIELD: &str = "_csrf";
+
+/// Forms larger than this can't carry their token in the body, send the header instead.
+const MAX_FORM_SIZE: usize = 2 * 1024 * 1024;
+
+/// POSTed to with a token from one of our emails, by mail providers on the
+/// subscriber's behalf (RFC 8058) or by the confirmation page, the secret
+/// token already proves where the request comes from. The subscribe widget
+/// posts from inside other sites, where browsers don't send our session
+/// cookie, Turnstile and the confirmation email guard its subscriptions
+/// instead. `POST /subscriptions` itself still needs the token.
+const EXEMPT_PATHS: &[&str] = &[
+    "/subscribe-widget",
+    "/subscriptions/unsubscribe",
+    "/subscriptions/confirm",
+];
+
+/// The token of the current session, to embed in forms as the `_csrf` field.
+///
+/// Sessions that don't exist yet get their token, and with it the session,
+/// once a handler asks for it, so that anonymous visitors of pages without a
+/// form don't leave a session behind.
+#[derive(Clone, Debug)]
+pub struct CsrfToken(pub String);
+
+/// Put in place of the [`CsrfToken`] by the layer when the session has none yet.
+#[derive(Clone)]
+struct MissingCsrfToken(Session);
+
+impl<S> FromRequestParts<S> for CsrfToken
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(token) = parts.extensions.get::<CsrfToken>() {
+            return Ok(token.clone());
+        }
+        let Some(MissingCsrfToken(session)) = parts.extensions.get::<MissingCsrfToken>().cloned()
+        else {
+            tracing::error!("The CSRF layer is missing");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        };
+        let token = CsrfToken(new_session_token(&session).await?);
+        parts.extensions.insert(token.clone());
+        Ok(token)
+    }
+}
+
+/// Synchronizer token CSRF protection.
+///
+/// Every session gets a random token, mirrored in the `csrf_token` cookie for
+/// the static pages' scripts. New sessions only get one from the
+/// [`CsrfToken`] extractor. Requests that change state must send it back in
+/// the `X-CSRF-Token` header or the `_csrf` form field, or are rejected with a
+/// 403. Must sit inside the session layer.
+#[derive(Clone, Default)]
+pub struct CsrfLayer;
+
+impl CsrfLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for CsrfLayer {
+    type Service = Csrf<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Csrf { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct Csrf<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for Csrf<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // the clone may not be ready, so call the service that was polled
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { Ok(protect(inner, request).await.unwrap_or_else(|e| e)) })
+    }
+}
+
+async fn protect<S>(mut inner: S, request: Request<Body>) -> Result<Response, Response>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible>,
+{
+    let Some(session) = request.extensions().get::<Session>().cloned() else {
+        tracing::error!("The CSRF layer must sit inside the session layer");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    };
+    let cookie_token = cookie_token(request.headers());
+    let session_token = session
+        .get::<String>(SESSION_KEY)
+        .await
+        .map_err(internal_error)?;
+
+    let mut request = request;
+    if needs_token(request.method(), request.uri().path())
+        && !is_api_request(request.headers(), request.uri().path())
+    {
+        let (submitted_token, checked_request) = submitted_token(request).await?;
+        request = checked_request;
+        let is_valid = matches!(
+            (&session_token, &submitted_token),
+            (Some(expected), Some(submitted)) if constant_time_eq(expected.as_bytes(), submitted.as_bytes())
+        );
+        if !is_valid {
+            tracing::warn!(
+                path = %request.uri().path(),
+                token_submitted = submitted_token.is_some(),
+                "Rejected a request with a missing or mismatched CSRF token"
+            );
+            return Err((StatusCode::FORBIDDEN, "Invalid CSRF token.").into_response());
+        }
+    }
+
+    // `get` loaded the session, it only has an ID if it was stored before
+    let token = match session_token {
+        Some(token) => Some(token),
+        None if session.id().is_some() => Some(new_session_token(&session).await?),
+        None => None,
+    };
+    match token {
+        Some(token) => {
+            request.extensions_mut().insert(CsrfToken(token));
+        }
+        None => {
+            request
+                .extensions_mut()
+                .insert(MissingCsrfToken(session.clone()));
+        }
+    }
+    let Ok(mut response) = inner.call(request).await;
+
+    // logging out flushes the session along with its token, the next form
+    // page hands out a new one
+    let token = session
+        .get::<String>(SESSION_KEY)
+        .await
+        .map_err(internal_error)?;
+    if let Some(token) = token {
+        if cookie_token.as_deref() != Some(token.as_str()) {
+            let cookie = format!("{}={}; Path=/; SameSite=Strict", CSRF_COOKIE, token);
+            response.headers_mut().append(
+                SET_COOKIE,
+                HeaderValue::from_str(&cookie).expect("The token is hex encoded"),
+            );
+        }
+    }
+    Ok(response)
+}
+
+fn needs_token(method: &Method, path: &str) -> bool {
+    !matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    ) && !EXEMPT_PATHS.contains(&path)
+}
+
+/// Browsers never attach an `Authorization` header on their own, and the
+/// admin area doesn't fall back to the session when there is one, so API
+/// key requests can't be forged cross-site.
+fn is_api_request(headers: &HeaderMap, path: &str) -> bool {
+    path.starts_with("/admin/")
+        && headers
+            .get(AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .is_some_and(|h| h.starts_with("Bearer "))
+}
+
+async fn new_session_token(session: &Session) -> Result<String, Response> {
+    let token = generate_csrf_token();
+    session
+        .insert(SESSION_KEY, &token)
+        .await
+        .map_err(internal_error)?;
+    Ok(token)
+}
+
+fn generate_csrf_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn internal_error(e: tower_sessions::session::Error) -> Response {
+    tracing::error!(cause_chain = ?e, "Failed to access the CSRF token in the session");
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+fn cookie_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == CSRF_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+/// The token from the header, or from the form field of a url-encoded body,
+/// in which case the body is read and put back for the handler.
+async fn submitted_token(
+    request: Request<Body>,
+) -> Result<(Option<String>, Request<Body>), Response> {
+    if let Some(token) = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|h| h.to_str().ok())
+    {
+        let token = token.to_string();
+        return Ok((Some(token), request));
+    }
+
+    let is_form = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|h| h.starts_with("application/x-www-form-urlencoded"));
+    if !is_form {
+        return Ok((None, request));
+    }
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_FORM_SIZE)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())?;
+    let token = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&bytes)
+        .ok()
+        .and_then(|fields| {
+            fields
+                .into_iter()
+                .find(|(name, _)| name == CSRF_FORM_FIELD)
+                .map(|(_, value)| value)
+        });
+    Ok((token, Request::from_parts(parts, Body::from(bytes))))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, Method};
+
+    use super::{constant_time_eq, cookie_token, generate_csrf_token, is_api_request, needs_token};
+
+    #[test]
+    fn tokens_are_32_random_bytes() {
+        let token = generate_csrf_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_csrf_token());
+    }
+
+    #[test]
+    fn only_state_changing_requests_need_a_token() {
+        assert!(!needs_token(&Method::GET, "/login"));
+        assert!(!needs_token(&Method::HEAD, "/login"));
+        assert!(needs_token(&Method::POST, "/login"));
+        assert!(needs_token(
+            &Method::DELETE,
+            "/admin/newsletters/scheduled/1"
+        ));
+        assert!(!needs_token(&Method::POST, "/subscriptions/unsubscribe"));
+        assert!(!needs_token(&Method::POST, "/subscriptions/confirm"));
+        assert!(!needs_token(&Method::POST, "/subscribe-widget"));
+        assert!(needs_token(&Method::POST, "/subscriptions"));
+    }
+
+    #[test]
+    fn admin_requests_with_a_bearer_token_are_api_requests() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_api_request(&headers, "/admin/api-keys"));
+        headers.insert("Authorization", HeaderValue::from_static("Bearer nzl_key"));
+        assert!(is_api_request(&headers, "/admin/api-keys"));
+        assert!(!is_api_request(&headers, "/subscriptions"));
+        headers.insert("Authorization", HeaderValue::from_static("Basic dXNlcg=="));
+        assert!(!is_api_request(&headers, "/admin/api-keys"));
+    }
+
+    #[test]
+    fn the_token_is_found_among_other_cookies() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Cookie",
+            HeaderValue::from_static("id=abc; csrf_token=123; theme=dark"),
+        );
+        assert_eq!(cookie_token(&headers).as_deref(), Some("123"));
+    }
+
+    #[test]
+    fn tokens_are_compared_in_full() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
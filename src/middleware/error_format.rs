@@ -0,0 +1,99 @@
+use axum::extract::Request;
+use axum::http::header::ACCEPT;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+
+use crate::error::ErrorCode;
+
+/// Browsers get the JSON rendered by [`crate::error::AppError`] turned into a
+/// redirect, to the login page when they have to log in and to the home page,
+/// which explains the `?error=<code>`, otherwise.
+pub async fn negotiate_error_format(request: Request, next: Next) -> Response {
+    let wants_html = accepts_html(request.headers());
+    let response = next.run(request).await;
+    if !wants_html {
+        return response;
+    }
+    match response.extensions().get::<ErrorCode>() {
+        Some(ErrorCode("unauthorized")) => Redirect::to("/login").into_response(),
+        Some(ErrorCode(code)) => Redirect::to(&format!("/?error={}", code)).into_response(),
+        None => response,
+    }
+}
+
+/// Only browsers put `text/html` in `Accept`, API clients and scripts send
+/// `application/json` or `*/*`.
+fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .any(|h| h.contains("text/html"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::negotiate_error_format;
+    use crate::error::AppError;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        middleware,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/missing",
+                get(|| async { Err::<(), _>(AppError::NotFound) }),
+            )
+            .route(
+                "/private",
+                get(|| async { Err::<(), _>(AppError::Unauthorized) }),
+            )
+            .route("/plain", get(|| async { StatusCode::NOT_FOUND }))
+            .layer(middleware::from_fn(negotiate_error_format))
+    }
+
+    async fn get_with_accept(uri: &str, accept: &str) -> axum::response::Response {
+        app()
+            .oneshot(
+                Request::get(uri)
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn browsers_are_redirected_to_the_home_page() {
+        let response = get_with_accept("/missing", "text/html,*/*;q=0.8").await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()["Location"], "/?error=not_found");
+    }
+
+    #[tokio::test]
+    async fn browsers_are_sent_to_the_login_page_when_unauthorized() {
+        let response = get_with_accept("/private", "text/html").await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()["Location"], "/login");
+    }
+
+    #[tokio::test]
+    async fn api_clients_keep_the_json_error() {
+        let response = get_with_accept("/missing", "*/*").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn other_error_responses_are_left_alone() {
+        let response = get_with_accept("/plain", "text/html").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    extract::ConnectInfo,
+    http::{HeaderMap, Request},
+};
+use ipnet::IpNet;
+use tower::{Layer, Service};
+
+/// The proxies allowed to tell us who the client is through `X-Forwarded-For`.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies(Vec<IpNet>);
+
+impl TrustedProxies {
+    pub fn parse(ranges: &[String]) -> Result<Self, ipnet::AddrParseError> {
+        ranges
+            .iter()
+            .map(|range| range.parse::<IpNet>())
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|range| range.contains(ip))
+    }
+
+    /// Our proxy appends the address it got the connection from to
+    /// `X-Forwarded-For`, so only the last entry is believed. Whatever comes
+    /// before it was sent by the client and could be anything, trusted
+    /// ranges included.
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.contains(&peer) {
+            return peer;
+        }
+        headers
+            .get_all("X-Forwarded-For")
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .last()
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+            .unwrap_or(peer)
+    }
+}
+
+/// Replaces the `ConnectInfo` of requests coming through a trusted proxy with
+/// the address of the client, so that rate limiting, the audit log and the
+/// metrics allowlist see the client rather than the proxy.
+#[derive(Clone)]
+pub struct ForwardedForLayer {
+    trusted_proxies: Arc<TrustedProxies>,
+}
+
+impl ForwardedForLayer {
+    pub fn new(trusted_proxies: TrustedProxies) -> Self {
+        Self {
+            trusted_proxies: Arc::new(trusted_proxies),
+        }
+    }
+}
+
+impl<S> Layer<S> for ForwardedForLayer {
+    type Service = ForwardedFor<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ForwardedFor {
+            inner,
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ForwardedFor<S> {
+    inner: S,
+    trusted_proxies: Arc<TrustedProxies>,
+}
+
+impl<S, B> Service<Request<B>> for ForwardedFor<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        if let Some(&ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() {
+            let client = self.trusted_proxies.client_ip(peer.ip(), request.headers());
+            if client != peer.ip() {
+                request
+                    .extensions_mut()
+                    .insert(ConnectInfo(SocketAddr::new(client, peer.port())));
+            }
+        }
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrustedProxies;
+    use axum::http::{HeaderMap, HeaderValue};
+    use std::net::IpAddr;
+
+    fn trusted() -> TrustedProxies {
+        TrustedProxies::parse(&["10.0.0.0/8".to_string(), "127.0.0.1/32".to_string()]).unwrap()
+    }
+
+    fn forwarded_for(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", HeaderValue::from_static(value));
+        headers
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!(TrustedProxies::parse(&["10.0.0.0/33".to_string()]).is_err());
+        assert!(TrustedProxies::parse(&["not-a-range".to_string()]).is_err());
+    }
+
+    #[test]
+    fn untrusted_peers_cannot_forward_an_address() {
+        let client = trusted().client_ip(ip("198.51.100.1"), &forwarded_for("203.0.113.7"));
+        assert_eq!(client, ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn trusted_peers_forward_the_client_address() {
+        let client = trusted().client_ip(ip("127.0.0.1"), &forwarded_for("203.0.113.7"));
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn addresses_the_client_made_up_are_ignored() {
+        // the client sent `192.0.2.1` itself, our proxy appended the real address
+        let client = trusted().client_ip(ip("127.0.0.1"), &forwarded_for("192.0.2.1, 203.0.113.7"));
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn made_up_addresses_in_trusted_ranges_are_ignored_too() {
+        let trust_everyone =
+            TrustedProxies::parse(&["0.0.0.0/0".to_string(), "::/0".to_string()]).unwrap();
+        let client = trust_everyone.client_ip(
+            ip("127.0.0.1"),
+            &forwarded_for("192.0.2.1, 10.0.0.2, 203.0.113.7"),
+        );
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn an_unparsable_last_entry_leaves_the_peer_address() {
+        let client = trusted().client_ip(ip("127.0.0.1"), &forwarded_for("203.0.113.7, unknown"));
+        assert_eq!(client, ip("127.0.0.1"));
+    }
+
+    #[test]
+    fn nothing_is_trusted_by_default() {
+        let client =
+            TrustedProxies::default().client_ip(ip("127.0.0.1"), &forwarded_for("203.0.113.7"));
+        assert_eq!(client, ip("127.0.0.1"));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::http::{header::STRICT_TRANSPORT_SECURITY, HeaderValue, Request, Response};
+use tower::{Layer, Service};
+
+use crate::configuration::Environment;
+
+/// Sets the `Strict-Transport-Security` header of every response in
+/// production, local development runs over plain HTTP and is left alone.
+#[derive(Clone)]
+pub struct HstsLayer {
+    header: Option<HeaderValue>,
+}
+
+impl HstsLayer {
+    pub fn new(environment: Environment, max_age_seconds: u64) -> Self {
+        let header = match environment {
+            Environment::Local => None,
+            Environment::Production => Some(header_value(max_age_seconds)),
+        };
+        Self { header }
+    }
+}
+
+impl<S> Layer<S> for HstsLayer {
+    type Service = Hsts<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Hsts {
+            inner,
+            header: self.header.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Hsts<S> {
+    inner: S,
+    header: Option<HeaderValue>,
+}
+
+// generic over the body, the compression layer it wraps changes its type
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Hsts<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<ResBody>, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let header = self.header.clone();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let mut response = response.await?;
+            if let Some(header) = header {
+                response
+                    .headers_mut()
+                    .insert(STRICT_TRANSPORT_SECURITY, header);
+            }
+            Ok(response)
+        })
+    }
+}
+
+fn header_value(max_age_seconds: u64) -> HeaderValue {
+    HeaderValue::from_str(&format!("max-age={max_age_seconds}; includeSubDomains"))
+        .expect("The header is valid ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::header_value;
+
+    #[test]
+    fn the_header_covers_subdomains() {
+        assert_eq!(
+            header_value(31536000),
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{
+        header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderValue, Request, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use minify_html::Cfg;
+use tower::{Layer, Service};
+
+use crate::configuration::Environment;
+
+/// Minifies the HTML pages in production, dropping comments and the
+/// indentation of the templates.
+///
+/// Locally the pages are sent as rendered, they are easier to debug that way.
+/// Must sit inside the compression layer, compressed bodies are left alone.
+#[derive(Clone, Copy)]
+pub struct HtmlMinifyLayer {
+    environment: Environment,
+}
+
+impl HtmlMinifyLayer {
+    pub fn new(environment: Environment) -> Self {
+        Self { environment }
+    }
+}
+
+impl<S> Layer<S> for HtmlMinifyLayer {
+    type Service = HtmlMinify<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HtmlMinify {
+            inner,
+            environment: self.environment,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HtmlMinify<S> {
+    inner: S,
+    environment: Environment,
+}
+
+impl<S> Service<Request<Body>> for HtmlMinify<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let environment = self.environment;
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let Ok(response) = response.await;
+            if environment == Environment::Local || !is_uncompressed_html(&response) {
+                return Ok(response);
+            }
+            Ok(minify(response).await)
+        })
+    }
+}
+
+fn is_uncompressed_html(response: &Response) -> bool {
+    let headers = response.headers();
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|h| h.starts_with("text/html"))
+        && !headers.contains_key(CONTENT_ENCODING)
+}
+
+async fn minify(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let html = match to_bytes(body, usize::MAX).await {
+        Ok(html) => html,
+        Err(e) => {
+            tracing::error!(error.cause_chain = ?e, "Failed to read the HTML response");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let minified = minify_html::minify(&html, &config());
+    if parts.headers.contains_key(CONTENT_LENGTH) {
+        parts
+            .headers
+            .insert(CONTENT_LENGTH, HeaderValue::from(minified.len()));
+    }
+    Response::from_parts(parts, Body::from(minified))
+}
+
+/// Leaves scripts and styles as they are, along with the closing tags some
+/// browsers' form handling trips over when missing.
+fn config() -> Cfg {
+    Cfg {
+        keep_closing_tags: true,
+        keep_html_and_head_opening_tags: true,
+        ..Cfg::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::config;
+
+    #[test]
+    fn comments_and_indentation_are_removed() {
+        let html = b"<div>\n    <!-- a comment -->\n    <p>Hello   world</p>\n</div>";
+        let minified = minify_html::minify(html, &config());
+        assert_eq!(
+            String::from_utf8(minified).unwrap(),
+            "<div><p>Hello world</p></div>"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use tower::{Layer, Service};
+
+/// Answers `POST`, `PUT` and `PATCH` requests whose `Content-Type` isn't JSON
+/// with a `415` before they reach the handler, for the JSON API routes.
+/// Other methods carry no body and go through.
+#[derive(Clone, Default)]
+pub struct RequireJsonContentType;
+
+impl<S> Layer<S> for RequireJsonContentType {
+    type Service = JsonContentTypeCheck<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        JsonContentTypeCheck { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct JsonContentTypeCheck<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for JsonContentTypeCheck<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if has_body(request.method()) && !is_json(&request) {
+            return Box::pin(async move {
+                Ok((
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    Json(serde_json::json!({
+                        "error": "content_type_required",
+                        "expected": "application/json",
+                    })),
+                )
+                    .into_response())
+            });
+        }
+
+        // the clone may not be ready, so call the service that was polled
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(inner.call(request))
+    }
+}
+
+fn has_body(method: &Method) -> bool {
+    [Method::POST, Method::PUT, Method::PATCH].contains(method)
+}
+
+/// `application/json; charset=utf-8` is JSON too.
+fn is_json(request: &Request<Body>) -> bool {
+    request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_body, is_json};
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+
+    fn request_with_content_type(content_type: &str) -> Request<Body> {
+        Request::post("/")
+            .header("Content-Type", content_type)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn json_with_a_charset_is_accepted() {
+        assert!(is_json(&request_with_content_type(
+            "application/json; charset=utf-8"
+        )));
+    }
+
+    #[test]
+    fn other_content_types_are_rejected() {
+        assert!(!is_json(&request_with_content_type("text/plain")));
+        assert!(!is_json(&Request::post("/").body(Body::empty()).unwrap()));
+    }
+
+    #[test]
+    fn only_methods_with_a_body_are_checked() {
+        assert!(has_body(&Method::PATCH));
+        assert!(!has_body(&Method::GET));
+        assert!(!has_body(&Method::DELETE));
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+
+/// Failed logins per client IP, to slow down password guessing.
+///
+/// Unlike the token buckets this isn't a layer, the login handler consults it
+/// before checking the credentials and reports back how the attempt went, so
+/// only failures count and a successful login starts over.
+pub struct LoginRateLimiter {
+    attempts: DashMap<IpAddr, (u32, Instant)>,
+    max_attempts: u32,
+    window: Duration,
+}
+
+impl LoginRateLimiter {
+    pub fn new(max_attempts: u32, window: Duration) -> Self {
+        Self {
+            attempts: DashMap::new(),
+            max_attempts,
+            window,
+        }
+    }
+
+    /// How long to wait once `ip` used up its attempts.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        self.retry_after(ip, Instant::now()).map_or(Ok(()), Err)
+    }
+
+    pub fn record_failure(&self, ip: IpAddr) {
+        self.record_failure_at(ip, Instant::now());
+    }
+
+    pub fn reset(&self, ip: IpAddr) {
+        self.attempts.remove(&ip);
+    }
+
+    /// Forgets the failures whose window has ended, they no longer count.
+    pub fn prune(&self) {
+        self.prune_at(Instant::now());
+    }
+
+    fn retry_after(&self, ip: IpAddr, now: Instant) -> Option<Duration> {
+        let attempts = self.attempts.get(&ip)?;
+        let (failures, window_start) = *attempts;
+        let window_end = window_start + self.window;
+        (failures >= self.max_attempts && now < window_end).then(|| window_end - now)
+    }
+
+    fn prune_at(&self, now: Instant) {
+        self.attempts.retain(|_, (_, window_start)| {
+            now.saturating_duration_since(*window_start) < self.window
+        });
+    }
+
+    fn record_failure_at(&self, ip: IpAddr, now: Instant) {
+        let mut attempts = self.attempts.entry(ip).or_insert((0, now));
+        let (failures, window_start) = attempts.value_mut();
+        if now.saturating_duration_since(*window_start) >= self.window {
+            *failures = 0;
+            *window_start = now;
+        }
+        *failures += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    use super::LoginRateLimiter;
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    const WINDOW: Duration = Duration::from_secs(15 * 60);
+
+    #[test]
+    fn attempts_are_refused_once_the_failures_add_up() {
+        let limiter = LoginRateLimiter::new(3, WINDOW);
+        let now = Instant::now();
+        for _ in 0..2 {
+            limiter.record_failure_at(IP, now);
+            assert_eq!(limiter.retry_after(IP, now), None);
+        }
+        limiter.record_failure_at(IP, now);
+        assert_eq!(limiter.retry_after(IP, now), Some(WINDOW));
+    }
+
+    #[test]
+    fn failures_are_forgotten_after_the_window() {
+        let limiter = LoginRateLimiter::new(1, WINDOW);
+        let now = Instant::now();
+        limiter.record_failure_at(IP, now);
+
+        let later = now + WINDOW;
+        assert_eq!(limiter.retry_after(IP, later), None);
+        limiter.record_failure_at(IP, later);
+        assert_eq!(limiter.retry_after(IP, later), Some(WINDOW));
+    }
+
+    #[test]
+    fn a_reset_clears_the_failures() {
+        let limiter = LoginRateLimiter::new(1, WINDOW);
+        let now = Instant::now();
+        limiter.record_failure_at(IP, now);
+        limiter.reset(IP);
+        assert_eq!(limiter.retry_after(IP, now), None);
+    }
+
+    #[test]
+    fn failures_are_pruned_once_their_window_ended() {
+        let limiter = LoginRateLimiter::new(1, WINDOW);
+        let now = Instant::now();
+        limiter.record_failure_at(IP, now);
+
+        limiter.prune_at(now);
+        assert_eq!(limiter.attempts.len(), 1);
+
+        limiter.prune_at(now + WINDOW);
+        assert!(limiter.attempts.is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+pub mod csp;
+pub mod csrf;
+pub mod error_format;
+pub mod forwarded;
+pub mod hsts;
+pub mod html_minify;
+pub mod json_content_type;
+pub mod login_rate_limit;
+pub mod rate_limit;
+pub mod request_id;
+pub mod timeout;
+pub mod validate_host;
+
+pub use csp::{CspLayer, CspNonce, EmbeddableAnywhere};
+pub use csrf::{CsrfLayer, CsrfToken};
+pub use error_format::negotiate_error_format;
+pub use forwarded::{ForwardedForLayer, TrustedProxies};
+pub use hsts::HstsLayer;
+pub use html_minify::HtmlMinifyLayer;
+pub use json_content_type::RequireJsonContentType;
+pub use login_rate_limit::LoginRateLimiter;
+pub use rate_limit::{too_many_requests, RateLimitLayer, RateLimiter};
+pub use request_id::{RequestId, RequestIdLayer};
+pub use timeout::handle_timeout_error;
+pub use validate_host::{RequestBaseUrl, ValidateHostLayer};
@@ -0,0 +1,231 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    http::{header::RETRY_AFTER, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use tower::{Layer, Service};
+
+use crate::{audit::ClientIp, configuration::TokenBucketSettings};
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token, or return how long to wait until one is available.
+    fn try_take(
+        &mut self,
+        now: Instant,
+        capacity: f64,
+        refill_per_second: f64,
+    ) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * refill_per_second).min(capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            // a bucket that never refills stays empty for good
+            Err(
+                Duration::try_from_secs_f64((1.0 - self.tokens) / refill_per_second)
+                    .unwrap_or(Duration::MAX),
+            )
+        }
+    }
+
+    /// A bucket that filled up again is no different from a new one.
+    fn is_full_at(&self, now: Instant, capacity: f64, refill_per_second: f64) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens + elapsed * refill_per_second >= capacity
+    }
+}
+
+/// One token bucket per client IP.
+pub struct RateLimiter {
+    buckets: DashMap<IpAddr, TokenBucket>,
+    capacity: f64,
+    refill_per_second: f64,
+}
+
+impl RateLimiter {
+    pub fn new(settings: &TokenBucketSettings) -> Self {
+        Self {
+            buckets: DashMap::new(),
+            capacity: f64::from(settings.capacity),
+            refill_per_second: f64::from(settings.refill_per_minute) / 60.0,
+        }
+    }
+
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        self.check_at(ip, Instant::now())
+    }
+
+    /// Forgets the clients whose bucket is full again, they would get a full
+    /// one on their next request anyway.
+    pub fn prune(&self) {
+        self.prune_at(Instant::now());
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        self.buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::full(self.capacity, now))
+            .try_take(now, self.capacity, self.refill_per_second)
+    }
+
+    fn prune_at(&self, now: Instant) {
+        self.buckets
+            .retain(|_, bucket| !bucket.is_full_at(now, self.capacity, self.refill_per_second));
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request<Body>> for RateLimit<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // clients we can't identify are let through
+        let ClientIp(client_ip) = ClientIp::resolve(request.extensions());
+        if let Some(Err(retry_after)) = client_ip.map(|ip| self.limiter.check(ip)) {
+            tracing::warn!(client_ip = ?client_ip, "Rate limited a request");
+            return Box::pin(async move { Ok(too_many_requests(retry_after)) });
+        }
+
+        // the clone may not be ready, so call the service that was polled
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(inner.call(request))
+    }
+}
+
+/// A `429` telling the client when to come back.
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    let retry_after_seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, retry_after_seconds.to_string())],
+        "Too many requests, please try again later.",
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    use super::{RateLimiter, TokenBucket};
+    use crate::configuration::TokenBucketSettings;
+
+    const IP: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn a_bucket_allows_bursts_up_to_its_capacity() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(3.0, now);
+        for _ in 0..3 {
+            assert!(bucket.try_take(now, 3.0, 1.0).is_ok());
+        }
+        assert!(bucket.try_take(now, 3.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn an_empty_bucket_tells_how_long_to_wait() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(1.0, now);
+        bucket.try_take(now, 1.0, 0.5).unwrap();
+        assert_eq!(bucket.try_take(now, 1.0, 0.5), Err(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn a_bucket_refills_over_time_without_overflowing() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::full(2.0, now);
+        bucket.try_take(now, 2.0, 1.0).unwrap();
+        bucket.try_take(now, 2.0, 1.0).unwrap();
+
+        let later = now + Duration::from_secs(60);
+        assert!(bucket.try_take(later, 2.0, 1.0).is_ok());
+        assert!(bucket.try_take(later, 2.0, 1.0).is_ok());
+        assert!(bucket.try_take(later, 2.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn buckets_are_pruned_once_they_are_full_again() {
+        let limiter = RateLimiter::new(&TokenBucketSettings {
+            capacity: 2,
+            refill_per_minute: 60,
+        });
+        let now = Instant::now();
+        limiter.check_at(IP, now).unwrap();
+
+        limiter.prune_at(now);
+        assert_eq!(limiter.buckets.len(), 1);
+
+        limiter.prune_at(now + Duration::from_secs(1));
+        assert!(limiter.buckets.is_empty());
+    }
+}
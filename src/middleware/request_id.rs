@@ -0,0 +1,128 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, Request},
+    response::Response,
+};
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// IDs sent by clients longer than this are replaced with one of ours.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// The ID of the current request, as echoed in the `X-Request-ID` header.
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Gives every request an ID, to correlate what clients see with our logs.
+///
+/// The `X-Request-ID` of the request is kept when a proxy in front of us
+/// already set one, a random UUID is used otherwise. The ID is added to the
+/// request extensions and sent back in the `X-Request-ID` response header.
+/// Must sit outside the trace layer, which records the ID on its span.
+#[derive(Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl RequestIdLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+// generic over the response body, the compression layer it wraps changes its type
+impl<S, ResBody> Service<Request<Body>> for RequestIdService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<ResBody>, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let header = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .filter(|h| is_acceptable(h))
+            .cloned()
+            .unwrap_or_else(|| {
+                HeaderValue::from_str(&Uuid::new_v4().to_string()).expect("UUIDs are valid ASCII")
+            });
+        let request_id = header
+            .to_str()
+            .expect("Only visible ASCII is accepted")
+            .to_string();
+        request.extensions_mut().insert(RequestId(request_id));
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let Ok(mut response) = response.await;
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static(REQUEST_ID_HEADER), header);
+            Ok(response)
+        })
+    }
+}
+
+/// Anything else could be used to forge log lines or blow up their size.
+fn is_acceptable(header: &HeaderValue) -> bool {
+    let bytes = header.as_bytes();
+    !bytes.is_empty()
+        && bytes.len() <= MAX_REQUEST_ID_LENGTH
+        && bytes.iter().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::is_acceptable;
+
+    #[test]
+    fn ids_from_upstream_are_kept() {
+        assert!(is_acceptable(&HeaderValue::from_static(
+            "0b7c9a5e-4f3e-4b8e-9d1a-2f6c8e7d5a31"
+        )));
+        assert!(is_acceptable(&HeaderValue::from_static("req_123")));
+    }
+
+    #[test]
+    fn unreasonable_ids_are_replaced() {
+        assert!(!is_acceptable(&HeaderValue::from_static("")));
+        assert!(!is_acceptable(&HeaderValue::from_static("with spaces")));
+        assert!(!is_acceptable(
+            &HeaderValue::from_str(&"a".repeat(129)).unwrap()
+        ));
+    }
+}
@@ -0,0 +1,81 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    BoxError, Json,
+};
+use serde::Serialize;
+use tower::timeout::error::Elapsed;
+
+#[derive(Serialize)]
+struct TimeoutError {
+    error: &'static str,
+}
+
+/// Turns the error of `tower::timeout::TimeoutLayer` into a `408`, for the
+/// `HandleErrorLayer` wrapping it. The layer fails no other way, but the
+/// signature has to accept any error.
+pub async fn handle_timeout_error(error: BoxError) -> Response {
+    if error.is::<Elapsed>() {
+        tracing::warn!("The request took too long and was aborted");
+        return (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(TimeoutError {
+                error: "request_timeout",
+            }),
+        )
+            .into_response();
+    }
+    tracing::error!(error.message = %error, "A middleware failed the request");
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::handle_timeout_error;
+    use axum::{
+        body::{to_bytes, Body},
+        error_handling::HandleErrorLayer,
+        http::Request,
+        routing::get,
+        Router,
+    };
+    use std::time::Duration;
+    use tower::{timeout::TimeoutLayer, ServiceBuilder, ServiceExt};
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/slow",
+                get(|| async { tokio::time::sleep(Duration::from_secs(60)).await }),
+            )
+            .route("/fast", get(|| async {}))
+            .layer(
+                ServiceBuilder::new()
+                    .layer(HandleErrorLayer::new(handle_timeout_error))
+                    .layer(TimeoutLayer::new(Duration::from_secs(30))),
+            )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_requests_time_out_with_a_408() {
+        let request = Request::get("/slow").body(Body::empty()).unwrap();
+
+        let started_at = tokio::time::Instant::now();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status().as_u16(), 408);
+        assert_eq!(started_at.elapsed(), Duration::from_secs(30));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, serde_json::json!({ "error": "request_timeout" }));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_requests_are_left_alone() {
+        let request = Request::get("/fast").body(Body::empty()).unwrap();
+
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status().as_u16(), 200);
+    }
+}
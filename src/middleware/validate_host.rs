@@ -0,0 +1,147 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{header::HOST, uri::Authority, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tower::{Layer, Service};
+
+/// `application.base_url` with the host the request was sent to, for the
+/// links emailed in response to it. Set by [`ValidateHostLayer`].
+#[derive(Clone, Debug)]
+pub struct RequestBaseUrl(pub String);
+
+/// Answers requests for a host outside of `application.allowed_hosts` with a
+/// `421`, so that a forged `Host` header can't end up in the links we send.
+#[derive(Clone)]
+pub struct ValidateHostLayer {
+    allowed_hosts: Arc<Vec<String>>,
+    scheme: Arc<str>,
+}
+
+impl ValidateHostLayer {
+    /// The links keep the scheme of `base_url`, TLS is terminated before the
+    /// request reaches us.
+    pub fn new(allowed_hosts: Vec<String>, base_url: &str) -> Self {
+        let scheme = base_url
+            .split_once("://")
+            .map_or("http", |(scheme, _)| scheme);
+        Self {
+            allowed_hosts: Arc::new(
+                allowed_hosts
+                    .into_iter()
+                    .map(|host| host.to_ascii_lowercase())
+                    .collect(),
+            ),
+            scheme: scheme.into(),
+        }
+    }
+}
+
+impl<S> Layer<S> for ValidateHostLayer {
+    type Service = ValidateHost<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ValidateHost {
+            inner,
+            allowed_hosts: self.allowed_hosts.clone(),
+            scheme: self.scheme.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ValidateHost<S> {
+    inner: S,
+    allowed_hosts: Arc<Vec<String>>,
+    scheme: Arc<str>,
+}
+
+impl<S> Service<Request<Body>> for ValidateHost<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let Some(authority) = request_authority(&request)
+            .filter(|authority| is_allowed(&self.allowed_hosts, authority))
+        else {
+            tracing::warn!(host = ?request.headers().get(HOST), "Rejected a request for an unknown host");
+            return Box::pin(async move {
+                Ok((
+                    StatusCode::MISDIRECTED_REQUEST,
+                    "This server does not serve the requested host.",
+                )
+                    .into_response())
+            });
+        };
+        request
+            .extensions_mut()
+            .insert(RequestBaseUrl(format!("{}://{}", self.scheme, authority)));
+
+        // the clone may not be ready, so call the service that was polled
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(inner.call(request))
+    }
+}
+
+/// The `Host` header, or the authority of the URI for HTTP/2 requests.
+fn request_authority(request: &Request<Body>) -> Option<Authority> {
+    match request.headers().get(HOST) {
+        Some(host) => host.to_str().ok()?.parse().ok(),
+        None => request.uri().authority().cloned(),
+    }
+}
+
+/// The port is left out of the comparison, the app can be reached through
+/// any port the proxy forwards.
+fn is_allowed(allowed_hosts: &[String], authority: &Authority) -> bool {
+    let host = authority.host().to_ascii_lowercase();
+    allowed_hosts.contains(&host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_allowed;
+
+    fn allowed_hosts() -> Vec<String> {
+        vec!["localhost".to_string(), "127.0.0.1".to_string()]
+    }
+
+    #[test]
+    fn the_port_is_ignored() {
+        assert!(is_allowed(
+            &allowed_hosts(),
+            &"127.0.0.1:8080".parse().unwrap()
+        ));
+    }
+
+    #[test]
+    fn hosts_are_compared_case_insensitively() {
+        assert!(is_allowed(&allowed_hosts(), &"LocalHost".parse().unwrap()));
+    }
+
+    #[test]
+    fn subdomains_of_an_allowed_host_are_rejected() {
+        assert!(!is_allowed(
+            &allowed_hosts(),
+            &"evil.localhost".parse().unwrap()
+        ));
+    }
+}
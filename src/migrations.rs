@@ -0,0 +1,151 @@
+use anyhow::Context;
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::SqlitePool;
+
+/// Every migration in `migrations/`, each `.up.sql` comes with a `.down.sql`
+/// undoing it.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Reverts the `steps` most recently applied migrations, newest first, and
+/// returns their versions. Rows that don't fit the older schema are lost, so
+/// this is for development databases only.
+#[tracing::instrument(name = "Revert migrations", skip(pool))]
+pub async fn migrate_down(pool: &SqlitePool, steps: usize) -> Result<Vec<i64>, anyhow::Error> {
+    let mut connection = pool
+        .acquire()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    connection.ensure_migrations_table().await?;
+    let mut applied: Vec<i64> = connection
+        .list_applied_migrations()
+        .await
+        .context("Failed to list the applied migrations.")?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    // `undo` needs a connection of its own
+    drop(connection);
+    applied.sort_unstable();
+    let keep = applied.len().saturating_sub(steps);
+    let reverted = applied.split_off(keep);
+    // everything newer than the last migration we keep is undone
+    let target = applied.last().copied().unwrap_or(0);
+    MIGRATOR
+        .undo(pool, target)
+        .await
+        .context("Failed to revert the migrations.")?;
+    Ok(reverted.into_iter().rev().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{migrate_down, MIGRATOR};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn migrated_pool() -> SqlitePool {
+        // an in-memory database only lives as long as its single connection
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn applied_versions(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn one_step_down_reverts_the_latest_migration() {
+        let pool = migrated_pool().await;
+        let mut versions = applied_versions(&pool).await;
+        let latest = versions.pop().unwrap();
+
+        let reverted = migrate_down(&pool, 1).await.unwrap();
+
+        assert_eq!(reverted, vec![latest]);
+        assert_eq!(latest, MIGRATOR.iter().last().unwrap().version);
+        // the ones before are still there
+        assert_eq!(applied_versions(&pool).await, versions);
+    }
+
+    async fn query_plan(pool: &SqlitePool, query: &str) -> String {
+        let steps: Vec<(i64, i64, i64, String)> =
+            sqlx::query_as(&format!("EXPLAIN QUERY PLAN {}", query))
+                .fetch_all(pool)
+                .await
+                .unwrap();
+        steps
+            .into_iter()
+            .map(|(_, _, _, detail)| detail)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[tokio::test]
+    async fn the_confirmed_subscribers_are_found_through_an_index() {
+        let pool = migrated_pool().await;
+
+        let plan = query_plan(
+            &pool,
+            "SELECT email FROM subscriptions WHERE status = 'confirmed'",
+        )
+        .await;
+
+        assert!(
+            plan.contains("USING INDEX idx_subscriptions_status"),
+            "{}",
+            plan
+        );
+    }
+
+    #[tokio::test]
+    async fn the_queue_of_an_issue_is_found_through_an_index() {
+        let pool = migrated_pool().await;
+
+        let plan = query_plan(
+            &pool,
+            "SELECT COUNT(*) FROM issue_delivery_queue WHERE newsletter_issue_uuid = 'an-issue'",
+        )
+        .await;
+
+        assert!(plan.contains("USING COVERING INDEX"), "{}", plan);
+    }
+
+    #[tokio::test]
+    async fn every_migration_can_be_reverted_and_applied_again() {
+        let pool = migrated_pool().await;
+        // rows the down migrations have to carry over
+        sqlx::query(
+            "INSERT INTO subscriptions (uuid, email, name, subscribed_at, status)
+            VALUES ('a-uuid', 'ursula@example.com', 'Ursula', 'now', 'confirmed')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let reverted = migrate_down(&pool, usize::MAX).await.unwrap();
+
+        assert_eq!(
+            reverted.len(),
+            MIGRATOR
+                .iter()
+                .filter(|m| m.migration_type.is_up_migration())
+                .count()
+        );
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name != '_sqlx_migrations'",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert!(tables.is_empty(), "Tables left behind: {:?}", tables);
+        MIGRATOR.run(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::{ApiKey, UserId};
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+use anyhow::Context;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::Utc;
+use secrecy::ExposeSecret;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct NewApiKey {
+    /// What the key is for, e.g. `ci`.
+    name: String,
+    /// The key never expires when missing.
+    expires_in_days: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CreatedApiKey {
+    uuid: String,
+    name: String,
+    /// Only ever shown here, the key can't be recovered afterwards.
+    key: String,
+    expires_at: Option<String>,
+}
+
+/// Create a key for the admin API, acting as the user who created it.
+#[tracing::instrument(
+    name = "Create an API key",
+    skip(app_state, user_id, client_ip, new_key),
+    fields(user_id=%user_id, name=%new_key.name)
+)]
+pub async fn create_api_key(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Json(new_key): Json<NewApiKey>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let name = new_key.name.trim();
+    if name.is_empty() {
+        return Err(e400(anyhow::anyhow!("The API key needs a name.")));
+    }
+    let key = ApiKey::generate();
+    let expires_at = new_key
+        .expires_in_days
+        .map(|days| (Utc::now() + chrono::Duration::days(days.into())).to_string());
+    let key_id = store_api_key(
+        app_state.pool(),
+        *user_id,
+        name,
+        &key,
+        expires_at.as_deref(),
+    )
+    .await
+    .context("Failed to store the API key.")
+    .map_err(e500)?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "create_api_key",
+            target_type: "api_key",
+            target_id: Some(key_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKey {
+            uuid: key_id.to_string(),
+            name: name.to_string(),
+            key: key.expose_secret().to_string(),
+            expires_at,
+        }),
+    )
+        .into_response())
+}
+
+#[tracing::instrument(name = "Store an API key", skip(pool, key))]
+async fn store_api_key(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+    name: &str,
+    key: &ApiKey,
+    expires_at: Option<&str>,
+) -> Result<Uuid, sqlx::Error> {
+    let key_id = Uuid::new_v4();
+    let key_id_string = key_id.to_string();
+    let user_id = user_id.to_string();
+    let key_hash = key.hash();
+    let now = Utc::now().to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO api_keys (uuid, user_uuid, key_hash, name, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        key_id_string,
+        user_id,
+        key_hash,
+        name,
+        now,
+        expires_at,
+    )
+    .execute(pool)
+    .await?;
+    Ok(key_id)
+}
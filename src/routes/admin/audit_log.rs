@@ -0,0 +1,94 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use std::sync::Arc;
+
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(serde::Deserialize)]
+pub struct AuditLogPagination {
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogEntry {
+    id: i64,
+    user_uuid: String,
+    action: String,
+    target_type: String,
+    target_id: Option<String>,
+    occurred_at: String,
+    ip_address: Option<String>,
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogPage {
+    page: u32,
+    per_page: u32,
+    total: i64,
+    entries: Vec<AuditLogEntry>,
+}
+
+/// The most recent admin actions first.
+#[tracing::instrument(name = "List audit log", skip(app_state, pagination))]
+pub async fn list_audit_log(
+    State(app_state): State<Arc<AppState>>,
+    Query(pagination): Query<AuditLogPagination>,
+) -> Result<impl IntoResponse, AppError> {
+    let page = pagination.page.unwrap_or(1);
+    let per_page = pagination.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if page == 0 || per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(AppError::BadRequest(format!(
+            "`page` must be positive and `per_page` between 1 and {}.",
+            MAX_PER_PAGE
+        )));
+    }
+
+    let entries = get_audit_log_page(app_state.pool(), page, per_page).await?;
+    Ok(Json(entries))
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_audit_log_page(
+    pool: &SqliteInstrumentedPool,
+    page: u32,
+    per_page: u32,
+) -> Result<AuditLogPage, anyhow::Error> {
+    let total = sqlx::query!(r#"SELECT COUNT(*) as "count!: i64" FROM admin_audit_log"#)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count the audit log entries.")?
+        .count;
+
+    let limit = i64::from(per_page);
+    let offset = i64::from(page - 1) * limit;
+    let entries = sqlx::query_as!(
+        AuditLogEntry,
+        r#"
+        SELECT id as "id!: i64", user_uuid, action, target_type, target_id, occurred_at, ip_address, reason
+        FROM admin_audit_log
+        ORDER BY id DESC
+        LIMIT $1 OFFSET $2
+        "#,
+        limit,
+        offset
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to retrieve the audit log entries.")?;
+
+    Ok(AuditLogPage {
+        page,
+        per_page,
+        total,
+        entries,
+    })
+}
@@ -0,0 +1,156 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::middleware::CsrfToken;
+use crate::startup::AppState;
+use crate::utils::e500;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse};
+use axum_messages::Messages;
+use rinja_axum::Template;
+use std::sync::Arc;
+
+struct BlogPostSummary {
+    slug: String,
+    title: String,
+    draft: bool,
+    updated_at: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin-blog/index.html")]
+struct BlogPostsTemplate {
+    posts: Vec<BlogPostSummary>,
+    messages: Vec<String>,
+    csrf_token: String,
+}
+
+/// The editor is shared by new and existing posts, `action` is where the
+/// form is submitted to.
+#[derive(Template)]
+#[template(path = "admin-blog-edit/index.html")]
+struct BlogPostEditorTemplate {
+    heading: &'static str,
+    action: String,
+    is_new: bool,
+    slug: String,
+    title: String,
+    description: String,
+    markdown_content: String,
+    errors: Vec<String>,
+    csrf_token: String,
+}
+
+#[tracing::instrument(name = "List blog posts", skip(app_state, messages, csrf_token))]
+pub async fn list_blog_posts(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let posts = get_blog_posts(app_state.pool())
+        .await
+        .context("Failed to retrieve the blog posts.")
+        .map_err(e500)?;
+    let html = BlogPostsTemplate {
+        posts,
+        messages: messages.into_iter().map(|m| m.message).collect(),
+        csrf_token,
+    }
+    .render()
+    .context("Failed to render the blog posts page.")
+    .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+#[tracing::instrument(name = "New blog post form", skip(messages, csrf_token))]
+pub async fn new_blog_post_form(
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let html = BlogPostEditorTemplate {
+        heading: "New Blog Post",
+        action: "/admin/blog".into(),
+        is_new: true,
+        slug: String::new(),
+        title: String::new(),
+        description: String::new(),
+        markdown_content: String::new(),
+        errors: messages.into_iter().map(|m| m.message).collect(),
+        csrf_token,
+    }
+    .render()
+    .context("Failed to render the blog post editor.")
+    .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+#[tracing::instrument(name = "Edit blog post form", skip(app_state, messages, csrf_token))]
+pub async fn edit_blog_post_form(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+    Path(slug): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let Some(post) = get_blog_post(app_state.pool(), &slug)
+        .await
+        .context("Failed to retrieve the blog post.")
+        .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Blog post not found").into_response());
+    };
+    let html = BlogPostEditorTemplate {
+        heading: "Edit Blog Post",
+        action: format!("/admin/blog/{}", slug),
+        is_new: false,
+        slug,
+        title: post.title,
+        description: post.description,
+        markdown_content: post.markdown_content,
+        errors: messages.into_iter().map(|m| m.message).collect(),
+        csrf_token,
+    }
+    .render()
+    .context("Failed to render the blog post editor.")
+    .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+#[tracing::instrument(name = "Get all blog posts", skip(pool))]
+async fn get_blog_posts(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<BlogPostSummary>, sqlx::Error> {
+    sqlx::query_as!(
+        BlogPostSummary,
+        r#"
+        SELECT slug, title, draft AS "draft: bool", updated_at
+        FROM blog_posts
+        ORDER BY updated_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+struct BlogPostContent {
+    title: String,
+    description: String,
+    markdown_content: String,
+}
+
+#[tracing::instrument(name = "Get a blog post", skip(pool))]
+async fn get_blog_post(
+    pool: &SqliteInstrumentedPool,
+    slug: &str,
+) -> Result<Option<BlogPostContent>, sqlx::Error> {
+    sqlx::query_as!(
+        BlogPostContent,
+        r#"
+        SELECT title, description, markdown_content
+        FROM blog_posts
+        WHERE slug = $1
+        "#,
+        slug,
+    )
+    .fetch_optional(pool)
+    .await
+}
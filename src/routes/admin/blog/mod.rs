@@ -0,0 +1,7 @@
+mod get;
+mod post;
+mod stats;
+
+pub use get::{edit_blog_post_form, list_blog_posts, new_blog_post_form};
+pub use post::{create_blog_post, toggle_blog_post_draft, update_blog_post};
+pub use stats::blog_post_stats;
@@ -0,0 +1,273 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::{e500, respond_for_content_type};
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::{Extension, Form};
+use axum_messages::Messages;
+use chrono::Utc;
+use std::sync::Arc;
+
+#[derive(serde::Deserialize)]
+pub struct NewPostFormData {
+    slug: String,
+    #[serde(flatten)]
+    post: PostFormData,
+}
+
+#[derive(serde::Deserialize)]
+pub struct PostFormData {
+    title: String,
+    #[serde(default)]
+    description: String,
+    markdown_content: String,
+}
+
+impl PostFormData {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.title.trim().is_empty() {
+            return Err("The title can't be empty.");
+        }
+        if self.markdown_content.trim().is_empty() {
+            return Err("The post can't be empty.");
+        }
+        Ok(())
+    }
+}
+
+/// Slugs end up in the url, keep them to lowercase words joined by dashes.
+fn validate_slug(slug: &str) -> Result<(), &'static str> {
+    let is_valid = !slug.is_empty()
+        && slug.len() <= 100
+        && slug.split('-').all(|word| {
+            !word.is_empty() && word.bytes().all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9'))
+        });
+    if !is_valid {
+        return Err("The slug may only contain lowercase letters, digits and single dashes.");
+    }
+    // the posts built by astro are served first, a post with the same slug would be unreachable
+    if std::path::Path::new("frontend/dist/blog")
+        .join(slug)
+        .exists()
+    {
+        return Err("A blog post with this slug already exists.");
+    }
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Create a blog post",
+    skip(app_state, messages, headers, user_id, client_ip, form),
+    fields(slug = %form.slug)
+)]
+pub async fn create_blog_post(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    headers: HeaderMap,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Form(form): Form<NewPostFormData>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    if let Err(e) = validate_slug(&form.slug).and_then(|_| form.post.validate()) {
+        return Ok(respond_for_content_type(
+            &headers,
+            messages,
+            "/admin/blog/new",
+            Err(e),
+        ));
+    }
+
+    let created = insert_blog_post(app_state.pool(), &form.slug, &form.post)
+        .await
+        .context("Failed to store the new blog post.")
+        .map_err(e500)?;
+    if !created {
+        return Ok(respond_for_content_type(
+            &headers,
+            messages,
+            "/admin/blog/new",
+            Err("A blog post with this slug already exists."),
+        ));
+    }
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "create_blog_post",
+            target_type: "blog_post",
+            target_id: Some(form.slug.clone()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(respond_for_content_type(
+        &headers,
+        messages,
+        "/admin/blog",
+        Ok(&format!(
+            "The draft \"{}\" has been saved.",
+            form.post.title.trim()
+        )),
+    ))
+}
+
+#[tracing::instrument(
+    name = "Update a blog post",
+    skip(app_state, messages, headers, user_id, client_ip, form)
+)]
+pub async fn update_blog_post(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    headers: HeaderMap,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(slug): Path<String>,
+    Form(form): Form<PostFormData>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    if let Err(e) = form.validate() {
+        return Ok(respond_for_content_type(
+            &headers,
+            messages,
+            &format!("/admin/blog/{}/edit", slug),
+            Err(e),
+        ));
+    }
+
+    let title = form.title.trim();
+    let description = form.description.trim();
+    let updated_at = Utc::now().to_string();
+    let updated = sqlx::query!(
+        r#"
+        UPDATE blog_posts
+        SET title = $1, description = $2, markdown_content = $3, updated_at = $4
+        WHERE slug = $5
+        "#,
+        title,
+        description,
+        form.markdown_content,
+        updated_at,
+        slug,
+    )
+    .execute(app_state.pool())
+    .await
+    .context("Failed to update the blog post.")
+    .map_err(e500)?
+    .rows_affected();
+    if updated == 0 {
+        return Ok((StatusCode::NOT_FOUND, "Blog post not found").into_response());
+    }
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "update_blog_post",
+            target_type: "blog_post",
+            target_id: Some(slug.clone()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(respond_for_content_type(
+        &headers,
+        messages,
+        "/admin/blog",
+        Ok(&format!("\"{}\" has been updated.", form.title.trim())),
+    ))
+}
+
+/// Publishes a draft, or turns a published post back into a draft.
+///
+/// The first publication date is kept when a post is unpublished and
+/// published again.
+#[tracing::instrument(
+    name = "Toggle a blog post draft",
+    skip(app_state, messages, headers, user_id, client_ip)
+)]
+pub async fn toggle_blog_post_draft(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    headers: HeaderMap,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(slug): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let now = Utc::now().to_string();
+    // the right hand side of each assignment sees the row before the update
+    let Some(post) = sqlx::query!(
+        r#"
+        UPDATE blog_posts
+        SET draft = NOT draft,
+            published_at = CASE WHEN draft THEN COALESCE(published_at, $1) ELSE published_at END,
+            updated_at = $1
+        WHERE slug = $2
+        RETURNING title, draft AS "draft: bool"
+        "#,
+        now,
+        slug,
+    )
+    .fetch_optional(app_state.pool())
+    .await
+    .context("Failed to toggle the blog post draft.")
+    .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Blog post not found").into_response());
+    };
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: if post.draft {
+                "unpublish_blog_post"
+            } else {
+                "publish_blog_post"
+            },
+            target_type: "blog_post",
+            target_id: Some(slug),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    let message = if post.draft {
+        format!("\"{}\" is a draft again.", post.title)
+    } else {
+        format!("\"{}\" has been published.", post.title)
+    };
+    Ok(respond_for_content_type(
+        &headers,
+        messages,
+        "/admin/blog",
+        Ok(&message),
+    ))
+}
+
+/// Returns `false` when the slug is already taken.
+#[tracing::instrument(name = "Store a new blog post", skip(pool, post))]
+async fn insert_blog_post(
+    pool: &SqliteInstrumentedPool,
+    slug: &str,
+    post: &PostFormData,
+) -> Result<bool, sqlx::Error> {
+    let title = post.title.trim();
+    let description = post.description.trim();
+    let now = Utc::now().to_string();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO blog_posts (slug, title, description, markdown_content, draft, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, TRUE, $5, $5)
+        ON CONFLICT (slug) DO NOTHING
+        "#,
+        slug,
+        title,
+        description,
+        post.markdown_content,
+        now,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
@@ -0,0 +1,60 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+#[derive(serde::Serialize)]
+pub struct BlogPostStats {
+    total_views: i64,
+    unique_ips_today: i64,
+    views_last_7_days: i64,
+}
+
+/// How often a post has been read, astro posts included. Unknown slugs
+/// simply have no views.
+#[tracing::instrument(name = "Get blog post stats", skip(app_state))]
+pub async fn blog_post_stats(
+    State(app_state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let stats = get_blog_post_stats(app_state.pool(), &slug)
+        .await
+        .context("Failed to compute the blog post stats.")?;
+    Ok(Json(stats))
+}
+
+async fn get_blog_post_stats(
+    pool: &SqliteInstrumentedPool,
+    slug: &str,
+) -> Result<BlogPostStats, sqlx::Error> {
+    // timestamps are stored as `Utc::now().to_string()`, so they compare as strings
+    let now = Utc::now();
+    let start_of_today = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("Midnight is a valid time")
+        .and_utc()
+        .to_string();
+    let week_ago = (now - Duration::days(7)).to_string();
+    sqlx::query_as!(
+        BlogPostStats,
+        r#"
+        SELECT
+            COUNT(*) AS "total_views!: i64",
+            COUNT(DISTINCT CASE WHEN viewed_at >= $2 THEN ip_hash END) AS "unique_ips_today!: i64",
+            COUNT(CASE WHEN viewed_at >= $3 THEN 1 END) AS "views_last_7_days!: i64"
+        FROM blog_post_views
+        WHERE slug = $1
+        "#,
+        slug,
+        start_of_today,
+        week_ago,
+    )
+    .fetch_one(pool)
+    .await
+}
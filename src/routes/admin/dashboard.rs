@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use crate::db::SqliteInstrumentedPool;
+use crate::middleware::{CspNonce, CsrfToken};
 use crate::session_state::TypedSession;
 use crate::startup::AppState;
 use crate::utils::e500;
@@ -7,30 +9,39 @@ use anyhow::Context;
 use axum::extract::State;
 use axum::response::{Html, IntoResponse, Redirect};
 use rinja_axum::Template;
-use sqlx::SqlitePool;
 use uuid::Uuid;
 
 #[derive(Template)]
 #[template(path = "dashboard/index.html")]
 struct DashboardTemplate<'a> {
-    username: &'a str,
+    user: &'a UserInfo,
+    delivery_paused: bool,
+    csrf_token: &'a str,
+    csp_nonce: &'a str,
 }
 
 pub async fn admin_dashboard(
     State(app_state): State<Arc<AppState>>,
     session: TypedSession,
+    CsrfToken(csrf_token): CsrfToken,
+    CspNonce(csp_nonce): CspNonce,
     // TODO:
     // do proper error handling
 ) -> Result<axum::response::Response, axum::response::Response> {
-    let username = if let Some(user_id) = session.get_user_id().await.map_err(e500)? {
-        get_username(user_id, &app_state.pool).await.map_err(e500)?
+    let user = if let Some(user_id) = session.get_user_id().await.map_err(e500)? {
+        get_user_info(user_id, app_state.pool())
+            .await
+            .map_err(e500)?
     } else {
         return Ok(Redirect::to("/login").into_response());
     };
 
     Ok(Html(
         DashboardTemplate {
-            username: &username,
+            user: &user,
+            delivery_paused: app_state.delivery_pause().is_paused(),
+            csrf_token: &csrf_token,
+            csp_nonce: &csp_nonce,
         }
         .render()
         .unwrap(),
@@ -38,8 +49,51 @@ pub async fn admin_dashboard(
     .into_response())
 }
 
+/// What the dashboard greets the user with.
+pub struct UserInfo {
+    username: String,
+    last_login_at: Option<String>,
+}
+
+impl UserInfo {
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// `None` until the user logs in again after the column was added.
+    pub fn last_login_at(&self) -> Option<&str> {
+        self.last_login_at.as_deref()
+    }
+}
+
+#[tracing::instrument(name = "Get user info", skip(pool))]
+async fn get_user_info(
+    user_id: Uuid,
+    pool: &SqliteInstrumentedPool,
+) -> Result<UserInfo, anyhow::Error> {
+    let user_id = user_id.to_string();
+    let row = sqlx::query!(
+        r#"
+        SELECT username, last_login_at
+        FROM users
+        WHERE uuid = $1
+        "#,
+        user_id,
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to perform a query to retrieve the user info.")?;
+    Ok(UserInfo {
+        username: row.username,
+        last_login_at: row.last_login_at,
+    })
+}
+
 #[tracing::instrument(name = "Get username", skip(pool))]
-pub async fn get_username(user_id: Uuid, pool: &SqlitePool) -> Result<String, anyhow::Error> {
+pub async fn get_username(
+    user_id: Uuid,
+    pool: &SqliteInstrumentedPool,
+) -> Result<String, anyhow::Error> {
     let user_id = user_id.to_string();
     let row = sqlx::query!(
         r#"
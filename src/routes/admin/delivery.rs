@@ -0,0 +1,245 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Form, Json};
+use uuid::Uuid;
+
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::error::AppError;
+use crate::issue_delivery_worker::{
+    delete_dead_letter_entry, get_dead_letter_entries, get_queue_depth,
+};
+use crate::startup::AppState;
+
+#[tracing::instrument(name = "List dead lettered deliveries", skip(app_state))]
+pub async fn list_dead_letter_entries(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let entries = get_dead_letter_entries(app_state.pool()).await?;
+    Ok(Json(entries))
+}
+
+#[tracing::instrument(name = "Acknowledge a dead lettered delivery", skip(app_state))]
+pub async fn acknowledge_dead_letter_entry(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    if delete_dead_letter_entry(app_state.pool(), id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound)
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct DeliveryStatus {
+    paused: bool,
+    /// Deliveries waiting in the queue, retries included.
+    queue_depth: u64,
+}
+
+#[tracing::instrument(name = "Get the delivery status", skip(app_state))]
+pub async fn delivery_status(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(DeliveryStatus {
+        paused: app_state.delivery_pause().is_paused(),
+        queue_depth: get_queue_depth(app_state.pool()).await?,
+    }))
+}
+
+/// Stops the worker from taking deliveries off the queue, the one it is
+/// sending is finished first.
+#[tracing::instrument(
+    name = "Pause the deliveries",
+    skip(app_state, user_id, client_ip),
+    fields(user_id=%user_id),
+)]
+pub async fn pause_delivery(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+) -> StatusCode {
+    app_state.delivery_pause().pause();
+    tracing::warn!("Paused the newsletter deliveries");
+    record_delivery_toggle(&app_state, user_id, client_ip, "pause_delivery");
+    StatusCode::NO_CONTENT
+}
+
+#[tracing::instrument(
+    name = "Resume the deliveries",
+    skip(app_state, user_id, client_ip),
+    fields(user_id=%user_id),
+)]
+pub async fn resume_delivery(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+) -> StatusCode {
+    app_state.delivery_pause().resume();
+    tracing::info!("Resumed the newsletter deliveries");
+    record_delivery_toggle(&app_state, user_id, client_ip, "resume_delivery");
+    StatusCode::NO_CONTENT
+}
+
+fn record_delivery_toggle(
+    app_state: &AppState,
+    user_id: UserId,
+    client_ip: Option<std::net::IpAddr>,
+    action: &'static str,
+) {
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action,
+            target_type: "delivery",
+            target_id: None,
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+}
+
+#[derive(serde::Deserialize)]
+pub struct RequeueForm {
+    /// Why the deliveries are given another try, kept in the audit log.
+    reason: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RequeueOutcome {
+    requeued: u64,
+}
+
+/// Longer reasons are cut short, the audit log isn't a place for essays.
+const MAX_REASON_CHARS: usize = 500;
+
+/// Give the failed deliveries of an issue another try, e.g. once the email
+/// provider is back or a misconfiguration is fixed.
+///
+/// The deliveries start over with no retries and leave the dead letter queue.
+/// Subscribers who are no longer confirmed are left alone.
+#[tracing::instrument(
+    name = "Requeue failed deliveries",
+    skip(app_state, user_id, client_ip, form),
+    fields(user_id=%user_id),
+)]
+pub async fn requeue_failed_deliveries(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(issue_id): Path<String>,
+    Form(form): Form<RequeueForm>,
+) -> Result<impl IntoResponse, AppError> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let reason = form
+        .reason
+        .map(|r| r.trim().chars().take(MAX_REASON_CHARS).collect::<String>())
+        .filter(|r| !r.is_empty());
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    let Some(requeued) = requeue_failed(&mut transaction, issue_id)
+        .await
+        .context("Failed to requeue the failed deliveries.")?
+    else {
+        return Err(AppError::NotFound);
+    };
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the requeued deliveries.")?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "requeue_failed_deliveries",
+            target_type: "newsletter_issue",
+            target_id: Some(issue_id.to_string()),
+            ip_address: client_ip,
+            reason,
+        },
+    );
+    tracing::info!(requeued, "Requeued failed deliveries");
+    Ok(Json(RequeueOutcome { requeued }))
+}
+
+/// Returns `None` when the issue doesn't exist.
+#[tracing::instrument(skip(transaction))]
+async fn requeue_failed(
+    transaction: &mut SqliteInstrumentedTransaction,
+    issue_id: Uuid,
+) -> Result<Option<u64>, sqlx::Error> {
+    let issue_id = issue_id.to_string();
+    let exists = sqlx::query!(
+        r#"SELECT 1 AS "exists!: i64" FROM active_newsletter_issues WHERE newsletter_issue_uuid = $1"#,
+        issue_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .is_some();
+    if !exists {
+        return Ok(None);
+    }
+
+    let requeued = sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO issue_delivery_queue (
+            newsletter_issue_uuid,
+            subscriber_email,
+            n_retries,
+            next_attempt_at
+        )
+        SELECT d.newsletter_issue_uuid, d.subscriber_email, 0, NULL
+        FROM newsletter_deliveries d
+        JOIN subscriptions s ON s.email = d.subscriber_email
+        WHERE d.newsletter_issue_uuid = $1
+            AND d.status = 'failed'
+            AND s.status = 'confirmed'
+        "#,
+        issue_id
+    )
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected();
+
+    // the outcome of the requeued deliveries is open again
+    sqlx::query!(
+        r#"
+        DELETE FROM newsletter_deliveries
+        WHERE newsletter_issue_uuid = $1
+            AND status = 'failed'
+            AND subscriber_email IN (
+                SELECT subscriber_email FROM issue_delivery_queue
+                WHERE newsletter_issue_uuid = $1
+            )
+        "#,
+        issue_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM issue_delivery_dead_letter
+        WHERE newsletter_issue_uuid = $1
+            AND subscriber_email IN (
+                SELECT subscriber_email FROM issue_delivery_queue
+                WHERE newsletter_issue_uuid = $1
+            )
+        "#,
+        issue_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(Some(requeued))
+}
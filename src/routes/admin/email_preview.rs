@@ -0,0 +1,106 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::domain::{NewSubscriber, SubscriberEmail, SubscriberName};
+use crate::email_client::list_unsubscribe_headers;
+use crate::issue_delivery_worker::{unsubscribe_link, NewsletterIssue};
+use crate::routes::{generate_subscription_token, ConfirmationEmail};
+use crate::startup::{AppState, HmacSecret};
+use crate::utils::{e400, e500};
+use anyhow::Context;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum::response::{Html, IntoResponse};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Links in previews point here rather than to the real deployment, so that
+/// clicking around a preview doesn't confirm or unsubscribe anyone.
+const PREVIEW_BASE_URL: &str = "http://localhost";
+
+#[derive(serde::Deserialize)]
+pub struct PreviewSubscriber {
+    #[serde(default = "default_name")]
+    name: String,
+    #[serde(default = "default_email")]
+    email: String,
+}
+
+fn default_name() -> String {
+    "Alice".into()
+}
+
+fn default_email() -> String {
+    "alice@example.com".into()
+}
+
+impl TryFrom<PreviewSubscriber> for NewSubscriber {
+    type Error = String;
+
+    fn try_from(value: PreviewSubscriber) -> Result<Self, Self::Error> {
+        let name = SubscriberName::parse(value.name).map_err(|e| e.to_string())?;
+        let email = SubscriberEmail::parse(value.email)?;
+        Ok(Self { name, email })
+    }
+}
+
+/// The HTML body of the email sent to new subscribers, with a confirmation
+/// link that doesn't confirm anyone.
+///
+/// The subscriber goes through the same validation as a real subscription.
+#[tracing::instrument(name = "Preview the confirmation email", skip(subscriber))]
+pub async fn preview_confirmation_email(
+    Query(subscriber): Query<PreviewSubscriber>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let _subscriber: NewSubscriber = subscriber.try_into().map_err(e400)?;
+    let email = ConfirmationEmail::new(PREVIEW_BASE_URL, &generate_subscription_token());
+    Ok(Html(email.html_body).into_response())
+}
+
+/// The HTML body of an issue as the delivery worker sends it, footer included.
+///
+/// The `List-Unsubscribe` headers the email goes out with are set on the
+/// response, their link is signed for a subscriber that doesn't exist.
+#[tracing::instrument(name = "Preview a newsletter issue", skip(app_state, hmac_secret))]
+pub async fn preview_newsletter_issue(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    Path(issue_id): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    let Some(issue) = get_issue(app_state.pool(), issue_id)
+        .await
+        .context("Failed to retrieve the newsletter issue.")
+        .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Newsletter issue not found").into_response());
+    };
+
+    let unsubscribe_link = unsubscribe_link(PREVIEW_BASE_URL, Uuid::nil(), &hmac_secret);
+    let mut response = Html(issue.html_content_with_footer(&unsubscribe_link)).into_response();
+    for header in list_unsubscribe_headers(&unsubscribe_link) {
+        response.headers_mut().insert(
+            HeaderName::from_bytes(header.name.as_bytes()).map_err(e500)?,
+            HeaderValue::from_str(&header.value).map_err(e500)?,
+        );
+    }
+    Ok(response)
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_issue(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<Option<NewsletterIssue>, sqlx::Error> {
+    let issue_id_string = issue_id.to_string();
+    sqlx::query_as!(
+        NewsletterIssue,
+        r#"
+        SELECT title, text_content, html_content, reply_to_email
+        FROM active_newsletter_issues
+        WHERE
+            newsletter_issue_uuid = $1
+        "#,
+        issue_id_string
+    )
+    .fetch_optional(pool)
+    .await
+}
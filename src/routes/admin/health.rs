@@ -0,0 +1,31 @@
+use crate::error::AppError;
+use crate::issue_delivery_worker::get_queue_depth;
+use crate::startup::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use std::sync::Arc;
+
+#[derive(serde::Serialize)]
+pub struct HealthStatus {
+    status: &'static str,
+    uptime_seconds: u64,
+    /// Connections currently open, idle ones included.
+    db_pool_size: u32,
+    queue_depth: u64,
+    version: &'static str,
+}
+
+/// Machine readable status of the running instance, for the load balancers.
+#[tracing::instrument(name = "Get the service health", skip(app_state))]
+pub async fn admin_health(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(HealthStatus {
+        status: "ok",
+        uptime_seconds: app_state.started_at.elapsed().as_secs(),
+        db_pool_size: app_state.pool().size(),
+        queue_depth: get_queue_depth(app_state.pool()).await?,
+        version: env!("CARGO_PKG_VERSION"),
+    }))
+}
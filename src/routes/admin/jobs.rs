@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::Json;
+
+use crate::scheduler::JobStatus;
+use crate::startup::AppState;
+
+/// Every scheduled background job, with when it last ran and for how long.
+#[tracing::instrument(name = "List background jobs", skip(app_state))]
+pub async fn list_jobs(State(app_state): State<Arc<AppState>>) -> Json<Vec<JobStatus>> {
+    Json(app_state.job_statuses.snapshot())
+}
@@ -0,0 +1,52 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::startup::AppState;
+use crate::telemetry::LogFilterError;
+use crate::utils::{e400, e500};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use std::sync::Arc;
+
+#[derive(serde::Deserialize)]
+pub struct LogLevel {
+    /// In the `EnvFilter` syntax, e.g. `newzletter=trace`.
+    filter: String,
+}
+
+/// Change what gets logged without restarting, until the next restart.
+#[tracing::instrument(
+    name = "Change the log filter",
+    skip(app_state, user_id, client_ip, log_level),
+    fields(user_id=%user_id, filter=%log_level.filter)
+)]
+pub async fn change_log_level(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Json(log_level): Json<LogLevel>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    app_state
+        .log_filter
+        .as_ref()
+        .ok_or(LogFilterError::NoSubscriber)
+        .and_then(|log_filter| log_filter.set(&log_level.filter))
+        .map_err(|e| match e {
+            LogFilterError::InvalidFilter(_) => e400(e),
+            LogFilterError::NoSubscriber => e500(e),
+        })?;
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "change_log_level",
+            target_type: "log_filter",
+            target_id: Some(log_level.filter.clone()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    tracing::info!("Changed the log filter");
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
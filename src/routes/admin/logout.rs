@@ -1,17 +1,41 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
 use crate::session_state::TypedSession;
-use crate::utils::e500;
+use crate::startup::AppState;
+use crate::utils::{e500, respond_for_content_type};
+use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::response::{IntoResponse, Redirect};
 use axum_messages::Messages;
+use std::sync::Arc;
 
 pub async fn log_out(
+    State(app_state): State<Arc<AppState>>,
     session: TypedSession,
     messages: Messages,
+    headers: HeaderMap,
+    ClientIp(client_ip): ClientIp,
 ) -> Result<axum::response::Response, axum::response::Response> {
-    if session.get_user_id().await.map_err(e500)?.is_none() {
-        Ok(Redirect::to("/login").into_response())
-    } else {
-        session.log_out().await.map_err(e500)?;
-        messages.info("You have successfully logged out.");
-        Ok(Redirect::to("/login").into_response())
+    match session.get_user_id().await.map_err(e500)? {
+        None => Ok(Redirect::to("/login").into_response()),
+        Some(user_id) => {
+            session.log_out().await.map_err(e500)?;
+            spawn_audit_event(
+                app_state.pool().clone(),
+                AuditEvent {
+                    user_id,
+                    action: "log_out",
+                    target_type: "user",
+                    target_id: Some(user_id.to_string()),
+                    ip_address: client_ip,
+                    reason: None,
+                },
+            );
+            Ok(respond_for_content_type(
+                &headers,
+                messages,
+                "/login",
+                Ok("You have successfully logged out."),
+            ))
+        }
     }
 }
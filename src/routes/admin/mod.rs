@@ -1,9 +1,47 @@
+mod api_keys;
+mod audit_log;
+mod blog;
 mod dashboard;
+mod delivery;
+mod email_preview;
+mod health;
+mod jobs;
+mod log_level;
 mod logout;
 mod newsletter;
 mod password;
+mod subscribers;
+mod system;
+mod tags;
+mod templates;
+mod two_factor;
+mod users;
 
-pub use dashboard::admin_dashboard;
+pub use api_keys::create_api_key;
+pub use audit_log::list_audit_log;
+pub use blog::*;
+pub use dashboard::{admin_dashboard, get_username};
+pub use delivery::{
+    acknowledge_dead_letter_entry, delivery_status, list_dead_letter_entries, pause_delivery,
+    requeue_failed_deliveries, resume_delivery,
+};
+pub use email_preview::{preview_confirmation_email, preview_newsletter_issue};
+pub use health::admin_health;
+pub use jobs::list_jobs;
+pub use log_level::change_log_level;
 pub use logout::log_out;
 pub use newsletter::*;
 pub use password::*;
+pub use subscribers::{
+    bulk_change_subscriber_status, change_subscriber_status, count_subscribers, delete_subscriber,
+    export_subscribers, import_subscribers, list_subscribers, search_subscribers,
+    unsubscribe_reasons, IMPORT_SIZE_LIMIT,
+};
+pub use system::system_diagnostics;
+pub use tags::list_tags;
+pub use templates::{
+    create_template, delete_template, get_template, get_templates, list_templates, update_template,
+    NewsletterTemplate,
+};
+pub use two_factor::{disable_two_factor, enable_two_factor, verify_two_factor};
+pub use users::invite_user;
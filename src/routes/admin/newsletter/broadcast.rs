@@ -0,0 +1,193 @@
+use super::post::word_count;
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::error::AppError;
+use crate::issue_delivery_queue::enqueue_delivery_tasks;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Form, Json};
+use chrono::Utc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long after a broadcast the next one is refused, a second click on the
+/// button shouldn't email everyone twice.
+const BROADCAST_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(serde::Deserialize)]
+pub struct BroadcastForm {
+    title: String,
+    html_content: String,
+    text_content: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct Broadcast {
+    broadcast_id: Uuid,
+    queued: i64,
+}
+
+/// Email every confirmed subscriber right away, skipping the idempotency key,
+/// tags and scheduling of regular issues.
+///
+/// Deliveries still go through the queue, so the content is stored as an
+/// issue with the `broadcast` status, which the archive and the feed leave out.
+#[tracing::instrument(
+    name = "Broadcast an announcement",
+    skip(app_state, user_id, client_ip, form),
+    fields(user_id=%user_id)
+)]
+pub async fn broadcast(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Form(form): Form<BroadcastForm>,
+) -> Result<axum::response::Response, AppError> {
+    for (field, value) in [
+        ("title", &form.title),
+        ("html_content", &form.html_content),
+        ("text_content", &form.text_content),
+    ] {
+        if value.trim().is_empty() {
+            return Err(AppError::BadRequest(format!("`{}` is required.", field)));
+        }
+    }
+    let Some(previous) = try_acquire_broadcast(&app_state.last_broadcast, Instant::now()) else {
+        return Ok((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Only one broadcast can be sent every 10 minutes.",
+        )
+            .into_response());
+    };
+
+    let (broadcast_id, queued) = match enqueue_broadcast(&app_state, &form).await {
+        Ok(enqueued) => enqueued,
+        Err(e) => {
+            // nothing was sent, so the admin can try again right away
+            *app_state.last_broadcast.lock().unwrap() = previous;
+            return Err(e.into());
+        }
+    };
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "broadcast",
+            target_type: "newsletter_issue",
+            target_id: Some(broadcast_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(Json(Broadcast {
+        broadcast_id,
+        queued,
+    })
+    .into_response())
+}
+
+/// Records `now` as the last broadcast unless the previous one is too recent,
+/// returning what it replaced so it can be restored if the broadcast fails.
+fn try_acquire_broadcast(
+    last_broadcast: &Mutex<Option<Instant>>,
+    now: Instant,
+) -> Option<Option<Instant>> {
+    let mut last_broadcast = last_broadcast.lock().unwrap();
+    if last_broadcast.is_some_and(|last| now.duration_since(last) < BROADCAST_INTERVAL) {
+        return None;
+    }
+    Some(last_broadcast.replace(now))
+}
+
+async fn enqueue_broadcast(
+    app_state: &AppState,
+    form: &BroadcastForm,
+) -> Result<(Uuid, i64), anyhow::Error> {
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a database connection.")?;
+    let broadcast_id = insert_broadcast(&mut transaction, form)
+        .await
+        .context("Failed to store the broadcast.")?;
+    let queued = enqueue_delivery_tasks(&mut transaction, broadcast_id)
+        .await
+        .context("Failed to enqueue the broadcast deliveries.")?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the broadcast.")?;
+    Ok((broadcast_id, queued))
+}
+
+#[tracing::instrument(skip_all)]
+async fn insert_broadcast(
+    transaction: &mut SqliteInstrumentedTransaction,
+    form: &BroadcastForm,
+) -> Result<Uuid, sqlx::Error> {
+    let broadcast_id = Uuid::new_v4();
+    let broadcast_id_string = broadcast_id.to_string();
+    let now = Utc::now().to_string();
+    let word_count = word_count(&form.text_content);
+    // broadcasts have no page of their own, the id keeps the slug unique
+    sqlx::query!(
+        r#"
+        INSERT INTO newsletter_issues (
+            newsletter_issue_uuid,
+            title,
+            text_content,
+            html_content,
+            published_at,
+            status,
+            slug,
+            word_count
+        )
+        VALUES ($1, $2, $3, $4, $5, 'broadcast', $1, $6)
+        "#,
+        broadcast_id_string,
+        form.title,
+        form.text_content,
+        form.html_content,
+        now,
+        word_count,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(broadcast_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_acquire_broadcast, BROADCAST_INTERVAL};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn a_second_broadcast_within_the_interval_is_refused() {
+        let last_broadcast = Mutex::new(None);
+        let now = Instant::now();
+        assert_eq!(try_acquire_broadcast(&last_broadcast, now), Some(None));
+        assert_eq!(
+            try_acquire_broadcast(&last_broadcast, now + Duration::from_secs(60)),
+            None
+        );
+    }
+
+    #[test]
+    fn broadcasts_are_allowed_again_once_the_interval_has_passed() {
+        let last_broadcast = Mutex::new(None);
+        let now = Instant::now();
+        try_acquire_broadcast(&last_broadcast, now);
+        assert_eq!(
+            try_acquire_broadcast(&last_broadcast, now + BROADCAST_INTERVAL),
+            Some(Some(now))
+        );
+    }
+}
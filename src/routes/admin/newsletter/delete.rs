@@ -0,0 +1,94 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::error::AppError;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Extension;
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Take an issue out of the archive, the feed and the admin pages.
+///
+/// The row is only marked as deleted, the deliveries that went out still
+/// refer to it. Deliveries that are still queued are dropped.
+#[tracing::instrument(
+    name = "Delete a newsletter issue",
+    skip(app_state, user_id, client_ip),
+    fields(user_id=%user_id),
+)]
+pub async fn delete_newsletter_issue(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(issue_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    if !soft_delete_issue(&mut transaction, issue_id)
+        .await
+        .context("Failed to delete the newsletter issue.")?
+    {
+        return Err(AppError::NotFound);
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the deleted newsletter issue.")?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "delete_newsletter",
+            target_type: "newsletter_issue",
+            target_id: Some(issue_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `false` when there is no such issue, or it was already deleted.
+#[tracing::instrument(skip(transaction))]
+async fn soft_delete_issue(
+    transaction: &mut SqliteInstrumentedTransaction,
+    issue_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let issue_id = issue_id.to_string();
+    let now = Utc::now().to_string();
+    let n_deleted_rows = sqlx::query!(
+        r#"
+        UPDATE newsletter_issues
+        SET deleted_at = $2
+        WHERE newsletter_issue_uuid = $1 AND deleted_at IS NULL
+        "#,
+        issue_id,
+        now
+    )
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected();
+    if n_deleted_rows == 0 {
+        return Ok(false);
+    }
+    sqlx::query!(
+        r#"
+        DELETE FROM issue_delivery_queue
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(true)
+}
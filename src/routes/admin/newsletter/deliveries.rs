@@ -0,0 +1,132 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+use anyhow::Context;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(serde::Deserialize)]
+pub struct Pagination {
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct Delivery {
+    subscriber_email: String,
+    status: String,
+    delivered_at: Option<String>,
+    failure_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DeliveriesPage {
+    page: u32,
+    per_page: u32,
+    total: i64,
+    deliveries: Vec<Delivery>,
+}
+
+#[tracing::instrument(name = "List newsletter deliveries", skip(app_state, pagination))]
+pub async fn list_newsletter_deliveries(
+    State(app_state): State<Arc<AppState>>,
+    Path(issue_id): Path<String>,
+    Query(pagination): Query<Pagination>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    let page = pagination.page.unwrap_or(1);
+    let per_page = pagination.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if page == 0 || per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(e400(anyhow::anyhow!(
+            "`page` must be positive and `per_page` between 1 and {}.",
+            MAX_PER_PAGE
+        )));
+    }
+
+    if !issue_exists(app_state.pool(), issue_id)
+        .await
+        .map_err(e500)?
+    {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+    let deliveries = get_deliveries_page(app_state.pool(), issue_id, page, per_page)
+        .await
+        .map_err(e500)?;
+    Ok(Json(deliveries).into_response())
+}
+
+#[tracing::instrument(skip(pool))]
+async fn issue_exists(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<bool, anyhow::Error> {
+    let issue_id = issue_id.to_string();
+    let issue = sqlx::query!(
+        r#"
+        SELECT newsletter_issue_uuid
+        FROM active_newsletter_issues
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to look up the newsletter issue.")?;
+    Ok(issue.is_some())
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_deliveries_page(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+    page: u32,
+    per_page: u32,
+) -> Result<DeliveriesPage, anyhow::Error> {
+    let issue_id = issue_id.to_string();
+    let total = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!: i64"
+        FROM newsletter_deliveries
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to count the deliveries of the newsletter issue.")?
+    .count;
+
+    let limit = i64::from(per_page);
+    let offset = i64::from(page - 1) * limit;
+    let deliveries = sqlx::query_as!(
+        Delivery,
+        r#"
+        SELECT subscriber_email, status, delivered_at, failure_reason
+        FROM newsletter_deliveries
+        WHERE newsletter_issue_uuid = $1
+        ORDER BY subscriber_email
+        LIMIT $2 OFFSET $3
+        "#,
+        issue_id,
+        limit,
+        offset
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to retrieve the deliveries of the newsletter issue.")?;
+
+    Ok(DeliveriesPage {
+        page,
+        per_page,
+        total,
+        deliveries,
+    })
+}
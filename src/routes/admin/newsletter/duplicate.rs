@@ -0,0 +1,117 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::error::AppError;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Redirect};
+use axum::Extension;
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Start a new issue from an old one.
+///
+/// The copy is a draft titled `Copy of <title>`, with the content and tags of
+/// the original. It stays out of the archive and the feed and isn't delivered
+/// to anyone until it gets published.
+#[tracing::instrument(
+    name = "Duplicate a newsletter issue",
+    skip(app_state, user_id, client_ip),
+    fields(user_id=%user_id),
+)]
+pub async fn duplicate_newsletter_issue(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(issue_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    let Some(copy_id) = copy_issue(&mut transaction, issue_id)
+        .await
+        .context("Failed to duplicate the newsletter issue.")?
+    else {
+        return Err(AppError::NotFound);
+    };
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the duplicated newsletter issue.")?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "duplicate_newsletter",
+            target_type: "newsletter_issue",
+            target_id: Some(copy_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(Redirect::to(&format!(
+        "/admin/newsletters/{}/edit",
+        copy_id
+    )))
+}
+
+/// Returns `None` when there is no issue to copy.
+#[tracing::instrument(skip(transaction))]
+async fn copy_issue(
+    transaction: &mut SqliteInstrumentedTransaction,
+    issue_id: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let issue_id = issue_id.to_string();
+    let copy_id = Uuid::new_v4();
+    let copy_id_string = copy_id.to_string();
+    let now = Utc::now().to_string();
+    // drafts have no slug, they only get one once they are published
+    let n_inserted_rows = sqlx::query!(
+        r#"
+        INSERT INTO newsletter_issues (
+            newsletter_issue_uuid,
+            title,
+            text_content,
+            html_content,
+            published_at,
+            status,
+            reply_to_email,
+            word_count
+        )
+        SELECT
+            $2, 'Copy of ' || title, text_content, html_content, $3, 'draft',
+            reply_to_email, word_count
+        FROM active_newsletter_issues
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id,
+        copy_id_string,
+        now,
+    )
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected();
+    if n_inserted_rows == 0 {
+        return Ok(None);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO newsletter_issue_tags (newsletter_issue_uuid, tag)
+        SELECT $2, tag
+        FROM newsletter_issue_tags
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id,
+        copy_id_string,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(Some(copy_id))
+}
@@ -0,0 +1,217 @@
+use super::post::word_count;
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedPool;
+use crate::middleware::CsrfToken;
+use crate::startup::AppState;
+use crate::utils::{e400, e500, respond_for_content_type};
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse};
+use axum::{Extension, Form};
+use axum_messages::Messages;
+use rinja_axum::Template;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Template)]
+#[template(path = "admin-newsletter-edit/index.html")]
+struct EditNewsletterTemplate {
+    issue_id: String,
+    title: String,
+    text_content: String,
+    html_content: String,
+    messages: Vec<String>,
+    csrf_token: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    title: String,
+    text_content: String,
+    html_content: String,
+}
+
+struct Issue {
+    title: String,
+    text_content: String,
+    html_content: String,
+    editable: bool,
+}
+
+const DELIVERY_COMPLETE: &str =
+    "The newsletter issue has already been delivered to every subscriber.";
+
+#[tracing::instrument(
+    name = "Edit newsletter issue form",
+    skip(app_state, messages, csrf_token)
+)]
+pub async fn edit_newsletter_issue_form(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+    Path(issue_id): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    let Some(issue) = get_issue(app_state.pool(), issue_id)
+        .await
+        .context("Failed to retrieve the newsletter issue.")
+        .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Newsletter issue not found").into_response());
+    };
+    if !issue.editable {
+        return Ok((StatusCode::CONFLICT, DELIVERY_COMPLETE).into_response());
+    }
+
+    let html = EditNewsletterTemplate {
+        issue_id: issue_id.to_string(),
+        title: issue.title,
+        text_content: issue.text_content,
+        html_content: issue.html_content,
+        messages: messages.into_iter().map(|m| m.message).collect(),
+        csrf_token,
+    }
+    .render()
+    .context("Failed to render the newsletter issue editor.")
+    .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+/// Fix the content of an issue while it is still being delivered.
+///
+/// The delivery worker reads the issue for every email it sends, so the
+/// subscribers still in the queue get the new version. Issues that are done
+/// delivering are answered with a `409`.
+#[tracing::instrument(
+    name = "Edit a newsletter issue",
+    skip(form, app_state, messages, headers, user_id, client_ip),
+    fields(user_id=%user_id),
+)]
+pub async fn edit_newsletter_issue(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    headers: HeaderMap,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(issue_id): Path<String>,
+    Form(form): Form<FormData>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    if [&form.title, &form.text_content, &form.html_content]
+        .iter()
+        .any(|field| field.trim().is_empty())
+    {
+        return Err(e400(anyhow::anyhow!(
+            "`title`, `text_content` and `html_content` are all required."
+        )));
+    }
+
+    if !update_issue(app_state.pool(), issue_id, &form)
+        .await
+        .context("Failed to update the newsletter issue.")
+        .map_err(e500)?
+    {
+        let exists = get_issue(app_state.pool(), issue_id)
+            .await
+            .context("Failed to retrieve the newsletter issue.")
+            .map_err(e500)?
+            .is_some();
+        return Ok(if exists {
+            (StatusCode::CONFLICT, DELIVERY_COMPLETE).into_response()
+        } else {
+            (StatusCode::NOT_FOUND, "Newsletter issue not found").into_response()
+        });
+    }
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "edit_newsletter",
+            target_type: "newsletter_issue",
+            target_id: Some(issue_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(respond_for_content_type(
+        &headers,
+        messages,
+        &format!("/admin/newsletters/{}/edit", issue_id),
+        Ok("The newsletter issue has been updated!"),
+    ))
+}
+
+/// Drafts and scheduled issues haven't been enqueued yet, the others can be
+/// edited as long as some of their deliveries are pending.
+#[tracing::instrument(skip(pool))]
+async fn get_issue(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<Option<Issue>, sqlx::Error> {
+    let issue_id = issue_id.to_string();
+    sqlx::query_as!(
+        Issue,
+        r#"
+        SELECT
+            title,
+            text_content,
+            html_content,
+            (
+                status IN ('draft', 'scheduled')
+                OR EXISTS (
+                    SELECT 1 FROM issue_delivery_queue
+                    WHERE newsletter_issue_uuid = active_newsletter_issues.newsletter_issue_uuid
+                )
+            ) AS "editable!: bool"
+        FROM active_newsletter_issues
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Returns `false` when the issue doesn't exist or is done delivering.
+#[tracing::instrument(skip(pool, form))]
+async fn update_issue(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+    form: &FormData,
+) -> Result<bool, sqlx::Error> {
+    let issue_id = issue_id.to_string();
+    let word_count = word_count(&form.text_content);
+    // the HTML no longer matches the Markdown it may have been rendered from
+    let n_updated_rows = sqlx::query!(
+        r#"
+        UPDATE newsletter_issues
+        SET
+            title = $2,
+            text_content = $3,
+            html_content = $4,
+            markdown_content = NULL,
+            word_count = $5
+        WHERE newsletter_issue_uuid = $1
+            AND deleted_at IS NULL
+            AND (
+                status IN ('draft', 'scheduled')
+                OR EXISTS (
+                    SELECT 1 FROM issue_delivery_queue
+                    WHERE newsletter_issue_uuid = $1
+                )
+            )
+        "#,
+        issue_id,
+        form.title,
+        form.text_content,
+        form.html_content,
+        word_count,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(n_updated_rows > 0)
+}
@@ -1,25 +1,127 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::response::{Html, IntoResponse};
+use axum::Json;
 use axum_messages::Messages;
 use rinja_axum::Template;
 
+use crate::db::SqliteInstrumentedPool;
+use crate::middleware::{CspNonce, CsrfToken};
+use crate::routes::{get_templates, NewsletterTemplate};
+use crate::startup::AppState;
+use crate::tags::all_tags;
+use crate::utils::{e500, ResponseFormat};
+
 #[derive(Template)]
 #[template(path = "publish_newsletter/index.html")]
 struct PublishNewsletterTemplate {
     idempotency_key: uuid::Uuid,
     errors: Vec<String>,
+    csrf_token: String,
+    csp_nonce: String,
+    tags: Vec<String>,
+    templates: Vec<NewsletterTemplate>,
+    issues: Vec<IssueSummary>,
+}
+
+#[derive(serde::Serialize)]
+struct IssueSummary {
+    newsletter_issue_uuid: String,
+    title: String,
+    published_at: String,
+    status: String,
+    sent: i64,
+    failed: i64,
+    pending: i64,
+    /// Unknown for the issues published before the words were counted.
+    word_count: Option<i64>,
+    /// At 200 words a minute, one minute at least.
+    reading_time_minutes: Option<i64>,
 }
 
-#[tracing::instrument(name = "Publish newsletter form", skip(messages))]
+/// API clients asking for JSON get the list of issues only.
+#[tracing::instrument(
+    name = "Publish newsletter form",
+    skip(app_state, messages, headers, csrf_token, csp_nonce)
+)]
 pub async fn publish_newsletter_form(
+    State(app_state): State<Arc<AppState>>,
     messages: Messages,
+    headers: HeaderMap,
+    CsrfToken(csrf_token): CsrfToken,
+    CspNonce(csp_nonce): CspNonce,
 ) -> Result<axum::response::Response, axum::response::Response> {
+    if ResponseFormat::negotiate(&headers) == ResponseFormat::Json {
+        let issues = get_issue_summaries(app_state.pool())
+            .await
+            .context("Failed to retrieve the newsletter issues.")
+            .map_err(e500)?;
+        return Ok(Json(issues).into_response());
+    }
+    let tags = all_tags(app_state.pool())
+        .await
+        .context("Failed to retrieve the tags.")
+        .map_err(e500)?;
+    let templates = get_templates(app_state.pool())
+        .await
+        .context("Failed to retrieve the newsletter templates.")
+        .map_err(e500)?;
+    let issues = get_issue_summaries(app_state.pool())
+        .await
+        .context("Failed to retrieve the newsletter issues.")
+        .map_err(e500)?;
     Ok(Html(
         PublishNewsletterTemplate {
             idempotency_key: uuid::Uuid::new_v4(),
             errors: messages.into_iter().map(|m| m.message).collect(),
+            csrf_token,
+            csp_nonce,
+            tags,
+            templates,
+            issues,
         }
         .render()
         .unwrap(),
     )
     .into_response())
 }
+
+/// Every issue but the drafts, newest first, with how its deliveries went.
+///
+/// The queue only holds what is still to be sent, the outcomes are recorded
+/// in `newsletter_deliveries`.
+#[tracing::instrument(skip(pool))]
+async fn get_issue_summaries(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<IssueSummary>, sqlx::Error> {
+    sqlx::query_as!(
+        IssueSummary,
+        r#"
+        SELECT
+            i.newsletter_issue_uuid,
+            i.title,
+            i.published_at,
+            i.status,
+            i.word_count,
+            MAX(i.word_count / 200, 1) AS "reading_time_minutes?: i64",
+            COUNT(CASE WHEN d.status = 'delivered' THEN 1 END) AS "sent!: i64",
+            COUNT(CASE WHEN d.status = 'failed' THEN 1 END) AS "failed!: i64",
+            (
+                SELECT COUNT(*)
+                FROM issue_delivery_queue q
+                WHERE q.newsletter_issue_uuid = i.newsletter_issue_uuid
+            ) AS "pending!: i64"
+        FROM active_newsletter_issues i
+        LEFT JOIN newsletter_deliveries d
+            ON d.newsletter_issue_uuid = i.newsletter_issue_uuid
+        WHERE i.status != 'draft'
+        GROUP BY i.newsletter_issue_uuid
+        ORDER BY i.published_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,56 @@
+use pulldown_cmark::{html, Event, Options, Parser, TagEnd};
+
+fn options() -> Options {
+    Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES
+}
+
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, Parser::new_ext(markdown, options()));
+    html_output
+}
+
+/// Keep the words and drop the formatting, used as the plain text version of
+/// an issue when the admin didn't write one.
+pub fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(markdown, options()) {
+        match event {
+            Event::Text(s) | Event::Code(s) => text.push_str(&s),
+            Event::SoftBreak | Event::HardBreak => text.push('\n'),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::CodeBlock
+                | TagEnd::BlockQuote(_)
+                | TagEnd::Table,
+            ) => text.push_str("\n\n"),
+            Event::End(TagEnd::Item | TagEnd::TableRow | TagEnd::TableHead) => text.push('\n'),
+            Event::End(TagEnd::TableCell) => text.push('\t'),
+            _ => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{markdown_to_html, markdown_to_plain_text};
+
+    #[test]
+    fn markdown_is_rendered_to_html() {
+        let html = markdown_to_html("# Hello\n\nSome **bold** text");
+        assert_eq!(
+            html,
+            "<h1>Hello</h1>\n<p>Some <strong>bold</strong> text</p>\n"
+        );
+    }
+
+    #[test]
+    fn formatting_is_stripped_from_the_plain_text() {
+        let text = markdown_to_plain_text(
+            "# Hello\n\nSome **bold** and [linked](https://example.com) `code`\n\n- one\n- two",
+        );
+        assert_eq!(text, "Hello\n\nSome bold and linked code\n\none\ntwo");
+    }
+}
@@ -1,5 +1,23 @@
+mod broadcast;
+mod delete;
+mod deliveries;
+mod duplicate;
+mod edit;
 mod get;
+mod markdown;
 mod post;
+mod progress;
+mod scheduled;
+mod test_send;
 
+pub use broadcast::broadcast;
+pub use delete::delete_newsletter_issue;
+pub use deliveries::list_newsletter_deliveries;
+pub use duplicate::duplicate_newsletter_issue;
+pub use edit::{edit_newsletter_issue, edit_newsletter_issue_form};
 pub use get::publish_newsletter_form;
+pub use markdown::markdown_to_html;
 pub use post::publish_newsletter;
+pub use progress::{newsletter_delivery_progress, newsletter_delivery_progress_stream};
+pub use scheduled::{cancel_scheduled_newsletter, list_scheduled_newsletters};
+pub use test_send::send_test_newsletter;
@@ -1,35 +1,177 @@
+use super::markdown::{markdown_to_html, markdown_to_plain_text};
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
 use crate::authentication::UserId;
+use crate::db::{SqliteInstrumentedPool, SqliteInstrumentedTransaction};
+use crate::domain::SubscriberEmail;
 use crate::idempotency::{save_response, try_processing, IdempotencyKey};
+use crate::issue_delivery_queue::enqueue_delivery_tasks;
+use crate::routes::get_template;
 use crate::startup::AppState;
-use crate::utils::{e400, e500};
+use crate::tags::{all_tags, set_issue_tags};
+use crate::utils::{e400, e500, respond_for_content_type, ResponseFormat};
 use anyhow::Context;
 use axum::extract::State;
-use axum::response::{IntoResponse, Redirect};
-use axum::{Extension, Form};
+use axum::http::HeaderMap;
+use axum::Extension;
+use axum_extra::extract::Form;
 use axum_messages::Messages;
-use chrono::Utc;
-use sqlx::{Sqlite, Transaction};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
 pub struct FormData {
     title: String,
+    /// Derived from `markdown_content` when left blank.
+    #[serde(default)]
     text_content: String,
-    html_content: String,
+    html_content: Option<String>,
+    markdown_content: Option<String>,
     idempotency_key: String,
+    /// ISO 8601 timestamp, the issue is delivered right away when missing.
+    scheduled_for: Option<String>,
+    /// Only subscribers with at least one of these tags get the issue,
+    /// everyone does when there are none.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Wraps the HTML body in the header and footer of this template.
+    template_uuid: Option<String>,
+    /// Where the replies go, the sender when missing.
+    reply_to_email: Option<String>,
+}
+
+/// Accepts RFC 3339 timestamps as well as the timezone-less values submitted by
+/// a `datetime-local` input, which are interpreted as UTC.
+fn parse_scheduled_for(scheduled_for: &str) -> Result<DateTime<Utc>, anyhow::Error> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(scheduled_for) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(scheduled_for, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(scheduled_for, "%Y-%m-%dT%H:%M"))
+        .map(|datetime| datetime.and_utc())
+        .with_context(|| format!("`{}` is not a valid ISO 8601 timestamp.", scheduled_for))
+}
+
+struct IssueContent {
+    text: String,
+    html: String,
+    markdown: Option<String>,
+}
+
+/// The body is written either in Markdown or in raw HTML, never both.
+fn issue_content(
+    text_content: String,
+    html_content: Option<String>,
+    markdown_content: Option<String>,
+) -> Result<IssueContent, anyhow::Error> {
+    // the hidden editor of the form is submitted as an empty string
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+    match (non_empty(html_content), non_empty(markdown_content)) {
+        (Some(_), Some(_)) => Err(anyhow::anyhow!(
+            "Only one of `html_content` and `markdown_content` can be provided."
+        )),
+        (None, None) => Err(anyhow::anyhow!(
+            "Either `html_content` or `markdown_content` must be provided."
+        )),
+        (Some(html), None) => {
+            if text_content.trim().is_empty() {
+                return Err(anyhow::anyhow!(
+                    "`text_content` is required for HTML newsletter issues."
+                ));
+            }
+            Ok(IssueContent {
+                text: text_content,
+                html,
+                markdown: None,
+            })
+        }
+        (None, Some(markdown)) => {
+            let text = if text_content.trim().is_empty() {
+                markdown_to_plain_text(&markdown)
+            } else {
+                text_content
+            };
+            Ok(IssueContent {
+                text,
+                html: markdown_to_html(&markdown),
+                markdown: Some(markdown),
+            })
+        }
+    }
+}
+
+/// Words of the plain text body, shown with the reading time in the issue list.
+pub(super) fn word_count(text_content: &str) -> i64 {
+    text_content.split_whitespace().count() as i64
+}
+
+/// The words of the title, e.g. `october-news` for "October news!".
+fn slugify(title: &str) -> String {
+    let slug = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .take(10)
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "issue".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Titles don't have to be unique, later issues get `-2`, `-3`, ... appended.
+fn first_free_slug(slug: String, taken: &HashSet<String>) -> String {
+    if !taken.contains(&slug) {
+        return slug;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("The taken slugs are finite")
+}
+
+#[tracing::instrument(skip(transaction))]
+async fn issue_slug(
+    transaction: &mut SqliteInstrumentedTransaction,
+    title: &str,
+) -> Result<String, sqlx::Error> {
+    let slug = slugify(title);
+    let taken = sqlx::query_scalar!(
+        r#"
+        SELECT slug AS "slug!"
+        FROM newsletter_issues
+        WHERE slug = $1 OR slug LIKE $1 || '-%'
+        "#,
+        slug
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    Ok(first_free_slug(slug, &taken.into_iter().collect()))
 }
 
 #[tracing::instrument(skip_all)]
 async fn insert_newsletter_issue(
-    transaction: &mut Transaction<'_, Sqlite>,
+    transaction: &mut SqliteInstrumentedTransaction,
     title: &str,
-    text_content: &str,
-    html_content: &str,
+    content: &IssueContent,
+    scheduled_for: Option<DateTime<Utc>>,
+    reply_to_email: &SubscriberEmail,
 ) -> Result<Uuid, sqlx::Error> {
     let newsletter_issue_uuid = Uuid::new_v4();
     let newsletter_issue_uuid_string = newsletter_issue_uuid.to_string();
     let now = Utc::now().to_string();
+    let status = if scheduled_for.is_some() {
+        "scheduled"
+    } else {
+        "queued"
+    };
+    let scheduled_for = scheduled_for.map(|datetime| datetime.to_string());
+    let slug = issue_slug(transaction, title).await?;
+    let reply_to_email = reply_to_email.as_ref();
+    let word_count = word_count(&content.text);
 
     sqlx::query!(
         r#"
@@ -38,67 +180,150 @@ async fn insert_newsletter_issue(
             title, 
             text_content, 
             html_content,
-            published_at
+            markdown_content,
+            published_at,
+            status,
+            scheduled_for,
+            slug,
+            reply_to_email,
+            word_count
         )
-        VALUES ($1, $2, $3, $4, $5)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
         "#,
         newsletter_issue_uuid_string,
         title,
-        text_content,
-        html_content,
-        now
+        content.text,
+        content.html,
+        content.markdown,
+        now,
+        status,
+        scheduled_for,
+        slug,
+        reply_to_email,
+        word_count
     )
-    .execute(&mut **transaction)
+    .execute(&mut *transaction)
     .await?;
 
     Ok(newsletter_issue_uuid)
 }
 
-#[tracing::instrument(skip_all)]
-async fn enqueue_delivery_tasks(
-    transaction: &mut Transaction<'_, Sqlite>,
-    newsletter_issue_uuid: Uuid,
-) -> Result<(), sqlx::Error> {
-    let newsletter_issue_uuid_string = newsletter_issue_uuid.to_string();
-
-    sqlx::query!(
-        r#"
-        INSERT INTO issue_delivery_queue (
-            newsletter_issue_uuid, 
-            subscriber_email
-        )
-        SELECT $1, email
-        FROM subscriptions
-        WHERE status = 'confirmed'
-        "#,
-        newsletter_issue_uuid_string,
+/// Enqueue again the deliveries of an issue that went out, for when the worker
+/// lost some of them. Scheduled issues are left to the scheduler.
+#[tracing::instrument(skip(pool))]
+async fn resume_delivery(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    let mut transaction = pool.begin().await?;
+    let issue_id_string = issue_id.to_string();
+    let status = sqlx::query_scalar!(
+        r#"SELECT status FROM active_newsletter_issues WHERE newsletter_issue_uuid = $1"#,
+        issue_id_string
     )
-    .execute(&mut **transaction)
+    .fetch_optional(&mut transaction)
     .await?;
-
+    if status.as_deref() != Some("queued") {
+        return Ok(());
+    }
+    let n_queued = enqueue_delivery_tasks(&mut transaction, issue_id).await?;
+    transaction.commit().await?;
+    if n_queued > 0 {
+        tracing::info!(n_queued, "Resumed the delivery of a newsletter issue");
+    }
     Ok(())
 }
 
 #[tracing::instrument(
     name = "Publish a newsletter issue",
-    skip(form, app_state, messages, user_id),
+    skip(form, app_state, messages, headers, user_id, client_ip),
     fields(user_id=%user_id),
 )]
 pub async fn publish_newsletter(
     State(app_state): State<Arc<AppState>>,
     messages: Messages,
+    headers: HeaderMap,
     Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
     Form(form): Form<FormData>,
 ) -> Result<axum::response::Response, axum::response::Response> {
     let idempotency_key: IdempotencyKey = form.idempotency_key.try_into().map_err(e400)?;
-
-    let mut transaction = match try_processing(&app_state.pool, &idempotency_key, *user_id)
+    // an empty `datetime-local` input is submitted as an empty string
+    let scheduled_for = form
+        .scheduled_for
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(parse_scheduled_for)
+        .transpose()
+        .map_err(e400)?;
+    let mut content =
+        issue_content(form.text_content, form.html_content, form.markdown_content).map_err(e400)?;
+    // "No template" is submitted as an empty string
+    let template_id = form
+        .template_uuid
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(Uuid::try_parse)
+        .transpose()
+        .map_err(e400)?;
+    if let Some(template_id) = template_id {
+        let template = get_template(app_state.pool(), template_id)
+            .await
+            .context("Failed to retrieve the newsletter template.")
+            .map_err(e500)?
+            .ok_or_else(|| {
+                e400(anyhow::anyhow!(
+                    "`{}` is not a known template.",
+                    template_id
+                ))
+            })?;
+        content.html = template.wrap(&content.html);
+    }
+    let reply_to_email = match form.reply_to_email.as_deref().map(str::trim) {
+        Some(email) if !email.is_empty() => {
+            SubscriberEmail::parse(email.to_string()).map_err(e400)?
+        }
+        _ => app_state.email_client().sender().clone(),
+    };
+    let known_tags = all_tags(app_state.pool())
         .await
-        .map_err(e500)?
+        .context("Failed to retrieve the tags.")
+        .map_err(e500)?;
+    // an issue sent to nobody because of a typo is worse than a rejected form
+    if let Some(tag) = form.tags.iter().find(|tag| !known_tags.contains(tag)) {
+        return Err(e400(anyhow::anyhow!("`{}` is not a known tag.", tag)));
+    }
+    let success_message = if scheduled_for.is_some() {
+        "The newsletter issue has been scheduled!"
+    } else {
+        "The newsletter issue has been published!"
+    };
+
+    let mut transaction = match try_processing(
+        app_state.pool(),
+        &idempotency_key,
+        *user_id,
+        app_state.idempotency_ttl_hours,
+    )
+    .await
+    .map_err(e500)?
     {
         crate::idempotency::NextAction::StartProcessing(transaction) => transaction,
-        crate::idempotency::NextAction::ReturnSavedResponse(saved_response) => {
-            messages.info("The newsletter issue has been published!");
+        crate::idempotency::NextAction::ReturnSavedResponse {
+            saved_response,
+            newsletter_issue_uuid,
+        } => {
+            if let Some(issue_id) = newsletter_issue_uuid {
+                resume_delivery(app_state.pool(), issue_id)
+                    .await
+                    .context("Failed to resume the delivery of the newsletter issue")
+                    .map_err(e500)?;
+            }
+            if ResponseFormat::negotiate(&headers) == ResponseFormat::Html {
+                messages.info(success_message);
+            }
             return Ok(saved_response);
         }
     };
@@ -106,24 +331,101 @@ pub async fn publish_newsletter(
     let issue_id = insert_newsletter_issue(
         &mut transaction,
         &form.title,
-        &form.text_content,
-        &form.html_content,
+        &content,
+        scheduled_for,
+        &reply_to_email,
     )
     .await
     .context("Failed to store newsletter issue details")
     .map_err(e500)?;
-
-    enqueue_delivery_tasks(&mut transaction, issue_id)
+    set_issue_tags(&mut transaction, &issue_id.to_string(), &form.tags)
         .await
-        .context("Failed to enqueue delivery tasks")
+        .context("Failed to store the newsletter issue tags")
         .map_err(e500)?;
 
-    messages.info("The newsletter issue has been published!");
+    // scheduled issues are enqueued by the delivery worker once they are due
+    if scheduled_for.is_none() {
+        enqueue_delivery_tasks(&mut transaction, issue_id)
+            .await
+            .context("Failed to enqueue delivery tasks")
+            .map_err(e500)?;
+    }
 
-    let response = Redirect::to("/admin/newsletters").into_response();
-    let response = save_response(transaction, &idempotency_key, *user_id, response)
-        .await
-        .map_err(e500)?;
+    let response = respond_for_content_type(
+        &headers,
+        messages,
+        "/admin/newsletters",
+        Ok(success_message),
+    );
+    let response = save_response(
+        transaction,
+        &idempotency_key,
+        *user_id,
+        Some(issue_id),
+        response,
+    )
+    .await
+    .map_err(e500)?;
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: if scheduled_for.is_some() {
+                "schedule_newsletter"
+            } else {
+                "publish_newsletter"
+            },
+            target_type: "newsletter_issue",
+            target_id: Some(issue_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
 
     return Ok(response);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{first_free_slug, slugify, word_count};
+    use std::collections::HashSet;
+
+    #[test]
+    fn words_are_separated_by_any_whitespace() {
+        assert_eq!(word_count("  Hello,\tworld!\n\nSee  you soon "), 5);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn the_slug_is_made_of_the_title_words() {
+        assert_eq!(
+            slugify("October news: Rust 2024!"),
+            "october-news-rust-2024"
+        );
+    }
+
+    #[test]
+    fn titles_without_ascii_words_still_get_a_slug() {
+        assert_eq!(slugify("日本語"), "issue");
+    }
+
+    #[test]
+    fn a_free_slug_is_kept_as_is() {
+        assert_eq!(
+            first_free_slug("october-news".to_string(), &HashSet::new()),
+            "october-news"
+        );
+    }
+
+    #[test]
+    fn colliding_slugs_get_the_first_free_suffix() {
+        let taken: HashSet<String> = ["october-news", "october-news-2", "october-news-4"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            first_free_slug("october-news".to_string(), &taken),
+            "october-news-3"
+        );
+    }
+}
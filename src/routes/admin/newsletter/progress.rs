@@ -0,0 +1,189 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::header::{ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often the progress stream reports on the deliveries.
+const PROGRESS_STREAM_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+pub struct DeliveryProgress {
+    total_queued: u64,
+    sent: u64,
+    failed: u64,
+    pending: u64,
+}
+
+#[tracing::instrument(name = "Get newsletter delivery progress", skip(app_state, headers))]
+pub async fn newsletter_delivery_progress(
+    State(app_state): State<Arc<AppState>>,
+    Path(issue_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    let Some(progress) = get_delivery_progress(app_state.pool(), issue_id)
+        .await
+        .map_err(e500)?
+    else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    // acknowledged dead letters and deleted subscribers move the counts
+    // backwards too, so all of them make up the tag
+    let etag = format!(
+        "\"{}-{}-{}\"",
+        progress.sent, progress.failed, progress.pending
+    );
+    let not_modified = headers
+        .get(IF_NONE_MATCH)
+        .is_some_and(|value| value.as_bytes() == etag.as_bytes());
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response());
+    }
+
+    Ok(([(ETAG, etag)], Json(progress)).into_response())
+}
+
+/// What the progress stream sends with each event.
+#[derive(Serialize)]
+struct ProgressEvent {
+    sent: u64,
+    failed: u64,
+    pending: u64,
+    total: u64,
+}
+
+impl From<&DeliveryProgress> for ProgressEvent {
+    fn from(progress: &DeliveryProgress) -> Self {
+        Self {
+            sent: progress.sent,
+            failed: progress.failed,
+            pending: progress.pending,
+            total: progress.total_queued,
+        }
+    }
+}
+
+/// Push the delivery progress to the admin every couple of seconds instead of
+/// having them poll, the stream ends once nothing is left to deliver.
+#[tracing::instrument(name = "Stream newsletter delivery progress", skip(app_state))]
+pub async fn newsletter_delivery_progress_stream(
+    State(app_state): State<Arc<AppState>>,
+    Path(issue_id): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    if get_delivery_progress(app_state.pool(), issue_id)
+        .await
+        .map_err(e500)?
+        .is_none()
+    {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let pool = app_state.pool().clone();
+    let events = async_stream::stream! {
+        let mut interval = tokio::time::interval(PROGRESS_STREAM_INTERVAL);
+        loop {
+            interval.tick().await;
+            let progress = match get_delivery_progress(&pool, issue_id).await {
+                Ok(Some(progress)) => progress,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!(cause_chain = ?e, "Failed to stream the delivery progress");
+                    break;
+                }
+            };
+            let event = Event::default()
+                .json_data(ProgressEvent::from(&progress))
+                .expect("The progress can always be serialised");
+            yield Ok::<_, Infallible>(event);
+            if progress.pending == 0 {
+                break;
+            }
+        }
+    };
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_delivery_progress(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<Option<DeliveryProgress>, anyhow::Error> {
+    let issue_id = issue_id.to_string();
+    let Some(totals) = sqlx::query!(
+        r#"
+        SELECT total_queued
+        FROM issue_delivery_totals
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to retrieve the delivery totals of the newsletter issue.")?
+    else {
+        return Ok(None);
+    };
+
+    let pending = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!: i64"
+        FROM issue_delivery_queue
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to count the pending deliveries of the newsletter issue.")?
+    .count;
+
+    // counted rather than derived from the total, the dead letters admins
+    // acknowledged and the deliveries of deleted subscribers weren't sent
+    let sent = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!: i64"
+        FROM newsletter_deliveries
+        WHERE newsletter_issue_uuid = $1 AND status = 'delivered'
+        "#,
+        issue_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to count the sent deliveries of the newsletter issue.")?
+    .count;
+
+    let failed = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!: i64"
+        FROM issue_delivery_dead_letter
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to count the failed deliveries of the newsletter issue.")?
+    .count;
+
+    Ok(Some(DeliveryProgress {
+        total_queued: totals.total_queued as u64,
+        sent: sent as u64,
+        failed: failed as u64,
+        pending: pending as u64,
+    }))
+}
@@ -0,0 +1,87 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct ScheduledIssue {
+    issue_id: String,
+    title: String,
+    scheduled_for: String,
+}
+
+#[tracing::instrument(name = "List scheduled newsletter issues", skip(app_state))]
+pub async fn list_scheduled_newsletters(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let issues = get_scheduled_issues(app_state.pool()).await?;
+    Ok(Json(issues))
+}
+
+#[tracing::instrument(name = "Cancel a scheduled newsletter issue", skip(app_state))]
+pub async fn cancel_scheduled_newsletter(
+    State(app_state): State<Arc<AppState>>,
+    Path(issue_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if delete_scheduled_issue(app_state.pool(), issue_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound)
+    }
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_scheduled_issues(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<ScheduledIssue>, anyhow::Error> {
+    let issues = sqlx::query_as!(
+        ScheduledIssue,
+        r#"
+        SELECT
+            newsletter_issue_uuid as issue_id,
+            title,
+            scheduled_for as "scheduled_for!"
+        FROM active_newsletter_issues
+        WHERE status = 'scheduled'
+        ORDER BY scheduled_for
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to retrieve the scheduled newsletter issues.")?;
+    Ok(issues)
+}
+
+/// Only issues that are still waiting to be enqueued can be cancelled, they
+/// are soft deleted like the others.
+#[tracing::instrument(skip(pool))]
+async fn delete_scheduled_issue(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<bool, anyhow::Error> {
+    let issue_id = issue_id.to_string();
+    let now = Utc::now().to_string();
+    let n_deleted_rows = sqlx::query!(
+        r#"
+        UPDATE newsletter_issues
+        SET deleted_at = $2
+        WHERE newsletter_issue_uuid = $1 AND status = 'scheduled' AND deleted_at IS NULL
+        "#,
+        issue_id,
+        now
+    )
+    .execute(pool)
+    .await
+    .context("Failed to delete the scheduled newsletter issue.")?
+    .rows_affected();
+    Ok(n_deleted_rows > 0)
+}
@@ -0,0 +1,160 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedPool;
+use crate::domain::SubscriberEmail;
+use crate::issue_delivery_worker::{unsubscribe_link, NewsletterIssue};
+use crate::startup::{AppState, HmacSecret};
+use crate::utils::{e400, e500};
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use dashmap::DashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Test copies an issue can get, so that the endpoint can't be used to spam.
+const MAX_TEST_SENDS_PER_ISSUE: u32 = 10;
+
+#[derive(serde::Serialize)]
+pub struct TestSend {
+    sent_to: String,
+}
+
+/// Send a copy of an issue to the logged in user, subject prefixed with `[TEST] `.
+///
+/// Subscribers are left alone, nothing is enqueued nor recorded as delivered.
+#[tracing::instrument(
+    name = "Send a test newsletter",
+    skip(app_state, hmac_secret, user_id, client_ip),
+    fields(user_id=%user_id)
+)]
+pub async fn send_test_newsletter(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(issue_id): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issue_id = Uuid::try_parse(&issue_id).map_err(e400)?;
+    let Some(issue) = get_issue(app_state.pool(), issue_id)
+        .await
+        .context("Failed to retrieve the newsletter issue.")
+        .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Newsletter issue not found").into_response());
+    };
+    let Some(email) = get_user_email(app_state.pool(), *user_id)
+        .await
+        .context("Failed to retrieve the user email.")
+        .map_err(e500)?
+    else {
+        return Ok((
+            StatusCode::CONFLICT,
+            "Your account has no email address to send the test to.",
+        )
+            .into_response());
+    };
+    let email = SubscriberEmail::parse(email)
+        .map_err(|e| anyhow::anyhow!(e))
+        .context("The stored user email is invalid.")
+        .map_err(e500)?;
+    if !try_acquire_test_send(&app_state.test_sends, issue_id) {
+        return Ok((
+            StatusCode::TOO_MANY_REQUESTS,
+            "This issue has already been sent for testing too many times.",
+        )
+            .into_response());
+    }
+
+    // the link is signed for a subscriber that doesn't exist, so it does nothing
+    let unsubscribe_link = unsubscribe_link(app_state.base_url(), Uuid::nil(), &hmac_secret);
+    app_state
+        .email_client()
+        .send_email(
+            &email,
+            &format!("[TEST] {}", issue.title),
+            &issue.html_content_with_footer(&unsubscribe_link),
+            &issue.text_content_with_footer(&unsubscribe_link),
+            None,
+            issue.reply_to().as_ref(),
+        )
+        .await
+        .context("Failed to send the test newsletter.")
+        .map_err(e500)?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "test_send_newsletter",
+            target_type: "newsletter_issue",
+            target_id: Some(issue_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(Json(TestSend {
+        sent_to: email.as_ref().to_string(),
+    })
+    .into_response())
+}
+
+fn try_acquire_test_send(test_sends: &DashMap<Uuid, u32>, issue_id: Uuid) -> bool {
+    let mut sent = test_sends.entry(issue_id).or_insert(0);
+    if *sent >= MAX_TEST_SENDS_PER_ISSUE {
+        return false;
+    }
+    *sent += 1;
+    true
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_issue(
+    pool: &SqliteInstrumentedPool,
+    issue_id: Uuid,
+) -> Result<Option<NewsletterIssue>, sqlx::Error> {
+    let issue_id = issue_id.to_string();
+    sqlx::query_as!(
+        NewsletterIssue,
+        r#"
+        SELECT title, text_content, html_content, reply_to_email
+        FROM active_newsletter_issues
+        WHERE newsletter_issue_uuid = $1
+        "#,
+        issue_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_user_email(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    let user_id = user_id.to_string();
+    let user = sqlx::query!(r#"SELECT email FROM users WHERE uuid = $1"#, user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(user.and_then(|user| user.email))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_acquire_test_send, MAX_TEST_SENDS_PER_ISSUE};
+    use dashmap::DashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn each_issue_gets_a_limited_number_of_test_sends() {
+        let test_sends = DashMap::new();
+        let issue_id = Uuid::new_v4();
+        for _ in 0..MAX_TEST_SENDS_PER_ISSUE {
+            assert!(try_acquire_test_send(&test_sends, issue_id));
+        }
+        assert!(!try_acquire_test_send(&test_sends, issue_id));
+        assert!(try_acquire_test_send(&test_sends, Uuid::new_v4()));
+    }
+}
@@ -2,6 +2,7 @@ use axum::response::{Html, IntoResponse, Redirect};
 use axum_messages::Messages;
 use rinja_axum::Template;
 
+use crate::middleware::CsrfToken;
 use crate::session_state::TypedSession;
 use crate::utils::e500;
 
@@ -9,11 +10,13 @@ use crate::utils::e500;
 #[template(path = "change_password/index.html")]
 struct ChangePasswordTemplate {
     errors: Vec<String>,
+    csrf_token: String,
 }
 
 pub async fn change_password_form(
     session: TypedSession,
     messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
 ) -> Result<axum::response::Response, axum::response::Response> {
     if session.get_user_id().await.map_err(e500)?.is_none() {
         return Ok(Redirect::to("/login").into_response());
@@ -21,6 +24,7 @@ pub async fn change_password_form(
     Ok(Html(
         ChangePasswordTemplate {
             errors: messages.into_iter().map(|m| m.message).collect(),
+            csrf_token,
         }
         .render()
         .unwrap(),
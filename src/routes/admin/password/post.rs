@@ -1,9 +1,11 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
 use crate::authentication::{self, validate_credentials, AuthError, Credentials, UserId};
 use crate::routes::admin::dashboard::get_username;
 use crate::startup::AppState;
-use crate::utils::e500;
+use crate::utils::{e500, respond_for_content_type};
 use axum::extract::State;
-use axum::response::{IntoResponse, Redirect};
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
 use axum::{Extension, Form};
 use axum_messages::Messages;
 use secrecy::{ExposeSecret, SecretString};
@@ -19,15 +21,20 @@ pub struct FormData {
 pub async fn change_password(
     State(app_state): State<Arc<AppState>>,
     messages: Messages,
+    headers: HeaderMap,
     Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
     Form(form): Form<FormData>,
 ) -> Result<axum::response::Response, axum::response::Response> {
+    let respond =
+        |outcome| respond_for_content_type(&headers, messages, "/admin/password", outcome);
     if form.new_password.expose_secret() != form.new_password_check.expose_secret() {
-        messages.error("You entered two different new passwords - the field values must match.");
-        return Ok(Redirect::to("/admin/password").into_response());
+        return Ok(respond(Err(
+            "You entered two different new passwords - the field values must match.",
+        )));
     }
 
-    let username = get_username(*user_id, &app_state.pool)
+    let username = get_username(*user_id, app_state.pool())
         .await
         .map_err(e500)?;
 
@@ -36,20 +43,29 @@ pub async fn change_password(
         password: form.current_password,
     };
 
-    if let Err(e) = validate_credentials(credentials, &app_state.pool).await {
+    if let Err(e) = validate_credentials(credentials, app_state.pool()).await {
         return match e {
             AuthError::InvalidCredentials(err) => {
-                messages.error("The current password is incorrect.");
                 tracing::error!(chain_error = ?err);
-                Ok(Redirect::to("/admin/password").into_response())
+                Ok(respond(Err("The current password is incorrect.")))
             }
             AuthError::UnexpectedError(_) => Err(e500(e).into_response()),
         };
     }
 
-    authentication::change_password(*user_id, form.new_password, &app_state.pool)
+    authentication::change_password(*user_id, form.new_password, app_state.pool())
         .await
         .map_err(e500)?;
-    messages.success("Your password has been changed.");
-    Ok(Redirect::to("/admin/password").into_response())
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "change_password",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(respond(Ok("Your password has been changed.")))
 }
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+
+/// Enough for a whole CSV import, small enough to keep the transaction short.
+const MAX_SUBSCRIBERS_PER_REQUEST: usize = 1000;
+/// SQLite builds before 3.32 only accept 999 bound variables per statement,
+/// one of which is taken by the status.
+const BATCH_SIZE: usize = 998;
+
+#[derive(serde::Deserialize)]
+pub struct BulkStatusChange {
+    uuids: Vec<String>,
+    status: String,
+    /// Required to confirm subscribers who unsubscribed.
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct BulkStatusChangeOutcome {
+    updated: u64,
+    not_found: Vec<String>,
+    /// Unsubscribed subscribers who were left alone because `force` wasn't set.
+    skipped: Vec<String>,
+}
+
+const STATUSES: [&str; 2] = ["confirmed", "unsubscribed"];
+
+/// Confirm or unsubscribe up to 1000 subscribers at once.
+///
+/// Unknown subscribers are reported in `not_found` instead of failing the
+/// whole batch. Like a single status change, confirming subscribers who
+/// unsubscribed requires `force`.
+#[tracing::instrument(
+    name = "Change the status of many subscribers",
+    skip(app_state, user_id, client_ip, change),
+    fields(user_id=%user_id, status=%change.status, count=change.uuids.len())
+)]
+pub async fn bulk_change_subscriber_status(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Json(change): Json<BulkStatusChange>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    if !STATUSES.contains(&change.status.as_str()) {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "`{}` can't be set in bulk, use `confirmed` or `unsubscribed`.",
+                change.status
+            ),
+        )
+            .into_response());
+    }
+    if change.uuids.len() > MAX_SUBSCRIBERS_PER_REQUEST {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "At most {} subscribers can be changed at once.",
+                MAX_SUBSCRIBERS_PER_REQUEST
+            ),
+        )
+            .into_response());
+    }
+    let mut uuids = Vec::with_capacity(change.uuids.len());
+    for uuid in &change.uuids {
+        let uuid = uuid::Uuid::try_parse(uuid).map_err(e400)?.to_string();
+        if !uuids.contains(&uuid) {
+            uuids.push(uuid);
+        }
+    }
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")
+        .map_err(e500)?;
+    let mut outcome = BulkStatusChangeOutcome {
+        updated: 0,
+        not_found: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for batch in uuids.chunks(BATCH_SIZE) {
+        let current_statuses = get_statuses(&mut transaction, batch)
+            .await
+            .context("Failed to retrieve the subscriber statuses.")
+            .map_err(e500)?;
+        let mut to_update = Vec::with_capacity(batch.len());
+        for uuid in batch {
+            match current_statuses.get(uuid).map(String::as_str) {
+                None => outcome.not_found.push(uuid.clone()),
+                Some("unsubscribed") if change.status == "confirmed" && !change.force => {
+                    outcome.skipped.push(uuid.clone())
+                }
+                Some(_) => to_update.push(uuid.as_str()),
+            }
+        }
+        outcome.updated += update_statuses(&mut transaction, &to_update, &change.status)
+            .await
+            .context("Failed to update the subscriber statuses.")
+            .map_err(e500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the subscriber status changes.")
+        .map_err(e500)?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "bulk_change_subscriber_status",
+            target_type: "subscription",
+            target_id: None,
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    tracing::info!(
+        updated = outcome.updated,
+        not_found = outcome.not_found.len(),
+        skipped = outcome.skipped.len(),
+        "Changed the status of many subscribers"
+    );
+    Ok(Json(outcome).into_response())
+}
+
+async fn get_statuses(
+    transaction: &mut SqliteInstrumentedTransaction,
+    uuids: &[String],
+) -> Result<HashMap<String, String>, sqlx::Error> {
+    let mut builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT uuid, status FROM subscriptions WHERE uuid IN (");
+    let mut separated = builder.separated(", ");
+    for uuid in uuids {
+        separated.push_bind(uuid.as_str());
+    }
+    builder.push(")");
+    let rows = builder
+        .build_query_as::<(String, String)>()
+        .fetch_all(&mut *transaction)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+async fn update_statuses(
+    transaction: &mut SqliteInstrumentedTransaction,
+    uuids: &[&str],
+    status: &str,
+) -> Result<u64, sqlx::Error> {
+    if uuids.is_empty() {
+        return Ok(0);
+    }
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE subscriptions SET status = ");
+    builder.push_bind(status).push(" WHERE uuid IN (");
+    let mut separated = builder.separated(", ");
+    for uuid in uuids {
+        separated.push_bind(*uuid);
+    }
+    builder.push(")");
+    let result = builder.build().execute(&mut *transaction).await?;
+    Ok(result.rows_affected())
+}
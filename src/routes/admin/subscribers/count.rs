@@ -0,0 +1,58 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::Json;
+use std::sync::Arc;
+
+#[derive(Default, serde::Serialize)]
+pub struct SubscriberCounts {
+    confirmed: i64,
+    pending: i64,
+    unsubscribed: i64,
+    total: i64,
+}
+
+/// How many subscribers there are in each status, fetched by the dashboard
+/// after the page loaded.
+#[tracing::instrument(name = "Count subscribers", skip(app_state))]
+pub async fn count_subscribers(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut counts = SubscriberCounts::default();
+    for (status, count) in count_by_status(app_state.pool())
+        .await
+        .context("Failed to count the subscribers.")?
+    {
+        match status.as_str() {
+            "confirmed" => counts.confirmed = count,
+            "pending_confirmation" => counts.pending = count,
+            "unsubscribed" => counts.unsubscribed = count,
+            _ => {}
+        }
+        counts.total += count;
+    }
+    Ok((
+        [(header::CACHE_CONTROL, "private, max-age=60")],
+        Json(counts),
+    ))
+}
+
+async fn count_by_status(pool: &SqliteInstrumentedPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT status, COUNT(*) AS "count!: i64"
+        FROM subscriptions
+        GROUP BY status
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.status, row.count))
+        .collect())
+}
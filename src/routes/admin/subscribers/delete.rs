@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Extension;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+
+/// What erased emails are replaced with in the delivery history.
+const REDACTED_EMAIL: &str = "[redacted]";
+
+/// Permanently erase a subscriber, for the GDPR right to erasure.
+///
+/// Their deliveries stay in the history so that the counts of each issue
+/// don't change, with the email redacted. Deliveries still waiting in the
+/// queue are dropped. A tombstone records who erased them and when.
+#[tracing::instrument(
+    name = "Delete a subscriber",
+    skip(app_state, user_id, client_ip),
+    fields(user_id=%user_id)
+)]
+pub async fn delete_subscriber(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(subscriber_id): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let subscriber_id = Uuid::try_parse(&subscriber_id).map_err(e400)?;
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")
+        .map_err(e500)?;
+    if !erase_subscriber(&mut transaction, subscriber_id, *user_id)
+        .await
+        .context("Failed to delete the subscriber.")
+        .map_err(e500)?
+    {
+        return Ok((StatusCode::NOT_FOUND, "Subscriber not found").into_response());
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the subscriber deletion.")
+        .map_err(e500)?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "delete_subscriber",
+            target_type: "subscription",
+            target_id: Some(subscriber_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Returns `false`, having changed nothing, when the subscriber doesn't exist.
+async fn erase_subscriber(
+    transaction: &mut SqliteInstrumentedTransaction,
+    subscriber_id: Uuid,
+    deleted_by: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let subscriber_id = subscriber_id.to_string();
+    let Some(subscriber) = sqlx::query!(
+        "SELECT email FROM subscriptions WHERE uuid = $1",
+        subscriber_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    // the tokens reference the subscription, they have to go first
+    sqlx::query!(
+        "DELETE FROM subscription_tokens WHERE subscriber_id = $1",
+        subscriber_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM subscriptions WHERE uuid = $1", subscriber_id)
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM issue_delivery_queue WHERE subscriber_email = $1",
+        subscriber.email
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "UPDATE newsletter_deliveries SET subscriber_email = $2 WHERE subscriber_email = $1",
+        subscriber.email,
+        REDACTED_EMAIL
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "UPDATE issue_delivery_dead_letter SET subscriber_email = $2 WHERE subscriber_email = $1",
+        subscriber.email,
+        REDACTED_EMAIL
+    )
+    .execute(&mut *transaction)
+    .await?;
+    // the reasons still count, what could identify the subscriber doesn't
+    sqlx::query!(
+        r#"
+        UPDATE unsubscribe_events
+        SET other_text = NULL, ip_address = NULL
+        WHERE subscriber_uuid = $1
+        "#,
+        subscriber_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let deleted_at = Utc::now().to_string();
+    let deleted_by = deleted_by.to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO subscriber_deletions (uuid, deleted_at, deleted_by_user_uuid)
+        VALUES ($1, $2, $3)
+        "#,
+        subscriber_id,
+        deleted_at,
+        deleted_by
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(true)
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use async_stream::try_stream;
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use chrono::Utc;
+use futures_core::Stream;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ExportParameters {
+    status: Option<String>,
+}
+
+/// Stream the subscribers as CSV, one row at a time, so the whole table never
+/// has to fit in memory.
+#[tracing::instrument(name = "Export subscribers", skip(app_state))]
+pub async fn export_subscribers(
+    State(app_state): State<Arc<AppState>>,
+    Query(parameters): Query<ExportParameters>,
+) -> impl IntoResponse {
+    let rows = csv_rows(app_state.pool().clone(), parameters.status);
+
+    let content_disposition = format!(
+        "attachment; filename=\"subscribers-{}.csv\"",
+        Utc::now().format("%Y-%m-%d")
+    );
+    (
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (CONTENT_DISPOSITION, content_disposition),
+        ],
+        Body::from_stream(rows),
+    )
+}
+
+/// The header, then one line per subscriber.
+fn csv_rows(
+    pool: SqliteInstrumentedPool,
+    status: Option<String>,
+) -> impl Stream<Item = Result<String, sqlx::Error>> {
+    try_stream! {
+        yield "uuid,name,email,status,subscribed_at\n".to_string();
+        let subscribers = sqlx::query!(
+            r#"
+            SELECT uuid, name, email, status, subscribed_at
+            FROM subscriptions
+            WHERE $1 IS NULL OR status = $1
+            ORDER BY id
+            "#,
+            status
+        )
+        .fetch(&pool);
+        for await subscriber in subscribers {
+            let subscriber = subscriber.inspect_err(|e| {
+                tracing::error!(cause_chain = ?e, "Failed to stream the subscribers export");
+            })?;
+            yield format!(
+                "{},{},{},{},{}\n",
+                csv_field(&subscriber.uuid),
+                csv_field(&subscriber.name),
+                csv_field(&subscriber.email),
+                csv_field(&subscriber.status),
+                csv_field(&subscriber.subscribed_at),
+            );
+        }
+    }
+}
+
+/// Quote a field when it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::csv_field;
+
+    #[test]
+    fn plain_fields_are_left_untouched() {
+        assert_eq!(
+            csv_field("ursula_le_guin@gmail.com"),
+            "ursula_le_guin@gmail.com"
+        );
+    }
+
+    #[test]
+    fn fields_with_separators_or_quotes_are_quoted() {
+        assert_eq!(csv_field("le guin, ursula"), "\"le guin, ursula\"");
+        assert_eq!(csv_field("the \"guin\""), "\"the \"\"guin\"\"\"");
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Multipart, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::domain::{SubscriberEmail, SubscriberName};
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+
+/// Largest CSV file accepted by the import, in bytes.
+pub const IMPORT_SIZE_LIMIT: usize = 10 * 1024 * 1024;
+
+#[derive(serde::Deserialize)]
+struct CsvRow {
+    name: String,
+    email: String,
+}
+
+#[derive(Serialize)]
+pub struct RowError {
+    row: u64,
+    reason: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct ImportReport {
+    imported: u64,
+    skipped_duplicates: u64,
+    validation_errors: Vec<RowError>,
+}
+
+/// Import `name,email` rows from the uploaded CSV file as confirmed
+/// subscribers.
+///
+/// Rows that don't validate are reported back with their line number, rows
+/// whose email is already subscribed are skipped.
+#[tracing::instrument(
+    name = "Import subscribers",
+    skip(app_state, user_id, client_ip, multipart),
+    fields(user_id=%user_id)
+)]
+pub async fn import_subscribers(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    mut multipart: Multipart,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let mut csv_file = None;
+    while let Some(field) = multipart.next_field().await.map_err(e400)? {
+        if field.file_name().is_some() {
+            csv_file = Some(field.bytes().await.map_err(e400)?);
+            break;
+        }
+    }
+    let Some(csv_file) = csv_file else {
+        return Ok((StatusCode::BAD_REQUEST, "Upload the subscribers.csv file.").into_response());
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv_file.as_ref());
+    let headers = reader.headers().map_err(e400)?.clone();
+    if !["name", "email"]
+        .iter()
+        .all(|column| headers.iter().any(|header| header == *column))
+    {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            "The CSV file must have a name and an email column.",
+        )
+            .into_response());
+    }
+
+    let mut report = ImportReport::default();
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")
+        .map_err(e500)?;
+    for record in reader.records() {
+        let (row, parsed) = match record {
+            Ok(record) => (
+                record.position().map_or(0, |p| p.line()),
+                record
+                    .deserialize::<CsvRow>(Some(&headers))
+                    .map_err(|e| e.to_string())
+                    .and_then(parse_row),
+            ),
+            Err(e) => (e.position().map_or(0, |p| p.line()), Err(e.to_string())),
+        };
+        let (name, email) = match parsed {
+            Ok(subscriber) => subscriber,
+            Err(reason) => {
+                report.validation_errors.push(RowError { row, reason });
+                continue;
+            }
+        };
+        if insert_confirmed_subscriber(&mut transaction, &name, &email)
+            .await
+            .context("Failed to insert an imported subscriber.")
+            .map_err(e500)?
+        {
+            report.imported += 1;
+        } else {
+            report.skipped_duplicates += 1;
+        }
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the subscribers import.")
+        .map_err(e500)?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "import_subscribers",
+            target_type: "subscription",
+            target_id: None,
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    tracing::info!(
+        imported = report.imported,
+        skipped_duplicates = report.skipped_duplicates,
+        validation_errors = report.validation_errors.len(),
+        "Imported subscribers"
+    );
+    Ok(Json(report).into_response())
+}
+
+fn parse_row(row: CsvRow) -> Result<(SubscriberName, SubscriberEmail), String> {
+    let name = SubscriberName::parse(row.name).map_err(|e| e.to_string())?;
+    let email = SubscriberEmail::parse(row.email)?;
+    Ok((name, email))
+}
+
+/// Returns `false` when the email is already subscribed.
+async fn insert_confirmed_subscriber(
+    transaction: &mut SqliteInstrumentedTransaction,
+    name: &SubscriberName,
+    email: &SubscriberEmail,
+) -> Result<bool, sqlx::Error> {
+    let uuid = Uuid::new_v4().to_string();
+    let name = name.as_ref();
+    let email = email.as_ref();
+    let subscribed_at = Utc::now().to_string();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO subscriptions(uuid, name, email, subscribed_at, status)
+        VALUES($1, $2, $3, $4, 'confirmed')
+        ON CONFLICT(email) DO NOTHING
+        "#,
+        uuid,
+        name,
+        email,
+        subscribed_at,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::e500;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 100;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ListParameters {
+    status: Option<String>,
+    sort: Option<String>,
+    order: Option<String>,
+    after: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Subscriber {
+    uuid: String,
+    name: String,
+    email: String,
+    status: String,
+    subscribed_at: String,
+    source_url: Option<String>,
+    utm_source: Option<String>,
+    utm_medium: Option<String>,
+    utm_campaign: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SubscribersPage {
+    subscribers: Vec<Subscriber>,
+    /// Pass it as `after` to get the next page, `None` on the last page.
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SortField {
+    Name,
+    Email,
+    SubscribedAt,
+}
+
+impl SortField {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "name" => Ok(Self::Name),
+            "email" => Ok(Self::Email),
+            "subscribed_at" => Ok(Self::SubscribedAt),
+            other => Err(format!(
+                "`{}` is not a sortable field, use `name`, `email` or `subscribed_at`.",
+                other
+            )),
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Email => "email",
+            Self::SubscribedAt => "subscribed_at",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "asc" => Ok(Self::Asc),
+            "desc" => Ok(Self::Desc),
+            other => Err(format!(
+                "`{}` is not a sort order, use `asc` or `desc`.",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ListQuery {
+    status: Option<String>,
+    sort: SortField,
+    order: SortOrder,
+    after: Option<String>,
+    limit: u32,
+}
+
+impl TryFrom<ListParameters> for ListQuery {
+    type Error = String;
+
+    fn try_from(parameters: ListParameters) -> Result<Self, Self::Error> {
+        if let Some(status) = &parameters.status {
+            if !["confirmed", "pending_confirmation", "unsubscribed"].contains(&status.as_str()) {
+                return Err(format!(
+                    "`{}` is not a subscription status, use `confirmed`, `pending_confirmation` or `unsubscribed`.",
+                    status
+                ));
+            }
+        }
+        let limit = parameters.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(format!("`limit` must be between 1 and {}.", MAX_LIMIT));
+        }
+        Ok(Self {
+            status: parameters.status,
+            sort: SortField::parse(parameters.sort.as_deref().unwrap_or("subscribed_at"))?,
+            order: SortOrder::parse(parameters.order.as_deref().unwrap_or("desc"))?,
+            after: parameters.after,
+            limit,
+        })
+    }
+}
+
+/// List the subscribers a page at a time.
+///
+/// Pages are keyed on the sort field and the uuid of the last subscriber seen
+/// rather than an offset, so deep pages cost as much as the first one and
+/// don't shift when subscribers come and go in between requests.
+#[tracing::instrument(name = "List subscribers", skip(app_state))]
+pub async fn list_subscribers(
+    State(app_state): State<Arc<AppState>>,
+    Query(parameters): Query<ListParameters>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let query = match ListQuery::try_from(parameters) {
+        Ok(query) => query,
+        Err(message) => return Ok((StatusCode::BAD_REQUEST, message).into_response()),
+    };
+    if let Some(after) = &query.after {
+        if !subscriber_exists(app_state.pool(), after)
+            .await
+            .context("Failed to look up the pagination cursor.")
+            .map_err(e500)?
+        {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                "`after` does not match any subscriber.",
+            )
+                .into_response());
+        }
+    }
+
+    let mut subscribers = get_subscribers_page(app_state.pool(), &query)
+        .await
+        .context("Failed to retrieve the subscribers.")
+        .map_err(e500)?;
+    // one extra row was fetched to tell whether there is a next page
+    let next_cursor = if subscribers.len() > query.limit as usize {
+        subscribers.truncate(query.limit as usize);
+        subscribers.last().map(|s| s.uuid.clone())
+    } else {
+        None
+    };
+    Ok(Json(SubscribersPage {
+        subscribers,
+        next_cursor,
+    })
+    .into_response())
+}
+
+async fn subscriber_exists(pool: &SqliteInstrumentedPool, uuid: &str) -> Result<bool, sqlx::Error> {
+    let subscriber = sqlx::query!("SELECT uuid FROM subscriptions WHERE uuid = $1", uuid)
+        .fetch_optional(pool)
+        .await?;
+    Ok(subscriber.is_some())
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_subscribers_page(
+    pool: &SqliteInstrumentedPool,
+    query: &ListQuery,
+) -> Result<Vec<Subscriber>, sqlx::Error> {
+    // the column and direction come from the enums above, never from the request
+    let column = query.sort.column();
+    let (direction, comparison) = match query.order {
+        SortOrder::Asc => ("ASC", ">"),
+        SortOrder::Desc => ("DESC", "<"),
+    };
+
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT uuid, name, email, status, subscribed_at, source_url, utm_source, utm_medium, utm_campaign \
+         FROM subscriptions WHERE 1 = 1",
+    );
+    if let Some(status) = &query.status {
+        builder.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(after) = &query.after {
+        builder
+            .push(format!(
+                " AND ({column}, uuid) {comparison} ((SELECT {column} FROM subscriptions WHERE uuid = "
+            ))
+            .push_bind(after.as_str())
+            .push("), ")
+            .push_bind(after.as_str())
+            .push(")");
+    }
+    builder
+        .push(format!(
+            " ORDER BY {column} {direction}, uuid {direction} LIMIT "
+        ))
+        .push_bind(i64::from(query.limit) + 1);
+
+    builder.build_query_as::<Subscriber>().fetch_all(pool).await
+}
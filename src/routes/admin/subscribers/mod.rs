@@ -0,0 +1,19 @@
+mod bulk_status;
+mod count;
+mod delete;
+mod export;
+mod import;
+mod list;
+mod search;
+mod status;
+mod unsubscribe_reasons;
+
+pub use bulk_status::bulk_change_subscriber_status;
+pub use count::count_subscribers;
+pub use delete::delete_subscriber;
+pub use export::export_subscribers;
+pub use import::{import_subscribers, IMPORT_SIZE_LIMIT};
+pub use list::list_subscribers;
+pub use search::search_subscribers;
+pub use status::change_subscriber_status;
+pub use unsubscribe_reasons::unsubscribe_reasons;
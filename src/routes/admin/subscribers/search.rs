@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::Json;
+
+use super::list::Subscriber;
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::startup::AppState;
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 100;
+/// The trigram tokenizer can't match anything shorter.
+const MIN_TERM_LENGTH: usize = 3;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SearchParameters {
+    q: String,
+    limit: Option<u32>,
+}
+
+/// Subscribers whose name or email contains `q`, best matches first.
+#[tracing::instrument(name = "Search subscribers", skip(app_state))]
+pub async fn search_subscribers(
+    State(app_state): State<Arc<AppState>>,
+    Query(parameters): Query<SearchParameters>,
+) -> Result<impl IntoResponse, AppError> {
+    let term = parameters.q.trim();
+    if term.chars().count() < MIN_TERM_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "`q` must be at least {} characters long.",
+            MIN_TERM_LENGTH
+        )));
+    }
+    let limit = parameters.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(AppError::BadRequest(format!(
+            "`limit` must be between 1 and {}.",
+            MAX_LIMIT
+        )));
+    }
+
+    let subscribers = search(app_state.pool(), term, limit)
+        .await
+        .context("Failed to search the subscribers.")?;
+    Ok(Json(subscribers))
+}
+
+/// Quotes the term into a single FTS5 string, so its characters are looked
+/// for as they are instead of being read as query syntax.
+fn match_expression(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+#[tracing::instrument(skip(pool))]
+async fn search(
+    pool: &SqliteInstrumentedPool,
+    term: &str,
+    limit: u32,
+) -> Result<Vec<Subscriber>, sqlx::Error> {
+    sqlx::query_as::<_, Subscriber>(
+        r#"
+        SELECT s.uuid, s.name, s.email, s.status, s.subscribed_at,
+            s.source_url, s.utm_source, s.utm_medium, s.utm_campaign
+        FROM subscriptions_fts
+        JOIN subscriptions s ON s.id = subscriptions_fts.rowid
+        WHERE subscriptions_fts MATCH $1
+        ORDER BY bm25(subscriptions_fts)
+        LIMIT $2
+        "#,
+    )
+    .bind(match_expression(term))
+    .bind(i64::from(limit))
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::match_expression;
+
+    #[test]
+    fn terms_are_searched_as_a_single_string() {
+        assert_eq!(match_expression("abood"), r#""abood""#);
+        assert_eq!(match_expression("a OR b"), r#""a OR b""#);
+        assert_eq!(match_expression(r#"say "hi""#), r#""say ""hi""""#);
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+
+use super::list::Subscriber;
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::startup::AppState;
+use crate::utils::{e400, e500};
+
+#[derive(serde::Deserialize)]
+pub struct StatusChange {
+    status: String,
+    /// Required to confirm a subscriber who unsubscribed.
+    #[serde(default)]
+    force: bool,
+}
+
+const STATUSES: [&str; 3] = ["confirmed", "pending_confirmation", "unsubscribed"];
+
+/// Manually confirm or unsubscribe a subscriber.
+///
+/// Confirming someone who unsubscribed goes against their last known wish,
+/// so it is refused with a `409` unless `force` is set.
+#[tracing::instrument(
+    name = "Change a subscriber status",
+    skip(app_state, user_id, client_ip, change),
+    fields(user_id=%user_id, status=%change.status, force=change.force)
+)]
+pub async fn change_subscriber_status(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(subscriber_id): Path<String>,
+    Json(change): Json<StatusChange>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let subscriber_id = uuid::Uuid::try_parse(&subscriber_id).map_err(e400)?;
+    if !STATUSES.contains(&change.status.as_str()) {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "`{}` is not a subscription status, use `confirmed`, `pending_confirmation` or `unsubscribed`.",
+                change.status
+            ),
+        )
+            .into_response());
+    }
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")
+        .map_err(e500)?;
+    let Some(current_status) = get_status(&mut transaction, &subscriber_id.to_string())
+        .await
+        .context("Failed to retrieve the subscriber status.")
+        .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Subscriber not found").into_response());
+    };
+    if current_status == "unsubscribed" && change.status == "confirmed" && !change.force {
+        return Ok((
+            StatusCode::CONFLICT,
+            "The subscriber unsubscribed, set `force` to confirm them anyway.",
+        )
+            .into_response());
+    }
+    let subscriber = update_status(&mut transaction, &subscriber_id.to_string(), &change.status)
+        .await
+        .context("Failed to update the subscriber status.")
+        .map_err(e500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the subscriber status change.")
+        .map_err(e500)?;
+
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "change_subscriber_status",
+            target_type: "subscription",
+            target_id: Some(subscriber_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    tracing::info!(from = %current_status, "Changed the subscriber status");
+    Ok(Json(subscriber).into_response())
+}
+
+async fn get_status(
+    transaction: &mut SqliteInstrumentedTransaction,
+    subscriber_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let subscriber = sqlx::query!(
+        "SELECT status FROM subscriptions WHERE uuid = $1",
+        subscriber_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+    Ok(subscriber.map(|s| s.status))
+}
+
+async fn update_status(
+    transaction: &mut SqliteInstrumentedTransaction,
+    subscriber_id: &str,
+    status: &str,
+) -> Result<Subscriber, sqlx::Error> {
+    sqlx::query_as::<_, Subscriber>(
+        r#"
+        UPDATE subscriptions
+        SET status = $2
+        WHERE uuid = $1
+        RETURNING uuid, name, email, status, subscribed_at, source_url, utm_source, utm_medium, utm_campaign
+        "#,
+    )
+    .bind(subscriber_id)
+    .bind(status)
+    .fetch_one(&mut *transaction)
+    .await
+}
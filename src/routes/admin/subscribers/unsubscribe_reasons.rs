@@ -0,0 +1,50 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::routes::UnsubscribeReason;
+use crate::startup::AppState;
+use anyhow::Context;
+use axum::extract::State;
+use axum::Json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Subscribers who left without answering the survey.
+const UNSPECIFIED: &str = "unspecified";
+
+/// How many subscribers left for each reason of the unsubscribe survey,
+/// reasons nobody picked are counted as `0`.
+#[tracing::instrument(name = "Count unsubscribe reasons", skip(app_state))]
+pub async fn unsubscribe_reasons(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<BTreeMap<String, i64>>, AppError> {
+    let mut counts: BTreeMap<String, i64> = UnsubscribeReason::ALL
+        .iter()
+        .map(|reason| (reason.as_str().to_string(), 0))
+        .chain(std::iter::once((UNSPECIFIED.to_string(), 0)))
+        .collect();
+    for (reason, count) in count_reasons(app_state.pool())
+        .await
+        .context("Failed to count the unsubscribe reasons.")?
+    {
+        counts.insert(reason.unwrap_or_else(|| UNSPECIFIED.to_string()), count);
+    }
+    Ok(Json(counts))
+}
+
+async fn count_reasons(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<(Option<String>, i64)>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT reason, COUNT(*) AS "count!: i64"
+        FROM unsubscribe_events
+        GROUP BY reason
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.reason, row.count))
+        .collect())
+}
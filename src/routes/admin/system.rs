@@ -0,0 +1,99 @@
+use crate::startup::AppState;
+use crate::telemetry::LogFilterHandle;
+use crate::utils::e500;
+use anyhow::Context;
+use axum::extract::State;
+use axum::response::{Html, IntoResponse};
+use rinja_axum::Template;
+use std::sync::Arc;
+use tower_sessions_redis_store::fred::{prelude::*, types::InfoKind};
+
+/// Nothing secret goes in here: no HMAC secret, database path nor Redis URI.
+#[derive(Template)]
+#[template(path = "admin-system/index.html")]
+struct SystemTemplate {
+    app_version: &'static str,
+    environment: &'static str,
+    sqlite_version: String,
+    redis_version: String,
+    max_connections: u32,
+    idle_connections: usize,
+    active_connections: usize,
+    log_filter: String,
+}
+
+/// Show how the running instance is configured and what it runs against.
+#[tracing::instrument(name = "Show the system diagnostics", skip(app_state))]
+pub async fn system_diagnostics(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let sqlite_version: String = sqlx::query_scalar("SELECT sqlite_version()")
+        .fetch_one(app_state.pool())
+        .await
+        .context("Failed to query the SQLite version.")
+        .map_err(e500)?;
+    // the page is most useful when something is off, so a broken Redis
+    // shouldn't take it down with it
+    let redis_version = match redis_version(&app_state).await {
+        Ok(version) => version,
+        Err(e) => {
+            tracing::warn!(error.message = %e, "Failed to query the Redis version");
+            "unavailable".to_string()
+        }
+    };
+    let pool = app_state.pool();
+    let size = pool.size() as usize;
+    let idle_connections = pool.num_idle();
+    let html = SystemTemplate {
+        app_version: env!("CARGO_PKG_VERSION"),
+        environment: app_state.environment.as_str(),
+        sqlite_version,
+        redis_version,
+        max_connections: pool.options().get_max_connections(),
+        idle_connections,
+        active_connections: size.saturating_sub(idle_connections),
+        log_filter: app_state
+            .log_filter
+            .as_ref()
+            .and_then(LogFilterHandle::current)
+            .unwrap_or_else(|| "unknown".to_string()),
+    }
+    .render()
+    .context("Failed to render the system diagnostics page.")
+    .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+async fn redis_version(app_state: &AppState) -> Result<String, anyhow::Error> {
+    let info: String = app_state
+        .redis_pool
+        .next()
+        .info(Some(InfoKind::Server))
+        .await?;
+    parse_redis_version(&info).context("`INFO server` has no `redis_version`")
+}
+
+fn parse_redis_version(info: &str) -> Option<String> {
+    info.lines()
+        .find_map(|line| line.strip_prefix("redis_version:"))
+        .map(|version| version.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_redis_version;
+
+    #[test]
+    fn the_version_is_read_from_the_server_section() {
+        let info = "# Server\r\nredis_version:7.2.4\r\nredis_mode:standalone\r\n";
+        assert_eq!(parse_redis_version(info), Some("7.2.4".to_string()));
+    }
+
+    #[test]
+    fn a_missing_version_is_none() {
+        assert_eq!(
+            parse_redis_version("# Server\r\nredis_mode:standalone\r\n"),
+            None
+        );
+    }
+}
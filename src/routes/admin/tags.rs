@@ -0,0 +1,44 @@
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::e500;
+use anyhow::Context;
+use axum::extract::State;
+use axum::Json;
+use std::sync::Arc;
+
+#[derive(serde::Serialize)]
+pub struct TagSummary {
+    name: String,
+    /// Confirmed subscribers only, the ones a tagged issue would reach.
+    subscribers: i64,
+}
+
+/// Every tag with how many subscribers opted into it.
+#[tracing::instrument(name = "List tags", skip(app_state))]
+pub async fn list_tags(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TagSummary>>, axum::response::Response> {
+    let tags = count_subscribers_per_tag(app_state.pool())
+        .await
+        .context("Failed to count the subscribers of each tag.")
+        .map_err(e500)?;
+    Ok(Json(tags))
+}
+
+async fn count_subscribers_per_tag(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<TagSummary>, sqlx::Error> {
+    sqlx::query_as!(
+        TagSummary,
+        r#"
+        SELECT t.name, COUNT(s.uuid) AS "subscribers!: i64"
+        FROM tags t
+        LEFT JOIN subscriber_tags st ON st.tag = t.name
+        LEFT JOIN subscriptions s ON s.uuid = st.subscriber_uuid AND s.status = 'confirmed'
+        GROUP BY t.name
+        ORDER BY t.name
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
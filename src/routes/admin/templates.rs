@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedPool;
+use crate::error::AppError;
+use crate::startup::AppState;
+
+#[derive(serde::Serialize)]
+pub struct NewsletterTemplate {
+    pub uuid: String,
+    pub name: String,
+    pub header_html: String,
+    pub footer_html: String,
+    pub created_at: String,
+}
+
+impl NewsletterTemplate {
+    /// Only the HTML body is wrapped, the plain text one is left as is.
+    pub fn wrap(&self, html_content: &str) -> String {
+        format!("{}{}{}", self.header_html, html_content, self.footer_html)
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct TemplateData {
+    name: String,
+    #[serde(default)]
+    header_html: String,
+    #[serde(default)]
+    footer_html: String,
+}
+
+impl TemplateData {
+    fn validated(self) -> Result<Self, AppError> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::BadRequest("The template needs a name.".into()));
+        }
+        Ok(Self { name, ..self })
+    }
+}
+
+#[tracing::instrument(name = "List the newsletter templates", skip(app_state))]
+pub async fn list_templates(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let templates = get_templates(app_state.pool())
+        .await
+        .context("Failed to retrieve the newsletter templates.")?;
+    Ok(Json(templates))
+}
+
+#[tracing::instrument(
+    name = "Create a newsletter template",
+    skip(app_state, user_id, client_ip, data),
+    fields(user_id=%user_id, name=%data.name)
+)]
+pub async fn create_template(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Json(data): Json<TemplateData>,
+) -> Result<impl IntoResponse, AppError> {
+    let data = data.validated()?;
+    let template = NewsletterTemplate {
+        uuid: Uuid::new_v4().to_string(),
+        name: data.name,
+        header_html: data.header_html,
+        footer_html: data.footer_html,
+        created_at: Utc::now().to_string(),
+    };
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO newsletter_templates (uuid, name, header_html, footer_html, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (name) DO NOTHING
+        "#,
+        template.uuid,
+        template.name,
+        template.header_html,
+        template.footer_html,
+        template.created_at
+    )
+    .execute(app_state.pool())
+    .await
+    .context("Failed to store the newsletter template.")?
+    .rows_affected();
+    if inserted == 0 {
+        return Err(name_taken(&template.name));
+    }
+
+    record_template_change(
+        &app_state,
+        user_id,
+        client_ip,
+        "create_template",
+        &template.uuid,
+    );
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+#[tracing::instrument(
+    name = "Update a newsletter template",
+    skip(app_state, user_id, client_ip, data),
+    fields(user_id=%user_id)
+)]
+pub async fn update_template(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(template_id): Path<Uuid>,
+    Json(data): Json<TemplateData>,
+) -> Result<impl IntoResponse, AppError> {
+    let data = data.validated()?;
+    let template_id = template_id.to_string();
+    let name_taken_by_another = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM newsletter_templates WHERE name = $1 AND uuid != $2",
+        data.name,
+        template_id
+    )
+    .fetch_one(app_state.pool())
+    .await
+    .context("Failed to check the newsletter template names.")?
+        > 0;
+    if name_taken_by_another {
+        return Err(name_taken(&data.name));
+    }
+    let template = sqlx::query_as!(
+        NewsletterTemplate,
+        r#"
+        UPDATE newsletter_templates
+        SET name = $2, header_html = $3, footer_html = $4
+        WHERE uuid = $1
+        RETURNING
+            uuid AS "uuid!",
+            name AS "name!",
+            header_html AS "header_html!",
+            footer_html AS "footer_html!",
+            created_at AS "created_at!"
+        "#,
+        template_id,
+        data.name,
+        data.header_html,
+        data.footer_html
+    )
+    .fetch_optional(app_state.pool())
+    .await
+    .context("Failed to update the newsletter template.")?
+    .ok_or(AppError::NotFound)?;
+
+    record_template_change(
+        &app_state,
+        user_id,
+        client_ip,
+        "update_template",
+        &template.uuid,
+    );
+    Ok(Json(template))
+}
+
+/// Issues already published keep the header and footer they were wrapped in.
+#[tracing::instrument(
+    name = "Delete a newsletter template",
+    skip(app_state, user_id, client_ip),
+    fields(user_id=%user_id)
+)]
+pub async fn delete_template(
+    State(app_state): State<Arc<AppState>>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Path(template_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let template_id = template_id.to_string();
+    let deleted = sqlx::query!(
+        "DELETE FROM newsletter_templates WHERE uuid = $1",
+        template_id
+    )
+    .execute(app_state.pool())
+    .await
+    .context("Failed to delete the newsletter template.")?
+    .rows_affected();
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    record_template_change(
+        &app_state,
+        user_id,
+        client_ip,
+        "delete_template",
+        &template_id,
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn name_taken(name: &str) -> AppError {
+    AppError::BadRequest(format!("There is already a template named `{}`.", name))
+}
+
+fn record_template_change(
+    app_state: &AppState,
+    user_id: UserId,
+    client_ip: Option<std::net::IpAddr>,
+    action: &'static str,
+    template_id: &str,
+) {
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action,
+            target_type: "newsletter_template",
+            target_id: Some(template_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+}
+
+#[tracing::instrument(skip(pool))]
+pub async fn get_templates(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<NewsletterTemplate>, sqlx::Error> {
+    sqlx::query_as!(
+        NewsletterTemplate,
+        r#"
+        SELECT uuid, name, header_html, footer_html, created_at
+        FROM newsletter_templates
+        ORDER BY name
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[tracing::instrument(skip(pool))]
+pub async fn get_template(
+    pool: &SqliteInstrumentedPool,
+    template_id: Uuid,
+) -> Result<Option<NewsletterTemplate>, sqlx::Error> {
+    let template_id = template_id.to_string();
+    sqlx::query_as!(
+        NewsletterTemplate,
+        r#"
+        SELECT uuid, name, header_html, footer_html, created_at
+        FROM newsletter_templates
+        WHERE uuid = $1
+        "#,
+        template_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NewsletterTemplate;
+
+    #[test]
+    fn only_the_body_is_wrapped() {
+        let template = NewsletterTemplate {
+            uuid: uuid::Uuid::new_v4().to_string(),
+            name: "branded".to_string(),
+            header_html: "<header>Newzletter</header>".to_string(),
+            footer_html: "<footer>Bye</footer>".to_string(),
+            created_at: chrono::Utc::now().to_string(),
+        };
+        assert_eq!(
+            template.wrap("<p>Body</p>"),
+            "<header>Newzletter</header><p>Body</p><footer>Bye</footer>"
+        );
+    }
+}
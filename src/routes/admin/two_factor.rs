@@ -0,0 +1,164 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::{
+    disable_totp, enable_totp, encrypt_totp_secret, generate_totp_secret, get_totp_settings,
+    store_totp_secret, totp, validate_credentials, verify_totp_code, AuthError, Credentials,
+    UserId,
+};
+use crate::error::AppError;
+use crate::routes::admin::dashboard::get_username;
+use crate::startup::{AppState, HmacSecret};
+use crate::utils::{e500, respond_for_content_type};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Form, Json};
+use axum_messages::Messages;
+use secrecy::SecretString;
+use std::sync::Arc;
+
+#[derive(serde::Serialize)]
+pub struct TwoFactorEnrollment {
+    /// For typing into the authenticator app by hand.
+    secret: String,
+    /// What the QR code scanned by the authenticator app encodes.
+    otpauth_url: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct VerifyFormData {
+    code: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DisableFormData {
+    current_password: SecretString,
+}
+
+/// Generates a new TOTP secret for the user. Logins don't ask for a code
+/// until one has been sent to `POST /admin/2fa/verify`.
+#[tracing::instrument(
+    name = "Enable two-factor authentication",
+    skip(app_state, hmac_secret, user_id),
+    fields(user_id=%user_id)
+)]
+pub async fn enable_two_factor(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    Extension(user_id): Extension<UserId>,
+) -> Result<Response, AppError> {
+    if get_totp_settings(app_state.pool(), *user_id).await?.enabled {
+        return Err(AppError::BadRequest(
+            "Two-factor authentication is already enabled.".into(),
+        ));
+    }
+    let username = get_username(*user_id, app_state.pool()).await?;
+    let secret = generate_totp_secret();
+    let encrypted_secret = encrypt_totp_secret(&secret, &hmac_secret)?;
+    let totp = totp(secret, &username)?;
+    store_totp_secret(app_state.pool(), *user_id, &encrypted_secret).await?;
+    Ok(Json(TwoFactorEnrollment {
+        secret: totp.get_secret_base32(),
+        otpauth_url: totp.get_url(),
+    })
+    .into_response())
+}
+
+#[tracing::instrument(
+    name = "Verify two-factor authentication",
+    skip(app_state, hmac_secret, messages, headers, user_id, form),
+    fields(user_id=%user_id)
+)]
+pub async fn verify_two_factor(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    messages: Messages,
+    headers: HeaderMap,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Form(form): Form<VerifyFormData>,
+) -> Result<Response, Response> {
+    let respond =
+        |outcome| respond_for_content_type(&headers, messages, "/admin/dashboard", outcome);
+    let username = get_username(*user_id, app_state.pool())
+        .await
+        .map_err(e500)?;
+    let verified = verify_totp_code(
+        app_state.pool(),
+        &hmac_secret,
+        *user_id,
+        &username,
+        &form.code,
+    )
+    .await
+    .map_err(e500)?;
+    if !verified {
+        return Ok(respond(Err("The code is invalid or has expired.")));
+    }
+
+    enable_totp(app_state.pool(), *user_id)
+        .await
+        .map_err(e500)?;
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "enable_two_factor",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(respond(Ok("Two-factor authentication has been enabled.")))
+}
+
+/// Asks for the password again, a session left open shouldn't be enough to
+/// turn the second factor off.
+#[tracing::instrument(
+    name = "Disable two-factor authentication",
+    skip(app_state, messages, headers, user_id, form),
+    fields(user_id=%user_id)
+)]
+pub async fn disable_two_factor(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    headers: HeaderMap,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+    Form(form): Form<DisableFormData>,
+) -> Result<Response, Response> {
+    let respond =
+        |outcome| respond_for_content_type(&headers, messages, "/admin/dashboard", outcome);
+    let username = get_username(*user_id, app_state.pool())
+        .await
+        .map_err(e500)?;
+    let credentials = Credentials {
+        username,
+        password: form.current_password,
+    };
+    if let Err(e) = validate_credentials(credentials, app_state.pool()).await {
+        return match e {
+            AuthError::InvalidCredentials(err) => {
+                tracing::warn!(chain_error = ?err);
+                Ok(respond(Err("The current password is incorrect.")))
+            }
+            AuthError::UnexpectedError(_) => Err(e500(e).into_response()),
+        };
+    }
+
+    disable_totp(app_state.pool(), *user_id)
+        .await
+        .map_err(e500)?;
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "disable_two_factor",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+    Ok(respond(Ok("Two-factor authentication has been disabled.")))
+}
@@ -0,0 +1,89 @@
+use crate::audit::{spawn_audit_event, AuditEvent, ClientIp};
+use crate::authentication::UserId;
+use crate::db::SqliteInstrumentedPool;
+use crate::middleware::RequestBaseUrl;
+use crate::startup::AppState;
+use crate::utils::e500;
+use anyhow::Context;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::Utc;
+use rand::distr::Alphanumeric;
+use rand::{rng, Rng};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// How long an invite link can be used to register.
+const INVITE_TOKEN_TTL_HOURS: i64 = 48;
+
+#[derive(Serialize)]
+pub struct Invite {
+    invite_url: String,
+    expires_at: String,
+}
+
+#[tracing::instrument(
+    name = "Invite a new user",
+    skip(app_state, base_url, user_id, client_ip),
+    fields(user_id=%user_id)
+)]
+pub async fn invite_user(
+    State(app_state): State<Arc<AppState>>,
+    Extension(base_url): Extension<RequestBaseUrl>,
+    Extension(user_id): Extension<UserId>,
+    ClientIp(client_ip): ClientIp,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let token = generate_invite_token();
+    let expires_at = store_invite(app_state.pool(), &token, *user_id)
+        .await
+        .map_err(e500)?;
+    spawn_audit_event(
+        app_state.pool().clone(),
+        AuditEvent {
+            user_id: *user_id,
+            action: "invite_user",
+            target_type: "user_invite",
+            target_id: None,
+            ip_address: client_ip,
+            reason: None,
+        },
+    );
+
+    Ok(Json(Invite {
+        invite_url: format!("{}/register?token={}", base_url.0, token),
+        expires_at,
+    })
+    .into_response())
+}
+
+fn generate_invite_token() -> String {
+    let mut rng = rng();
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(32)
+        .collect()
+}
+
+#[tracing::instrument(skip(pool, token))]
+async fn store_invite(
+    pool: &SqliteInstrumentedPool,
+    token: &str,
+    invited_by: uuid::Uuid,
+) -> Result<String, anyhow::Error> {
+    let invited_by = invited_by.to_string();
+    let expires_at = (Utc::now() + chrono::Duration::hours(INVITE_TOKEN_TTL_HOURS)).to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO user_invites (token, invited_by, expires_at, used)
+        VALUES ($1, $2, $3, FALSE)
+        "#,
+        token,
+        invited_by,
+        expires_at,
+    )
+    .execute(pool)
+    .await
+    .context("Failed to store the invite.")?;
+    Ok(expires_at)
+}
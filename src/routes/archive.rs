@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse},
+};
+use rinja_axum::Template;
+
+use super::blog::format_date;
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::e500;
+
+struct ArchivedIssue {
+    slug: String,
+    title: String,
+    published_at: String,
+}
+
+impl ArchivedIssue {
+    /// The day the issue went out, e.g. "Oct 16, 2026".
+    fn published_on(&self) -> String {
+        format_date(&self.published_at)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "archive/index.html")]
+struct ArchiveTemplate {
+    issues: Vec<ArchivedIssue>,
+}
+
+#[derive(Template)]
+#[template(path = "archive-issue/index.html")]
+struct ArchiveIssueTemplate<'a> {
+    title: &'a str,
+    published_at: &'a str,
+    published_on: &'a str,
+    content: &'a str,
+}
+
+struct IssueBody {
+    title: String,
+    html_content: String,
+    published_at: String,
+}
+
+/// Every newsletter issue sent so far, newest first.
+#[tracing::instrument(name = "Newsletter archive", skip(app_state))]
+pub async fn archive_index(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issues = get_archived_issues(app_state.pool())
+        .await
+        .context("Failed to retrieve the archived newsletter issues.")
+        .map_err(e500)?;
+    let html = ArchiveTemplate { issues }
+        .render()
+        .context("Failed to render the newsletter archive.")
+        .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+/// The HTML body of a sent issue, scheduled issues are reported as missing.
+#[tracing::instrument(name = "Archived newsletter issue", skip(app_state))]
+pub async fn archive_issue(
+    State(app_state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let Some(issue) = get_archived_issue(app_state.pool(), &slug)
+        .await
+        .context("Failed to retrieve the newsletter issue.")
+        .map_err(e500)?
+    else {
+        return Ok((StatusCode::NOT_FOUND, "Newsletter issue not found").into_response());
+    };
+    let html = ArchiveIssueTemplate {
+        title: &issue.title,
+        published_at: &issue.published_at,
+        published_on: &format_date(&issue.published_at),
+        content: &issue.html_content,
+    }
+    .render()
+    .context("Failed to render the newsletter issue.")
+    .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
+#[tracing::instrument(name = "Get archived newsletter issues", skip(pool))]
+async fn get_archived_issues(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<ArchivedIssue>, sqlx::Error> {
+    sqlx::query_as!(
+        ArchivedIssue,
+        r#"
+        SELECT slug AS "slug!", title, published_at
+        FROM active_newsletter_issues
+        WHERE status NOT IN ('draft', 'scheduled', 'broadcast')
+        ORDER BY published_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Get an archived newsletter issue", skip(pool))]
+async fn get_archived_issue(
+    pool: &SqliteInstrumentedPool,
+    slug: &str,
+) -> Result<Option<IssueBody>, sqlx::Error> {
+    sqlx::query_as!(
+        IssueBody,
+        r#"
+        SELECT title, html_content, published_at
+        FROM active_newsletter_issues
+        WHERE slug = $1 AND status NOT IN ('draft', 'scheduled', 'broadcast')
+        "#,
+        slug,
+    )
+    .fetch_optional(pool)
+    .await
+}
@@ -1,24 +1,217 @@
+use crate::audit::ClientIp;
+use crate::db::SqliteInstrumentedPool;
+use crate::routes::admin::markdown_to_html;
+use crate::startup::AppState;
+use crate::utils::e500;
+use anyhow::Context;
 use axum::{
-    extract::Path,
+    extract::{Path, State},
     response::{Html, IntoResponse},
 };
+use chrono::{NaiveDate, Utc};
+use rinja_axum::Template;
+use sha2::{Digest, Sha256};
 use std::fs;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::Instrument;
 
-/// Handler for the blog index page that lists all blog posts
-pub async fn blog_index() -> impl IntoResponse {
-    let blog_path = PathBuf::from("frontend/dist/blog/index.html");
-    match fs::read_to_string(blog_path) {
-        Ok(content) => Html(content).into_response(),
-        Err(_) => (axum::http::StatusCode::NOT_FOUND, "Blog index not found").into_response(),
+struct PublishedPost {
+    slug: String,
+    title: String,
+    published_at: String,
+}
+
+impl PublishedPost {
+    /// The day the post went out, e.g. "Oct 16, 2026".
+    fn published_on(&self) -> String {
+        format_date(&self.published_at)
     }
 }
 
+#[derive(Template)]
+#[template(path = "blog/index.html")]
+struct BlogIndexTemplate {
+    posts: Vec<PublishedPost>,
+}
+
+#[derive(Template)]
+#[template(path = "blog-post/index.html")]
+struct BlogPostTemplate<'a> {
+    title: &'a str,
+    description: &'a str,
+    published_at: &'a str,
+    published_on: &'a str,
+    content: &'a str,
+}
+
+/// Handler for the blog index page that lists all blog posts
+///
+/// The astro posts are baked into the page, the published posts written from
+/// the admin area are listed before them.
+pub async fn blog_index(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let posts = get_published_posts(app_state.pool())
+        .await
+        .context("Failed to retrieve the published blog posts.")
+        .map_err(e500)?;
+    let html = BlogIndexTemplate { posts }
+        .render()
+        .context("Failed to render the blog index.")
+        .map_err(e500)?;
+    Ok(Html(html).into_response())
+}
+
 /// Handler for individual blog posts
-pub async fn blog_post(Path(slug): Path<String>) -> impl IntoResponse {
+///
+/// Falls back to the posts written from the admin area when astro didn't build
+/// one with that slug, drafts are reported as missing. Every read is counted,
+/// see `GET /admin/blog/{slug}/stats`.
+pub async fn blog_post(
+    State(app_state): State<Arc<AppState>>,
+    ClientIp(client_ip): ClientIp,
+    Path(slug): Path<String>,
+) -> Result<axum::response::Response, axum::response::Response> {
     let blog_path = PathBuf::from(format!("frontend/dist/blog/{}/index.html", slug));
-    match fs::read_to_string(blog_path) {
-        Ok(content) => Html(content).into_response(),
-        Err(_) => (axum::http::StatusCode::NOT_FOUND, "Blog post not found").into_response(),
+    if let Ok(content) = fs::read_to_string(blog_path) {
+        spawn_blog_post_view(app_state.pool().clone(), slug, client_ip);
+        return Ok(Html(content).into_response());
+    }
+
+    let Some(post) = get_published_post(app_state.pool(), &slug)
+        .await
+        .context("Failed to retrieve the blog post.")
+        .map_err(e500)?
+    else {
+        return Ok((axum::http::StatusCode::NOT_FOUND, "Blog post not found").into_response());
+    };
+    let published_at = post.published_at.unwrap_or_default();
+    let html = BlogPostTemplate {
+        title: &post.title,
+        description: &post.description,
+        published_at: &published_at,
+        published_on: &format_date(&published_at),
+        content: &markdown_to_html(&post.markdown_content),
+    }
+    .render()
+    .context("Failed to render the blog post.")
+    .map_err(e500)?;
+    spawn_blog_post_view(app_state.pool().clone(), slug, client_ip);
+    Ok(Html(html).into_response())
+}
+
+/// Records a read in the background, the reader doesn't wait for it and a
+/// failure is only logged.
+fn spawn_blog_post_view(pool: SqliteInstrumentedPool, slug: String, client_ip: Option<IpAddr>) {
+    tokio::spawn(
+        async move {
+            let now = Utc::now();
+            let ip_hash = client_ip.map(|ip| ip_hash(ip, now.date_naive()));
+            if let Err(e) = record_blog_post_view(&pool, &slug, &now.to_string(), ip_hash).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to record a blog post view",
+                );
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+/// The same reader hashes to the same value for a day only.
+fn ip_hash(ip: IpAddr, day: NaiveDate) -> String {
+    hex::encode(Sha256::digest(format!("{}|{}", ip, day).as_bytes()))
+}
+
+#[tracing::instrument(name = "Record a blog post view", skip(pool, ip_hash))]
+async fn record_blog_post_view(
+    pool: &SqliteInstrumentedPool,
+    slug: &str,
+    viewed_at: &str,
+    ip_hash: Option<String>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO blog_post_views (slug, viewed_at, ip_hash)
+        VALUES ($1, $2, $3)
+        "#,
+        slug,
+        viewed_at,
+        ip_hash,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub(super) fn format_date(timestamp: &str) -> String {
+    timestamp
+        .parse::<chrono::DateTime<chrono::Utc>>()
+        .map(|date| date.format("%b %-d, %Y").to_string())
+        .unwrap_or_default()
+}
+
+#[tracing::instrument(name = "Get published blog posts", skip(pool))]
+async fn get_published_posts(
+    pool: &SqliteInstrumentedPool,
+) -> Result<Vec<PublishedPost>, sqlx::Error> {
+    sqlx::query_as!(
+        PublishedPost,
+        r#"
+        SELECT slug, title, published_at AS "published_at!"
+        FROM blog_posts
+        WHERE draft = FALSE
+        ORDER BY published_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+struct Post {
+    title: String,
+    description: String,
+    markdown_content: String,
+    published_at: Option<String>,
+}
+
+#[tracing::instrument(name = "Get a published blog post", skip(pool))]
+async fn get_published_post(
+    pool: &SqliteInstrumentedPool,
+    slug: &str,
+) -> Result<Option<Post>, sqlx::Error> {
+    sqlx::query_as!(
+        Post,
+        r#"
+        SELECT title, description, markdown_content, published_at
+        FROM blog_posts
+        WHERE slug = $1 AND draft = FALSE
+        "#,
+        slug,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ip_hash;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn ip_hashes_only_match_within_a_day() {
+        let ip = "203.0.113.7".parse().unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2026, 10, 13).unwrap();
+        assert_eq!(ip_hash(ip, monday), ip_hash(ip, monday));
+        assert_eq!(ip_hash(ip, monday).len(), 64);
+        assert_ne!(ip_hash(ip, monday), ip_hash(ip, tuesday));
+        assert_ne!(
+            ip_hash(ip, monday),
+            ip_hash("203.0.113.8".parse().unwrap(), monday)
+        );
     }
 }
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use atom_syndication::{Content, Entry, Feed, Link, Text};
+use axum::{
+    extract::State,
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    response::IntoResponse,
+};
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::AppState;
+use crate::utils::e500;
+
+/// How many issues the feed carries, readers have caught up on older ones.
+const FEED_SIZE: i64 = 50;
+
+struct SentIssue {
+    newsletter_issue_uuid: String,
+    title: String,
+    html_content: String,
+    published_at: String,
+    slug: String,
+}
+
+/// Atom feed of the latest newsletter issues, newest first.
+#[tracing::instrument(name = "Newsletter feed", skip(app_state))]
+pub async fn newsletter_feed(
+    State(app_state): State<Arc<AppState>>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let issues = get_sent_issues(app_state.pool())
+        .await
+        .context("Failed to retrieve the newsletter issues.")
+        .map_err(e500)?;
+    let feed = build_feed(app_state.base_url(), &issues);
+    Ok((
+        [
+            (CONTENT_TYPE, "application/atom+xml; charset=utf-8"),
+            (CACHE_CONTROL, "public, max-age=300"),
+        ],
+        feed.to_string(),
+    )
+        .into_response())
+}
+
+fn build_feed(base_url: &str, issues: &[SentIssue]) -> Feed {
+    let entries: Vec<Entry> = issues
+        .iter()
+        .map(|issue| {
+            let published_at = to_fixed_offset(&issue.published_at);
+            let mut entry = Entry::default();
+            entry.set_id(format!("urn:uuid:{}", issue.newsletter_issue_uuid));
+            entry.set_title(Text::plain(issue.title.as_str()));
+            entry.set_published(Some(published_at));
+            entry.set_updated(published_at);
+            entry.set_links(vec![alternate_link(format!(
+                "{}/archive/{}",
+                base_url, issue.slug
+            ))]);
+            let mut content = Content::default();
+            content.set_content_type(Some("html".to_string()));
+            content.set_value(Some(issue.html_content.clone()));
+            entry.set_content(Some(content));
+            entry
+        })
+        .collect();
+
+    let mut feed = Feed::default();
+    feed.set_id(format!("{}/feed.xml", base_url));
+    feed.set_title(Text::plain("Newzletter"));
+    // an empty feed was last touched when it was first served
+    feed.set_updated(
+        entries
+            .first()
+            .map(|entry| *entry.updated())
+            .unwrap_or_else(|| Utc::now().fixed_offset()),
+    );
+    feed.set_links(vec![
+        alternate_link(format!("{}/archive", base_url)),
+        Link {
+            href: format!("{}/feed.xml", base_url),
+            rel: "self".to_string(),
+            ..Default::default()
+        },
+    ]);
+    feed.set_entries(entries);
+    feed
+}
+
+fn alternate_link(href: String) -> Link {
+    Link {
+        href,
+        rel: "alternate".to_string(),
+        ..Default::default()
+    }
+}
+
+fn to_fixed_offset(timestamp: &str) -> DateTime<FixedOffset> {
+    timestamp
+        .parse::<DateTime<Utc>>()
+        .unwrap_or_default()
+        .fixed_offset()
+}
+
+/// Scheduled issues haven't gone out yet.
+#[tracing::instrument(name = "Get sent newsletter issues", skip(pool))]
+async fn get_sent_issues(pool: &SqliteInstrumentedPool) -> Result<Vec<SentIssue>, sqlx::Error> {
+    sqlx::query_as!(
+        SentIssue,
+        r#"
+        SELECT
+            newsletter_issue_uuid,
+            title,
+            html_content,
+            published_at,
+            slug AS "slug!"
+        FROM active_newsletter_issues
+        WHERE status NOT IN ('draft', 'scheduled', 'broadcast')
+        ORDER BY published_at DESC
+        LIMIT $1
+        "#,
+        FEED_SIZE
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -1,5 +1,66 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use axum::{extract::State, response::IntoResponse, Json};
 use reqwest::StatusCode;
+use serde::Serialize;
+use tower_sessions_redis_store::fred::prelude::*;
+
+use crate::startup::AppState;
+
+/// How long a dependency has to answer before it is reported as down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub async fn health_check() -> StatusCode {
     StatusCode::OK
 }
+
+#[derive(Serialize)]
+pub struct DependenciesHealth {
+    sqlite: String,
+    redis: String,
+}
+
+/// Check that the database and the session store are reachable, answering
+/// `503` when either of them is not.
+#[tracing::instrument(name = "Deep health check", skip(app_state))]
+pub async fn deep_health_check(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    let (sqlite, redis) = tokio::join!(
+        probe(
+            // reading a table also catches a broken schema, not only a missing file
+            sqlx::query("SELECT 1 FROM subscriptions LIMIT 1").fetch_optional(app_state.pool())
+        ),
+        probe(app_state.redis_pool.next().ping::<()>(None)),
+    );
+    let status = if sqlite.is_ok() && redis.is_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let report = |result: Result<(), String>| match result {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    (
+        status,
+        Json(DependenciesHealth {
+            sqlite: report(sqlite),
+            redis: report(redis),
+        }),
+    )
+}
+
+async fn probe<T, E: std::fmt::Display>(
+    check: impl Future<Output = Result<T, E>>,
+) -> Result<(), String> {
+    match tokio::time::timeout(PROBE_TIMEOUT, check).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => {
+            tracing::error!(error.message = %e, "A dependency failed the health check");
+            Err(e.to_string())
+        }
+        Err(_) => {
+            tracing::error!("A dependency timed out during the health check");
+            Err(format!("timed out after {}ms", PROBE_TIMEOUT.as_millis()))
+        }
+    }
+}
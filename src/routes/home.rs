@@ -1,10 +1,35 @@
-use axum::response::Html;
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::State;
+use axum::response::{Html, IntoResponse};
 use rinja_axum::Template;
 
+use crate::middleware::CsrfToken;
+use crate::startup::AppState;
+use crate::tags::all_tags;
+use crate::utils::e500;
+
 #[derive(Template)]
 #[template(path = "index.html")]
-struct HomeTemplate;
+struct HomeTemplate {
+    csrf_token: String,
+    /// Offered as checkboxes on the signup form, there are none when the
+    /// deployment doesn't configure `newsletter_tags`.
+    tags: Vec<String>,
+}
 
-pub async fn home() -> impl axum::response::IntoResponse {
-    Html(HomeTemplate.render().unwrap())
+pub async fn home(
+    State(app_state): State<Arc<AppState>>,
+    CsrfToken(csrf_token): CsrfToken,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let tags = all_tags(app_state.pool())
+        .await
+        .context("Failed to retrieve the tags.")
+        .map_err(e500)?;
+    let html = HomeTemplate { csrf_token, tags }
+        .render()
+        .context("Failed to render the home page.")
+        .map_err(e500)?;
+    Ok(Html(html).into_response())
 }
@@ -2,17 +2,21 @@ use axum::response::{Html, IntoResponse};
 use axum_messages::Messages;
 use rinja_axum::Template;
 
+use crate::middleware::CsrfToken;
+
 #[derive(Template)]
 #[template(path = "login/index.html")]
 struct LoginTemplate {
     errors: Vec<String>,
+    csrf_token: String,
 }
 
-#[tracing::instrument(name = "Login form", skip(messages))]
-pub async fn login_form(messages: Messages) -> impl IntoResponse {
+#[tracing::instrument(name = "Login form", skip(messages, csrf_token))]
+pub async fn login_form(messages: Messages, CsrfToken(csrf_token): CsrfToken) -> impl IntoResponse {
     Html(
         LoginTemplate {
             errors: messages.into_iter().map(|m| m.message).collect(),
+            csrf_token,
         }
         .render()
         .unwrap(),
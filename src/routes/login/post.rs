@@ -1,15 +1,23 @@
 use std::sync::Arc;
 
+use anyhow::Context;
 use axum::{
     extract::State,
     response::{IntoResponse, Redirect, Response},
     Form,
 };
 use axum_messages::Messages;
+use chrono::Utc;
 use secrecy::SecretString;
+use uuid::Uuid;
 
+use crate::db::SqliteInstrumentedPool;
 use crate::{
-    authentication::{validate_credentials, AuthError, Credentials},
+    audit::ClientIp,
+    authentication::{
+        get_totp_settings, get_user_role, validate_credentials, AuthError, Credentials,
+    },
+    middleware::too_many_requests,
     routes::error_chain_fmt,
     session_state::TypedSession,
     startup::AppState,
@@ -21,6 +29,8 @@ pub struct FormData {
     password: SecretString,
 }
 
+/// Clients with too many failed attempts are refused before their
+/// credentials are even looked at.
 #[tracing::instrument(
     skip(form, app_state, session, messages),
     fields(username=tracing::field::Empty, user_id=tracing::field::Empty)
@@ -29,31 +39,39 @@ pub async fn login(
     State(app_state): State<Arc<AppState>>,
     session: TypedSession,
     messages: Messages,
+    ClientIp(client_ip): ClientIp,
     Form(form): Form<FormData>,
 ) -> Result<Response, Response> {
+    // clients we can't identify are let through
+    if let Some(Err(retry_after)) = client_ip.map(|ip| app_state.login_rate_limiter.check(ip)) {
+        tracing::warn!(client_ip = ?client_ip, "Refused a login after too many failed attempts");
+        return Err(too_many_requests(retry_after));
+    }
     let credentials = Credentials {
         username: form.username,
         password: form.password,
     };
     tracing::Span::current().record("username", tracing::field::display(&credentials.username));
-    match validate_credentials(credentials, &app_state.pool).await {
+    match validate_credentials(credentials, app_state.pool()).await {
         Ok(user_id) => {
             tracing::Span::current().record("user_id", tracing::field::display(&user_id));
-
-            if let Err(e) = session.rotate_id().await {
-                let err = LoginError::UnexpectedError(e.into());
-                tracing::error!(cause_chain = ?err);
-                messages.error("Could not rotate session id");
-                return Err(Redirect::to("/login").into_response());
+            match get_totp_settings(app_state.pool(), user_id).await {
+                // the failed attempts are only forgotten once the code is right too
+                Ok(totp) if totp.enabled => {
+                    return start_two_factor_challenge(&session, messages, user_id).await;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let err = LoginError::UnexpectedError(e);
+                    tracing::error!(cause_chain = ?err);
+                    messages.error("Could not check for two-factor authentication");
+                    return Err(Redirect::to("/login").into_response());
+                }
             }
-
-            if let Err(e) = session.insert_user_id(user_id).await {
-                let err = LoginError::UnexpectedError(e.into());
-                tracing::error!(cause_chain = ?err);
-                messages.error("Could not insert user id");
-                return Err(Redirect::to("/login").into_response());
+            if let Some(ip) = client_ip {
+                app_state.login_rate_limiter.reset(ip);
             }
-
+            establish_session(&app_state, &session, messages, user_id).await?;
             Ok(Redirect::to("/admin/dashboard").into_response())
         }
         Err(e) => {
@@ -63,6 +81,9 @@ pub async fn login(
                 // AuthError::InvalidCredentials(e) => {
                 AuthError::InvalidCredentials(_) => {
                     tracing::warn!(cause_chain = ?e);
+                    if let Some(ip) = client_ip {
+                        app_state.login_rate_limiter.record_failure(ip);
+                    }
                     LoginError::AuthError(e.into())
                 }
                 AuthError::UnexpectedError(_) => {
@@ -77,6 +98,101 @@ pub async fn login(
     }
 }
 
+/// Stores everything a logged in session carries, once the password, and the
+/// second factor when it's enabled, have been checked.
+pub(super) async fn establish_session(
+    app_state: &AppState,
+    session: &TypedSession,
+    messages: Messages,
+    user_id: Uuid,
+) -> Result<(), Response> {
+    if let Err(e) = session.rotate_id().await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not rotate session id");
+        return Err(Redirect::to("/login").into_response());
+    }
+
+    if let Err(e) = session.insert_user_id(user_id).await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not insert user id");
+        return Err(Redirect::to("/login").into_response());
+    }
+
+    let now = Utc::now().to_string();
+    if let Err(e) = session.insert_logged_in_at(now.clone()).await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not insert login time");
+        return Err(Redirect::to("/login").into_response());
+    }
+
+    if let Err(e) = record_last_login(app_state.pool(), user_id, &now).await {
+        let err = LoginError::UnexpectedError(e);
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not record the login time");
+        return Err(Redirect::to("/login").into_response());
+    }
+
+    let role = match get_user_role(app_state.pool(), user_id).await {
+        Ok(role) => role,
+        Err(e) => {
+            let err = LoginError::UnexpectedError(e);
+            tracing::error!(cause_chain = ?err);
+            messages.error("Could not retrieve user role");
+            return Err(Redirect::to("/login").into_response());
+        }
+    };
+    if let Err(e) = session.insert_user_role(role).await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not insert user role");
+        return Err(Redirect::to("/login").into_response());
+    }
+    Ok(())
+}
+
+#[tracing::instrument(name = "Record last login", skip(pool))]
+async fn record_last_login(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+    logged_in_at: &str,
+) -> Result<(), anyhow::Error> {
+    let user_id = user_id.to_string();
+    sqlx::query!(
+        "UPDATE users SET last_login_at = $1 WHERE uuid = $2",
+        logged_in_at,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .context("Failed to record the login time.")?;
+    Ok(())
+}
+
+/// The session only remembers who got the password right, `/login/2fa` logs
+/// them in once they've entered a code.
+async fn start_two_factor_challenge(
+    session: &TypedSession,
+    messages: Messages,
+    user_id: Uuid,
+) -> Result<Response, Response> {
+    if let Err(e) = session.rotate_id().await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not rotate session id");
+        return Err(Redirect::to("/login").into_response());
+    }
+    if let Err(e) = session.insert_pending_two_factor_user_id(user_id).await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not start the two-factor challenge");
+        return Err(Redirect::to("/login").into_response());
+    }
+    Ok(Redirect::to("/login/2fa").into_response())
+}
+
 #[derive(thiserror::Error)]
 pub enum LoginError {
     #[error("Authentication failed")]
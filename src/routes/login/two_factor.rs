@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_messages::Messages;
+use rinja_axum::Template;
+
+use super::post::{establish_session, LoginError};
+use crate::{
+    audit::ClientIp,
+    authentication::verify_totp_code,
+    middleware::{too_many_requests, CsrfToken},
+    routes::get_username,
+    session_state::TypedSession,
+    startup::{AppState, HmacSecret},
+};
+
+#[derive(Template)]
+#[template(path = "login-2fa/index.html")]
+struct TwoFactorTemplate {
+    errors: Vec<String>,
+    csrf_token: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    code: String,
+}
+
+/// Only reachable between the password and the code, everyone else is sent
+/// back to `/login`.
+#[tracing::instrument(name = "Two-factor form", skip(session, messages, csrf_token))]
+pub async fn two_factor_form(
+    session: TypedSession,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+) -> Response {
+    if !matches!(session.get_pending_two_factor_user_id().await, Ok(Some(_))) {
+        return Redirect::to("/login").into_response();
+    }
+    Html(
+        TwoFactorTemplate {
+            errors: messages.into_iter().map(|m| m.message).collect(),
+            csrf_token,
+        }
+        .render()
+        .unwrap(),
+    )
+    .into_response()
+}
+
+/// Wrong codes count as failed logins, so the rate limit of `POST /login`
+/// covers guessing them too.
+#[tracing::instrument(
+    name = "Two-factor login",
+    skip(app_state, hmac_secret, session, messages, form),
+    fields(user_id=tracing::field::Empty)
+)]
+pub async fn login_two_factor(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    session: TypedSession,
+    messages: Messages,
+    ClientIp(client_ip): ClientIp,
+    Form(form): Form<FormData>,
+) -> Result<Response, Response> {
+    if let Some(Err(retry_after)) = client_ip.map(|ip| app_state.login_rate_limiter.check(ip)) {
+        tracing::warn!(client_ip = ?client_ip, "Refused a two-factor code after too many failed attempts");
+        return Err(too_many_requests(retry_after));
+    }
+    let Ok(Some(user_id)) = session.get_pending_two_factor_user_id().await else {
+        return Ok(Redirect::to("/login").into_response());
+    };
+    tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+
+    let verified = match get_username(user_id, app_state.pool()).await {
+        Ok(username) => {
+            verify_totp_code(
+                app_state.pool(),
+                &hmac_secret,
+                user_id,
+                &username,
+                &form.code,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    match verified {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!("Rejected an invalid two-factor code");
+            if let Some(ip) = client_ip {
+                app_state.login_rate_limiter.record_failure(ip);
+            }
+            messages.error("The code is invalid or has expired.");
+            return Ok(Redirect::to("/login/2fa").into_response());
+        }
+        Err(e) => {
+            let err = LoginError::UnexpectedError(e);
+            tracing::error!(cause_chain = ?err);
+            messages.error(err.to_string());
+            return Ok(Redirect::to("/login/2fa").into_response());
+        }
+    }
+
+    if let Some(ip) = client_ip {
+        app_state.login_rate_limiter.reset(ip);
+    }
+    if let Err(e) = session.remove_pending_two_factor_user_id().await {
+        let err = LoginError::UnexpectedError(e.into());
+        tracing::error!(cause_chain = ?err);
+        messages.error("Could not finish the two-factor challenge");
+        return Err(Redirect::to("/login").into_response());
+    }
+    establish_session(&app_state, &session, messages, user_id).await?;
+    Ok(Redirect::to("/admin/dashboard").into_response())
+}
@@ -1,17 +1,27 @@
 mod admin;
+mod archive;
 mod blog;
+mod feed;
 mod health_check;
 mod home;
 mod login;
+mod prometheus_metrics;
+mod register;
+mod reset_password;
 mod subscriptions;
 mod subscriptions_confirm;
 mod xkcd_proxy;
 
 pub use admin::*;
+pub use archive::*;
 pub use blog::*;
+pub use feed::*;
 pub use health_check::*;
 pub use home::*;
 pub use login::*;
+pub use prometheus_metrics::*;
+pub use register::*;
+pub use reset_password::*;
 pub use subscriptions::*;
 pub use subscriptions_confirm::*;
 pub use xkcd_proxy::*;
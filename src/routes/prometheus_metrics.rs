@@ -0,0 +1,74 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+};
+
+use crate::db::SqliteInstrumentedPool;
+use crate::{startup::AppState, utils::e500};
+
+/// Expose the application metrics in the Prometheus text format.
+#[tracing::instrument(name = "Export metrics", skip(app_state))]
+pub async fn prometheus_metrics(
+    State(app_state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    if let Some(allowed_cidr) = &app_state.metrics_allowed_cidr {
+        if !allowed_cidr.contains(&addr.ip()) {
+            tracing::warn!("Rejected a metrics scrape from outside the allowed CIDR");
+            return Ok(StatusCode::FORBIDDEN.into_response());
+        }
+    }
+
+    record_database_gauges(app_state.pool())
+        .await
+        .map_err(e500)?;
+
+    Ok((
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        app_state.prometheus_handle.render(),
+    )
+        .into_response())
+}
+
+/// The subscriptions and the delivery queue live in SQLite, so we read them at
+/// scrape time instead of keeping counters in sync.
+async fn record_database_gauges(pool: &SqliteInstrumentedPool) -> Result<(), anyhow::Error> {
+    let subscriptions = sqlx::query!(
+        r#"
+        SELECT status, COUNT(*) as "count!: i64"
+        FROM subscriptions
+        GROUP BY status
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to count subscriptions by status.")?;
+    for status in ["pending", "confirmed", "unsubscribed"] {
+        let count = subscriptions
+            .iter()
+            .find(|r| metrics_status(&r.status) == status)
+            .map(|r| r.count)
+            .unwrap_or(0);
+        metrics::gauge!("newzletter_subscriptions_total", "status" => status).set(count as f64);
+    }
+
+    let queue_depth = sqlx::query!(r#"SELECT COUNT(*) as "count!: i64" FROM issue_delivery_queue"#)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count the queued deliveries.")?
+        .count;
+    metrics::gauge!("newzletter_delivery_queue_depth").set(queue_depth as f64);
+
+    Ok(())
+}
+
+fn metrics_status(status: &str) -> &str {
+    match status {
+        "pending_confirmation" => "pending",
+        other => other,
+    }
+}
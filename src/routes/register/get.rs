@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse},
+};
+use axum_messages::Messages;
+use rinja_axum::Template;
+
+use crate::middleware::CsrfToken;
+use crate::startup::AppState;
+
+use super::invite::{check_invite, InviteError};
+
+#[derive(serde::Deserialize)]
+pub struct RegisterParameters {
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "register/index.html")]
+struct RegisterTemplate {
+    token: String,
+    errors: Vec<String>,
+    csrf_token: String,
+}
+
+#[tracing::instrument(
+    name = "Register form",
+    skip(app_state, messages, csrf_token, parameters)
+)]
+pub async fn register_form(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+    Query(parameters): Query<RegisterParameters>,
+) -> Result<impl IntoResponse, InviteError> {
+    check_invite(app_state.pool(), &parameters.token).await?;
+
+    let html = RegisterTemplate {
+        token: parameters.token,
+        errors: messages.into_iter().map(|m| m.message).collect(),
+        csrf_token,
+    }
+    .render()
+    .context("Failed to render the registration page.")?;
+    Ok(Html(html))
+}
@@ -0,0 +1,94 @@
+use anyhow::Context;
+use axum::response::IntoResponse;
+use chrono::Utc;
+use reqwest::StatusCode;
+use sqlx::SqliteExecutor;
+
+use crate::db::SqliteInstrumentedTransaction;
+use crate::routes::error_chain_fmt;
+
+#[derive(thiserror::Error)]
+pub enum InviteError {
+    #[error("There is no invite associated with the provided token.")]
+    UnknownToken,
+    #[error("The invite has expired.")]
+    ExpiredToken,
+    #[error("The invite has already been used.")]
+    AlreadyUsed,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for InviteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl IntoResponse for InviteError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::UnknownToken => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::NOT_FOUND
+            }
+            Self::ExpiredToken | Self::AlreadyUsed => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::GONE
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+        .into_response()
+    }
+}
+
+/// Make sure the invite exists, hasn't expired and hasn't been used yet.
+#[tracing::instrument(name = "Check invite", skip(executor, token))]
+pub async fn check_invite<'e>(
+    executor: impl SqliteExecutor<'e>,
+    token: &str,
+) -> Result<(), InviteError> {
+    let invite = sqlx::query!(
+        r#"
+        SELECT expires_at, used as "used: bool"
+        FROM user_invites
+        WHERE token = $1
+        "#,
+        token,
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to retrieve the invite.")?
+    .ok_or(InviteError::UnknownToken)?;
+
+    if invite.used {
+        return Err(InviteError::AlreadyUsed);
+    }
+    // timestamps are stored as `Utc::now().to_string()`, so they compare as strings
+    if invite.expires_at <= Utc::now().to_string() {
+        return Err(InviteError::ExpiredToken);
+    }
+    Ok(())
+}
+
+#[tracing::instrument(name = "Mark invite as used", skip(transaction, token))]
+pub async fn mark_invite_as_used(
+    transaction: &mut SqliteInstrumentedTransaction,
+    token: &str,
+) -> Result<(), InviteError> {
+    let updated = sqlx::query!(
+        r#"UPDATE user_invites SET used = TRUE WHERE token = $1 AND used = FALSE"#,
+        token,
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to mark the invite as used.")?;
+    // somebody else registered with the same invite in the meantime
+    if updated.rows_affected() == 0 {
+        return Err(InviteError::AlreadyUsed);
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+mod get;
+mod invite;
+mod post;
+
+pub use get::register_form;
+pub use invite::InviteError;
+pub use post::register;
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_messages::Messages;
+use chrono::Utc;
+use secrecy::{ExposeSecret, SecretString};
+
+use crate::{
+    authentication::{create_user, UserRole},
+    db::SqliteInstrumentedTransaction,
+    domain::SubscriberEmail,
+    session_state::TypedSession,
+    startup::AppState,
+};
+
+use super::invite::{check_invite, mark_invite_as_used, InviteError};
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    token: String,
+    username: String,
+    /// Used to recover the account, so it can be left blank.
+    email: Option<String>,
+    password: SecretString,
+    password_check: SecretString,
+}
+
+/// Create the invited user and log them in right away.
+#[tracing::instrument(
+    skip(form, app_state, session, messages),
+    fields(username=%form.username, user_id=tracing::field::Empty)
+)]
+pub async fn register(
+    State(app_state): State<Arc<AppState>>,
+    session: TypedSession,
+    messages: Messages,
+    Form(form): Form<FormData>,
+) -> Result<Response, InviteError> {
+    let register_page = format!("/register?token={}", form.token);
+    let username = form.username.trim();
+    if username.is_empty() {
+        messages.error("The username can't be empty.");
+        return Ok(Redirect::to(&register_page).into_response());
+    }
+    let email = match form
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        Some(email) => match SubscriberEmail::parse(email.to_string()) {
+            Ok(email) => Some(email),
+            Err(e) => {
+                messages.error(e);
+                return Ok(Redirect::to(&register_page).into_response());
+            }
+        },
+        None => None,
+    };
+    if form.password.expose_secret() != form.password_check.expose_secret() {
+        messages.error("You entered two different passwords - the field values must match.");
+        return Ok(Redirect::to(&register_page).into_response());
+    }
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a SQLite connection from the pool")?;
+    check_invite(&mut transaction, &form.token).await?;
+    if username_is_taken(&mut transaction, username).await? {
+        messages.error("This username is already taken.");
+        return Ok(Redirect::to(&register_page).into_response());
+    }
+    if let Some(email) = &email {
+        if email_is_taken(&mut transaction, email.as_ref()).await? {
+            messages.error("This email address is already in use.");
+            return Ok(Redirect::to(&register_page).into_response());
+        }
+    }
+    let role = UserRole::Admin;
+    let user_id = create_user(
+        &mut transaction,
+        username,
+        email.as_ref().map(AsRef::as_ref),
+        form.password,
+        role,
+    )
+    .await?;
+    mark_invite_as_used(&mut transaction, &form.token).await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to register a new user.")?;
+    tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+
+    session
+        .rotate_id()
+        .await
+        .context("Failed to rotate the session id.")?;
+    session
+        .insert_user_id(user_id)
+        .await
+        .context("Failed to insert the user id in the session.")?;
+    session
+        .insert_logged_in_at(Utc::now().to_string())
+        .await
+        .context("Failed to insert the login time in the session.")?;
+    session
+        .insert_user_role(role)
+        .await
+        .context("Failed to insert the user role in the session.")?;
+
+    Ok(Redirect::to("/admin/dashboard").into_response())
+}
+
+#[tracing::instrument(skip(transaction))]
+async fn username_is_taken(
+    transaction: &mut SqliteInstrumentedTransaction,
+    username: &str,
+) -> Result<bool, anyhow::Error> {
+    let user = sqlx::query!(r#"SELECT uuid FROM users WHERE username = $1"#, username)
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to look up the username.")?;
+    Ok(user.is_some())
+}
+
+#[tracing::instrument(skip(transaction))]
+async fn email_is_taken(
+    transaction: &mut SqliteInstrumentedTransaction,
+    email: &str,
+) -> Result<bool, anyhow::Error> {
+    let user = sqlx::query!(r#"SELECT uuid FROM users WHERE email = $1"#, email)
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to look up the email address.")?;
+    Ok(user.is_some())
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_messages::Messages;
+use rinja_axum::Template;
+use secrecy::{ExposeSecret, SecretString};
+
+use crate::{
+    authentication::{change_password, purge_user_sessions},
+    middleware::CsrfToken,
+    startup::AppState,
+};
+
+use super::token::{check_reset_token, mark_reset_token_as_used, ResetTokenError};
+
+#[derive(serde::Deserialize)]
+pub struct ConfirmParameters {
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "reset-password-confirm/index.html")]
+struct ConfirmPasswordResetTemplate {
+    token: String,
+    errors: Vec<String>,
+    csrf_token: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    token: String,
+    new_password: SecretString,
+    new_password_check: SecretString,
+}
+
+#[tracing::instrument(
+    name = "Confirm password reset form",
+    skip(app_state, messages, csrf_token, parameters)
+)]
+pub async fn confirm_password_reset_form(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+    Query(parameters): Query<ConfirmParameters>,
+) -> Result<impl IntoResponse, ResetTokenError> {
+    check_reset_token(app_state.pool(), &parameters.token).await?;
+
+    let html = ConfirmPasswordResetTemplate {
+        token: parameters.token,
+        errors: messages.into_iter().map(|m| m.message).collect(),
+        csrf_token,
+    }
+    .render()
+    .context("Failed to render the password reset page.")?;
+    Ok(Html(html))
+}
+
+/// Set the new password and log the user out everywhere, since whoever knew
+/// the old password may still be logged in.
+#[tracing::instrument(
+    name = "Confirm password reset",
+    skip(form, app_state, messages),
+    fields(user_id = tracing::field::Empty)
+)]
+pub async fn confirm_password_reset(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    Form(form): Form<FormData>,
+) -> Result<Response, ResetTokenError> {
+    if form.new_password.expose_secret() != form.new_password_check.expose_secret() {
+        messages.error("You entered two different new passwords - the field values must match.");
+        return Ok(
+            Redirect::to(&format!("/reset-password/confirm?token={}", form.token)).into_response(),
+        );
+    }
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a SQLite connection from the pool")?;
+    let user_id = check_reset_token(&mut transaction, &form.token).await?;
+    tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+    mark_reset_token_as_used(&mut transaction, &form.token).await?;
+    change_password(user_id, form.new_password, &mut transaction).await?;
+    purge_user_sessions(&mut transaction, user_id).await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to reset a password.")?;
+
+    messages.info("Your password has been reset, you can now log in.");
+    Ok(Redirect::to("/login").into_response())
+}
@@ -0,0 +1,7 @@
+mod confirm;
+mod request;
+mod token;
+
+pub use confirm::{confirm_password_reset, confirm_password_reset_form};
+pub use request::{request_password_reset, reset_password_form};
+pub use token::ResetTokenError;
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Redirect},
+    Extension, Form,
+};
+use axum_messages::Messages;
+use rinja_axum::Template;
+use uuid::Uuid;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::{
+    domain::SubscriberEmail,
+    email_client::EmailClient,
+    middleware::{CsrfToken, RequestBaseUrl},
+    startup::AppState,
+    utils::{e400, e500},
+};
+
+use super::token::store_reset_token;
+
+#[derive(Template)]
+#[template(path = "reset-password/index.html")]
+struct ResetPasswordTemplate {
+    messages: Vec<String>,
+    csrf_token: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FormData {
+    email: String,
+}
+
+#[tracing::instrument(name = "Reset password form", skip(messages, csrf_token))]
+pub async fn reset_password_form(
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+) -> impl IntoResponse {
+    Html(
+        ResetPasswordTemplate {
+            messages: messages.into_iter().map(|m| m.message).collect(),
+            csrf_token,
+        }
+        .render()
+        .unwrap(),
+    )
+}
+
+#[tracing::instrument(
+    name = "Request a password reset",
+    skip(form, app_state, base_url, messages),
+    fields(email = %form.email)
+)]
+pub async fn request_password_reset(
+    State(app_state): State<Arc<AppState>>,
+    Extension(base_url): Extension<RequestBaseUrl>,
+    messages: Messages,
+    Form(form): Form<FormData>,
+) -> Result<axum::response::Response, axum::response::Response> {
+    let email = SubscriberEmail::parse(form.email).map_err(e400)?;
+
+    // we don't tell whether the address belongs to an account or not
+    if let Some(user_id) = get_user_id_by_email(app_state.pool(), email.as_ref())
+        .await
+        .map_err(e500)?
+    {
+        let token = store_reset_token(app_state.pool(), user_id)
+            .await
+            .map_err(e500)?;
+        send_reset_email(app_state.email_client(), &email, &base_url.0, &token)
+            .await
+            .context("Failed to send a password reset email.")
+            .map_err(e500)?;
+    }
+
+    messages.info("If an account uses this email address, a reset link is on its way.");
+    Ok(Redirect::to("/reset-password").into_response())
+}
+
+#[tracing::instrument(name = "Get user id by email", skip(pool))]
+async fn get_user_id_by_email(
+    pool: &SqliteInstrumentedPool,
+    email: &str,
+) -> Result<Option<Uuid>, anyhow::Error> {
+    let user = sqlx::query!(r#"SELECT uuid FROM users WHERE email = $1"#, email)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to look up the user by email.")?;
+    user.map(|u| Uuid::parse_str(&u.uuid).context("The stored user id is not a uuid."))
+        .transpose()
+}
+
+#[tracing::instrument(
+    name = "Send a password reset email",
+    skip(email_client, base_url, token)
+)]
+async fn send_reset_email(
+    email_client: &EmailClient,
+    recipient: &SubscriberEmail,
+    base_url: &str,
+    token: &str,
+) -> Result<(), reqwest::Error> {
+    let reset_link = format!("{}/reset-password/confirm?token={}", base_url, token);
+    let plain_body = format!(
+        "Somebody asked to reset the password of your Newzletter account.\n\
+Choose a new password by visiting the link below, it is valid for one hour:\n\
+{}\n\n\
+If you did not ask for this, you can safely ignore this email.",
+        reset_link
+    );
+    let html_body = format!(
+        "<p>Somebody asked to reset the password of your Newzletter account.</p>\
+<p>Choose a new password by clicking <a href=\"{}\">here</a>, the link is valid for one hour.</p>\
+<p>If you did not ask for this, you can safely ignore this email.</p>",
+        reset_link
+    );
+    email_client
+        .send_email(
+            recipient,
+            "Reset your Newzletter password",
+            &html_body,
+            &plain_body,
+            None,
+            None,
+        )
+        .await
+}
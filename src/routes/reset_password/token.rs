@@ -0,0 +1,121 @@
+use anyhow::Context;
+use axum::response::IntoResponse;
+use chrono::Utc;
+use reqwest::StatusCode;
+use sqlx::SqliteExecutor;
+use uuid::Uuid;
+
+use crate::db::{SqliteInstrumentedPool, SqliteInstrumentedTransaction};
+use crate::routes::error_chain_fmt;
+
+/// How long a password reset link stays valid after it has been sent out.
+const RESET_TOKEN_TTL_HOURS: i64 = 1;
+
+#[derive(thiserror::Error)]
+pub enum ResetTokenError {
+    #[error("There is no password reset associated with the provided token.")]
+    UnknownToken,
+    #[error("The password reset token has expired.")]
+    ExpiredToken,
+    #[error("The password reset token has already been used.")]
+    AlreadyUsed,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ResetTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl IntoResponse for ResetTokenError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::UnknownToken => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::NOT_FOUND
+            }
+            Self::ExpiredToken | Self::AlreadyUsed => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::GONE
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+        .into_response()
+    }
+}
+
+#[tracing::instrument(name = "Store password reset token", skip(pool))]
+pub async fn store_reset_token(
+    pool: &SqliteInstrumentedPool,
+    user_id: Uuid,
+) -> Result<String, anyhow::Error> {
+    let token = Uuid::new_v4().to_string();
+    let user_id = user_id.to_string();
+    let expires_at = (Utc::now() + chrono::Duration::hours(RESET_TOKEN_TTL_HOURS)).to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO password_reset_tokens (token, user_uuid, expires_at, used)
+        VALUES ($1, $2, $3, FALSE)
+        "#,
+        token,
+        user_id,
+        expires_at,
+    )
+    .execute(pool)
+    .await
+    .context("Failed to store the password reset token.")?;
+    Ok(token)
+}
+
+/// Return the user the token was issued for, as long as it is still usable.
+#[tracing::instrument(name = "Check password reset token", skip(executor, token))]
+pub async fn check_reset_token<'e>(
+    executor: impl SqliteExecutor<'e>,
+    token: &str,
+) -> Result<Uuid, ResetTokenError> {
+    let reset = sqlx::query!(
+        r#"
+        SELECT user_uuid, expires_at, used as "used: bool"
+        FROM password_reset_tokens
+        WHERE token = $1
+        "#,
+        token,
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to retrieve the password reset token.")?
+    .ok_or(ResetTokenError::UnknownToken)?;
+
+    if reset.used {
+        return Err(ResetTokenError::AlreadyUsed);
+    }
+    // timestamps are stored as `Utc::now().to_string()`, so they compare as strings
+    if reset.expires_at <= Utc::now().to_string() {
+        return Err(ResetTokenError::ExpiredToken);
+    }
+    let user_id = Uuid::parse_str(&reset.user_uuid).context("The stored user id is not a uuid.")?;
+    Ok(user_id)
+}
+
+#[tracing::instrument(name = "Mark password reset token as used", skip(transaction, token))]
+pub async fn mark_reset_token_as_used(
+    transaction: &mut SqliteInstrumentedTransaction,
+    token: &str,
+) -> Result<(), ResetTokenError> {
+    let updated = sqlx::query!(
+        r#"UPDATE password_reset_tokens SET used = TRUE WHERE token = $1 AND used = FALSE"#,
+        token,
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to mark the password reset token as used.")?;
+    if updated.rows_affected() == 0 {
+        return Err(ResetTokenError::AlreadyUsed);
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::domain::SubscriberEmail;
+use crate::error::AppError;
+use crate::startup::AppState;
+
+/// Every answer takes at least this long, so walking a list of addresses
+/// through the endpoint is slow on top of the per-IP rate limit.
+const RESPONSE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(serde::Deserialize)]
+pub struct CheckEmailParameters {
+    email: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct EmailAvailability {
+    available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+}
+
+/// Lets the signup form warn about an address that is already on the list
+/// before it is submitted.
+///
+/// Malformed addresses are reported as available, so the answer says nothing
+/// about how the address was validated.
+#[tracing::instrument(
+    name = "Check whether an email is subscribed",
+    skip(app_state, parameters)
+)]
+pub async fn check_email(
+    State(app_state): State<Arc<AppState>>,
+    Query(parameters): Query<CheckEmailParameters>,
+) -> Result<impl IntoResponse, AppError> {
+    tokio::time::sleep(RESPONSE_DELAY).await;
+
+    let Ok(email) = SubscriberEmail::parse(parameters.email) else {
+        return Ok(Json(EmailAvailability {
+            available: true,
+            status: None,
+        }));
+    };
+    let status = get_subscription_status(app_state.pool(), email.as_ref())
+        .await
+        .context("Failed to look up the subscription status.")?;
+    Ok(Json(EmailAvailability {
+        available: status.is_none(),
+        status,
+    }))
+}
+
+async fn get_subscription_status(
+    pool: &SqliteInstrumentedPool,
+    email: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT status FROM subscriptions WHERE email = $1", email)
+        .fetch_optional(pool)
+        .await
+}
@@ -0,0 +1,53 @@
+use crate::db::SqliteInstrumentedPool;
+use anyhow::Context;
+use chrono::Utc;
+
+/// Delete the subscribers who never confirmed within `ttl_days` of signing
+/// up, along with their tokens, returning how many of them were removed.
+/// They can simply sign up again.
+#[tracing::instrument(skip(pool))]
+pub async fn cleanup_stale_pending_subscriptions(
+    pool: &SqliteInstrumentedPool,
+    ttl_days: u64,
+) -> Result<u64, anyhow::Error> {
+    let ttl = chrono::Duration::try_days(ttl_days.try_into()?)
+        .ok_or_else(|| anyhow::anyhow!("The pending subscription TTL is too large"))?;
+    let subscribed_before = (Utc::now() - ttl).to_string();
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    // the tokens reference the subscription, they have to go first
+    sqlx::query!(
+        r#"
+        DELETE FROM subscription_tokens
+        WHERE subscriber_id IN (
+            SELECT uuid FROM subscriptions
+            WHERE status = 'pending_confirmation' AND subscribed_at < $1
+        )
+        "#,
+        subscribed_before
+    )
+    .execute(&mut transaction)
+    .await
+    .context("Failed to delete the tokens of the stale pending subscriptions.")?;
+    let n_deleted_rows = sqlx::query!(
+        r#"
+        DELETE FROM subscriptions
+        WHERE status = 'pending_confirmation' AND subscribed_at < $1
+        "#,
+        subscribed_before
+    )
+    .execute(&mut transaction)
+    .await
+    .context("Failed to delete the stale pending subscriptions.")?
+    .rows_affected();
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the pending subscriptions cleanup.")?;
+
+    tracing::info!(n_deleted_rows, "Deleted the stale pending subscriptions");
+    Ok(n_deleted_rows)
+}
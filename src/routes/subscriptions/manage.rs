@@ -0,0 +1,305 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse, Redirect},
+    Form,
+};
+use axum_extra::extract::Form as MultiValueForm;
+use axum_messages::Messages;
+use reqwest::StatusCode;
+use rinja_axum::Template;
+use uuid::Uuid;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::domain::SubscriberName;
+use crate::middleware::CsrfToken;
+use crate::startup::{AppState, HmacSecret};
+use crate::tags::{all_tags, set_subscriber_tags, subscriber_tags};
+
+use super::{error_chain_fmt, status_label};
+use crate::domain::generate_unsubscribe_token;
+
+#[derive(serde::Deserialize)]
+pub struct ManageParameters {
+    token: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct NameChange {
+    token: String,
+    name: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct TagsChange {
+    token: String,
+    /// The checked tags, unchecked boxes aren't submitted at all.
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+struct TagChoice {
+    name: String,
+    checked: bool,
+}
+
+#[derive(Template)]
+#[template(path = "subscription-manage/index.html")]
+struct ManageSubscriptionTemplate<'a> {
+    name: &'a str,
+    email: &'a str,
+    status: &'a str,
+    token: &'a str,
+    unsubscribe_token: &'a str,
+    /// What the name field is filled with, the rejected name after an error.
+    name_input: &'a str,
+    error: Option<&'a str>,
+    messages: Vec<String>,
+    csrf_token: &'a str,
+    tags: &'a [TagChoice],
+}
+
+#[derive(thiserror::Error)]
+pub enum ManageSubscriptionError {
+    #[error("There is no active subscriber associated with the provided token.")]
+    UnknownToken,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ManageSubscriptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl IntoResponse for ManageSubscriptionError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::UnknownToken => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::NOT_FOUND
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+        .into_response()
+    }
+}
+
+struct ActiveSubscriber {
+    uuid: String,
+    name: String,
+    email: String,
+    status: String,
+}
+
+/// Lets subscribers see their subscription and change the name we greet
+/// them with, the subscription token they got by email is their credential.
+#[tracing::instrument(
+    name = "Show the subscription management page",
+    skip(parameters, app_state, hmac_secret, messages, csrf_token)
+)]
+pub async fn manage_subscription_form(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+    Query(parameters): Query<ManageParameters>,
+) -> Result<impl IntoResponse, ManageSubscriptionError> {
+    let subscriber = get_active_subscriber(app_state.pool(), &parameters.token)
+        .await
+        .context("Failed to retrieve the subscriber associated with the provided token.")?
+        .ok_or(ManageSubscriptionError::UnknownToken)?;
+    let messages = messages.into_iter().map(|m| m.message).collect();
+    let tags = tag_choices(app_state.pool(), &subscriber.uuid)
+        .await
+        .context("Failed to retrieve the subscriber tags.")?;
+    let html = render_page(
+        &subscriber,
+        &parameters.token,
+        &hmac_secret,
+        &subscriber.name,
+        None,
+        messages,
+        &csrf_token,
+        &tags,
+    )?;
+    Ok(Html(html))
+}
+
+/// Invalid names are shown next to the field rather than redirected away
+/// from, so the subscriber can fix them in place.
+#[tracing::instrument(
+    name = "Change a subscriber name",
+    skip(form, app_state, hmac_secret, messages, csrf_token)
+)]
+pub async fn change_subscriber_name(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    messages: Messages,
+    CsrfToken(csrf_token): CsrfToken,
+    Form(form): Form<NameChange>,
+) -> Result<axum::response::Response, ManageSubscriptionError> {
+    let subscriber = get_active_subscriber(app_state.pool(), &form.token)
+        .await
+        .context("Failed to retrieve the subscriber associated with the provided token.")?
+        .ok_or(ManageSubscriptionError::UnknownToken)?;
+
+    let name = match SubscriberName::parse(form.name.clone()) {
+        Ok(name) => name,
+        Err(e) => {
+            let tags = tag_choices(app_state.pool(), &subscriber.uuid)
+                .await
+                .context("Failed to retrieve the subscriber tags.")?;
+            let html = render_page(
+                &subscriber,
+                &form.token,
+                &hmac_secret,
+                &form.name,
+                Some(&e.reason),
+                Vec::new(),
+                &csrf_token,
+                &tags,
+            )?;
+            return Ok((StatusCode::BAD_REQUEST, Html(html)).into_response());
+        }
+    };
+    update_name(app_state.pool(), &subscriber.uuid, name.as_ref())
+        .await
+        .context("Failed to update the subscriber name.")?;
+
+    messages.info("Your name has been updated.");
+    Ok(Redirect::to(&format!(
+        "/subscriptions/manage?token={}",
+        urlencoding::encode(&form.token)
+    ))
+    .into_response())
+}
+
+/// Pick the topics to get newsletter issues about, issues without tags go
+/// to every subscriber regardless.
+#[tracing::instrument(
+    name = "Change the tags of a subscriber",
+    skip(form, app_state, messages)
+)]
+pub async fn change_subscriber_tags(
+    State(app_state): State<Arc<AppState>>,
+    messages: Messages,
+    MultiValueForm(form): MultiValueForm<TagsChange>,
+) -> Result<axum::response::Response, ManageSubscriptionError> {
+    let subscriber = get_active_subscriber(app_state.pool(), &form.token)
+        .await
+        .context("Failed to retrieve the subscriber associated with the provided token.")?
+        .ok_or(ManageSubscriptionError::UnknownToken)?;
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    set_subscriber_tags(&mut transaction, &subscriber.uuid, &form.tags)
+        .await
+        .context("Failed to update the subscriber tags.")?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the subscriber tags.")?;
+
+    messages.info("Your topics have been updated.");
+    Ok(Redirect::to(&format!(
+        "/subscriptions/manage?token={}",
+        urlencoding::encode(&form.token)
+    ))
+    .into_response())
+}
+
+async fn tag_choices(
+    pool: &SqliteInstrumentedPool,
+    subscriber_uuid: &str,
+) -> Result<Vec<TagChoice>, sqlx::Error> {
+    let checked = subscriber_tags(pool, subscriber_uuid).await?;
+    Ok(all_tags(pool)
+        .await?
+        .into_iter()
+        .map(|name| TagChoice {
+            checked: checked.contains(&name),
+            name,
+        })
+        .collect())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_page(
+    subscriber: &ActiveSubscriber,
+    token: &str,
+    hmac_secret: &HmacSecret,
+    name_input: &str,
+    error: Option<&str>,
+    messages: Vec<String>,
+    csrf_token: &str,
+    tags: &[TagChoice],
+) -> Result<String, ManageSubscriptionError> {
+    let subscriber_id =
+        Uuid::parse_str(&subscriber.uuid).context("The stored subscriber id is not a uuid.")?;
+    let unsubscribe_token = generate_unsubscribe_token(subscriber_id, hmac_secret);
+    let html = ManageSubscriptionTemplate {
+        name: &subscriber.name,
+        email: &subscriber.email,
+        status: status_label(&subscriber.status),
+        token,
+        unsubscribe_token: &unsubscribe_token,
+        name_input,
+        error,
+        messages,
+        csrf_token,
+        tags,
+    }
+    .render()
+    .context("Failed to render the subscription management page.")?;
+    Ok(html)
+}
+
+/// Unsubscribed subscribers have nothing left to manage.
+#[tracing::instrument(
+    name = "Get active subscriber by token",
+    skip(subscription_token, pool)
+)]
+async fn get_active_subscriber(
+    pool: &SqliteInstrumentedPool,
+    subscription_token: &str,
+) -> Result<Option<ActiveSubscriber>, sqlx::Error> {
+    sqlx::query_as!(
+        ActiveSubscriber,
+        r#"
+        SELECT s.uuid, s.name, s.email, s.status
+        FROM subscription_tokens t
+        JOIN subscriptions s ON s.uuid = t.subscriber_id
+        WHERE t.subscription_token = $1
+            AND s.status IN ('confirmed', 'pending_confirmation')
+        "#,
+        subscription_token,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(name = "Update subscriber name", skip(pool, name))]
+async fn update_name(
+    pool: &SqliteInstrumentedPool,
+    subscriber_id: &str,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE subscriptions SET name = $2 WHERE uuid = $1",
+        subscriber_id,
+        name
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
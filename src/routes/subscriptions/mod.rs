@@ -1,3 +1,17 @@
+pub mod check_email;
+pub mod cleanup;
+pub mod manage;
 pub mod post;
+pub mod resend_confirmation;
+pub mod status;
+pub mod unsubscribe;
+pub mod widget;
 
+pub use check_email::*;
+pub use cleanup::*;
+pub use manage::*;
 pub use post::*;
+pub use resend_confirmation::*;
+pub use status::*;
+pub use unsubscribe::*;
+pub use widget::*;
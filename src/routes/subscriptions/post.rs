@@ -2,21 +2,27 @@ use std::sync::Arc;
 
 use anyhow::Context;
 use axum::{
-    extract::State,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Redirect},
-    Form,
+    Extension, Json,
 };
+use axum_extra::extract::{Form, FormRejection};
 use chrono::Utc;
 use rand::{distr::Alphanumeric, rng, Rng};
-use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
-use sqlx::{Sqlite, Transaction};
 use uuid::Uuid;
 
 use crate::{
-    domain::{NewSubscriber, SubscriberEmail, SubscriberName},
+    audit::ClientIp,
+    db::SqliteInstrumentedTransaction,
+    domain::{NewSubscriber, SubscriberEmail, SubscriberName, ValidationError},
     email_client::EmailClient,
+    middleware::RequestBaseUrl,
     startup::AppState,
+    tags::set_subscriber_tags,
+    turnstile::TurnstileError,
+    utils::ResponseFormat,
 };
 
 #[derive(Deserialize)]
@@ -25,14 +31,60 @@ pub struct FormData {
     email: String,
     #[serde(rename = "cf-turnstile-response")]
     cf_turnstile_response: String,
+    source_url: Option<String>,
+    utm_source: Option<String>,
+    utm_medium: Option<String>,
+    utm_campaign: Option<String>,
+    /// The topics checked on the signup form, when the deployment has any.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Hidden from people, so only bots fill it in.
+    website: Option<String>,
+}
+
+/// The campaign can also be passed on the form action url, e.g.
+/// `/subscriptions?utm_source=newsletter`.
+#[derive(Deserialize)]
+pub struct CampaignParameters {
+    utm_source: Option<String>,
+    utm_medium: Option<String>,
+    utm_campaign: Option<String>,
+}
+
+/// Where a subscriber came from, every part of it is optional.
+#[derive(Debug, Default)]
+pub struct SubscriptionSource {
+    pub source_url: Option<String>,
+    pub utm_source: Option<String>,
+    pub utm_medium: Option<String>,
+    pub utm_campaign: Option<String>,
+}
+
+impl SubscriptionSource {
+    /// The form fields win over the query parameters, blank values are dropped
+    /// and overly long ones cut short rather than failing the subscription.
+    fn new(form: &FormData, query: CampaignParameters) -> Self {
+        let clean = |value: Option<String>, max_chars: usize| {
+            value
+                .map(|v| v.trim().chars().take(max_chars).collect::<String>())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            source_url: clean(form.source_url.clone(), 2048),
+            utm_source: clean(form.utm_source.clone().or(query.utm_source), 256),
+            utm_medium: clean(form.utm_medium.clone().or(query.utm_medium), 256),
+            utm_campaign: clean(form.utm_campaign.clone().or(query.utm_campaign), 256),
+        }
+    }
 }
 
 impl TryFrom<FormData> for NewSubscriber {
-    type Error = String;
+    type Error = ValidationError;
 
     fn try_from(value: FormData) -> Result<Self, Self::Error> {
         let name = SubscriberName::parse(value.name)?;
-        let email = SubscriberEmail::parse(value.email)?;
+        let email =
+            SubscriberEmail::parse(value.email).map_err(|e| ValidationError::new("email", e))?;
         Ok(Self { name, email })
     }
 }
@@ -40,9 +92,9 @@ impl TryFrom<FormData> for NewSubscriber {
 #[derive(thiserror::Error)]
 pub enum SubscribeError {
     #[error("{0}")]
-    ValidationError(String),
+    ValidationError(ValidationError),
     #[error("Turnstile verification failed")]
-    TurnstileError,
+    TurnstileError(#[source] TurnstileError),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -55,51 +107,127 @@ impl std::fmt::Debug for SubscribeError {
 
 impl IntoResponse for SubscribeError {
     fn into_response(self) -> axum::response::Response {
+        self.respond(ResponseFormat::Html)
+    }
+}
+
+impl SubscribeError {
+    fn respond(self, format: ResponseFormat) -> axum::response::Response {
         match self {
             SubscribeError::ValidationError(e) => {
                 tracing::error!(cause_chain = ?e);
-                Redirect::to("/?error=validation").into_response()
+                match format {
+                    ResponseFormat::Json => (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({
+                            "error": "validation",
+                            "field": e.field,
+                            "message": e.reason,
+                        })),
+                    )
+                        .into_response(),
+                    // the home page shows the reason next to the subscription form
+                    ResponseFormat::Html => Redirect::to(&format!(
+                        "/?error=validation&field={}&reason={}",
+                        e.field,
+                        urlencoding::encode(&e.reason)
+                    ))
+                    .into_response(),
+                }
             }
-            SubscribeError::TurnstileError => {
-                tracing::error!("Turnstile verification failed");
-                Redirect::to("/?error=captcha").into_response()
+            SubscribeError::TurnstileError(e) => {
+                tracing::warn!(cause_chain = ?e);
+                match format {
+                    ResponseFormat::Json => (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({ "error": "captcha" })),
+                    )
+                        .into_response(),
+                    ResponseFormat::Html => Redirect::to("/?error=captcha").into_response(),
+                }
             }
             SubscribeError::UnexpectedError(e) => {
                 tracing::error!(cause_chain = ?e);
-                Redirect::to("/?error=server").into_response()
+                match format {
+                    ResponseFormat::Json => (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({ "error": "server" })),
+                    )
+                        .into_response(),
+                    ResponseFormat::Html => Redirect::to("/?error=server").into_response(),
+                }
             }
         }
     }
 }
 
+pub async fn subscribe(
+    State(app_state): State<Arc<AppState>>,
+    Extension(base_url): Extension<RequestBaseUrl>,
+    headers: HeaderMap,
+    ClientIp(client_ip): ClientIp,
+    Query(campaign): Query<CampaignParameters>,
+    form: Result<Form<FormData>, FormRejection>,
+) -> axum::response::Response {
+    // `axum_extra`'s `Form` takes the repeated `tags` fields, but answers an
+    // invalid form with a `400` where `axum::Form` answered with a `422`
+    let form = match form {
+        Ok(Form(form)) => form,
+        Err(FormRejection::FailedToDeserializeForm(e)) => {
+            return (StatusCode::UNPROCESSABLE_ENTITY, e.body_text()).into_response()
+        }
+        Err(e) => return e.into_response(),
+    };
+    // bots are told they made it, so they don't try again another way;
+    // checked before Turnstile to spare a call to Cloudflare
+    if form
+        .website
+        .as_deref()
+        .is_some_and(|w| !w.trim().is_empty())
+    {
+        tracing::warn!(client_ip = ?client_ip, "Ignored a subscription with the honeypot filled in");
+        return Redirect::to("/?subscribed=true").into_response();
+    }
+    match add_subscriber(&app_state, &base_url, campaign, form).await {
+        Ok(redirect) => redirect.into_response(),
+        Err(e) => e.respond(ResponseFormat::negotiate(&headers)),
+    }
+}
+
 #[tracing::instrument(
     name = "Adding a new subscriber",
-    skip(form, app_state),
+    skip(form, campaign, app_state, base_url),
     fields(
         subscriber_name = %form.name,
         subscriber_email = %form.email
     )
 )]
-pub async fn subscribe(
-    State(app_state): State<Arc<AppState>>,
-    Form(form): Form<FormData>,
-) -> Result<impl IntoResponse, SubscribeError> {
+async fn add_subscriber(
+    app_state: &AppState,
+    base_url: &RequestBaseUrl,
+    campaign: CampaignParameters,
+    mut form: FormData,
+) -> Result<Redirect, SubscribeError> {
     // Verify Turnstile token first
-    verify_turnstile(&app_state.turnstile_secret, &form.cf_turnstile_response)
+    app_state
+        .turnstile_client()
+        .verify(&form.cf_turnstile_response)
         .await
-        .map_err(|_| SubscribeError::TurnstileError)?;
+        .map_err(SubscribeError::TurnstileError)?;
 
+    let source = SubscriptionSource::new(&form, campaign);
+    let tags = std::mem::take(&mut form.tags);
     let new_subscriber = form.try_into().map_err(SubscribeError::ValidationError)?;
     let mut transaction = app_state
-        .pool
+        .pool()
         .begin()
         .await
         .context("Failed to acquire a Postgres connection from the pool")?;
 
     // Try to insert subscriber - if email already exists, just redirect to success
     // (don't leak information about who's subscribed)
-    let subscriber_id = match insert_subscriber(&mut transaction, &new_subscriber).await {
-        Ok(id) => id,
+    let subscriber_id = match insert_subscriber(&mut transaction, &new_subscriber, &source).await {
+        Ok(subscriber) => subscriber.uuid,
         Err(e) => {
             // Check if it's a UNIQUE constraint error (duplicate email)
             if e.to_string().contains("UNIQUE constraint failed") {
@@ -110,18 +238,24 @@ pub async fn subscribe(
         }
     };
 
-    let subscription_token = generate_subscription_token();
-    store_token(&mut transaction, subscriber_id, &subscription_token)
+    set_subscriber_tags(&mut transaction, &subscriber_id.to_string(), &tags)
         .await
-        .context("Failed to store the confirmation token for a new subscriber.")?;
+        .context("Failed to store the tags of a new subscriber.")?;
+    let subscription_token = store_token(
+        &mut transaction,
+        subscriber_id,
+        &generate_subscription_token(),
+    )
+    .await
+    .context("Failed to store the confirmation token for a new subscriber.")?;
     transaction
         .commit()
         .await
         .context("Failed to commit SQL transaction to store a new subscriber.")?;
     send_confirmation_email(
-        &app_state.email_client,
+        app_state.email_client(),
         new_subscriber,
-        &app_state.base_url.0,
+        &base_url.0,
         &subscription_token,
     )
     .await
@@ -130,7 +264,10 @@ pub async fn subscribe(
     Ok(Redirect::to("/?subscribed=true"))
 }
 
-fn generate_subscription_token() -> String {
+/// How long a confirmation link stays valid after it has been sent out.
+const SUBSCRIPTION_TOKEN_TTL_HOURS: i64 = 24;
+
+pub fn generate_subscription_token() -> String {
     let mut rng = rng();
     std::iter::repeat_with(|| rng.sample(Alphanumeric))
         .map(char::from)
@@ -138,64 +275,27 @@ fn generate_subscription_token() -> String {
         .collect()
 }
 
-#[derive(Deserialize)]
-struct TurnstileResponse {
-    success: bool,
-}
-
-#[tracing::instrument(name = "Verifying Turnstile token", skip(secret, response_token))]
-async fn verify_turnstile(
-    secret: &SecretString,
-    response_token: &str,
-) -> Result<(), anyhow::Error> {
-    let client = reqwest::Client::new();
-    let response = client
-        .post("https://challenges.cloudflare.com/turnstile/v0/siteverify")
-        .form(&[
-            ("secret", secret.expose_secret()),
-            ("response", response_token),
-        ])
-        .send()
-        .await
-        .context("Failed to send Turnstile verification request")?;
-
-    let turnstile_response: TurnstileResponse = response
-        .json()
-        .await
-        .context("Failed to parse Turnstile response")?;
-
-    if turnstile_response.success {
-        tracing::info!("Turnstile verification successful");
-        Ok(())
-    } else {
-        tracing::warn!("Turnstile verification failed");
-        anyhow::bail!("Turnstile verification failed")
-    }
+pub struct ConfirmationEmail {
+    pub html_body: String,
+    pub plain_body: String,
 }
 
-#[tracing::instrument(
-    name = "Send a confirmation email to a new subscriber",
-    skip(email_client, new_subscriber, base_url, subscription_token)
-)]
-pub async fn send_confirmation_email(
-    email_client: &EmailClient,
-    new_subscriber: NewSubscriber,
-    base_url: &str,
-    subscription_token: &str,
-) -> Result<(), reqwest::Error> {
-    let confirmation_link = format!(
-        "{}/subscriptions/confirm?subscription_token={}",
-        base_url, subscription_token
-    );
-    let plain_body = format!(
-        "Thanks for subscribing to Newzletter!\n\
+impl ConfirmationEmail {
+    /// The bodies of the email sent by [`send_confirmation_email`].
+    pub fn new(base_url: &str, subscription_token: &str) -> Self {
+        let confirmation_link = format!(
+            "{}/subscriptions/confirm?subscription_token={}",
+            base_url, subscription_token
+        );
+        let plain_body = format!(
+            "Thanks for subscribing to Newzletter!\n\
 Please confirm your email address by visiting the link below:\n\
 {}\n\n\
 If you did not subscribe, you can safely ignore this email.",
-        confirmation_link
-    );
-    let html_body = format!(
-        r#"<!doctype html>
+            confirmation_link
+        );
+        let html_body = format!(
+            r#"<!doctype html>
 <html lang="en">
   <body style="margin:0;padding:24px;background-color:#f3f4f6;font-family:Arial,sans-serif;color:#111827;">
     <table role="presentation" width="100%" cellpadding="0" cellspacing="0">
@@ -218,65 +318,120 @@ If you did not subscribe, you can safely ignore this email.",
     </table>
   </body>
 </html>"#,
-        confirmation_link
-    );
+            confirmation_link
+        );
+        Self {
+            html_body,
+            plain_body,
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Send a confirmation email to a new subscriber",
+    skip(email_client, new_subscriber, base_url, subscription_token)
+)]
+pub async fn send_confirmation_email(
+    email_client: &EmailClient,
+    new_subscriber: NewSubscriber,
+    base_url: &str,
+    subscription_token: &str,
+) -> Result<(), reqwest::Error> {
+    let ConfirmationEmail {
+        html_body,
+        plain_body,
+    } = ConfirmationEmail::new(base_url, subscription_token);
     email_client
         .send_email(
             &new_subscriber.email,
             "Please confirm your Newzletter subscription",
             &html_body,
             &plain_body,
+            None,
+            None,
         )
         .await
 }
 
+/// A subscriber as the database stored it.
+#[derive(Debug)]
+pub struct StoredSubscriber {
+    pub uuid: Uuid,
+    pub name: String,
+    pub email: String,
+    pub subscribed_at: String,
+}
+
 #[tracing::instrument(
     name = "Saving new subscriber details in the database",
-    skip(new_subscriber, transaction)
+    skip(new_subscriber, transaction),
+    fields(
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty
+    )
 )]
 pub async fn insert_subscriber(
-    transaction: &mut Transaction<'_, Sqlite>,
+    transaction: &mut SqliteInstrumentedTransaction,
     new_subscriber: &NewSubscriber,
-) -> Result<Uuid, sqlx::Error> {
-    let uuid = Uuid::new_v4();
-    let subscriber_id = uuid.to_string();
+    source: &SubscriptionSource,
+) -> Result<StoredSubscriber, sqlx::Error> {
+    let subscriber_id = Uuid::new_v4().to_string();
     let timestamptz = Utc::now().to_string();
     let name = new_subscriber.name.as_ref();
     let email = new_subscriber.email.as_ref();
-    sqlx::query!(
+    let stored = sqlx::query!(
         r#"
-            INSERT INTO subscriptions(uuid, name, email, subscribed_at, status) VALUES($1, $2, $3, $4, 'pending_confirmation')
+            INSERT INTO subscriptions(uuid, name, email, subscribed_at, status, source_url, utm_source, utm_medium, utm_campaign)
+            VALUES($1, $2, $3, $4, 'pending_confirmation', $5, $6, $7, $8)
+            RETURNING uuid, name, email, subscribed_at
             "#,
         subscriber_id,
         name,
         email,
         timestamptz,
-    ).execute(&mut **transaction).await?;
-    Ok(uuid)
+        source.source_url,
+        source.utm_source,
+        source.utm_medium,
+        source.utm_campaign,
+    ).fetch_one(&mut *transaction).await?;
+    Ok(StoredSubscriber {
+        uuid: Uuid::try_parse(&stored.uuid).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        name: stored.name,
+        email: stored.email,
+        subscribed_at: stored.subscribed_at,
+    })
 }
 
 #[tracing::instrument(
     name = "Store subscription token in the database",
-    skip(subscription_token, transaction)
+    skip(subscription_token, transaction),
+    fields(
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty
+    )
 )]
 pub async fn store_token(
-    transaction: &mut Transaction<'_, Sqlite>,
+    transaction: &mut SqliteInstrumentedTransaction,
     subscriber_id: Uuid,
     subscription_token: &str,
-) -> Result<(), StoreTokenError> {
+) -> Result<String, StoreTokenError> {
     let subscriber_id = subscriber_id.to_string();
-    sqlx::query!(
+    let token_expires_at =
+        (Utc::now() + chrono::Duration::hours(SUBSCRIPTION_TOKEN_TTL_HOURS)).to_string();
+    let stored_token = sqlx::query_scalar!(
         r#"
-    INSERT INTO subscription_tokens (subscription_token, subscriber_id)
-    VALUES ($1, $2)
+    INSERT INTO subscription_tokens (subscription_token, subscriber_id, token_expires_at)
+    VALUES ($1, $2, $3)
+    RETURNING subscription_token
         "#,
         subscription_token,
-        subscriber_id
+        subscriber_id,
+        token_expires_at
     )
-    .execute(&mut **transaction)
+    .fetch_one(&mut *transaction)
     .await
     .map_err(StoreTokenError)?;
-    Ok(())
+    Ok(stored_token)
 }
 
 pub struct StoreTokenError(sqlx::Error);
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::{IntoResponse, Redirect},
+    Extension, Form,
+};
+use dashmap::DashMap;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::{
+    domain::{NewSubscriber, SubscriberEmail, SubscriberName},
+    middleware::RequestBaseUrl,
+    startup::AppState,
+};
+
+use super::{error_chain_fmt, generate_subscription_token, send_confirmation_email, store_token};
+
+const MAX_RESENDS_PER_HOUR: usize = 3;
+const RESEND_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Deserialize)]
+pub struct ResendConfirmationFormData {
+    email: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum ResendConfirmationError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Too many confirmation emails have been requested for this address.")]
+    TooManyRequests,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for ResendConfirmationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl IntoResponse for ResendConfirmationError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::BAD_REQUEST
+            }
+            Self::TooManyRequests => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+        .into_response()
+    }
+}
+
+#[tracing::instrument(
+    name = "Resend a confirmation email",
+    skip(form, app_state, base_url),
+    fields(subscriber_email = %form.email)
+)]
+pub async fn resend_confirmation(
+    State(app_state): State<Arc<AppState>>,
+    Extension(base_url): Extension<RequestBaseUrl>,
+    Form(form): Form<ResendConfirmationFormData>,
+) -> Result<impl IntoResponse, ResendConfirmationError> {
+    let email =
+        SubscriberEmail::parse(form.email).map_err(ResendConfirmationError::ValidationError)?;
+    if !app_state
+        .resend_confirmation_limiter
+        .try_acquire(email.as_ref())
+    {
+        return Err(ResendConfirmationError::TooManyRequests);
+    }
+
+    // like `subscribe`, we don't tell whether the address is on the list or not
+    let Some((subscriber_id, name)) = get_pending_subscriber(app_state.pool(), email.as_ref())
+        .await
+        .context("Failed to look up the pending subscriber.")?
+    else {
+        return Ok(Redirect::to("/?subscribed=true"));
+    };
+    let name = SubscriberName::parse(name).map_err(|e| anyhow::anyhow!(e))?;
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a SQLite connection from the pool")?;
+    let subscriber_id_string = subscriber_id.to_string();
+    sqlx::query!(
+        r#"DELETE FROM subscription_tokens WHERE subscriber_id = $1"#,
+        subscriber_id_string
+    )
+    .execute(&mut transaction)
+    .await
+    .context("Failed to delete the previous confirmation tokens.")?;
+    let subscription_token = store_token(
+        &mut transaction,
+        subscriber_id,
+        &generate_subscription_token(),
+    )
+    .await
+    .context("Failed to store the new confirmation token.")?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new confirmation token.")?;
+
+    send_confirmation_email(
+        app_state.email_client(),
+        NewSubscriber { name, email },
+        &base_url.0,
+        &subscription_token,
+    )
+    .await
+    .context("Failed to send a confirmation email.")?;
+
+    Ok(Redirect::to("/?subscribed=true"))
+}
+
+/// Confirmation emails resent per address, so the endpoint can't be used to
+/// flood someone's inbox.
+#[derive(Default)]
+pub struct ResendConfirmationLimiter {
+    attempts: DashMap<String, Vec<Instant>>,
+}
+
+impl ResendConfirmationLimiter {
+    /// Allow up to `MAX_RESENDS_PER_HOUR` requests per address in a sliding window.
+    pub fn try_acquire(&self, email: &str) -> bool {
+        self.try_acquire_at(email, Instant::now())
+    }
+
+    /// Forgets the addresses without a resend in the last hour.
+    pub fn prune(&self) {
+        self.prune_at(Instant::now());
+    }
+
+    fn try_acquire_at(&self, email: &str, now: Instant) -> bool {
+        let mut attempts = self.attempts.entry(email.to_lowercase()).or_default();
+        attempts.retain(|attempt| now.saturating_duration_since(*attempt) < RESEND_WINDOW);
+        if attempts.len() >= MAX_RESENDS_PER_HOUR {
+            return false;
+        }
+        attempts.push(now);
+        true
+    }
+
+    fn prune_at(&self, now: Instant) {
+        self.attempts.retain(|_, attempts| {
+            attempts.retain(|attempt| now.saturating_duration_since(*attempt) < RESEND_WINDOW);
+            !attempts.is_empty()
+        });
+    }
+}
+
+#[tracing::instrument(name = "Get pending subscriber by email", skip(pool))]
+async fn get_pending_subscriber(
+    pool: &SqliteInstrumentedPool,
+    email: &str,
+) -> Result<Option<(Uuid, String)>, anyhow::Error> {
+    let subscriber = sqlx::query!(
+        r#"
+        SELECT uuid, name
+        FROM subscriptions
+        WHERE email = $1 AND status = 'pending_confirmation'
+        "#,
+        email
+    )
+    .fetch_optional(pool)
+    .await?;
+    subscriber
+        .map(|s| Ok((Uuid::parse_str(&s.uuid)?, s.name)))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::{ResendConfirmationLimiter, MAX_RESENDS_PER_HOUR, RESEND_WINDOW};
+
+    #[test]
+    fn resends_are_limited_per_email_address() {
+        let limiter = ResendConfirmationLimiter::default();
+        let now = Instant::now();
+        for _ in 0..MAX_RESENDS_PER_HOUR {
+            assert!(limiter.try_acquire_at("ursula@example.com", now));
+        }
+        assert!(!limiter.try_acquire_at("ursula@example.com", now));
+        assert!(!limiter.try_acquire_at("URSULA@example.com", now));
+        assert!(limiter.try_acquire_at("le_guin@example.com", now));
+    }
+
+    #[test]
+    fn addresses_are_pruned_once_their_resends_are_an_hour_old() {
+        let limiter = ResendConfirmationLimiter::default();
+        let now = Instant::now();
+        limiter.try_acquire_at("ursula@example.com", now);
+
+        limiter.prune_at(now);
+        assert_eq!(limiter.attempts.len(), 1);
+
+        limiter.prune_at(now + RESEND_WINDOW);
+        assert!(limiter.attempts.is_empty());
+    }
+}
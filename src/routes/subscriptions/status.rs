@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse},
+};
+use reqwest::StatusCode;
+use rinja_axum::Template;
+use uuid::Uuid;
+
+use crate::db::SqliteInstrumentedPool;
+use crate::startup::{AppState, HmacSecret};
+
+use super::error_chain_fmt;
+use crate::domain::generate_unsubscribe_token;
+
+#[derive(serde::Deserialize)]
+pub struct StatusParameters {
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "subscription-status/index.html")]
+struct SubscriptionStatusTemplate<'a> {
+    name: &'a str,
+    email: &'a str,
+    status: &'a str,
+    subscribed_at: &'a str,
+    can_unsubscribe: bool,
+    unsubscribe_token: &'a str,
+}
+
+#[derive(thiserror::Error)]
+pub enum SubscriptionStatusError {
+    #[error("There is no subscriber associated with the provided token.")]
+    UnknownToken,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for SubscriptionStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl IntoResponse for SubscriptionStatusError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::UnknownToken => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::NOT_FOUND
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+        .into_response()
+    }
+}
+
+struct SubscriberDetails {
+    uuid: String,
+    name: String,
+    email: String,
+    status: String,
+    subscribed_at: String,
+}
+
+#[tracing::instrument(
+    name = "Show the subscription status",
+    skip(parameters, app_state, hmac_secret)
+)]
+pub async fn subscription_status(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    Query(parameters): Query<StatusParameters>,
+) -> Result<impl IntoResponse, SubscriptionStatusError> {
+    let subscriber = get_subscriber_by_token(app_state.pool(), &parameters.token)
+        .await
+        .context("Failed to retrieve the subscriber associated with the provided token.")?
+        .ok_or(SubscriptionStatusError::UnknownToken)?;
+
+    let subscriber_id =
+        Uuid::parse_str(&subscriber.uuid).context("The stored subscriber id is not a uuid.")?;
+    let unsubscribe_token = generate_unsubscribe_token(subscriber_id, &hmac_secret);
+    let html = SubscriptionStatusTemplate {
+        name: &subscriber.name,
+        email: &subscriber.email,
+        status: status_label(&subscriber.status),
+        subscribed_at: &subscriber.subscribed_at,
+        can_unsubscribe: subscriber.status != "unsubscribed",
+        unsubscribe_token: &unsubscribe_token,
+    }
+    .render()
+    .context("Failed to render the subscription status page.")?;
+    Ok(Html(html))
+}
+
+pub(super) fn status_label(status: &str) -> &str {
+    match status {
+        "pending_confirmation" => "Pending confirmation",
+        "confirmed" => "Confirmed",
+        "unsubscribed" => "Unsubscribed",
+        other => other,
+    }
+}
+
+#[tracing::instrument(name = "Get subscriber by token", skip(subscription_token, pool))]
+async fn get_subscriber_by_token(
+    pool: &SqliteInstrumentedPool,
+    subscription_token: &str,
+) -> Result<Option<SubscriberDetails>, sqlx::Error> {
+    sqlx::query_as!(
+        SubscriberDetails,
+        r#"
+        SELECT s.uuid, s.name, s.email, s.status, s.subscribed_at
+        FROM subscription_tokens t
+        JOIN subscriptions s ON s.uuid = t.subscriber_id
+        WHERE t.subscription_token = $1
+        "#,
+        subscription_token,
+    )
+    .fetch_optional(pool)
+    .await
+}
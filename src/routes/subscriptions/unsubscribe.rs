@@ -0,0 +1,243 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{rejection::FormRejection, Query, State},
+    response::{Html, IntoResponse},
+    Form,
+};
+use chrono::Utc;
+use reqwest::StatusCode;
+use rinja_axum::Template;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use crate::audit::ClientIp;
+use crate::db::SqliteInstrumentedTransaction;
+use crate::domain::{verify_unsubscribe_token, UnsubscribeTokenError};
+use crate::startup::{AppState, HmacSecret};
+
+use super::error_chain_fmt;
+
+/// Longer explanations are cut, nobody reads them in full anyway.
+const MAX_OTHER_TEXT_LENGTH: usize = 1000;
+
+#[derive(serde::Deserialize)]
+pub struct UnsubscribeParameters {
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "unsubscribe/index.html")]
+struct UnsubscribeTemplate<'a> {
+    token: &'a str,
+}
+
+/// The optional survey of the unsubscribe page, every field can be left out.
+#[derive(serde::Deserialize, Default)]
+pub struct UnsubscribeSurvey {
+    reason: Option<UnsubscribeReason>,
+    other_text: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsubscribeReason {
+    TooFrequent,
+    NotRelevant,
+    NeverSubscribed,
+    Other,
+}
+
+impl UnsubscribeReason {
+    pub const ALL: [UnsubscribeReason; 4] = [
+        Self::TooFrequent,
+        Self::NotRelevant,
+        Self::NeverSubscribed,
+        Self::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TooFrequent => "too_frequent",
+            Self::NotRelevant => "not_relevant",
+            Self::NeverSubscribed => "never_subscribed",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum UnsubscribeError {
+    #[error("The unsubscribe token is invalid.")]
+    InvalidToken(#[source] anyhow::Error),
+    #[error("The unsubscribe token has expired.")]
+    ExpiredToken,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for UnsubscribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl From<UnsubscribeTokenError> for UnsubscribeError {
+    fn from(e: UnsubscribeTokenError) -> Self {
+        match e {
+            UnsubscribeTokenError::Invalid(e) => Self::InvalidToken(e),
+            UnsubscribeTokenError::Expired => Self::ExpiredToken,
+        }
+    }
+}
+
+impl IntoResponse for UnsubscribeError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::InvalidToken(_) => {
+                tracing::warn!(cause_chain = ?self);
+                StatusCode::BAD_REQUEST.into_response()
+            }
+            Self::ExpiredToken => {
+                tracing::info!(cause_chain = ?self);
+                let expired_page_path =
+                    PathBuf::from("frontend/dist/unsubscribe-expired/index.html");
+                match fs::read_to_string(expired_page_path) {
+                    Ok(content) => (StatusCode::GONE, Html(content)).into_response(),
+                    Err(_) => {
+                        (StatusCode::GONE, "This unsubscribe link has expired").into_response()
+                    }
+                }
+            }
+            Self::UnexpectedError(e) => {
+                tracing::error!(cause_chain = ?e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// The page unsubscribe links lead to, asking why before confirming.
+///
+/// Nothing changes until the page's form is posted, so link scanners that
+/// follow every link of an email don't unsubscribe anyone.
+#[tracing::instrument(name = "Show the unsubscribe page", skip(parameters, hmac_secret))]
+pub async fn unsubscribe(
+    State(hmac_secret): State<HmacSecret>,
+    Query(parameters): Query<UnsubscribeParameters>,
+) -> Result<impl IntoResponse, UnsubscribeError> {
+    verify_unsubscribe_token(&parameters.token, &hmac_secret)?;
+
+    let html = UnsubscribeTemplate {
+        token: &parameters.token,
+    }
+    .render()
+    .context("Failed to render the unsubscribe page.")?;
+    Ok(Html(html))
+}
+
+/// RFC 8058 one-click unsubscribe: mail providers `POST` to the
+/// `List-Unsubscribe` URL and don't follow redirects, so we answer with the
+/// page directly. The unsubscribe and subscription status pages post here as
+/// well.
+///
+/// The reason given in the survey is recorded when the subscriber actually
+/// leaves, a survey that can't be read doesn't stop them from leaving.
+#[tracing::instrument(
+    name = "Unsubscribe a subscriber with one click",
+    skip(parameters, app_state, hmac_secret, client_ip, survey)
+)]
+pub async fn unsubscribe_one_click(
+    State(app_state): State<Arc<AppState>>,
+    State(hmac_secret): State<HmacSecret>,
+    ClientIp(client_ip): ClientIp,
+    Query(parameters): Query<UnsubscribeParameters>,
+    survey: Result<Form<UnsubscribeSurvey>, FormRejection>,
+) -> Result<impl IntoResponse, UnsubscribeError> {
+    let subscriber_id = verify_unsubscribe_token(&parameters.token, &hmac_secret)?;
+    let survey = survey.map(|Form(survey)| survey).unwrap_or_else(|e| {
+        tracing::warn!(error.message = %e, "Ignoring an unreadable unsubscribe survey");
+        UnsubscribeSurvey::default()
+    });
+
+    let mut transaction = app_state
+        .pool()
+        .begin()
+        .await
+        .context("Failed to acquire a Sqlite connection from the pool")?;
+    // unsubscribing twice with the same link is a no-op
+    if mark_subscriber_as_unsubscribed(&mut transaction, subscriber_id)
+        .await
+        .context("Failed to update the subscriber status to `unsubscribed`.")?
+    {
+        store_unsubscribe_event(&mut transaction, subscriber_id, &survey, client_ip)
+            .await
+            .context("Failed to store the unsubscribe event.")?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit the unsubscription.")?;
+
+    let unsubscribed_page_path = PathBuf::from("frontend/dist/unsubscribed/index.html");
+    match fs::read_to_string(unsubscribed_page_path) {
+        Ok(content) => Ok(Html(content).into_response()),
+        Err(_) => Ok("You have been unsubscribed".into_response()),
+    }
+}
+
+/// Returns `false` if the subscriber had already unsubscribed.
+#[tracing::instrument(name = "Mark subscriber as unsubscribed", skip(transaction))]
+pub async fn mark_subscriber_as_unsubscribed(
+    transaction: &mut SqliteInstrumentedTransaction,
+    subscriber_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let subscriber_id = subscriber_id.to_string();
+    let n_updated_rows = sqlx::query!(
+        r#"UPDATE subscriptions SET status = 'unsubscribed' WHERE uuid = $1 AND status != 'unsubscribed'"#,
+        subscriber_id,
+    )
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected();
+    Ok(n_updated_rows > 0)
+}
+
+#[tracing::instrument(name = "Store an unsubscribe event", skip(transaction, survey))]
+async fn store_unsubscribe_event(
+    transaction: &mut SqliteInstrumentedTransaction,
+    subscriber_id: Uuid,
+    survey: &UnsubscribeSurvey,
+    client_ip: Option<IpAddr>,
+) -> Result<(), sqlx::Error> {
+    let subscriber_id = subscriber_id.to_string();
+    let reason = survey.reason.map(|reason| reason.as_str());
+    // the text box only means something next to the `other` choice
+    let other_text = match survey.reason {
+        Some(UnsubscribeReason::Other) => survey
+            .other_text
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| text.chars().take(MAX_OTHER_TEXT_LENGTH).collect::<String>()),
+        _ => None,
+    };
+    let occurred_at = Utc::now().to_string();
+    let ip_address = client_ip.map(|ip| ip.to_string());
+    sqlx::query!(
+        r#"
+        INSERT INTO unsubscribe_events
+            (subscriber_uuid, reason, other_text, occurred_at, ip_address)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        subscriber_id,
+        reason,
+        other_text,
+        occurred_at,
+        ip_address,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    Ok(())
+}
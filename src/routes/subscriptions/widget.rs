@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::State,
+    http::header,
+    response::{Html, IntoResponse},
+};
+use rinja_axum::Template;
+
+use crate::error::AppError;
+use crate::middleware::{CspNonce, EmbeddableAnywhere};
+use crate::startup::AppState;
+use crate::tags::all_tags;
+
+#[derive(Template)]
+#[template(path = "subscribe-widget/index.html")]
+struct SubscribeWidgetTemplate {
+    csp_nonce: String,
+    tags: Vec<String>,
+}
+
+/// The bare subscription form, for other sites to put in an `<iframe>`.
+///
+/// It posts to `POST /subscribe-widget`, which subscribes like
+/// `POST /subscriptions` without asking for a CSRF token, and tells the
+/// embedding page how the subscription went with a
+/// `{ event: "subscribed" | "error" }` message.
+#[tracing::instrument(name = "Show the subscribe widget", skip(app_state, csp_nonce))]
+pub async fn subscribe_widget(
+    State(app_state): State<Arc<AppState>>,
+    CspNonce(csp_nonce): CspNonce,
+) -> Result<impl IntoResponse, AppError> {
+    let tags = all_tags(app_state.pool())
+        .await
+        .context("Failed to retrieve the tags.")?;
+    let html = SubscribeWidgetTemplate { csp_nonce, tags }
+        .render()
+        .context("Failed to render the subscribe widget.")?;
+    let mut response = Html(html).into_response();
+    response.extensions_mut().insert(EmbeddableAnywhere);
+    Ok(response)
+}
+
+/// The `<iframe>` to paste into another site to embed the widget.
+pub async fn subscribe_widget_embed_code(
+    State(app_state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        embed_code(app_state.base_url()),
+    )
+}
+
+fn embed_code(base_url: &str) -> String {
+    format!(
+        r#"<iframe src="{}/subscribe-widget" title="Subscribe to the newsletter" width="400" height="420" style="border: 0;"></iframe>"#,
+        base_url.trim_end_matches('/')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::embed_code;
+
+    #[test]
+    fn the_embed_code_points_to_the_widget() {
+        assert!(embed_code("https://talga.dev")
+            .starts_with(r#"<iframe src="https://talga.dev/subscribe-widget""#));
+        assert!(embed_code("https://talga.dev/")
+            .starts_with(r#"<iframe src="https://talga.dev/subscribe-widget""#));
+    }
+}
@@ -4,11 +4,15 @@ use anyhow::Context;
 use axum::{
     extract::{Query, State},
     response::{Html, IntoResponse},
+    Form,
 };
+use chrono::Utc;
 use reqwest::StatusCode;
-use sqlx::SqlitePool;
+use rinja_axum::Template;
 use uuid::Uuid;
 
+use crate::db::SqliteInstrumentedPool;
+use crate::middleware::CsrfToken;
 use crate::startup::AppState;
 
 use super::error_chain_fmt;
@@ -18,12 +22,20 @@ pub struct Parameters {
     subscription_token: String,
 }
 
+#[derive(Template)]
+#[template(path = "confirm_subscription/index.html")]
+struct ConfirmSubscriptionTemplate<'a> {
+    subscription_token: &'a str,
+}
+
 #[derive(thiserror::Error)]
 pub enum ConfirmationError {
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
     #[error("There is no subscriber associated with the provided token.")]
     UnknownToken,
+    #[error("The provided token has expired.")]
+    ExpiredToken,
 }
 
 impl std::fmt::Debug for ConfirmationError {
@@ -37,31 +49,63 @@ impl IntoResponse for ConfirmationError {
         match self {
             Self::UnknownToken => {
                 tracing::error!(cause_chain = ?self);
-                StatusCode::UNAUTHORIZED
+                StatusCode::UNAUTHORIZED.into_response()
+            }
+            Self::ExpiredToken => {
+                tracing::info!(cause_chain = ?self);
+                // the page lets the subscriber ask for a new confirmation link
+                let expired_page_path =
+                    PathBuf::from("frontend/dist/confirmation-expired/index.html");
+                match fs::read_to_string(expired_page_path) {
+                    Ok(content) => (StatusCode::GONE, Html(content)).into_response(),
+                    Err(_) => {
+                        (StatusCode::GONE, "This confirmation link has expired").into_response()
+                    }
+                }
             }
             Self::UnexpectedError(e) => {
                 tracing::error!(cause_chain = ?e);
-                StatusCode::INTERNAL_SERVER_ERROR
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
             }
         }
-        .into_response()
     }
 }
 
+/// The page confirmation links lead to, with a button posting the token back.
+///
+/// Some email clients open every link of an email to scan it, confirming on
+/// `GET` would subscribe people who never clicked anything.
+#[tracing::instrument(
+    name = "Show the subscription confirmation page",
+    skip(parameters, app_state)
+)]
+pub async fn confirm_form(
+    State(app_state): State<Arc<AppState>>,
+    Query(parameters): Query<Parameters>,
+    // the expired page's resend form reads it from the `csrf_token` cookie
+    _: CsrfToken,
+) -> Result<impl IntoResponse, ConfirmationError> {
+    valid_subscription_token(app_state.pool(), &parameters.subscription_token).await?;
+
+    let html = ConfirmSubscriptionTemplate {
+        subscription_token: &parameters.subscription_token,
+    }
+    .render()
+    .context("Failed to render the subscription confirmation page.")?;
+    Ok(Html(html))
+}
+
 // could later take only the pool from the state, if you want to do it check the
 // axum's State docs
 #[tracing::instrument(name = "Confirm a pending subscriber", skip(parameters, app_state))]
 pub async fn confirm(
     State(app_state): State<Arc<AppState>>,
-    Query(parameters): Query<Parameters>,
+    Form(parameters): Form<Parameters>,
 ) -> Result<impl IntoResponse, ConfirmationError> {
-    let subscriber_id =
-        get_subscriber_id_from_token(&app_state.pool, &parameters.subscription_token)
-            .await
-            .context("Failed to retrieve the subscriber id associated with the provided token.")?
-            .ok_or(ConfirmationError::UnknownToken)?;
+    let token = valid_subscription_token(app_state.pool(), &parameters.subscription_token).await?;
+    let subscriber_id = token.subscriber_id;
 
-    confirm_subscriber(&app_state.pool, subscriber_id)
+    confirm_subscriber(app_state.pool(), subscriber_id)
         .await
         .context("Failed to update the subscriber status to `confirmed`.")?;
 
@@ -76,8 +120,25 @@ pub async fn confirm(
     }
 }
 
+async fn valid_subscription_token(
+    pool: &SqliteInstrumentedPool,
+    subscription_token: &str,
+) -> Result<SubscriptionToken, ConfirmationError> {
+    let token = get_subscription_token(pool, subscription_token)
+        .await
+        .context("Failed to retrieve the subscriber id associated with the provided token.")?
+        .ok_or(ConfirmationError::UnknownToken)?;
+    if token.is_expired() {
+        return Err(ConfirmationError::ExpiredToken);
+    }
+    Ok(token)
+}
+
 #[tracing::instrument(name = "Mark subscriber as confirmed", skip(subscriber_id, pool))]
-pub async fn confirm_subscriber(pool: &SqlitePool, subscriber_id: Uuid) -> Result<(), sqlx::Error> {
+pub async fn confirm_subscriber(
+    pool: &SqliteInstrumentedPool,
+    subscriber_id: Uuid,
+) -> Result<(), sqlx::Error> {
     let subscriber_id = subscriber_id.to_string();
     sqlx::query!(
         r#"UPDATE subscriptions SET status = 'confirmed' WHERE uuid = $1"#,
@@ -88,16 +149,37 @@ pub async fn confirm_subscriber(pool: &SqlitePool, subscriber_id: Uuid) -> Resul
     Ok(())
 }
 
+pub struct SubscriptionToken {
+    pub subscriber_id: Uuid,
+    expires_at: Option<String>,
+}
+
+impl SubscriptionToken {
+    pub fn is_expired(&self) -> bool {
+        // timestamps are stored as `Utc::now().to_string()`, so they compare as strings
+        self.expires_at
+            .as_ref()
+            .is_some_and(|expires_at| *expires_at <= Utc::now().to_string())
+    }
+}
+
 #[tracing::instrument(name = "Get subscriber_id from token", skip(subscription_token, pool))]
-pub async fn get_subscriber_id_from_token(
-    pool: &SqlitePool,
+pub async fn get_subscription_token(
+    pool: &SqliteInstrumentedPool,
     subscription_token: &str,
-) -> Result<Option<Uuid>, sqlx::Error> {
+) -> Result<Option<SubscriptionToken>, sqlx::Error> {
     let result = sqlx::query!(
-        r#"SELECT subscriber_id FROM subscription_tokens WHERE subscription_token = $1"#,
+        r#"
+        SELECT subscriber_id, token_expires_at
+        FROM subscription_tokens
+        WHERE subscription_token = $1
+        "#,
         subscription_token,
     )
     .fetch_optional(pool)
     .await?;
-    Ok(result.map(|r| Uuid::try_parse(&r.subscriber_id).unwrap()))
+    Ok(result.map(|r| SubscriptionToken {
+        subscriber_id: Uuid::try_parse(&r.subscriber_id).unwrap(),
+        expires_at: r.token_expires_at,
+    }))
 }
@@ -1,5 +1,13 @@
-use axum::{extract::Query, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
 use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::startup::AppState;
 
 #[derive(Deserialize)]
 pub struct XkcdProxyParams {
@@ -10,6 +18,7 @@ pub struct XkcdProxyParams {
 /// Proxies requests to xkcd.com/info.0.json to avoid CORS issues on the frontend.
 /// Only allows fetching XKCD comic JSON — not an open proxy.
 pub async fn xkcd_proxy(
+    State(app_state): State<Arc<AppState>>,
     Query(params): Query<XkcdProxyParams>,
 ) -> Result<impl IntoResponse, StatusCode> {
     let url = match params.num {
@@ -17,7 +26,10 @@ pub async fn xkcd_proxy(
         None => "https://xkcd.com/info.0.json".to_string(),
     };
 
-    let response = reqwest::get(&url)
+    let response = app_state
+        .http_client()
+        .get(&url)
+        .send()
         .await
         .map_err(|_| StatusCode::BAD_GATEWAY)?;
 
@@ -0,0 +1,188 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+type Job = Box<dyn Fn() -> BoxFuture<'static, ()> + Send>;
+
+struct ScheduledJob {
+    name: String,
+    interval: Duration,
+    job: Job,
+}
+
+#[derive(Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval_seconds: u64,
+    /// RFC 3339, `None` until the job ran for the first time.
+    pub last_run_at: Option<String>,
+    pub last_run_duration_ms: Option<u64>,
+    pub last_run_panicked: bool,
+}
+
+/// What every job of a scheduler has been up to, cheap to clone and still
+/// readable after the scheduler started running.
+#[derive(Clone, Default)]
+pub struct JobStatuses(Arc<Mutex<Vec<JobStatus>>>);
+
+impl JobStatuses {
+    pub fn snapshot(&self) -> Vec<JobStatus> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn record_run(&self, index: usize, started_at: Instant, panicked: bool) {
+        let mut statuses = self.0.lock().unwrap();
+        let status = &mut statuses[index];
+        status.last_run_at = Some(chrono::Utc::now().to_rfc3339());
+        status.last_run_duration_ms = Some(started_at.elapsed().as_millis() as u64);
+        status.last_run_panicked = panicked;
+    }
+}
+
+/// Runs the periodic background jobs of the application.
+///
+/// Every job gets its own task and first runs one `interval` after the
+/// scheduler started. A job that panics is logged and simply runs again on
+/// its next tick.
+#[derive(Default)]
+pub struct JobScheduler {
+    jobs: Vec<ScheduledJob>,
+    statuses: JobStatuses,
+}
+
+impl JobScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_job(
+        &mut self,
+        name: &str,
+        interval: Duration,
+        job: impl Fn() -> BoxFuture<'static, ()> + Send + 'static,
+    ) {
+        self.statuses.0.lock().unwrap().push(JobStatus {
+            name: name.to_string(),
+            interval_seconds: interval.as_secs(),
+            last_run_at: None,
+            last_run_duration_ms: None,
+            last_run_panicked: false,
+        });
+        self.jobs.push(ScheduledJob {
+            name: name.to_string(),
+            interval,
+            job: Box::new(job),
+        });
+    }
+
+    pub fn statuses(&self) -> JobStatuses {
+        self.statuses.clone()
+    }
+
+    /// Returns once every job has noticed the cancellation, a job that is
+    /// running at that point gets to finish first.
+    pub async fn run_until_cancelled(self, token: CancellationToken) {
+        let mut tasks = JoinSet::new();
+        for (index, job) in self.jobs.into_iter().enumerate() {
+            tasks.spawn(run_job(index, job, self.statuses.clone(), token.clone()));
+        }
+        while tasks.join_next().await.is_some() {}
+        tracing::info!("Job scheduler has been shut down");
+    }
+}
+
+async fn run_job(index: usize, job: ScheduledJob, statuses: JobStatuses, token: CancellationToken) {
+    let mut ticks =
+        tokio::time::interval_at(tokio::time::Instant::now() + job.interval, job.interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = token.cancelled() => break,
+        }
+        let started_at = Instant::now();
+        // a task of its own, so that a panic ends up in the `JoinError`
+        // instead of taking the whole loop down
+        let outcome = tokio::spawn((job.job)()).await;
+        let panicked = match outcome {
+            Ok(()) => false,
+            Err(e) => {
+                tracing::error!(
+                    job = %job.name,
+                    error.message = %e,
+                    "A scheduled job panicked",
+                );
+                true
+            }
+        };
+        statuses.record_run(index, started_at, panicked);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JobScheduler;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn jobs_run_on_every_interval() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let mut scheduler = JobScheduler::new();
+        let counter = runs.clone();
+        scheduler.add_job("count", Duration::from_millis(10), move || {
+            let counter = counter.clone();
+            Box::pin(async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+        let statuses = scheduler.statuses();
+        let token = CancellationToken::new();
+        let scheduler = tokio::spawn(scheduler.run_until_cancelled(token.clone()));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(1), scheduler)
+            .await
+            .expect("The scheduler ignored the cancellation")
+            .unwrap();
+
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+        let statuses = statuses.snapshot();
+        assert_eq!(statuses[0].name, "count");
+        assert!(statuses[0].last_run_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn a_panicking_job_keeps_being_scheduled() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let mut scheduler = JobScheduler::new();
+        let counter = runs.clone();
+        scheduler.add_job("panic", Duration::from_millis(10), move || {
+            let counter = counter.clone();
+            Box::pin(async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                panic!("boom");
+            })
+        });
+        let statuses = scheduler.statuses();
+        let token = CancellationToken::new();
+        let scheduler = tokio::spawn(scheduler.run_until_cancelled(token.clone()));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        token.cancel();
+        scheduler.await.unwrap();
+
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+        assert!(statuses.snapshot()[0].last_run_panicked);
+    }
+}
@@ -1,11 +1,160 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
 use axum::{extract::FromRequestParts, http::request::Parts};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hkdf::Hkdf;
+use secrecy::ExposeSecret;
+use sha2::Sha256;
+use std::collections::HashMap;
+use tower_sessions::session::{Id, Record};
+use tower_sessions::session_store::{self, SessionStore};
 use tower_sessions::{self, session, Session};
 use uuid::Uuid;
 
+use crate::authentication::UserRole;
+use crate::startup::HmacSecret;
+
+/// The only entry of the records handed to the wrapped store, it holds the
+/// real entries encrypted.
+const ENCRYPTED_DATA_KEY: &str = "encrypted";
+
+/// The length of the AES-GCM nonce stored in front of the ciphertext.
+const NONCE_LENGTH: usize = 12;
+
+/// Wraps a session store so that it only ever sees the session data encrypted
+/// with AES-256-GCM, a compromised Redis doesn't give away who is logged in.
+/// The key is derived from the HMAC secret with HKDF, the nonce is stored in
+/// front of the ciphertext and the session id is authenticated along with it,
+/// so the data of one session can't be copied over to another. Sessions
+/// stored in plaintext aren't loaded.
+#[derive(Clone)]
+pub struct SessionEncryptionLayer<S> {
+    inner: S,
+    cipher: Aes256Gcm,
+}
+
+impl<S> SessionEncryptionLayer<S> {
+    pub fn new(inner: S, secret: &HmacSecret) -> Self {
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, secret.0.expose_secret().as_bytes())
+            .expand(b"newzletter session encryption", &mut key)
+            .expect("32 bytes is a valid length for HKDF-SHA256");
+        Self {
+            inner,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    fn encrypt(&self, record: &Record) -> session_store::Result<Record> {
+        let plaintext = serde_json::to_vec(&record.data)
+            .map_err(|e| session_store::Error::Encode(e.to_string()))?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: record.id.to_string().as_bytes(),
+                },
+            )
+            .map_err(|_| session_store::Error::Encode("Failed to encrypt the session.".into()))?;
+        let encrypted = STANDARD.encode([nonce.as_slice(), &ciphertext].concat());
+        Ok(Record {
+            id: record.id,
+            data: HashMap::from([(ENCRYPTED_DATA_KEY.to_string(), encrypted.into())]),
+            expiry_date: record.expiry_date,
+        })
+    }
+
+    fn decrypt(&self, record: Record) -> session_store::Result<Record> {
+        let encrypted = record
+            .data
+            .get(ENCRYPTED_DATA_KEY)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| session_store::Error::Decode("The session is not encrypted.".into()))?;
+        let bytes = STANDARD
+            .decode(encrypted)
+            .map_err(|e| session_store::Error::Decode(e.to_string()))?;
+        if bytes.len() <= NONCE_LENGTH {
+            return Err(session_store::Error::Decode(
+                "The encrypted session is too short.".into(),
+            ));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: record.id.to_string().as_bytes(),
+                },
+            )
+            .map_err(|_| session_store::Error::Decode("Failed to decrypt the session.".into()))?;
+        Ok(Record {
+            id: record.id,
+            data: serde_json::from_slice(&plaintext)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?,
+            expiry_date: record.expiry_date,
+        })
+    }
+}
+
+// the cipher holds the key, keep it out of the logs
+impl<S: std::fmt::Debug> std::fmt::Debug for SessionEncryptionLayer<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionEncryptionLayer")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: SessionStore> SessionStore for SessionEncryptionLayer<S> {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        let mut encrypted = self.encrypt(record)?;
+        self.inner.create(&mut encrypted).await?;
+        // the wrapped store picks another id when the generated one is taken,
+        // the data is bound to the id it was encrypted for
+        if encrypted.id != record.id {
+            record.id = encrypted.id;
+            self.inner.save(&self.encrypt(record)?).await?;
+        }
+        Ok(())
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        self.inner.save(&self.encrypt(record)?).await
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        let Some(record) = self.inner.load(session_id).await? else {
+            return Ok(None);
+        };
+        match self.decrypt(record) {
+            Ok(record) => Ok(Some(record)),
+            // e.g. stored in plaintext before the encryption, or with another key,
+            // the visitor just has to log in again
+            Err(e) => {
+                tracing::warn!(error.message = %e, "Failed to decrypt a session, ignoring it");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        self.inner.delete(session_id).await
+    }
+}
+
 pub struct TypedSession(Session);
 
 impl TypedSession {
     const USER_ID_KEY: &'static str = "user_id";
+    const USER_ROLE_KEY: &'static str = "user_role";
+    const LOGGED_IN_AT_KEY: &'static str = "logged_in_at";
+    const PENDING_TWO_FACTOR_USER_ID_KEY: &'static str = "pending_two_factor_user_id";
 
     pub async fn rotate_id(&self) -> Result<(), session::Error> {
         // prevent session fixation attacks
@@ -20,6 +169,46 @@ impl TypedSession {
         self.0.get(Self::USER_ID_KEY).await
     }
 
+    // the role is kept in the session to avoid a database lookup on every request
+    pub async fn insert_user_role(&self, role: UserRole) -> Result<(), session::Error> {
+        self.0.insert(Self::USER_ROLE_KEY, role).await
+    }
+
+    pub async fn get_user_role(&self) -> Result<Option<UserRole>, session::Error> {
+        self.0.get(Self::USER_ROLE_KEY).await
+    }
+
+    // compared against `users.sessions_invalidated_at` to purge old sessions
+    pub async fn insert_logged_in_at(&self, logged_in_at: String) -> Result<(), session::Error> {
+        self.0.insert(Self::LOGGED_IN_AT_KEY, logged_in_at).await
+    }
+
+    pub async fn get_logged_in_at(&self) -> Result<Option<String>, session::Error> {
+        self.0.get(Self::LOGGED_IN_AT_KEY).await
+    }
+
+    // the password has been checked, the session is only established once the
+    // second factor has been too
+    pub async fn insert_pending_two_factor_user_id(
+        &self,
+        user_id: Uuid,
+    ) -> Result<(), session::Error> {
+        self.0
+            .insert(Self::PENDING_TWO_FACTOR_USER_ID_KEY, user_id)
+            .await
+    }
+
+    pub async fn get_pending_two_factor_user_id(&self) -> Result<Option<Uuid>, session::Error> {
+        self.0.get(Self::PENDING_TWO_FACTOR_USER_ID_KEY).await
+    }
+
+    pub async fn remove_pending_two_factor_user_id(&self) -> Result<(), session::Error> {
+        self.0
+            .remove::<Uuid>(Self::PENDING_TWO_FACTOR_USER_ID_KEY)
+            .await
+            .map(|_| ())
+    }
+
     pub async fn log_out(self) -> Result<(), tower_sessions::session::Error> {
         self.0.flush().await
     }
@@ -37,3 +226,72 @@ where
         Ok(Self(session))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SessionEncryptionLayer, ENCRYPTED_DATA_KEY};
+    use crate::startup::HmacSecret;
+    use secrecy::SecretString;
+    use std::collections::HashMap;
+    use tower_sessions::session::{Id, Record};
+    use tower_sessions::MemoryStore;
+
+    fn sample_record() -> Record {
+        Record {
+            id: Id::default(),
+            data: HashMap::from([
+                (
+                    "user_id".to_string(),
+                    uuid::Uuid::new_v4().to_string().into(),
+                ),
+                ("user_role".to_string(), "admin".into()),
+            ]),
+            expiry_date: time::OffsetDateTime::now_utc() + time::Duration::minutes(10),
+        }
+    }
+
+    fn layer(secret: &str) -> SessionEncryptionLayer<MemoryStore> {
+        SessionEncryptionLayer::new(
+            MemoryStore::default(),
+            &HmacSecret(SecretString::from(secret)),
+        )
+    }
+
+    #[test]
+    fn an_encrypted_session_can_be_decrypted_with_the_same_key() {
+        let layer = layer("secret");
+        let record = sample_record();
+
+        let encrypted = layer.encrypt(&record).unwrap();
+
+        assert_eq!(encrypted.id, record.id);
+        assert_eq!(encrypted.data.len(), 1);
+        let stored = encrypted.data[ENCRYPTED_DATA_KEY].as_str().unwrap();
+        assert!(!stored.contains("admin"));
+        let decrypted = layer.decrypt(encrypted).unwrap();
+        assert_eq!(decrypted.id, record.id);
+        assert_eq!(decrypted.data, record.data);
+        assert_eq!(decrypted.expiry_date, record.expiry_date);
+    }
+
+    #[test]
+    fn an_encrypted_session_cannot_be_decrypted_with_another_key() {
+        let encrypted = layer("secret").encrypt(&sample_record()).unwrap();
+        assert!(layer("other").decrypt(encrypted).is_err());
+    }
+
+    #[test]
+    fn an_encrypted_session_cannot_be_moved_to_another_id() {
+        let layer = layer("secret");
+        let mut encrypted = layer.encrypt(&sample_record()).unwrap();
+
+        encrypted.id = Id::default();
+
+        assert!(layer.decrypt(encrypted).is_err());
+    }
+
+    #[test]
+    fn a_plaintext_session_is_rejected() {
+        assert!(layer("secret").decrypt(sample_record()).is_err());
+    }
+}
@@ -1,20 +1,37 @@
-use std::sync::Arc;
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc};
 
 use axum::{
-    extract::{FromRef, Request},
-    middleware,
+    error_handling::HandleErrorLayer,
+    extract::{
+        connect_info::IntoMakeServiceWithConnectInfo, ConnectInfo, DefaultBodyLimit, FromRef,
+        Request,
+    },
+    http::header::USER_AGENT,
+    middleware::{self, AddExtension},
     response::Response,
-    routing::{get, post},
+    routing::{delete, get, patch, post, put},
     serve::Serve,
     Router,
 };
 use axum_messages::MessagesManagerLayer;
+use dashmap::DashMap;
+use ipnet::IpNet;
+use metrics_exporter_prometheus::PrometheusHandle;
+use opentelemetry_sdk::trace::TracerProvider;
 use secrecy::{ExposeSecret, SecretString};
-use sqlx::SqlitePool;
 use time::Duration;
 use tokio::net::TcpListener;
-use tower::ServiceBuilder;
-use tower_http::{services::ServeDir, trace::TraceLayer};
+use tokio_util::sync::CancellationToken;
+use tower::{timeout::TimeoutLayer, ServiceBuilder};
+use tower_http::{
+    compression::{
+        predicate::{DefaultPredicate, Predicate, SizeAbove},
+        CompressionLayer,
+    },
+    limit::RequestBodyLimitLayer,
+    services::ServeDir,
+    trace::TraceLayer,
+};
 use tower_sessions::{Expiry, SessionManagerLayer};
 use tower_sessions_redis_store::{
     fred::{clients::Pool, prelude::*},
@@ -22,44 +39,222 @@ use tower_sessions_redis_store::{
 };
 
 use crate::routes::{
-    admin_dashboard, blog_index, blog_post, change_password, change_password_form, confirm,
-    health_check, home, log_out, login, login_form, publish_newsletter, publish_newsletter_form,
-    subscribe, xkcd_proxy,
+    acknowledge_dead_letter_entry, admin_dashboard, admin_health, archive_index, archive_issue,
+    blog_index, blog_post, blog_post_stats, broadcast, bulk_change_subscriber_status,
+    cancel_scheduled_newsletter, change_log_level, change_password, change_password_form,
+    change_subscriber_name, change_subscriber_status, change_subscriber_tags, check_email,
+    cleanup_stale_pending_subscriptions, confirm, confirm_form, confirm_password_reset,
+    confirm_password_reset_form, count_subscribers, create_api_key, create_blog_post,
+    create_template, deep_health_check, delete_newsletter_issue, delete_subscriber,
+    delete_template, delivery_status, disable_two_factor, duplicate_newsletter_issue,
+    edit_blog_post_form, edit_newsletter_issue, edit_newsletter_issue_form, enable_two_factor,
+    export_subscribers, health_check, home, import_subscribers, invite_user, list_audit_log,
+    list_blog_posts, list_dead_letter_entries, list_jobs, list_newsletter_deliveries,
+    list_scheduled_newsletters, list_subscribers, list_tags, list_templates, log_out, login,
+    login_form, login_two_factor, manage_subscription_form, new_blog_post_form,
+    newsletter_delivery_progress, newsletter_delivery_progress_stream, newsletter_feed,
+    pause_delivery, preview_confirmation_email, preview_newsletter_issue, prometheus_metrics,
+    publish_newsletter, publish_newsletter_form, register, register_form, request_password_reset,
+    requeue_failed_deliveries, resend_confirmation, reset_password_form, resume_delivery,
+    search_subscribers, send_test_newsletter, subscribe, subscribe_widget,
+    subscribe_widget_embed_code, subscription_status, system_diagnostics, toggle_blog_post_draft,
+    two_factor_form, unsubscribe, unsubscribe_one_click, unsubscribe_reasons, update_blog_post,
+    update_template, verify_two_factor, xkcd_proxy, ResendConfirmationLimiter, IMPORT_SIZE_LIMIT,
 };
 use crate::{
-    authentication::reject_anonymous_users,
-    configuration::{configure_database, Settings},
+    authentication::{reject_anonymous_users, reject_non_admin},
+    configuration::{
+        configure_database, ApplicationSettings, Environment, RateLimitSettings, SessionSettings,
+        Settings,
+    },
+    db::SqliteInstrumentedPool,
     email_client::EmailClient,
+    idempotency::cleanup_expired_idempotency_keys,
+    issue_delivery_worker::DeliveryPause,
+    middleware::{
+        handle_timeout_error, negotiate_error_format, CspLayer, CsrfLayer, ForwardedForLayer,
+        HstsLayer, HtmlMinifyLayer, LoginRateLimiter, RateLimitLayer, RateLimiter, RequestId,
+        RequestIdLayer, RequireJsonContentType, TrustedProxies, ValidateHostLayer,
+    },
+    scheduler::{BoxFuture, JobScheduler, JobStatuses},
+    session_state::SessionEncryptionLayer,
+    tags::sync_tags,
+    telemetry::{prometheus_handle, track_http_requests, LogFilterHandle},
+    turnstile::TurnstileClient,
+    vacuum_worker::vacuum_job,
 };
 use tracing::{info, info_span, Span};
-use uuid::Uuid;
 
+/// What every handler gets as `State<Arc<AppState>>`.
+///
+/// The fields stay inside the crate, handlers go through the accessors for
+/// the pool, the API clients and the secrets:
+///
+/// ```
+/// use newzletter::{db::SqliteInstrumentedPool, startup::AppState};
+///
+/// fn pool(app_state: &AppState) -> &SqliteInstrumentedPool {
+///     app_state.pool()
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use newzletter::{db::SqliteInstrumentedPool, startup::AppState};
+///
+/// fn pool(app_state: &AppState) -> &SqliteInstrumentedPool {
+///     &app_state.pool
+/// }
+/// ```
 pub struct AppState {
-    pub pool: SqlitePool,
-    pub email_client: EmailClient,
-    pub base_url: ApplicationBaseUrl,
-    pub turnstile_secret: SecretString,
-    _hmac_secret: HmacSecret,
+    pub(crate) pool: SqliteInstrumentedPool,
+    pub(crate) email_client: EmailClient,
+    pub(crate) base_url: ApplicationBaseUrl,
+    pub(crate) turnstile_client: TurnstileClient,
+    /// Pooled connections for every outgoing request, the API clients above share it.
+    pub(crate) http_client: Arc<reqwest::Client>,
+    pub(crate) idempotency_ttl_hours: u64,
+    /// Only callers from this network may scrape `/metrics`; everyone may if unset.
+    pub(crate) metrics_allowed_cidr: Option<IpNet>,
+    pub(crate) prometheus_handle: PrometheusHandle,
+    pub(crate) resend_confirmation_limiter: Arc<ResendConfirmationLimiter>,
+    /// How many test copies of each newsletter issue have been sent.
+    pub(crate) test_sends: DashMap<uuid::Uuid, u32>,
+    /// When the last `POST /admin/broadcast` went out, broadcasts are spaced out.
+    pub(crate) last_broadcast: std::sync::Mutex<Option<std::time::Instant>>,
+    /// Guards `POST /login`, `POST /subscriptions` and the emailing forms against brute
+    /// forcing and spam.
+    pub(crate) strict_rate_limiter: Arc<RateLimiter>,
+    pub(crate) default_rate_limiter: Arc<RateLimiter>,
+    /// Keeps `GET /subscriptions/check-email` from being used to enumerate subscribers.
+    pub(crate) check_email_rate_limiter: Arc<RateLimiter>,
+    pub(crate) login_rate_limiter: Arc<LoginRateLimiter>,
+    /// Backs the sessions, kept around for the deep health check.
+    pub(crate) redis_pool: Pool,
+    pub(crate) environment: Environment,
+    /// What the background jobs have been up to, for `/admin/jobs`.
+    pub(crate) job_statuses: JobStatuses,
+    pub(crate) hmac_secret: HmacSecret,
+    /// Shared with the delivery worker.
+    pub(crate) delivery_pause: DeliveryPause,
+    /// When the application was built, for the uptime in `/admin/health`.
+    pub(crate) started_at: std::time::Instant,
+    /// The filter of the subscriber this application logs to, `None` when it
+    /// wasn't registered through `telemetry`.
+    pub(crate) log_filter: Option<LogFilterHandle>,
+}
+
+impl AppState {
+    pub fn pool(&self) -> &SqliteInstrumentedPool {
+        &self.pool
+    }
+
+    pub fn email_client(&self) -> &EmailClient {
+        &self.email_client
+    }
+
+    pub fn turnstile_client(&self) -> &TurnstileClient {
+        &self.turnstile_client
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url.0
+    }
+
+    pub fn hmac_secret(&self) -> &HmacSecret {
+        &self.hmac_secret
+    }
+
+    pub fn delivery_pause(&self) -> &DeliveryPause {
+        &self.delivery_pause
+    }
 }
 
 // substate
 impl FromRef<Arc<AppState>> for HmacSecret {
     fn from_ref(input: &Arc<AppState>) -> Self {
-        input._hmac_secret.clone()
+        input.hmac_secret().clone()
     }
 }
 
 pub struct ApplicationBaseUrl(pub String);
 
+// `ConnectInfo` is needed so the metrics endpoint can check the caller's address
+type Server = Serve<
+    TcpListener,
+    IntoMakeServiceWithConnectInfo<Router, SocketAddr>,
+    AddExtension<Router, ConnectInfo<SocketAddr>>,
+>;
+
+/// Everything `Application::build` prepares for [`run`] besides the listener
+/// and the database.
+pub struct ServerSettings {
+    pub http_client: Arc<reqwest::Client>,
+    pub email_client: EmailClient,
+    pub turnstile_client: TurnstileClient,
+    pub application: ApplicationSettings,
+    pub redis_uri: SecretString,
+    pub session: SessionSettings,
+    pub rate_limiters: RateLimiters,
+    pub job_statuses: JobStatuses,
+    pub delivery_pause: DeliveryPause,
+    pub log_filter: Option<LogFilterHandle>,
+}
+
+/// The per client IP limiters, shared by the handlers and the job pruning them.
+#[derive(Clone)]
+pub struct RateLimiters {
+    pub strict: Arc<RateLimiter>,
+    pub default: Arc<RateLimiter>,
+    pub check_email: Arc<RateLimiter>,
+    pub login: Arc<LoginRateLimiter>,
+    pub resend_confirmation: Arc<ResendConfirmationLimiter>,
+}
+
+impl RateLimiters {
+    pub fn new(rate_limit: &RateLimitSettings, application: &ApplicationSettings) -> Self {
+        Self {
+            strict: Arc::new(RateLimiter::new(&rate_limit.strict)),
+            default: Arc::new(RateLimiter::new(&rate_limit.default)),
+            check_email: Arc::new(RateLimiter::new(&rate_limit.check_email)),
+            login: Arc::new(LoginRateLimiter::new(
+                application.login_max_attempts,
+                std::time::Duration::from_secs(application.login_window_minutes * 60),
+            )),
+            resend_confirmation: Arc::new(ResendConfirmationLimiter::default()),
+        }
+    }
+
+    fn prune(&self) {
+        self.strict.prune();
+        self.default.prune();
+        self.check_email.prune();
+        self.login.prune();
+        self.resend_confirmation.prune();
+    }
+}
+
 pub async fn run(
     listener: TcpListener,
-    pool: SqlitePool,
-    email_client: EmailClient,
-    base_url: String,
-    _hmac_secret: SecretString,
-    redis_uri: SecretString,
-    turnstile_secret: SecretString,
-) -> anyhow::Result<Serve<TcpListener, Router, Router>> {
+    pool: SqliteInstrumentedPool,
+    settings: ServerSettings,
+) -> anyhow::Result<Server> {
+    let ServerSettings {
+        http_client,
+        email_client,
+        turnstile_client,
+        application,
+        redis_uri,
+        session,
+        rate_limiters,
+        job_statuses,
+        delivery_pause,
+        log_filter,
+    } = settings;
+
     // redis sessions
     let redis_url = redis_uri.expose_secret();
     let redis_config = Config::from_url(redis_url)
@@ -70,20 +265,28 @@ pub async fn run(
     let _redis_conn = redis_pool.connect();
     redis_pool.wait_for_connect().await?;
 
-    let session_store = RedisStore::new(redis_pool);
-    let session_layer = SessionManagerLayer::new(session_store)
-        .with_secure(false)
-        .with_expiry(Expiry::OnInactivity(Duration::minutes(10)));
+    let metrics_allowed_cidr = application
+        .metrics_allowed_cidr
+        .as_deref()
+        .map(str::parse::<IpNet>)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Failed to parse `metrics_allowed_cidr`: {}", e))?;
+    let trusted_proxies = TrustedProxies::parse(&application.trusted_proxies)
+        .map_err(|e| anyhow::anyhow!("Failed to parse `trusted_proxies`: {}", e))?;
 
-    let admin_routes = Router::new()
-        .route("/dashboard", get(admin_dashboard))
-        .route("/password", get(change_password_form).post(change_password))
-        .route("/logout", post(log_out))
-        .route(
-            "/newsletters",
-            get(publish_newsletter_form).post(publish_newsletter),
-        )
-        .layer(middleware::from_fn(reject_anonymous_users));
+    let same_site = session
+        .same_site()
+        .map_err(|e| anyhow::anyhow!("Failed to parse `session.same_site`: {}", e))?;
+    let hmac_secret = HmacSecret(application.hmac_secret);
+    let session_store =
+        SessionEncryptionLayer::new(RedisStore::new(redis_pool.clone()), &hmac_secret);
+    let mut session_layer = SessionManagerLayer::new(session_store)
+        .with_secure(session.secure)
+        .with_same_site(same_site)
+        .with_expiry(Expiry::OnInactivity(Duration::minutes(10)));
+    if let Some(domain) = session.domain {
+        session_layer = session_layer.with_domain(domain);
+    }
 
     // Wrapped in an Arc pointer to allow cheap cloning of AppState across handlers.
     // This prevents unnecessary cloning of EmailClient, which has two String fields,
@@ -91,39 +294,255 @@ pub async fn run(
     let app_state = Arc::new(AppState {
         pool,
         email_client,
-        base_url: ApplicationBaseUrl(base_url),
-        turnstile_secret,
-        _hmac_secret: HmacSecret(SecretString::from(_hmac_secret)),
+        base_url: ApplicationBaseUrl(application.base_url),
+        turnstile_client,
+        http_client,
+        idempotency_ttl_hours: application.idempotency_ttl_hours,
+        metrics_allowed_cidr,
+        prometheus_handle: prometheus_handle(),
+        resend_confirmation_limiter: rate_limiters.resend_confirmation,
+        test_sends: DashMap::new(),
+        last_broadcast: std::sync::Mutex::new(None),
+        strict_rate_limiter: rate_limiters.strict,
+        default_rate_limiter: rate_limiters.default,
+        check_email_rate_limiter: rate_limiters.check_email,
+        login_rate_limiter: rate_limiters.login,
+        redis_pool,
+        environment: application.environment,
+        job_statuses,
+        hmac_secret,
+        delivery_pause,
+        started_at: std::time::Instant::now(),
+        log_filter,
     });
 
+    // the JSON API, checked for a JSON body before the handlers run
+    let admin_only_json_routes = Router::new()
+        .route(
+            "/subscribers/bulk-status",
+            post(bulk_change_subscriber_status),
+        )
+        .route(
+            "/subscribers/{subscriber_id}/status",
+            patch(change_subscriber_status),
+        )
+        .route("/log-level", post(change_log_level))
+        .route_layer(RequireJsonContentType);
+    let json_routes = Router::new()
+        .route("/api-keys", post(create_api_key))
+        .route("/templates", get(list_templates).post(create_template))
+        .route(
+            "/templates/{template_id}",
+            put(update_template).delete(delete_template),
+        )
+        .route_layer(RequireJsonContentType);
+
+    // editors can't change passwords nor export the subscriber list
+    let admin_only_routes = Router::new()
+        .route("/password", get(change_password_form).post(change_password))
+        .route("/subscribers", get(list_subscribers))
+        .route("/subscribers/export", get(export_subscribers))
+        .route("/subscribers/search", get(search_subscribers))
+        .route("/subscribers/{subscriber_id}", delete(delete_subscriber))
+        .route(
+            "/subscribers/import",
+            post(import_subscribers).layer(DefaultBodyLimit::max(IMPORT_SIZE_LIMIT)),
+        )
+        .route("/unsubscribe-reasons", get(unsubscribe_reasons))
+        .route("/audit-log", get(list_audit_log))
+        .route("/newsletters/{issue_id}", delete(delete_newsletter_issue))
+        .route("/users/invite", post(invite_user))
+        .route("/delivery/pause", post(pause_delivery))
+        .route("/delivery/resume", post(resume_delivery))
+        .merge(admin_only_json_routes)
+        .layer(middleware::from_fn(reject_non_admin));
+
+    let admin_routes = Router::new()
+        .route("/dashboard", get(admin_dashboard))
+        .route("/subscribers/count", get(count_subscribers))
+        .route("/logout", post(log_out))
+        .route("/2fa/enable", post(enable_two_factor))
+        .route("/2fa/verify", post(verify_two_factor))
+        .route("/2fa/disable", post(disable_two_factor))
+        .route(
+            "/newsletters",
+            get(publish_newsletter_form).post(publish_newsletter),
+        )
+        .route("/newsletters/scheduled", get(list_scheduled_newsletters))
+        .route("/broadcast", post(broadcast))
+        .route(
+            "/newsletters/scheduled/{issue_id}",
+            delete(cancel_scheduled_newsletter),
+        )
+        .route(
+            "/newsletters/{issue_id}/duplicate",
+            post(duplicate_newsletter_issue),
+        )
+        .route(
+            "/newsletters/{issue_id}/test-send",
+            post(send_test_newsletter),
+        )
+        .route(
+            "/newsletters/{issue_id}/edit",
+            get(edit_newsletter_issue_form).post(edit_newsletter_issue),
+        )
+        .route(
+            "/email-preview/confirmation",
+            get(preview_confirmation_email),
+        )
+        .route(
+            "/email-preview/newsletter/{issue_id}",
+            get(preview_newsletter_issue),
+        )
+        .route(
+            "/newsletters/{issue_id}/progress",
+            get(newsletter_delivery_progress),
+        )
+        .route(
+            "/newsletters/{issue_id}/progress/stream",
+            get(newsletter_delivery_progress_stream),
+        )
+        .route(
+            "/newsletters/{issue_id}/deliveries",
+            get(list_newsletter_deliveries),
+        )
+        .route("/jobs", get(list_jobs))
+        .route("/tags", get(list_tags))
+        .route("/delivery/dead-letter", get(list_dead_letter_entries))
+        .route(
+            "/delivery/dead-letter/{id}",
+            delete(acknowledge_dead_letter_entry),
+        )
+        .route(
+            "/delivery/requeue/{issue_id}",
+            post(requeue_failed_deliveries),
+        )
+        .route("/delivery/status", get(delivery_status))
+        .route("/blog", get(list_blog_posts).post(create_blog_post))
+        .route("/blog/new", get(new_blog_post_form))
+        .route("/blog/{slug}", post(update_blog_post))
+        .route("/blog/{slug}/edit", get(edit_blog_post_form))
+        .route("/blog/{slug}/publish", post(toggle_blog_post_draft))
+        .route("/blog/{slug}/stats", get(blog_post_stats))
+        .route("/system", get(system_diagnostics))
+        .route("/health", get(admin_health))
+        .merge(json_routes)
+        .merge(admin_only_routes)
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            reject_anonymous_users,
+        ));
+
     let app = Router::new()
         .route("/", get(home))
         .route("/login", get(login_form))
-        .route("/login", post(login))
+        .route(
+            "/login",
+            post(login).layer(RateLimitLayer::new(app_state.strict_rate_limiter.clone())),
+        )
+        .route("/login/2fa", get(two_factor_form))
+        .route(
+            "/login/2fa",
+            post(login_two_factor)
+                .layer(RateLimitLayer::new(app_state.strict_rate_limiter.clone())),
+        )
+        .route("/register", get(register_form).post(register))
+        .route("/reset-password", get(reset_password_form))
+        .route(
+            "/reset-password",
+            post(request_password_reset)
+                .layer(RateLimitLayer::new(app_state.strict_rate_limiter.clone())),
+        )
+        .route(
+            "/reset-password/confirm",
+            get(confirm_password_reset_form).post(confirm_password_reset),
+        )
         .route("/health_check", get(health_check))
-        .route("/subscriptions", post(subscribe))
-        .route("/subscriptions/confirm", get(confirm))
+        .route("/health_check/deep", get(deep_health_check))
+        .route("/metrics", get(prometheus_metrics))
+        .route(
+            "/subscriptions",
+            post(subscribe).layer(RateLimitLayer::new(app_state.strict_rate_limiter.clone())),
+        )
+        .route("/subscriptions/confirm", get(confirm_form).post(confirm))
+        .route(
+            "/subscriptions/check-email",
+            get(check_email).layer(RateLimitLayer::new(
+                app_state.check_email_rate_limiter.clone(),
+            )),
+        )
+        .route("/subscriptions/status", get(subscription_status))
+        .route(
+            "/subscriptions/manage",
+            get(manage_subscription_form).post(change_subscriber_name),
+        )
+        .route("/subscriptions/manage/tags", post(change_subscriber_tags))
+        .route(
+            "/subscriptions/resend-confirmation",
+            post(resend_confirmation)
+                .layer(RateLimitLayer::new(app_state.strict_rate_limiter.clone())),
+        )
+        .route(
+            "/subscriptions/unsubscribe",
+            get(unsubscribe).post(unsubscribe_one_click),
+        )
+        .route("/subscribe-widget", get(subscribe_widget))
+        .route(
+            "/subscribe-widget",
+            post(subscribe).layer(RateLimitLayer::new(app_state.strict_rate_limiter.clone())),
+        )
+        .route(
+            "/subscribe-widget/embed-code",
+            get(subscribe_widget_embed_code),
+        )
         .route("/blog", get(blog_index))
+        // the built index is a template, keep the file server from handing it out as is
+        .route("/blog/", get(blog_index))
         .route("/blog/{slug}", get(blog_post))
+        .route("/archive", get(archive_index))
+        .route("/archive/", get(archive_index))
+        .route("/archive/{slug}", get(archive_issue))
+        .route("/feed.xml", get(newsletter_feed))
         .route("/api/xkcd", get(xkcd_proxy))
+        // only wraps the routes above, the admin ones are behind a login and
+        // the subscriber import needs a lot more room
+        .layer(RequestBodyLimitLayer::new(
+            application.max_request_body_bytes,
+        ))
         .nest("/admin", admin_routes)
         .fallback_service(ServeDir::new("frontend/dist"))
         .layer(
             ServiceBuilder::new()
+                .layer(RequestIdLayer::new())
+                // before anything looking at the client address
+                .layer(ForwardedForLayer::new(trusted_proxies))
                 .layer(
                     TraceLayer::new_for_http()
                         .make_span_with(|request: &Request<_>| {
-                            let request_id = Uuid::new_v4();
+                            let request_id = request
+                                .extensions()
+                                .get::<RequestId>()
+                                .map(|id| id.0.as_str())
+                                .unwrap_or_default();
                             info_span!(
                                 "http_request",
                                 method = ?request.method(),
                                 uri = ?request.uri(),
                                 version = ?request.version(),
-                                request_id = ?request_id,
+                                request_id = %request_id,
+                                user_agent = user_agent(request),
                             )
                         })
+                        .on_request(|request: &Request<_>, _span: &Span| {
+                            tracing::debug!(
+                                method = %request.method(),
+                                uri = %request.uri(),
+                                user_agent = user_agent(request),
+                                "Incoming request"
+                            );
+                        })
                         .on_response(
-                            |response: &Response, latency: std::time::Duration, span: &Span| {
+                            |response: &Response<_>, latency: std::time::Duration, span: &Span| {
                                 let status = response.status();
                                 let headers = response.headers();
                                 span.record("status", &status.as_u16());
@@ -134,12 +553,38 @@ pub async fn run(
                         // logging of errors so disable that
                         .on_failure(()),
                 )
+                // as far out as possible, so that errors of the inner layers get it too
+                .layer(HstsLayer::new(
+                    application.environment,
+                    application.hsts_max_age_seconds,
+                ))
+                // inside the trace layer, so that it logs the compressed responses
+                .layer(compression_layer(application.compress_responses))
+                .layer(HtmlMinifyLayer::new(application.environment))
+                .layer(middleware::from_fn(track_http_requests))
+                // inside the metrics, so that timed out requests are counted as `408`s
+                .layer(HandleErrorLayer::new(handle_timeout_error))
+                .layer(TimeoutLayer::new(std::time::Duration::from_secs(
+                    application.request_timeout_seconds,
+                )))
+                .layer(CspLayer::new(application.environment))
+                .layer(ValidateHostLayer::new(
+                    application.allowed_hosts,
+                    app_state.base_url(),
+                ))
+                .layer(RateLimitLayer::new(app_state.default_rate_limiter.clone()))
                 .layer(session_layer)
-                .layer(MessagesManagerLayer),
+                .layer(MessagesManagerLayer)
+                // reads and writes its token in the session
+                .layer(CsrfLayer::new())
+                .layer(middleware::from_fn(negotiate_error_format)),
         )
         .with_state(app_state);
 
-    Ok(axum::serve(listener, app))
+    Ok(axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    ))
 }
 
 #[derive(Clone)]
@@ -147,13 +592,30 @@ pub struct HmacSecret(pub SecretString);
 
 pub struct Application {
     port: u16,
-    server: Serve<TcpListener, Router, Router>,
+    server: Server,
+    shutdown_token: CancellationToken,
+    shutdown_timeout: std::time::Duration,
+    delivery_pause: DeliveryPause,
+    tracer_provider: Option<TracerProvider>,
 }
 
 impl Application {
     // build is the one that invokes the `run()` function
     // then any fn invokes `run_until_stopped`
-    pub async fn build(configuration: Settings) -> anyhow::Result<Self> {
+    /// `log_filter` and `tracer_provider` belong to the subscriber the caller
+    /// registered, the former backs `POST /admin/log-level` and the latter is
+    /// flushed on shutdown.
+    pub async fn build(
+        configuration: Settings,
+        log_filter: Option<LogFilterHandle>,
+        tracer_provider: Option<TracerProvider>,
+    ) -> anyhow::Result<Self> {
+        let problems = configuration.validate();
+        if !problems.is_empty() {
+            let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+            anyhow::bail!("Invalid configuration:\n  {}", problems.join("\n  "));
+        }
+
         let listener = TcpListener::bind(format!(
             "{}:{}",
             configuration.application.host, configuration.application.port
@@ -161,7 +623,56 @@ impl Application {
         .await?;
         let port = listener.local_addr()?.port();
 
-        let pool = configure_database(&configuration.database).await?;
+        let pool = SqliteInstrumentedPool::new(configure_database(&configuration.database).await?);
+        sync_tags(&pool, &configuration.application.newsletter_tags).await?;
+        let shutdown_token = CancellationToken::new();
+        let delivery_pause = DeliveryPause::default();
+        let rate_limiters =
+            RateLimiters::new(&configuration.rate_limit, &configuration.application);
+        let mut scheduler = JobScheduler::new();
+        scheduler.add_job(
+            "idempotency_cleanup",
+            std::time::Duration::from_secs(60 * 60),
+            idempotency_cleanup_job(
+                pool.clone(),
+                configuration.application.idempotency_ttl_hours,
+            ),
+        );
+        scheduler.add_job(
+            "pending_subscriptions_cleanup",
+            std::time::Duration::from_secs(24 * 60 * 60),
+            pending_subscriptions_cleanup_job(
+                pool.clone(),
+                configuration.application.pending_ttl_days,
+            ),
+        );
+        if configuration
+            .database
+            .journal_mode
+            .eq_ignore_ascii_case("WAL")
+        {
+            scheduler.add_job(
+                "wal_checkpoint",
+                std::time::Duration::from_secs(
+                    configuration.database.wal_checkpoint_interval_minutes * 60,
+                ),
+                wal_checkpoint_job(pool.clone()),
+            );
+        }
+        if configuration.database.vacuum_enabled {
+            scheduler.add_job(
+                "incremental_vacuum",
+                std::time::Duration::from_secs(configuration.database.vacuum_interval_minutes * 60),
+                vacuum_job(pool.clone(), configuration.database.vacuum_pages_per_run),
+            );
+        }
+        scheduler.add_job(
+            "rate_limit_pruning",
+            std::time::Duration::from_secs(60),
+            rate_limit_pruning_job(rate_limiters.clone()),
+        );
+        let job_statuses = scheduler.statuses();
+        tokio::spawn(scheduler.run_until_cancelled(shutdown_token.clone()));
 
         // let sender_email = configuration
         //     .email_client
@@ -174,27 +685,275 @@ impl Application {
         //     configuration.email_client.authorization_token,
         //     timeout,
         // );
-        let email_client = configuration.email_client.client();
+        let http_client = Arc::new(configuration.http_client.client());
+        let email_client = configuration.email_client.client(http_client.clone());
+        if configuration.application.turnstile_test_mode {
+            tracing::warn!("Turnstile test mode is on, every token will be accepted");
+        }
+        let turnstile_client = configuration.turnstile.client(
+            http_client.clone(),
+            configuration.application.turnstile_test_mode,
+        );
+        let shutdown_timeout =
+            std::time::Duration::from_secs(configuration.application.shutdown_timeout_seconds);
 
         let server = run(
             listener,
             pool,
-            email_client,
-            configuration.application.base_url,
-            configuration.application.hmac_secret,
-            configuration.redis_uri,
-            configuration.application.turnstile_secret_key,
+            ServerSettings {
+                http_client,
+                email_client,
+                turnstile_client,
+                application: configuration.application,
+                redis_uri: configuration.redis_uri,
+                session: configuration.session,
+                rate_limiters,
+                job_statuses,
+                delivery_pause: delivery_pause.clone(),
+                log_filter,
+            },
         )
         .await?;
 
-        Ok(Self { server, port })
+        Ok(Self {
+            server,
+            port,
+            shutdown_token,
+            shutdown_timeout,
+            delivery_pause,
+            tracer_provider,
+        })
+    }
+
+    /// Cancelled once the application starts shutting down, hand it to the
+    /// background workers so they stop alongside the HTTP server.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown_token.clone()
+    }
+
+    /// Toggled by `POST /admin/delivery/pause` and `/resume`, hand it to the
+    /// delivery worker.
+    pub fn delivery_pause(&self) -> DeliveryPause {
+        self.delivery_pause.clone()
     }
 
     pub async fn run_until_stopped(self) -> anyhow::Result<()> {
-        Ok(self.server.await?)
+        let shutdown_token = self.shutdown_token.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            tracing::info!("Shutdown signal received, draining in-flight requests");
+            shutdown_token.cancel();
+        });
+
+        let server = self
+            .server
+            .with_graceful_shutdown(self.shutdown_token.clone().cancelled_owned());
+        // in-flight requests get `shutdown_timeout` to complete before we give up on them
+        let hard_shutdown = async {
+            self.shutdown_token.cancelled().await;
+            tokio::time::sleep(self.shutdown_timeout).await;
+        };
+        let outcome = tokio::select! {
+            outcome = server.into_future() => outcome.map_err(anyhow::Error::from),
+            _ = hard_shutdown => {
+                tracing::warn!("In-flight requests did not complete in time, shutting down anyway");
+                Ok(())
+            }
+        };
+        // flush the spans still waiting to be exported, shutting the provider down blocks
+        if let Some(tracer_provider) = self.tracer_provider {
+            let _ = tokio::task::spawn_blocking(move || tracer_provider.shutdown()).await;
+        }
+        outcome
     }
 
     pub fn port(&self) -> u16 {
         self.port
     }
 }
+
+/// `User-Agent` headers can be made arbitrarily long, only so much of them
+/// ends up in the logs.
+const MAX_LOGGED_USER_AGENT_CHARS: usize = 200;
+
+fn user_agent<B>(request: &Request<B>) -> Option<&str> {
+    let user_agent = request.headers().get(USER_AGENT)?.to_str().ok()?;
+    match user_agent.char_indices().nth(MAX_LOGGED_USER_AGENT_CHARS) {
+        Some((end, _)) => Some(&user_agent[..end]),
+        None => Some(user_agent),
+    }
+}
+
+/// Brotli is picked over gzip when the client accepts both. Small responses
+/// aren't worth the CPU, and neither are images or server-sent events, which
+/// the default predicate already leaves alone.
+fn compression_layer(enabled: bool) -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .br(enabled)
+        .gzip(enabled)
+        .compress_when(DefaultPredicate::new().and(SizeAbove::new(1024)))
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install the Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install the SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Checkpoints the write-ahead log and truncates it, in WAL mode it
+/// otherwise only shrinks when SQLite gets around to it on its own.
+pub fn wal_checkpoint_job(
+    pool: SqliteInstrumentedPool,
+) -> impl Fn() -> BoxFuture<'static, ()> + Send + 'static {
+    move || {
+        let pool = pool.clone();
+        Box::pin(async move {
+            if let Err(e) = wal_checkpoint(&pool).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to checkpoint the write-ahead log",
+                );
+            }
+        })
+    }
+}
+
+/// Idempotency keys are only useful for a limited amount of time, this gets
+/// rid of the expired ones.
+fn idempotency_cleanup_job(
+    pool: SqliteInstrumentedPool,
+    idempotency_ttl_hours: u64,
+) -> impl Fn() -> BoxFuture<'static, ()> + Send + 'static {
+    move || {
+        let pool = pool.clone();
+        Box::pin(async move {
+            if let Err(e) = cleanup_expired_idempotency_keys(&pool, idempotency_ttl_hours).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to clean up expired idempotency keys",
+                );
+            }
+        })
+    }
+}
+
+/// Buckets that filled up again and lockouts whose window ended are as good as
+/// no entry at all, without this the limiters would remember every client
+/// that ever showed up.
+fn rate_limit_pruning_job(
+    rate_limiters: RateLimiters,
+) -> impl Fn() -> BoxFuture<'static, ()> + Send + 'static {
+    move || {
+        let rate_limiters = rate_limiters.clone();
+        Box::pin(async move { rate_limiters.prune() })
+    }
+}
+
+/// Subscribers who never clicked their confirmation link would otherwise
+/// pile up forever.
+fn pending_subscriptions_cleanup_job(
+    pool: SqliteInstrumentedPool,
+    pending_ttl_days: u64,
+) -> impl Fn() -> BoxFuture<'static, ()> + Send + 'static {
+    move || {
+        let pool = pool.clone();
+        Box::pin(async move {
+            if let Err(e) = cleanup_stale_pending_subscriptions(&pool, pending_ttl_days).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to clean up stale pending subscriptions",
+                );
+            }
+        })
+    }
+}
+
+#[tracing::instrument(name = "WAL checkpoint", skip(pool))]
+async fn wal_checkpoint(pool: &SqliteInstrumentedPool) -> Result<(), sqlx::Error> {
+    let (busy, wal_pages, walckpt_done): (i64, i64, i64) =
+        sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE)")
+            .fetch_one(pool)
+            .await?;
+    // a busy checkpoint couldn't get hold of the database and will be retried next time
+    tracing::info!(
+        busy = busy != 0,
+        walckpt_done,
+        wal_pages,
+        "Checkpointed the write-ahead log"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{user_agent, wal_checkpoint, MAX_LOGGED_USER_AGENT_CHARS};
+    use crate::db::SqliteInstrumentedPool;
+    use axum::extract::Request;
+    use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+
+    #[tokio::test]
+    async fn the_wal_is_checkpointed() {
+        let path = std::env::temp_dir().join(format!("wal-checkpoint-{}.db", uuid::Uuid::new_v4()));
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqliteInstrumentedPool::new(
+            SqlitePoolOptions::new()
+                .connect_with(options)
+                .await
+                .unwrap(),
+        );
+        sqlx::query("CREATE TABLE notes (body TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO notes (body) VALUES ('hello')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        wal_checkpoint(&pool).await.unwrap();
+
+        pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn long_user_agents_are_cut_short() {
+        let long = "a".repeat(MAX_LOGGED_USER_AGENT_CHARS + 50);
+        let request = Request::builder()
+            .header("User-Agent", &long)
+            .body(())
+            .unwrap();
+        assert_eq!(
+            user_agent(&request),
+            Some(&long[..MAX_LOGGED_USER_AGENT_CHARS])
+        );
+
+        let request = Request::builder().body(()).unwrap();
+        assert_eq!(user_agent(&request), None);
+    }
+}
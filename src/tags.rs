@@ -0,0 +1,91 @@
+use crate::db::{SqliteInstrumentedPool, SqliteInstrumentedTransaction};
+use chrono::Utc;
+
+/// Make sure every configured tag exists, tags that are no longer
+/// configured are kept along with their subscribers.
+#[tracing::instrument(name = "Sync the newsletter tags", skip(pool))]
+pub async fn sync_tags(pool: &SqliteInstrumentedPool, tags: &[String]) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_string();
+    for tag in tags {
+        sqlx::query!(
+            "INSERT INTO tags (name, created_at) VALUES ($1, $2) ON CONFLICT (name) DO NOTHING",
+            tag,
+            now
+        )
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+#[tracing::instrument(name = "Get all tags", skip(pool))]
+pub async fn all_tags(pool: &SqliteInstrumentedPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT name FROM tags ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+#[tracing::instrument(name = "Get the tags of a subscriber", skip(pool))]
+pub async fn subscriber_tags(
+    pool: &SqliteInstrumentedPool,
+    subscriber_uuid: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT tag FROM subscriber_tags WHERE subscriber_uuid = $1 ORDER BY tag",
+        subscriber_uuid
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace the tags of a subscriber, unknown tags are ignored.
+#[tracing::instrument(name = "Set the tags of a subscriber", skip(transaction))]
+pub async fn set_subscriber_tags(
+    transaction: &mut SqliteInstrumentedTransaction,
+    subscriber_uuid: &str,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM subscriber_tags WHERE subscriber_uuid = $1",
+        subscriber_uuid
+    )
+    .execute(&mut *transaction)
+    .await?;
+    for tag in tags {
+        sqlx::query!(
+            r#"
+            INSERT INTO subscriber_tags (subscriber_uuid, tag)
+            SELECT $1, name FROM tags WHERE name = $2
+            ON CONFLICT DO NOTHING
+            "#,
+            subscriber_uuid,
+            tag
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// The tags must exist, see [`all_tags`].
+#[tracing::instrument(name = "Set the tags of a newsletter issue", skip(transaction))]
+pub async fn set_issue_tags(
+    transaction: &mut SqliteInstrumentedTransaction,
+    newsletter_issue_uuid: &str,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    for tag in tags {
+        sqlx::query!(
+            r#"
+            INSERT INTO newsletter_issue_tags (newsletter_issue_uuid, tag)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            newsletter_issue_uuid,
+            tag
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    Ok(())
+}
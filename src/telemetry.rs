@@ -1,13 +1,29 @@
+use anyhow::Context;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use std::sync::OnceLock;
 use tokio::task::JoinHandle;
 use tracing::subscriber::set_global_default;
 use tracing::Subscriber;
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
+use tracing_subscriber::filter::ParseError;
 use tracing_subscriber::fmt::MakeWriter;
-use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Registry};
+use tracing_subscriber::{layer::SubscriberExt, reload, EnvFilter, Registry};
+
+use crate::routes::error_chain_fmt;
 
 /// Compose multiple layers into a `tracing`'s subscriber.
 ///
+/// `env_filter` is only used when neither `APP_LOG_FILTER` nor `RUST_LOG` is
+/// set, and the filter can later be changed through the returned handle.
+///
 /// # Implementation Notes
 ///
 /// We are using `impl Subscriber` as return type to avoid having to spell out the actual
@@ -16,17 +32,77 @@ pub fn get_subscriber<Sink>(
     name: String,
     env_filter: String,
     sink: Sink,
-) -> impl Subscriber + Sync + Send
+) -> (impl Subscriber + Sync + Send, LogFilterHandle)
 where
     Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
 {
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(env_filter));
+    let (env_filter, log_filter) = reloadable_filter(env_filter);
     let formatting_layer = BunyanFormattingLayer::new(name, sink);
-    Registry::default()
+    let subscriber = Registry::default()
         .with(env_filter)
         .with(JsonStorageLayer)
-        .with(formatting_layer)
+        .with(formatting_layer);
+    (subscriber, log_filter)
+}
+
+/// Swaps the filter of one subscriber while it keeps running.
+///
+/// The filter sits in a `reload::Layer`: the subscriber only holds it behind a
+/// lock, and the `reload::Handle` in here can replace it. The handle is weak,
+/// it stops working once its subscriber is dropped.
+#[derive(Clone)]
+pub struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
+impl LogFilterHandle {
+    pub fn set(&self, directives: &str) -> Result<(), LogFilterError> {
+        let filter = EnvFilter::try_new(directives)?;
+        self.0
+            .reload(filter)
+            .map_err(|_| LogFilterError::NoSubscriber)
+    }
+
+    /// The filter currently in effect, `None` once the subscriber is gone.
+    pub fn current(&self) -> Option<String> {
+        self.0.with_current(|filter| filter.to_string()).ok()
+    }
+}
+
+/// `APP_LOG_FILTER` takes precedence over `RUST_LOG`, which takes precedence
+/// over the default of the caller. Both variables use the `EnvFilter` syntax.
+fn reloadable_filter(
+    default_filter: String,
+) -> (reload::Layer<EnvFilter, Registry>, LogFilterHandle) {
+    let env_filter = std::env::var("APP_LOG_FILTER")
+        .ok()
+        .and_then(|directives| match EnvFilter::try_new(&directives) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                // the subscriber isn't there yet to report it
+                eprintln!(
+                    "Ignoring the invalid APP_LOG_FILTER `{}`: {}",
+                    directives, e
+                );
+                None
+            }
+        })
+        .or_else(|| EnvFilter::try_from_default_env().ok())
+        .unwrap_or_else(|| EnvFilter::new(default_filter));
+    let (layer, handle) = reload::Layer::new(env_filter);
+    (layer, LogFilterHandle(handle))
+}
+
+#[derive(thiserror::Error)]
+pub enum LogFilterError {
+    #[error("Invalid log filter")]
+    InvalidFilter(#[from] ParseError),
+    #[error("No subscriber is running")]
+    NoSubscriber,
+}
+
+impl std::fmt::Debug for LogFilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
 }
 
 /// Register a subscriber as global default to process span data.
@@ -37,6 +113,55 @@ pub fn init_subscriber(subscriber: impl Subscriber + Sync + Send) {
     set_global_default(subscriber).expect("Failed to set subscriber");
 }
 
+/// Same as [`get_subscriber`], but spans are also exported to the OTLP gRPC
+/// collector listening on `otel_endpoint`, when there is one.
+///
+/// The tracer provider comes back alongside the filter handle, the spans it
+/// still holds are only exported once it is shut down.
+pub fn get_otel_subscriber<Sink>(
+    name: String,
+    env_filter: String,
+    sink: Sink,
+    otel_endpoint: Option<&str>,
+) -> anyhow::Result<(
+    impl Subscriber + Sync + Send,
+    LogFilterHandle,
+    Option<TracerProvider>,
+)>
+where
+    Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let tracer_provider = otel_endpoint
+        .map(|endpoint| otlp_tracer_provider(&name, endpoint))
+        .transpose()?;
+    let otel_layer = tracer_provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(name.clone())));
+    let (env_filter, log_filter) = reloadable_filter(env_filter);
+    let formatting_layer = BunyanFormattingLayer::new(name, sink);
+    let subscriber = Registry::default()
+        .with(env_filter)
+        .with(JsonStorageLayer)
+        .with(formatting_layer)
+        .with(otel_layer);
+    Ok((subscriber, log_filter, tracer_provider))
+}
+
+fn otlp_tracer_provider(name: &str, endpoint: &str) -> anyhow::Result<TracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .context("Failed to build the OTLP span exporter.")?;
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            name.to_string(),
+        )]))
+        .build())
+}
+
 pub fn spawn_blocking_with_tracing<F, R>(f: F) -> JoinHandle<R>
 where
     F: FnOnce() -> R + Send + 'static,
@@ -45,3 +170,62 @@ where
     let current_span = tracing::Span::current();
     tokio::task::spawn_blocking(move || current_span.in_scope(f))
 }
+
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the Prometheus recorder as the global `metrics` recorder.
+///
+/// The recorder can only be installed once per process, so every call after the
+/// first one (e.g. when spawning multiple applications in tests) gets a handle
+/// to the same recorder.
+pub fn prometheus_handle() -> PrometheusHandle {
+    PROMETHEUS_HANDLE
+        .get_or_init(|| {
+            let handle = PrometheusBuilder::new()
+                .install_recorder()
+                .expect("Failed to install the Prometheus recorder");
+            metrics::describe_counter!(
+                "newzletter_http_requests_total",
+                "Number of HTTP requests handled"
+            );
+            metrics::describe_counter!(
+                "newzletter_emails_sent_total",
+                "Number of newsletter emails delivered"
+            );
+            metrics::describe_counter!(
+                "newzletter_emails_failed_total",
+                "Number of newsletter email delivery attempts that failed"
+            );
+            metrics::describe_gauge!(
+                "newzletter_subscriptions_total",
+                "Number of subscriptions by status"
+            );
+            metrics::describe_gauge!(
+                "newzletter_delivery_queue_depth",
+                "Number of deliveries waiting in the queue"
+            );
+            handle
+        })
+        .clone()
+}
+
+/// Count every HTTP request by method, matched route and status code.
+pub async fn track_http_requests(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    // use the route template rather than the raw path to keep the cardinality
+    // bounded, anything that wasn't routed ends up in the static files fallback
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "fallback".to_owned());
+    let response = next.run(request).await;
+    metrics::counter!(
+        "newzletter_http_requests_total",
+        "method" => method,
+        "route" => route,
+        "status_code" => response.status().as_u16().to_string()
+    )
+    .increment(1);
+    response
+}
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use reqwest::{Client, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::routes::error_chain_fmt;
+
+/// Verifies the Cloudflare Turnstile tokens submitted along with the
+/// subscription form.
+pub struct TurnstileClient {
+    http_client: Arc<Client>,
+    base_url: String,
+    secret: SecretString,
+    timeout: std::time::Duration,
+    /// Every token passes without a call to Cloudflare.
+    test_mode: bool,
+}
+
+#[derive(Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+    #[serde(rename = "error-codes", default)]
+    error_codes: Vec<String>,
+}
+
+#[derive(thiserror::Error)]
+pub enum TurnstileError {
+    #[error("Failed to reach the Turnstile API.")]
+    NetworkError(#[source] reqwest::Error),
+    #[error("The Turnstile token was rejected: {0:?}.")]
+    VerificationFailed(Vec<String>),
+    #[error("The Turnstile API returned an invalid response.")]
+    InvalidResponse(#[source] reqwest::Error),
+}
+
+impl std::fmt::Debug for TurnstileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl TurnstileClient {
+    pub fn new(
+        http_client: Arc<Client>,
+        base_url: String,
+        secret: SecretString,
+        timeout: std::time::Duration,
+        test_mode: bool,
+    ) -> Self {
+        Self {
+            http_client,
+            base_url,
+            secret,
+            timeout,
+            test_mode,
+        }
+    }
+
+    #[tracing::instrument(name = "Verifying Turnstile token", skip(self, token))]
+    pub async fn verify(&self, token: &str) -> Result<(), TurnstileError> {
+        if self.test_mode {
+            return Ok(());
+        }
+        let base = Url::parse(&self.base_url).expect("url from config is wrong");
+        let url = base
+            .join("turnstile/v0/siteverify")
+            .expect("can't append the siteverify path to the turnstile url from config");
+        let response = self
+            .http_client
+            .post(url)
+            .timeout(self.timeout)
+            .form(&[("secret", self.secret.expose_secret()), ("response", token)])
+            .send()
+            .await
+            .map_err(TurnstileError::NetworkError)?
+            .error_for_status()
+            .map_err(TurnstileError::InvalidResponse)?
+            .json::<SiteVerifyResponse>()
+            .await
+            .map_err(TurnstileError::InvalidResponse)?;
+
+        if response.success {
+            tracing::info!("Turnstile verification successful");
+            Ok(())
+        } else {
+            Err(TurnstileError::VerificationFailed(response.error_codes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+    use secrecy::SecretString;
+    use std::sync::Arc;
+    use wiremock::{
+        matchers::{body_string_contains, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::{TurnstileClient, TurnstileError};
+
+    fn turnstile_client(base_url: String) -> TurnstileClient {
+        TurnstileClient::new(
+            Arc::new(reqwest::Client::new()),
+            base_url,
+            SecretString::from("my-turnstile-secret"),
+            std::time::Duration::from_millis(200),
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn verify_sends_the_secret_and_the_token() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let turnstile_client = turnstile_client(mock_server.uri());
+
+        Mock::given(path("/turnstile/v0/siteverify"))
+            .and(method("POST"))
+            .and(body_string_contains("secret=my-turnstile-secret"))
+            .and(body_string_contains("response=a-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": true,
+                "error-codes": []
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = turnstile_client.verify("a-token").await;
+
+        // Assert
+        assert_ok!(outcome);
+    }
+
+    #[tokio::test]
+    async fn verify_accepts_any_token_in_test_mode_without_calling_turnstile() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let turnstile_client = TurnstileClient::new(
+            Arc::new(reqwest::Client::new()),
+            mock_server.uri(),
+            SecretString::from("my-turnstile-secret"),
+            std::time::Duration::from_millis(200),
+            true,
+        );
+
+        Mock::given(path("/turnstile/v0/siteverify"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": false,
+                "error-codes": ["invalid-input-response"]
+            })))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = turnstile_client.verify("").await;
+
+        // Assert
+        assert_ok!(outcome);
+    }
+
+    #[tokio::test]
+    async fn verify_fails_if_the_token_is_rejected() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let turnstile_client = turnstile_client(mock_server.uri());
+
+        Mock::given(path("/turnstile/v0/siteverify"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": false,
+                "error-codes": ["invalid-input-response"]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = turnstile_client.verify("a-token").await;
+
+        // Assert
+        assert!(matches!(
+            outcome,
+            Err(TurnstileError::VerificationFailed(codes)) if codes == ["invalid-input-response"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_fails_if_the_response_is_not_json() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let turnstile_client = turnstile_client(mock_server.uri());
+
+        Mock::given(path("/turnstile/v0/siteverify"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = turnstile_client.verify("a-token").await;
+
+        // Assert
+        assert!(matches!(outcome, Err(TurnstileError::InvalidResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn verify_times_out_if_the_server_takes_too_long() {
+        // Arrange
+        let mock_server = MockServer::start().await;
+        let turnstile_client = turnstile_client(mock_server.uri());
+
+        Mock::given(path("/turnstile/v0/siteverify"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(180)))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Act
+        let outcome = turnstile_client.verify("a-token").await;
+
+        // Assert
+        assert_err!(outcome);
+    }
+}
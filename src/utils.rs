@@ -1,18 +1,77 @@
-use axum::http::StatusCode;
-use axum::response::{IntoResponse, Response};
+use axum::http::{header::ACCEPT, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Json;
+use axum_messages::Messages;
 
+use crate::error::AppError;
+
+/// Shorthand for `map_err` that renders an [`AppError::InternalError`],
+/// handlers returning `AppError` themselves can use `?` on `anyhow` errors.
 pub fn e500<T>(e: T) -> Response
 where
     T: std::fmt::Debug + std::fmt::Display + 'static,
 {
-    tracing::error!(cause_chain = ?e);
-    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    AppError::InternalError(anyhow::anyhow!("{:?}", e)).into_response()
 }
 
+/// Shorthand for `map_err` that renders an [`AppError::BadRequest`] with the
+/// error message.
 pub fn e400<T>(e: T) -> Response
 where
     T: std::fmt::Debug + std::fmt::Display + 'static,
 {
-    tracing::error!(cause_chain = ?e);
-    StatusCode::BAD_REQUEST.into_response()
+    AppError::BadRequest(e.to_string()).into_response()
+}
+
+/// Browsers submitting a form are redirected, clients asking for JSON get
+/// the outcome in the body instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResponseFormat {
+    Html,
+    Json,
+}
+
+impl ResponseFormat {
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        let wants_json = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .any(|h| h.contains("application/json"));
+        if wants_json {
+            Self::Json
+        } else {
+            Self::Html
+        }
+    }
+}
+
+/// Answers an admin form submission.
+///
+/// Browsers are redirected to `redirect_to`, where `outcome` is shown as a
+/// flash message. API callers get `{ "ok": true }`, or a `400` with
+/// `{ "error": "bad_request", "details": "<message>" }`, and nothing is
+/// left in their session.
+pub fn respond_for_content_type(
+    headers: &HeaderMap,
+    messages: Messages,
+    redirect_to: &str,
+    outcome: Result<&str, &str>,
+) -> Response {
+    match (ResponseFormat::negotiate(headers), outcome) {
+        (ResponseFormat::Json, Ok(_)) => Json(serde_json::json!({ "ok": true })).into_response(),
+        (ResponseFormat::Json, Err(details)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "bad_request", "details": details })),
+        )
+            .into_response(),
+        (ResponseFormat::Html, Ok(message)) => {
+            messages.info(message);
+            Redirect::to(redirect_to).into_response()
+        }
+        (ResponseFormat::Html, Err(message)) => {
+            messages.error(message);
+            Redirect::to(redirect_to).into_response()
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use crate::helpers::{spawn_app, TestApp, TestUser};
+
+async fn login_as_editor(app: &TestApp) -> TestUser {
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(app).await;
+    editor
+}
+
+#[tokio::test]
+async fn editors_can_publish_newsletters() {
+    // Arrange
+    let app = spawn_app().await;
+    login_as_editor(&app).await;
+
+    // Act
+    let form_response = app.get_publish_newsletter().await;
+    let dashboard_response = app.get_admin_dashboard().await;
+
+    // Assert
+    assert_eq!(form_response.status().as_u16(), 200);
+    assert_eq!(dashboard_response.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_change_passwords() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = login_as_editor(&app).await;
+    let new_password = uuid::Uuid::new_v4().to_string();
+
+    // Act
+    let form_response = app.get_change_password().await;
+    let response = app
+        .post_change_password(&serde_json::json!({
+            "current_password": &editor.password,
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(form_response.status().as_u16(), 403);
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_export_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+    login_as_editor(&app).await;
+
+    // Act
+    let response = app.get_subscribers_export(None).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_import_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+    login_as_editor(&app).await;
+
+    // Act
+    let response = app
+        .post_subscribers_import("name,email\nursula,ursula@example.com\n")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn admins_can_change_passwords_and_export_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let password_response = app.get_change_password().await;
+    let export_response = app.get_subscribers_export(None).await;
+
+    // Assert
+    assert_eq!(password_response.status().as_u16(), 200);
+    assert_eq!(export_response.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
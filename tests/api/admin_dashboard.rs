@@ -45,3 +45,28 @@ async fn logout_clears_session_state() {
 
     app.cleanup_test_db().await.unwrap()
 }
+
+#[tokio::test]
+async fn the_dashboard_shows_when_the_user_last_logged_in() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_logout().await;
+
+    // Act
+    app.test_user.login(&app).await;
+
+    // Assert
+    let user_id = app.test_user.uuid.to_string();
+    let last_login_at =
+        sqlx::query_scalar!("SELECT last_login_at FROM users WHERE uuid = $1", user_id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap()
+            .expect("The login time was not recorded");
+    let html_page = app.get_admin_dashboard_html().await;
+    assert!(html_page.contains(&format!("Last login: {}", last_login_at)));
+    assert!(!html_page.contains("First login"));
+
+    app.cleanup_test_db().await.unwrap()
+}
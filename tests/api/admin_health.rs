@@ -0,0 +1,37 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+use std::time::Duration;
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_the_service_health() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_health().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_service_health_reports_the_runtime_status() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    // the uptime is counted in whole seconds
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    // Act
+    let response = app.get_admin_health().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "ok");
+    assert!(body["uptime_seconds"].as_u64().unwrap() > 0);
+    assert!(body["db_pool_size"].as_u64().unwrap() > 0);
+    assert_eq!(body["queue_depth"], 0);
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    app.cleanup_test_db().await.unwrap();
+}
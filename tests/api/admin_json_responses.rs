@@ -0,0 +1,261 @@
+use crate::helpers::{spawn_app, TestApp};
+
+async fn assert_is_ok(response: reqwest::Response) {
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "ok": true }));
+}
+
+async fn assert_is_bad_request(response: reqwest::Response, details: &str) {
+    assert_eq!(response.status().as_u16(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "bad_request");
+    assert_eq!(body["details"], details);
+}
+
+fn blog_post(slug: &str) -> serde_json::Value {
+    serde_json::json!({
+        "slug": slug,
+        "title": "A post",
+        "description": "",
+        "markdown_content": "Some *Markdown*",
+    })
+}
+
+/// Schedule an issue through the JSON API and return its id, it can still be
+/// edited.
+async fn schedule_issue(app: &TestApp) -> String {
+    let response = app
+        .post_admin_form_as_json(
+            "/newsletters",
+            &serde_json::json!({
+                "title": "Newsletter title",
+                "text_content": "Newsletter body as plain text",
+                "html_content": "<p>Newsletter body as HTML</p>",
+                "scheduled_for": "2099-01-01T00:00",
+                "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            }),
+        )
+        .await;
+    assert_is_ok(response).await;
+    sqlx::query_scalar!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn changing_the_password_answers_with_json() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_admin_form_as_json(
+            "/password",
+            &serde_json::json!({
+                "current_password": &app.test_user.password,
+                "new_password": "a-new-password",
+                "new_password_check": "a-new-password",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_is_ok(response).await;
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_rejected_password_change_answers_with_the_reason() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let mismatch = app
+        .post_admin_form_as_json(
+            "/password",
+            &serde_json::json!({
+                "current_password": &app.test_user.password,
+                "new_password": "a-new-password",
+                "new_password_check": "another-new-password",
+            }),
+        )
+        .await;
+    let wrong_password = app
+        .post_admin_form_as_json(
+            "/password",
+            &serde_json::json!({
+                "current_password": "wrong-password",
+                "new_password": "a-new-password",
+                "new_password_check": "a-new-password",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_is_bad_request(
+        mismatch,
+        "You entered two different new passwords - the field values must match.",
+    )
+    .await;
+    assert_is_bad_request(wrong_password, "The current password is incorrect.").await;
+    // nothing is left to show on the next page
+    let html_page = app.get_change_password_html().await;
+    assert!(!html_page.contains("The current password is incorrect."));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn publishing_a_newsletter_answers_with_json() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act & Assert
+    schedule_issue(&app).await;
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invalid_newsletter_is_rejected_with_a_json_error() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_admin_form_as_json(
+            "/newsletters",
+            &serde_json::json!({
+                "title": "Newsletter title",
+                "text_content": "Newsletter body as plain text",
+                "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "bad_request");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editing_a_newsletter_answers_with_json() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = schedule_issue(&app).await;
+
+    // Act
+    let edited = app
+        .post_admin_form_as_json(
+            &format!("/newsletters/{}/edit", issue_id),
+            &serde_json::json!({
+                "title": "Fixed title",
+                "text_content": "Fixed body",
+                "html_content": "<p>Fixed body</p>",
+            }),
+        )
+        .await;
+    let rejected = app
+        .post_admin_form_as_json(
+            &format!("/newsletters/{}/edit", issue_id),
+            &serde_json::json!({
+                "title": "",
+                "text_content": "Fixed body",
+                "html_content": "<p>Fixed body</p>",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_is_ok(edited).await;
+    assert_eq!(rejected.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn blog_posts_are_managed_with_json() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let created = app
+        .post_admin_form_as_json("/blog", &blog_post("json-post"))
+        .await;
+    let updated = app
+        .post_admin_form_as_json("/blog/json-post", &blog_post("json-post"))
+        .await;
+    let published = app
+        .post_admin_form_as_json("/blog/json-post/publish", &serde_json::json!({}))
+        .await;
+
+    // Assert
+    assert_is_ok(created).await;
+    assert_is_ok(updated).await;
+    assert_is_ok(published).await;
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_blog_posts_are_rejected_with_the_reason() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_admin_form_as_json("/blog", &blog_post("json-post"))
+        .await;
+
+    // Act
+    let invalid_slug = app
+        .post_admin_form_as_json("/blog", &blog_post("Not A Slug"))
+        .await;
+    let taken_slug = app
+        .post_admin_form_as_json("/blog", &blog_post("json-post"))
+        .await;
+    let empty_update = app
+        .post_admin_form_as_json(
+            "/blog/json-post",
+            &serde_json::json!({ "title": "", "markdown_content": "Some *Markdown*" }),
+        )
+        .await;
+
+    // Assert
+    assert_is_bad_request(
+        invalid_slug,
+        "The slug may only contain lowercase letters, digits and single dashes.",
+    )
+    .await;
+    assert_is_bad_request(taken_slug, "A blog post with this slug already exists.").await;
+    assert_is_bad_request(empty_update, "The title can't be empty.").await;
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn logging_out_answers_with_json() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_admin_form_as_json("/logout", &serde_json::json!({}))
+        .await;
+
+    // Assert
+    assert_is_ok(response).await;
+
+    app.cleanup_test_db().await.unwrap();
+}
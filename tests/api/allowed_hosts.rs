@@ -0,0 +1,104 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app, spawn_app_with, FormData};
+
+#[tokio::test]
+async fn requests_for_an_allowed_host_are_served() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .header("Host", format!("localhost:{}", app.port))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn requests_for_an_unknown_host_are_rejected_with_a_421() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .header("Host", "evil.example.com")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 421);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn only_the_configured_hosts_are_allowed() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.application.allowed_hosts = vec!["newsletter.example.com".to_string()];
+    })
+    .await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 421);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn confirmation_links_point_at_the_requested_host() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    let body = FormData {
+        name: Some("abood".to_string()),
+        email: Some("abood@example.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+
+    // Act
+    app.api_client
+        .post(&format!("{}/subscriptions", &app.address))
+        .header("Host", format!("localhost:{}", app.port))
+        .header("X-CSRF-Token", app.csrf_token().await)
+        .form(&body)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .error_for_status()
+        .unwrap();
+
+    // Assert
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let email: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let expected = format!("http://localhost:{}/subscriptions/confirm?", app.port);
+    assert!(email["HtmlBody"].as_str().unwrap().contains(&expected));
+    assert!(email["TextBody"].as_str().unwrap().contains(&expected));
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,172 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp, TestUser};
+
+/// A client without cookies, so only the API key can authenticate it.
+fn api_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+}
+
+async fn create_key(app: &TestApp, expires_in_days: Option<u32>) -> String {
+    let response = app
+        .post_api_key(&serde_json::json!({
+            "name": "ci",
+            "expires_in_days": expires_in_days,
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["key"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn a_logged_in_user_can_create_an_api_key() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_api_key(&serde_json::json!({ "name": "ci", "expires_in_days": 30 }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let key = body["key"].as_str().unwrap();
+    assert!(key.starts_with("nzl_"));
+    assert_eq!(body["name"], "ci");
+    assert!(body["expires_at"].is_string());
+    let stored = sqlx::query!("SELECT key_hash, user_uuid FROM api_keys")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    // only the hash of the key is kept
+    assert_ne!(stored.key_hash, key);
+    assert_eq!(stored.user_uuid, app.test_user.uuid.to_string());
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_create_an_api_key() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.post_api_key(&serde_json::json!({ "name": "ci" })).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_api_key_authenticates_admin_requests() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let key = create_key(&app, None).await;
+
+    // Act
+    let response = api_client()
+        .get(format!("{}/admin/unsubscribe-reasons", app.address))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let last_used_at = sqlx::query_scalar!("SELECT last_used_at FROM api_keys")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(last_used_at.is_some());
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn api_key_requests_do_not_need_a_csrf_token() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let key = create_key(&app, None).await;
+
+    // Act
+    let response = api_client()
+        .post(format!("{}/admin/api-keys", app.address))
+        .bearer_auth(&key)
+        .json(&serde_json::json!({ "name": "rotated" }))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_expired_api_key_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let key = create_key(&app, Some(1)).await;
+    sqlx::query!("UPDATE api_keys SET expires_at = '2020-01-01 00:00:00 UTC'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = api_client()
+        .get(format!("{}/admin/unsubscribe-reasons", app.address))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unknown_api_key_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = api_client()
+        .get(format!("{}/admin/unsubscribe-reasons", app.address))
+        .bearer_auth("nzl_not-a-key")
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_editor_api_key_cannot_reach_admin_only_routes() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+    let key = create_key(&app, None).await;
+
+    // Act
+    let response = api_client()
+        .get(format!("{}/admin/unsubscribe-reasons", app.address))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,90 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn the_archive_lists_sent_issues_to_anyone() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.publish_issue("October news!", "<p>October</p>").await;
+    app.publish_issue("November news", "<p>November</p>").await;
+    app.post_logout().await;
+
+    // Act
+    let response = app.get_archive().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    let november = html.find(r#"href="/archive/november-news""#).unwrap();
+    let october = html.find(r#"href="/archive/october-news""#).unwrap();
+    assert!(november < october);
+    assert!(html.contains("October news!"));
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn issues_with_the_same_title_get_numbered_slugs() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    app.publish_issue("Weekly digest", "<p>First</p>").await;
+    app.publish_issue("Weekly digest", "<p>Second</p>").await;
+    app.publish_issue("Weekly digest", "<p>Third</p>").await;
+
+    // Assert
+    for (slug, body) in [
+        ("weekly-digest", "<p>First</p>"),
+        ("weekly-digest-2", "<p>Second</p>"),
+        ("weekly-digest-3", "<p>Third</p>"),
+    ] {
+        let response = app.get_archive_issue(slug).await;
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(response.text().await.unwrap().contains(body));
+    }
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn scheduled_issues_are_not_archived_yet() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Future issue",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "scheduled_for": "2999-01-01T09:00",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // Act
+    let archive = app.get_archive().await.text().await.unwrap();
+    let response = app.get_archive_issue("future-issue").await;
+
+    // Assert
+    assert!(!archive.contains("Future issue"));
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn unknown_slugs_are_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_archive_issue("no-such-issue").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap()
+}
@@ -0,0 +1,247 @@
+use std::time::Duration;
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp, TestUser};
+
+/// Audit events are written in the background, so give them a moment to land.
+async fn wait_for_audit_entries(app: &TestApp, count: usize) -> Vec<serde_json::Value> {
+    for _ in 0..50 {
+        let body: serde_json::Value = app.get_audit_log(1, 50).await.json().await.unwrap();
+        let entries = body["entries"].as_array().unwrap().clone();
+        if entries.len() >= count {
+            return entries;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Expected {} audit log entries.", count);
+}
+
+#[tokio::test]
+async fn changing_the_password_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let new_password = uuid::Uuid::new_v4().to_string();
+
+    // Act
+    let response = app
+        .post_change_password(&serde_json::json!({
+            "current_password": &app.test_user.password,
+            "new_password": &new_password,
+            "new_password_check": &new_password,
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/password");
+
+    // Assert
+    let entries = wait_for_audit_entries(&app, 1).await;
+    assert_eq!(entries[0]["action"], "change_password");
+    assert_eq!(entries[0]["target_type"], "user");
+    assert_eq!(entries[0]["user_uuid"], app.test_user.uuid.to_string());
+    assert_eq!(entries[0]["ip_address"], "127.0.0.1");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn publishing_a_newsletter_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string()
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // Assert
+    let entries = wait_for_audit_entries(&app, 1).await;
+    assert_eq!(entries[0]["action"], "publish_newsletter");
+    assert_eq!(entries[0]["target_type"], "newsletter_issue");
+    assert!(entries[0]["target_id"].is_string());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_forwarded_client_address_is_recorded() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .api_client
+        .post(&format!("{}/admin/logout", &app.address))
+        // `192.0.2.1` was made up by the client, the proxy appended the rest
+        .header("X-Forwarded-For", "192.0.2.1, 203.0.113.7")
+        .header("X-CSRF-Token", app.csrf_token().await)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_is_redirect_to(&response, "/login");
+
+    // Assert
+    app.test_user.login(&app).await;
+    let entries = wait_for_audit_entries(&app, 1).await;
+    assert_eq!(entries[0]["action"], "log_out");
+    assert_eq!(entries[0]["ip_address"], "203.0.113.7");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_read_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app.get_audit_log(1, 50).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_audit_log_rejects_an_invalid_page_size() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_audit_log(1, 1000).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editing_a_newsletter_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    // scheduled issues can be edited until they are delivered
+    app.post_publish_newsletter(&serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        "scheduled_for": "2999-01-01T00:00:00Z",
+    }))
+    .await;
+    let issue_id = wait_for_audit_entries(&app, 1).await[0]["target_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Act
+    let response = app
+        .post_edit_newsletter_issue(
+            &issue_id,
+            &serde_json::json!({
+                "title": "Fixed newsletter title",
+                "text_content": "Fixed newsletter body as plain text",
+                "html_content": "<p>Fixed newsletter body as HTML</p>",
+            }),
+        )
+        .await;
+    assert_is_redirect_to(&response, &format!("/admin/newsletters/{}/edit", issue_id));
+
+    // Assert
+    let entries = wait_for_audit_entries(&app, 2).await;
+    assert_eq!(entries[0]["action"], "edit_newsletter");
+    assert_eq!(entries[0]["target_type"], "newsletter_issue");
+    assert_eq!(entries[0]["target_id"], issue_id.as_str());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn changing_a_subscriber_status_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let uuid = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+
+    // Act
+    let response = app
+        .patch_subscriber_status(&uuid, &serde_json::json!({ "status": "unsubscribed" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Assert
+    let entries = wait_for_audit_entries(&app, 1).await;
+    assert_eq!(entries[0]["action"], "change_subscriber_status");
+    assert_eq!(entries[0]["target_type"], "subscription");
+    assert_eq!(entries[0]["target_id"], uuid.as_str());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn changing_the_log_filter_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    // the filter of the log level endpoint tests, the subscriber is shared
+    let response = app
+        .post_log_level(&serde_json::json!({ "filter": "newzletter=debug" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    // Assert
+    let entries = wait_for_audit_entries(&app, 1).await;
+    assert_eq!(entries[0]["action"], "change_log_level");
+    assert_eq!(entries[0]["target_type"], "log_filter");
+    assert_eq!(entries[0]["target_id"], "newzletter=debug");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn deleting_a_subscriber_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let uuid = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+
+    // Act
+    let response = app.delete_subscriber(&uuid).await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    // Assert
+    let entries = wait_for_audit_entries(&app, 1).await;
+    assert_eq!(entries[0]["action"], "delete_subscriber");
+    assert_eq!(entries[0]["target_type"], "subscription");
+    assert_eq!(entries[0]["target_id"], uuid.as_str());
+
+    app.cleanup_test_db().await.unwrap();
+}
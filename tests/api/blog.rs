@@ -0,0 +1,277 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+/// Write a draft as the test admin and return its slug.
+async fn create_draft(app: &TestApp, title: &str) -> String {
+    let slug = format!("post-{}", uuid::Uuid::new_v4().simple());
+    let response = app
+        .post_blog_post(&serde_json::json!({
+            "slug": &slug,
+            "title": title,
+            "description": "A post written from the admin area",
+            "markdown_content": "Hello **world**",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/blog");
+    slug
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_blog_posts() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let list_response = app.get_admin_blog().await;
+    let create_response = app
+        .post_blog_post(&serde_json::json!({
+            "slug": "sneaky-post",
+            "title": "Sneaky",
+            "markdown_content": "Not allowed",
+        }))
+        .await;
+    let publish_response = app.post_toggle_blog_post("sneaky-post").await;
+
+    // Assert
+    assert_is_redirect_to(&list_response, "/login");
+    assert_is_redirect_to(&create_response, "/login");
+    assert_is_redirect_to(&publish_response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn new_posts_are_drafts_hidden_from_the_public_blog() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let slug = create_draft(&app, "A very secret draft").await;
+
+    // Assert
+    let admin_html = app.get_admin_blog_html().await;
+    assert!(admin_html.contains("A very secret draft"));
+    assert!(admin_html.contains("Draft"));
+    assert!(!app
+        .get_blog_index_html()
+        .await
+        .contains("A very secret draft"));
+    assert_eq!(app.get_blog_post(&slug).await.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn publishing_and_unpublishing_toggles_public_visibility() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let slug = create_draft(&app, "Fresh off the press").await;
+
+    // Act - Part 1 - Publish
+    let response = app.post_toggle_blog_post(&slug).await;
+    assert_is_redirect_to(&response, "/admin/blog");
+    assert!(app
+        .get_admin_blog_html()
+        .await
+        .contains("&quot;Fresh off the press&quot; has been published."));
+
+    // Assert - Part 1
+    assert!(app
+        .get_blog_index_html()
+        .await
+        .contains("Fresh off the press"));
+    let response = app.get_blog_post(&slug).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("<strong>world</strong>"));
+
+    // Act - Part 2 - Unpublish
+    let response = app.post_toggle_blog_post(&slug).await;
+    assert_is_redirect_to(&response, "/admin/blog");
+
+    // Assert - Part 2
+    assert!(!app
+        .get_blog_index_html()
+        .await
+        .contains("Fresh off the press"));
+    assert_eq!(app.get_blog_post(&slug).await.status().as_u16(), 404);
+    assert!(app
+        .get_admin_blog_html()
+        .await
+        .contains("Fresh off the press"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn republishing_keeps_the_first_publication_date() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let slug = create_draft(&app, "Published twice").await;
+    app.post_toggle_blog_post(&slug).await;
+    let first_published_at = get_published_at(&app, &slug).await;
+
+    // Act
+    app.post_toggle_blog_post(&slug).await;
+    app.post_toggle_blog_post(&slug).await;
+
+    // Assert
+    assert_eq!(get_published_at(&app, &slug).await, first_published_at);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_post_can_be_edited() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let slug = create_draft(&app, "Before").await;
+    app.post_toggle_blog_post(&slug).await;
+
+    // Act
+    let response = app.get_edit_blog_post(&slug).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains("Hello **world**"));
+    let response = app
+        .post_update_blog_post(
+            &slug,
+            &serde_json::json!({
+                "title": "After",
+                "description": "",
+                "markdown_content": "Goodbye",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/blog");
+    let html = app.get_blog_post(&slug).await.text().await.unwrap();
+    assert!(html.contains("After"));
+    assert!(html.contains("Goodbye"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_slugs_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    for slug in ["", "Upper-Case", "two--dashes", "../escape", "astro-rust"] {
+        // Act
+        let response = app
+            .post_blog_post(&serde_json::json!({
+                "slug": slug,
+                "title": "Title",
+                "markdown_content": "Content",
+            }))
+            .await;
+
+        // Assert
+        assert_is_redirect_to(&response, "/admin/blog/new");
+    }
+    let count = sqlx::query_scalar!("SELECT COUNT(*) FROM blog_posts")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_posts_cannot_be_published() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.post_toggle_blog_post("does-not-exist").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn reads_of_a_post_show_up_in_its_stats() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let slug = create_draft(&app, "Worth reading").await;
+    app.post_toggle_blog_post(&slug).await;
+
+    // Act
+    for _ in 0..3 {
+        let response = app.get_blog_post(&slug).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    // Assert
+    // views are recorded in the background, give them a moment to land
+    let mut stats = serde_json::Value::Null;
+    for _ in 0..20 {
+        stats = app.get_blog_post_stats(&slug).await.json().await.unwrap();
+        if stats["total_views"].as_u64().unwrap() >= 3 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert!(stats["total_views"].as_u64().unwrap() >= 3);
+    assert!(stats["views_last_7_days"].as_u64().unwrap() >= 3);
+    // every read came from the same address
+    assert_eq!(stats["unique_ips_today"], 1);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn missing_posts_are_not_counted() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_blog_post("does-not-exist").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    let stats: serde_json::Value = app
+        .get_blog_post_stats("does-not-exist")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["total_views"], 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_blog_post_stats() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_blog_post_stats("any-post").await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+async fn get_published_at(app: &TestApp, slug: &str) -> Option<String> {
+    sqlx::query_scalar!("SELECT published_at FROM blog_posts WHERE slug = $1", slug)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
@@ -0,0 +1,132 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn announcement() -> serde_json::Value {
+    serde_json::json!({
+        "title": "Quick announcement",
+        "text_content": "Announcement body as plain text",
+        "html_content": "<p>Announcement body as HTML</p>",
+    })
+}
+
+#[tokio::test]
+async fn a_broadcast_is_queued_for_every_confirmed_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    for _ in 0..3 {
+        app.create_confirmed_subscriber().await;
+    }
+    app.create_unconfirmed_subscriber().await;
+
+    // Act
+    let response = app.post_broadcast(&announcement()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let confirmed = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!: i64" FROM subscriptions WHERE status = 'confirmed'"#
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    let queued =
+        sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!: i64" FROM issue_delivery_queue"#)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(confirmed, 3);
+    assert_eq!(queued, confirmed);
+    assert_eq!(body["queued"], confirmed);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn broadcasts_are_delivered_and_stay_out_of_the_archive() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.create_confirmed_subscriber().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    app.post_broadcast(&announcement())
+        .await
+        .error_for_status()
+        .unwrap();
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let archive = app.get_archive().await.text().await.unwrap();
+    assert!(!archive.contains("Quick announcement"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn only_one_broadcast_is_sent_every_ten_minutes() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_broadcast(&announcement())
+        .await
+        .error_for_status()
+        .unwrap();
+
+    // Act
+    let response = app.post_broadcast(&announcement()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_broadcast_without_content_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_broadcast(&serde_json::json!({
+            "title": "Quick announcement",
+            "text_content": "",
+            "html_content": "<p>Announcement body as HTML</p>",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_broadcast() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.post_broadcast(&announcement()).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    let queued =
+        sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!: i64" FROM issue_delivery_queue"#)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(queued, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,86 @@
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+
+async fn content_encoding(app: &TestApp, path: &str, accept_encoding: &str) -> Option<String> {
+    let response = app
+        .api_client
+        .get(&format!("{}{}", &app.address, path))
+        .header("Accept-Encoding", accept_encoding)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 200);
+    response
+        .headers()
+        .get("Content-Encoding")
+        .map(|encoding| encoding.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn responses_are_gzipped_when_the_client_accepts_it() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let encoding = content_encoding(&app, "/blog", "gzip").await;
+
+    // Assert
+    assert_eq!(encoding.as_deref(), Some("gzip"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn brotli_is_preferred_over_gzip() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let encoding = content_encoding(&app, "/blog", "gzip, br").await;
+
+    // Assert
+    assert_eq!(encoding.as_deref(), Some("br"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn static_files_are_compressed() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let encoding = content_encoding(&app, "/about/", "gzip").await;
+
+    // Assert
+    assert_eq!(encoding.as_deref(), Some("gzip"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn small_responses_are_not_compressed() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let encoding = content_encoding(&app, "/health_check", "gzip").await;
+
+    // Assert
+    assert_eq!(encoding, None);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn compression_can_be_turned_off() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.compress_responses = false).await;
+
+    // Act
+    let encoding = content_encoding(&app, "/blog", "gzip").await;
+
+    // Assert
+    assert_eq!(encoding, None);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,94 @@
+use newzletter::configuration::Environment;
+
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+
+async fn spawn_production_app() -> TestApp {
+    spawn_app_with(|c| c.application.environment = Environment::Production).await
+}
+
+async fn csp_header(app: &TestApp, path: &str) -> String {
+    app.api_client
+        .get(&format!("{}{}", &app.address, path))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .headers()
+        .get("Content-Security-Policy")
+        .expect("The response has no Content-Security-Policy header.")
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn nonce(policy: &str) -> &str {
+    let start = policy.find("'nonce-").expect("The policy has no nonce.") + "'nonce-".len();
+    let end = start + policy[start..].find('\'').unwrap();
+    &policy[start..end]
+}
+
+#[tokio::test]
+async fn the_local_policy_is_permissive() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let policy = csp_header(&app, "/health_check").await;
+
+    // Assert
+    assert_eq!(policy, "default-src 'self' 'unsafe-inline'");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_production_policy_only_allows_scripts_with_a_fresh_nonce() {
+    // Arrange
+    let app = spawn_production_app().await;
+
+    // Act
+    let first = csp_header(&app, "/health_check").await;
+    let second = csp_header(&app, "/health_check").await;
+
+    // Assert
+    assert!(first.starts_with("default-src 'self'; script-src 'self' 'nonce-"));
+    assert!(first.contains("style-src 'self'"));
+    assert!(first.contains("img-src 'self' data:"));
+    assert!(!first.contains("unsafe-inline"));
+    assert_ne!(nonce(&first), nonce(&second));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn static_files_get_the_policy_too() {
+    // Arrange
+    let app = spawn_production_app().await;
+
+    // Act
+    let policy = csp_header(&app, "/about/").await;
+
+    // Assert
+    assert!(policy.contains("script-src 'self' 'nonce-"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn inline_scripts_carry_the_nonce_of_the_policy() {
+    // Arrange
+    let app = spawn_production_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_publish_newsletter().await;
+
+    // Assert
+    let policy = response.headers()["Content-Security-Policy"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let html = response.text().await.unwrap();
+    assert!(html.contains(&format!(r#"<script nonce="{}">"#, nonce(&policy))));
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,193 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn a_form_without_a_csrf_token_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.csrf_token().await;
+
+    // Act
+    let response = app
+        .api_client
+        .post(&format!("{}/login", &app.address))
+        .form(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password,
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    assert_is_redirect_to(&app.get_admin_dashboard().await, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_mismatched_csrf_token_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let wrong_token = "0".repeat(64);
+    assert_ne!(app.csrf_token().await, wrong_token);
+
+    // Act
+    let response = app
+        .api_client
+        .post(&format!("{}/admin/logout", &app.address))
+        .header("X-CSRF-Token", &wrong_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    // still logged in
+    assert_eq!(app.get_admin_dashboard().await.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_token_from_another_session_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    let other_client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let other_token = other_client
+        .get(&format!("{}/login", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .cookies()
+        .find(|cookie| cookie.name() == "csrf_token")
+        .expect("The `csrf_token` cookie was not set.")
+        .value()
+        .to_string();
+    app.csrf_token().await;
+
+    // Act
+    let response = app
+        .api_client
+        .post(&format!("{}/login", &app.address))
+        .header("X-CSRF-Token", &other_token)
+        .form(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password,
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_valid_csrf_header_is_accepted() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_login(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password,
+        }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/dashboard");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_valid_csrf_form_field_is_accepted() {
+    // Arrange
+    let app = spawn_app().await;
+    let html = app.get_login_html().await;
+    let csrf_token = app.csrf_token().await;
+    assert!(html.contains(&format!(r#"name="_csrf" value="{}""#, csrf_token)));
+
+    // Act
+    let response = app
+        .api_client
+        .post(&format!("{}/login", &app.address))
+        .form(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password,
+            "_csrf": &csrf_token,
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/dashboard");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn logging_out_issues_a_new_csrf_token() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let old_token = app.csrf_token().await;
+
+    // Act
+    app.post_logout().await;
+
+    // Assert
+    assert_ne!(app.csrf_token().await, old_token);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn pages_without_a_form_do_not_create_a_session() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response.headers().get("Set-Cookie").is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn form_pages_hand_out_a_token_to_new_visitors() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/login", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response
+        .cookies()
+        .any(|cookie| cookie.name() == "csrf_token"));
+
+    app.cleanup_test_db().await.unwrap();
+}
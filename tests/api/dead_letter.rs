@@ -0,0 +1,253 @@
+use reqwest::StatusCode;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+async fn enqueue_delivery_to_invalid_address(app: &TestApp) {
+    let issue_id = Uuid::new_v4().to_string();
+    sqlx::query!(
+        "INSERT INTO newsletter_issues (
+            newsletter_issue_uuid, title, text_content, html_content, published_at
+        )
+        VALUES ($1, 'Newsletter title', 'Plain text', '<p>HTML</p>', '2026-01-01 00:00:00 UTC')",
+        issue_id,
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO issue_delivery_queue (newsletter_issue_uuid, subscriber_email)
+        VALUES ($1, 'definitely-not-an-email')",
+        issue_id,
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_the_dead_letter_queue() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_dead_letter_entries().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn undeliverable_addresses_end_up_in_the_dead_letter_queue() {
+    // Arrange
+    let app = spawn_app().await;
+    enqueue_delivery_to_invalid_address(&app).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    app.dispatch_all_pending_emails().await;
+    let response = app.get_dead_letter_entries().await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let entries: serde_json::Value = response.json().await.unwrap();
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["subscriber_email"], "definitely-not-an-email");
+    assert_eq!(entries[0]["attempt_count"], 0);
+    assert!(entries[0]["failure_reason"].as_str().is_some());
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn acknowledging_a_dead_letter_entry_removes_it() {
+    // Arrange
+    let app = spawn_app().await;
+    enqueue_delivery_to_invalid_address(&app).await;
+    app.test_user.login(&app).await;
+    app.dispatch_all_pending_emails().await;
+    let entries: serde_json::Value = app.get_dead_letter_entries().await.json().await.unwrap();
+    let id = entries[0]["id"].as_i64().unwrap();
+
+    // Act - Part 1 - Acknowledge the entry
+    let response = app.delete_dead_letter_entry(id).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // Act - Part 2 - Acknowledge it again
+    let response = app.delete_dead_letter_entry(id).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Assert
+    let entries: serde_json::Value = app.get_dead_letter_entries().await.json().await.unwrap();
+    assert!(entries.as_array().unwrap().is_empty());
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+/// Publish an issue to a single confirmed subscriber, whose email provider
+/// turns it down for good.
+async fn publish_undeliverable_issue(app: &TestApp) -> String {
+    app.insert_subscriber(
+        "ursula",
+        "ursula@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(400))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+    app.dispatch_all_pending_emails().await;
+    sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid
+}
+
+#[tokio::test]
+async fn acknowledged_dead_letter_entries_are_not_reported_as_sent() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = publish_undeliverable_issue(&app).await;
+    let entries: serde_json::Value = app.get_dead_letter_entries().await.json().await.unwrap();
+    let id = entries[0]["id"].as_i64().unwrap();
+
+    // Act
+    let response = app.delete_dead_letter_entry(id).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // Assert
+    let progress: serde_json::Value = app
+        .get_newsletter_progress(&issue_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(progress["total_queued"], 1);
+    assert_eq!(progress["sent"], 0);
+    assert_eq!(progress["pending"], 0);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn requeued_deliveries_are_sent_again() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = publish_undeliverable_issue(&app).await;
+    let entries: serde_json::Value = app.get_dead_letter_entries().await.json().await.unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_requeue_failed_deliveries(&issue_id, "Postmark was down")
+        .await;
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "requeued": 1 }));
+    let page: serde_json::Value = app
+        .get_newsletter_deliveries(&issue_id, 1, 10)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page["deliveries"][0]["status"], "delivered");
+    let entries: serde_json::Value = app.get_dead_letter_entries().await.json().await.unwrap();
+    assert!(entries.as_array().unwrap().is_empty());
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn the_requeue_reason_is_recorded_in_the_audit_log() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = publish_undeliverable_issue(&app).await;
+
+    // Act
+    let response = app
+        .post_requeue_failed_deliveries(&issue_id, "Postmark was down")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Assert
+    let mut reason = None;
+    for _ in 0..50 {
+        reason = sqlx::query!(
+            "SELECT reason FROM admin_audit_log WHERE action = 'requeue_failed_deliveries'"
+        )
+        .fetch_optional(&app.db_pool)
+        .await
+        .unwrap()
+        .and_then(|row| row.reason);
+        if reason.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(reason.as_deref(), Some("Postmark was down"));
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn requeueing_an_unknown_issue_is_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_requeue_failed_deliveries(&Uuid::new_v4().to_string(), "")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_requeue_deliveries() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_requeue_failed_deliveries(&Uuid::new_v4().to_string(), "")
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap()
+}
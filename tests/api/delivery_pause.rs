@@ -0,0 +1,77 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestUser};
+
+#[tokio::test]
+async fn deliveries_can_be_paused_and_resumed() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    assert_eq!(app.get_delivery_status().await["paused"], false);
+
+    // Act - Part 1 - Pause
+    let response = app.post_pause_delivery().await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    // Assert - Part 1
+    assert_eq!(app.get_delivery_status().await["paused"], true);
+    let html_page = app.get_admin_dashboard_html().await;
+    assert!(html_page.contains("Delivery paused"));
+
+    // Act - Part 2 - Resume
+    let response = app.post_resume_delivery().await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    // Assert - Part 2
+    assert_eq!(app.get_delivery_status().await["paused"], false);
+    let html_page = app.get_admin_dashboard_html().await;
+    assert!(!html_page.contains("Delivery paused"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_status_reports_how_many_deliveries_are_queued() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    for email in ["first@example.com", "second@example.com"] {
+        app.insert_subscriber("name", email, "confirmed", "now")
+            .await;
+    }
+    app.post_pause_delivery().await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    let status = app.get_delivery_status().await;
+    assert_eq!(status["paused"], true);
+    assert_eq!(status["queue_depth"], 2);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_pause_deliveries() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app.post_pause_delivery().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(app.get_delivery_status().await["paused"], false);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,114 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn the_confirmation_email_preview_renders_the_html_body() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .get_confirmation_email_preview("name=Alice&email=alice%40example.com")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["Content-Type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/html"));
+    let html = response.text().await.unwrap();
+    assert!(html.contains("Confirm your subscription"));
+    assert!(html.contains("http://localhost/subscriptions/confirm?subscription_token="));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_confirmation_email_preview_rejects_an_invalid_email() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .get_confirmation_email_preview("name=Alice&email=not-an-email")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_newsletter_email_preview_renders_the_issue_with_its_footer() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_publish_newsletter(&serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    }))
+    .await;
+    let issue_id = sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid;
+
+    // Act
+    let response = app.get_newsletter_email_preview(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let list_unsubscribe = response.headers()["List-Unsubscribe"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    assert!(list_unsubscribe.starts_with("<http://localhost/subscriptions/unsubscribe?token="));
+    let html = response.text().await.unwrap();
+    assert!(html.contains("<p>Newsletter body as HTML</p>"));
+    assert!(html.contains("http://localhost/subscriptions/unsubscribe?token="));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn previewing_an_unknown_newsletter_issue_is_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .get_newsletter_email_preview(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_preview_emails() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let confirmation = app
+        .get_confirmation_email_preview("name=Alice&email=alice%40example.com")
+        .await;
+    let newsletter = app
+        .get_newsletter_email_preview(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&confirmation, "/login");
+    assert_is_redirect_to(&newsletter, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
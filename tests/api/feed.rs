@@ -0,0 +1,87 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn the_feed_lists_issues_newest_first() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.publish_issue("First issue", "<p>Newsletter body as HTML</p>")
+        .await;
+    app.publish_issue("Second issue", "<p>Newsletter body as HTML</p>")
+        .await;
+    app.post_logout().await;
+
+    // Act
+    let response = app.get_feed().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["Content-Type"]
+        .to_str()
+        .unwrap()
+        .starts_with("application/atom+xml"));
+    assert_eq!(response.headers()["Cache-Control"], "public, max-age=300");
+    let feed = response.text().await.unwrap();
+    let second = feed
+        .find("Second issue")
+        .expect("The second issue is missing");
+    let first = feed
+        .find("First issue")
+        .expect("The first issue is missing");
+    assert!(second < first);
+    assert!(feed.contains("<id>urn:uuid:"));
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn feed_entries_link_to_the_issue_page() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.publish_issue("Linked issue", "<p>Newsletter body as HTML</p>")
+        .await;
+
+    // Act
+    let feed = app.get_feed().await.text().await.unwrap();
+
+    // Assert
+    let prefix = format!("{}/archive/", app.base_url);
+    let start = feed.find(&prefix).expect("The entry has no link") + prefix.len();
+    let slug: String = feed[start..].chars().take_while(|c| *c != '"').collect();
+    assert_eq!(slug, "linked-issue");
+    let response = app.get_archive_issue(&slug).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("<p>Newsletter body as HTML</p>"));
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn scheduled_issues_are_left_out_of_the_feed() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Future issue",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "scheduled_for": "2999-01-01T09:00",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // Act
+    let feed = app.get_feed().await.text().await.unwrap();
+
+    // Assert
+    assert!(!feed.contains("Future issue"));
+
+    app.cleanup_test_db().await.unwrap()
+}
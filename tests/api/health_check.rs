@@ -21,3 +21,49 @@ pub async fn health_check_works() {
 
     app.cleanup_test_db().await.unwrap();
 }
+
+#[tokio::test]
+async fn deep_health_check_reports_reachable_dependencies() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = Client::new()
+        .get(&format!("{}/health_check/deep", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body, serde_json::json!({ "sqlite": "ok", "redis": "ok" }));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn deep_health_check_fails_when_the_database_is_broken() {
+    // Arrange
+    let app = spawn_app().await;
+    // Sabotage the database
+    sqlx::query!("ALTER TABLE subscriptions RENAME TO broken_subscriptions;")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = Client::new()
+        .get(&format!("{}/health_check/deep", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["sqlite"].as_str().unwrap().starts_with("error: "));
+    assert_eq!(body["redis"], "ok");
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -1,85 +1,959 @@
-use std::{fs, sync::LazyLock};
+use std::{
+    fs,
+    sync::{Arc, LazyLock},
+};
+
+use argon2::{
+    password_hash::{rand_core, PasswordHasher, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use newzletter::{
+    configuration::{configure_database, get_configuration, Settings},
+    db::SqliteInstrumentedPool,
+    issue_delivery_worker::try_execute_task,
+    startup::{Application, HmacSecret},
+    telemetry::{get_subscriber, init_subscriber, LogFilterHandle},
+};
+use newzletter::{email_client::EmailClient, issue_delivery_worker::ExecutionOutcome};
+use reqwest::cookie::CookieStore;
+use serde::Serialize;
+use tokio::fs::remove_file;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// Ensure that the `tracing` stack is only initialised once using `once_cell`
+// and keep the handle to its filter, every `TestApp` shares that subscriber
+static TRACING: LazyLock<LogFilterHandle> = LazyLock::new(|| {
+    let default_filter_level = "info".to_string();
+    let subscriber_name = "test".to_string();
+    if std::env::var("TEST_LOG").is_ok() {
+        let (subscriber, log_filter) =
+            get_subscriber(subscriber_name, default_filter_level, std::io::stdout);
+        init_subscriber(subscriber);
+        log_filter
+    } else {
+        let (subscriber, log_filter) =
+            get_subscriber(subscriber_name, default_filter_level, std::io::sink);
+        init_subscriber(subscriber);
+        log_filter
+    }
+});
+
+pub struct TestApp {
+    pub address: String,
+    pub port: u16,
+    pub db_pool: SqliteInstrumentedPool,
+    pub email_server: MockServer,
+    pub turnstile_server: MockServer,
+    // to later delete it
+    pub db_path: String,
+    pub test_user: TestUser,
+    pub api_client: reqwest::Client,
+    pub cookie_jar: Arc<reqwest::cookie::Jar>,
+    pub email_client: EmailClient,
+    pub max_retries: u8,
+    pub email_send_timeout: std::time::Duration,
+    pub base_url: String,
+    pub hmac_secret: HmacSecret,
+    pub shutdown_token: CancellationToken,
+}
+
+#[derive(Serialize)]
+pub struct FormData {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    #[serde(rename = "cf-turnstile-response")]
+    pub cf_turnstile_response: Option<String>,
+}
+
+pub struct ConfirmationLinks {
+    pub html: reqwest::Url,
+    pub plain_text: reqwest::Url,
+}
+
+impl TestApp {
+    /// The CSRF token of the client's session, as the `csrf_token` cookie.
+    ///
+    /// The home page has a form, so it hands out a token even when the
+    /// session was flushed or doesn't exist yet.
+    pub async fn csrf_token(&self) -> String {
+        self.api_client
+            .get(&format!("{}/", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let url = reqwest::Url::parse(&self.address).unwrap();
+        let cookies = self
+            .cookie_jar
+            .cookies(&url)
+            .expect("The application did not set any cookie.");
+        cookies
+            .to_str()
+            .unwrap()
+            .split("; ")
+            .find_map(|cookie| cookie.strip_prefix("csrf_token="))
+            .expect("The application did not set the `csrf_token` cookie.")
+            .to_string()
+    }
+
+    pub async fn post_subscriptions(&self, form_data: &FormData) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions", &self.address))
+            .form(form_data)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// As if a proxy forwarded the request of a client at `ip`.
+    pub async fn post_subscriptions_with_ip(
+        &self,
+        form_data: &FormData,
+        ip: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions", &self.address))
+            .form(form_data)
+            .header("X-Forwarded-For", ip)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Send `body` as is, to check how malformed payloads are handled.
+    pub async fn post_subscriptions_raw(
+        &self,
+        body: &str,
+        content_type: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions", &self.address))
+            .header("Content-Type", content_type)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Subscribe through a form action url carrying campaign query parameters.
+    pub async fn post_subscriptions_with_query<Body>(
+        &self,
+        query: &[(&str, &str)],
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/subscriptions", &self.address))
+            .query(query)
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_login<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/login", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_home_html(&self) -> String {
+        self.api_client
+            .get(&self.address)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_login_html(&self) -> String {
+        self.api_client
+            .get(&format!("{}/login", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_admin_dashboard_html(&self) -> String {
+        self.get_admin_dashboard().await.text().await.unwrap()
+    }
+
+    pub async fn get_change_password(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/password", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_change_password_html(&self) -> String {
+        self.get_change_password().await.text().await.unwrap()
+    }
+
+    pub async fn post_logout(&self) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/logout", &self.address))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_invite_user(&self) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/users/invite", &self.address))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_register(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/register", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_register<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/register", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_reset_password(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/reset-password", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_reset_password(&self, email: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/reset-password", &self.address))
+            .form(&[("email", email)])
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_reset_password_confirm(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/reset-password/confirm", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_reset_password_confirm<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/reset-password/confirm", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Submit an admin form as an API client, asking for JSON instead of a redirect.
+    pub async fn post_admin_form_as_json<Body>(&self, path: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin{}", &self.address, path))
+            .header("Accept", "application/json")
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin/password", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_enable_two_factor(&self) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/2fa/enable", &self.address))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_verify_two_factor<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin/2fa/verify", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_disable_two_factor<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin/2fa/disable", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_login_two_factor(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/login/2fa", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_login_two_factor<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/login/2fa", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_publish_newsletter(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/newsletters", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_publish_newsletter_html(&self) -> String {
+        self.get_publish_newsletter().await.text().await.unwrap()
+    }
+
+    pub async fn get_newsletters_json(&self) -> serde_json::Value {
+        self.api_client
+            .get(&format!("{}/admin/newsletters", &self.address))
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_admin_dashboard(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/dashboard", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_publish_newsletter<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin/newsletters", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_confirmation_email_preview(&self, query: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!(
+                "{}/admin/email-preview/confirmation?{}",
+                &self.address, query
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_newsletter_email_preview(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!(
+                "{}/admin/email-preview/newsletter/{}",
+                &self.address, issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_edit_newsletter_issue(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!(
+                "{}/admin/newsletters/{}/edit",
+                &self.address, issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_edit_newsletter_issue<Body>(
+        &self,
+        issue_id: &str,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!(
+                "{}/admin/newsletters/{}/edit",
+                &self.address, issue_id
+            ))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_newsletter_progress(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!(
+                "{}/admin/newsletters/{}/progress",
+                &self.address, issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_newsletter_progress_stream(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!(
+                "{}/admin/newsletters/{}/progress/stream",
+                &self.address, issue_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_newsletter_deliveries(
+        &self,
+        issue_id: &str,
+        page: u32,
+        per_page: u32,
+    ) -> reqwest::Response {
+        self.api_client
+            .get(&format!(
+                "{}/admin/newsletters/{}/deliveries",
+                &self.address, issue_id
+            ))
+            .query(&[("page", page), ("per_page", per_page)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_requeue_failed_deliveries(
+        &self,
+        issue_id: &str,
+        reason: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(&format!(
+                "{}/admin/delivery/requeue/{}",
+                &self.address, issue_id
+            ))
+            .form(&[("reason", reason)])
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_audit_log(&self, page: u32, per_page: u32) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/audit-log", &self.address))
+            .query(&[("page", page), ("per_page", per_page)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_subscribers(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/subscribers", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_subscribers_search(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/subscribers/search", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_subscribers_export(&self, status: Option<&str>) -> reqwest::Response {
+        let mut request = self
+            .api_client
+            .get(&format!("{}/admin/subscribers/export", &self.address));
+        if let Some(status) = status {
+            request = request.query(&[("status", status)]);
+        }
+        request.send().await.expect("Failed to execute request.")
+    }
+
+    pub async fn post_subscribers_import(&self, csv: &str) -> reqwest::Response {
+        let file = reqwest::multipart::Part::text(csv.to_string())
+            .file_name("subscribers.csv")
+            .mime_str("text/csv")
+            .unwrap();
+        self.api_client
+            .post(&format!("{}/admin/subscribers/import", &self.address))
+            .multipart(reqwest::multipart::Form::new().part("file", file))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_subscriber(&self, subscriber_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(&format!(
+                "{}/admin/subscribers/{}",
+                &self.address, subscriber_id
+            ))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn patch_subscriber_status(
+        &self,
+        subscriber_id: &str,
+        body: &serde_json::Value,
+    ) -> reqwest::Response {
+        self.api_client
+            .patch(&format!(
+                "{}/admin/subscribers/{}/status",
+                &self.address, subscriber_id
+            ))
+            .json(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_bulk_subscriber_status(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/subscribers/bulk-status", &self.address))
+            .json(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_template(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/templates", &self.address))
+            .json(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn put_template(
+        &self,
+        template_id: &str,
+        body: &serde_json::Value,
+    ) -> reqwest::Response {
+        self.api_client
+            .put(&format!(
+                "{}/admin/templates/{}",
+                &self.address, template_id
+            ))
+            .json(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_template(&self, template_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(&format!(
+                "{}/admin/templates/{}",
+                &self.address, template_id
+            ))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_templates(&self) -> serde_json::Value {
+        self.api_client
+            .get(&format!("{}/admin/templates", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_api_key(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/api-keys", &self.address))
+            .json(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_log_level(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/log-level", &self.address))
+            .json(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_scheduled_newsletters(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/newsletters/scheduled", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_duplicate_newsletter_issue(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!(
+                "{}/admin/newsletters/{}/duplicate",
+                &self.address, issue_id
+            ))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_test_send(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!(
+                "{}/admin/newsletters/{}/test-send",
+                &self.address, issue_id
+            ))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_broadcast<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin/broadcast", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_scheduled_newsletter(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(&format!(
+                "{}/admin/newsletters/scheduled/{}",
+                &self.address, issue_id
+            ))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_newsletter_issue(&self, issue_id: &str) -> reqwest::Response {
+        self.api_client
+            .delete(&format!("{}/admin/newsletters/{}", &self.address, issue_id))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_pause_delivery(&self) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/delivery/pause", &self.address))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
 
-use argon2::{
-    password_hash::{rand_core, PasswordHasher, SaltString},
-    Algorithm, Argon2, Params, Version,
-};
-use newzletter::{
-    configuration::{configure_database, get_configuration},
-    issue_delivery_worker::try_execute_task,
-    startup::Application,
-    telemetry::{get_subscriber, init_subscriber},
-};
-use newzletter::{email_client::EmailClient, issue_delivery_worker::ExecutionOutcome};
-use serde::Serialize;
-use sqlx::sqlite::SqlitePool;
-use tokio::fs::remove_file;
-use uuid::Uuid;
-use wiremock::MockServer;
+    pub async fn post_resume_delivery(&self) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/delivery/resume", &self.address))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
 
-// Ensure that the `tracing` stack is only initialised once using `once_cell`
-static TRACING: LazyLock<()> = LazyLock::new(|| {
-    let default_filter_level = "info".to_string();
-    let subscriber_name = "test".to_string();
-    if std::env::var("TEST_LOG").is_ok() {
-        let subscriber = get_subscriber(subscriber_name, default_filter_level, std::io::stdout);
-        init_subscriber(subscriber);
-    } else {
-        let subscriber = get_subscriber(subscriber_name, default_filter_level, std::io::sink);
-        init_subscriber(subscriber);
-    };
-});
+    pub async fn get_delivery_status(&self) -> serde_json::Value {
+        self.api_client
+            .get(&format!("{}/admin/delivery/status", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json()
+            .await
+            .unwrap()
+    }
 
-pub struct TestApp {
-    pub address: String,
-    pub port: u16,
-    pub db_pool: SqlitePool,
-    pub email_server: MockServer,
-    // to later delete it
-    pub db_path: String,
-    pub test_user: TestUser,
-    pub api_client: reqwest::Client,
-    pub email_client: EmailClient,
-}
+    pub async fn get_admin_health(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/health", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
 
-#[derive(Serialize)]
-pub struct FormData {
-    pub name: Option<String>,
-    pub email: Option<String>,
-    #[serde(rename = "cf-turnstile-response")]
-    pub cf_turnstile_response: Option<String>,
-}
+    pub async fn get_dead_letter_entries(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/delivery/dead-letter", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
 
-pub struct ConfirmationLinks {
-    pub html: reqwest::Url,
-    pub plain_text: reqwest::Url,
-}
+    pub async fn delete_dead_letter_entry(&self, id: i64) -> reqwest::Response {
+        self.api_client
+            .delete(&format!(
+                "{}/admin/delivery/dead-letter/{}",
+                &self.address, id
+            ))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
 
-impl TestApp {
-    pub async fn post_subscriptions(&self, form_data: &FormData) -> reqwest::Response {
+    pub async fn get_metrics(&self) -> reqwest::Response {
         self.api_client
-            .post(&format!("{}/subscriptions", &self.address))
-            .form(form_data)
+            .get(&format!("{}/metrics", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn post_login<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn post_resend_confirmation(&self, email: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!(
+                "{}/subscriptions/resend-confirmation",
+                &self.address
+            ))
+            .form(&[("email", email)])
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_check_email(&self, email: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/subscriptions/check-email", &self.address))
+            .query(&[("email", email)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_subscriptions_confirm(&self, subscription_token: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions/confirm", &self.address))
+            .form(&[("subscription_token", subscription_token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Open the confirmation link and click the button of the page it shows.
+    pub async fn confirm_subscription(&self, confirmation_link: reqwest::Url) -> reqwest::Response {
+        self.api_client
+            .get(confirmation_link.clone())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .error_for_status()
+            .unwrap();
+        let subscription_token = confirmation_link
+            .query_pairs()
+            .find(|(key, _)| key == "subscription_token")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        self.post_subscriptions_confirm(&subscription_token).await
+    }
+
+    pub async fn post_unsubscribe_one_click(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions/unsubscribe", &self.address))
+            .query(&[("token", token)])
+            .form(&[("List-Unsubscribe", "One-Click")])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_unsubscribe_survey(
+        &self,
+        token: &str,
+        survey: &[(&str, &str)],
+    ) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions/unsubscribe", &self.address))
+            .query(&[("token", token)])
+            .form(survey)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_unsubscribe_reasons(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/unsubscribe-reasons", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_subscription_status(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/subscriptions/status", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_manage_subscription(&self, token: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/subscriptions/manage", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// `body` is sent as is, `serde_urlencoded` can't repeat the `tags` field.
+    pub async fn post_manage_subscription_tags(&self, body: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/subscriptions/manage/tags", &self.address))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_manage_subscription<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.api_client
-            .post(&format!("{}/login", &self.address))
+            .post(&format!("{}/subscriptions/manage", &self.address))
             .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn get_login_html(&self) -> String {
+    pub async fn get_unsubscribe(&self, token: &str) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/login", &self.address))
+            .get(&format!("{}/subscriptions/unsubscribe", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_blog_index_html(&self) -> String {
+        self.api_client
+            .get(&format!("{}/blog", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -88,69 +962,128 @@ impl TestApp {
             .unwrap()
     }
 
-    pub async fn get_admin_dashboard_html(&self) -> String {
-        self.get_admin_dashboard().await.text().await.unwrap()
+    pub async fn get_blog_post(&self, slug: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/blog/{}", &self.address, slug))
+            .send()
+            .await
+            .expect("Failed to execute request.")
     }
 
-    pub async fn get_change_password(&self) -> reqwest::Response {
+    pub async fn get_archive(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/admin/password", &self.address))
+            .get(&format!("{}/archive", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn get_change_password_html(&self) -> String {
-        self.get_change_password().await.text().await.unwrap()
+    pub async fn get_archive_issue(&self, slug: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/archive/{}", &self.address, slug))
+            .send()
+            .await
+            .expect("Failed to execute request.")
     }
 
-    pub async fn post_logout(&self) -> reqwest::Response {
+    pub async fn get_feed(&self) -> reqwest::Response {
         self.api_client
-            .post(&format!("{}/admin/logout", &self.address))
+            .get(&format!("{}/feed.xml", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn post_change_password<Body>(&self, body: &Body) -> reqwest::Response
-    where
-        Body: serde::Serialize,
-    {
+    pub async fn get_jobs(&self) -> reqwest::Response {
         self.api_client
-            .post(&format!("{}/admin/password", &self.address))
-            .form(body)
+            .get(&format!("{}/admin/jobs", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn get_publish_newsletter(&self) -> reqwest::Response {
+    pub async fn get_subscriber_counts(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/admin/newsletters", &self.address))
+            .get(&format!("{}/admin/subscribers/count", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn get_publish_newsletter_html(&self) -> String {
-        self.get_publish_newsletter().await.text().await.unwrap()
+    pub async fn get_tags(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/tags", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
     }
 
-    pub async fn get_admin_dashboard(&self) -> reqwest::Response {
+    pub async fn get_admin_system(&self) -> reqwest::Response {
         self.api_client
-            .get(&format!("{}/admin/dashboard", &self.address))
+            .get(&format!("{}/admin/system", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
-    pub async fn post_publish_newsletter<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn get_admin_blog(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/blog", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_blog_html(&self) -> String {
+        self.get_admin_blog().await.text().await.unwrap()
+    }
+
+    pub async fn post_blog_post<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.api_client
-            .post(&format!("{}/admin/newsletters", &self.address))
+            .post(&format!("{}/admin/blog", &self.address))
+            .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_update_blog_post<Body>(&self, slug: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(&format!("{}/admin/blog/{}", &self.address, slug))
             .form(body)
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_edit_blog_post(&self, slug: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/blog/{}/edit", &self.address, slug))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_toggle_blog_post(&self, slug: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!("{}/admin/blog/{}/publish", &self.address, slug))
+            .header("X-CSRF-Token", self.csrf_token().await)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_blog_post_stats(&self, slug: &str) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/admin/blog/{}/stats", &self.address, slug))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -182,16 +1115,123 @@ impl TestApp {
 
     pub async fn dispatch_all_pending_emails(&self) {
         loop {
-            if let ExecutionOutcome::EmptyQueue =
-                try_execute_task(&self.db_pool, &self.email_client)
-                    .await
-                    .unwrap()
+            if let ExecutionOutcome::EmptyQueue = try_execute_task(
+                &self.db_pool,
+                &self.email_client,
+                &self.base_url,
+                &self.hmac_secret,
+                self.max_retries,
+                self.email_send_timeout,
+            )
+            .await
+            .unwrap()
             {
                 break;
             }
         }
     }
 
+    /// Subscribe a new address through the API, returning the links of its
+    /// confirmation email.
+    pub async fn create_unconfirmed_subscriber(&self) -> ConfirmationLinks {
+        self.create_unconfirmed_subscriber_with_email(&format!("{}@example.com", Uuid::new_v4()))
+            .await
+    }
+
+    pub async fn create_unconfirmed_subscriber_with_email(&self, email: &str) -> ConfirmationLinks {
+        let body = FormData {
+            name: Some("abood".to_string()),
+            email: Some(email.to_string()),
+            cf_turnstile_response: Some("test-token".to_string()),
+        };
+
+        let _mock_guard = Mock::given(path("/email"))
+            .and(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .named("Create unconfirmed subscriber")
+            .expect(1)
+            .mount_as_scoped(&self.email_server)
+            .await;
+        self.post_subscriptions(&body)
+            .await
+            .error_for_status()
+            .unwrap();
+
+        let email_request = &self
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        self.get_confirmation_links(email_request)
+    }
+
+    /// Subscribe and confirm a new address through the API, returning the
+    /// subscriber's id.
+    pub async fn create_confirmed_subscriber(&self) -> String {
+        self.create_confirmed_subscriber_with_email(&format!("{}@example.com", Uuid::new_v4()))
+            .await
+    }
+
+    pub async fn create_confirmed_subscriber_with_email(&self, email: &str) -> String {
+        let confirmation_link = self
+            .create_unconfirmed_subscriber_with_email(email)
+            .await
+            .html;
+        self.confirm_subscription(confirmation_link)
+            .await
+            .error_for_status()
+            .unwrap();
+        sqlx::query_scalar!("SELECT uuid FROM subscriptions WHERE email = $1", email)
+            .fetch_one(&self.db_pool)
+            .await
+            .unwrap()
+    }
+
+    /// Publish an issue as the logged-in user, returning its id.
+    pub async fn publish_issue(&self, title: &str, html_content: &str) -> String {
+        let response = self
+            .post_publish_newsletter(&serde_json::json!({
+                "title": title,
+                "text_content": "Newsletter body as plain text",
+                "html_content": html_content,
+                "idempotency_key": Uuid::new_v4().to_string(),
+            }))
+            .await;
+        assert_is_redirect_to(&response, "/admin/newsletters");
+        sqlx::query_scalar!(
+            "SELECT newsletter_issue_uuid FROM newsletter_issues ORDER BY rowid DESC LIMIT 1"
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .unwrap()
+    }
+
+    /// Store a subscriber straight in the database, returning their id.
+    pub async fn insert_subscriber(
+        &self,
+        name: &str,
+        email: &str,
+        status: &str,
+        subscribed_at: &str,
+    ) -> String {
+        let uuid = Uuid::new_v4().to_string();
+        sqlx::query!(
+            "INSERT INTO subscriptions (uuid, name, email, subscribed_at, status)
+            VALUES ($1, $2, $3, $4, $5)",
+            uuid,
+            name,
+            email,
+            subscribed_at,
+            status,
+        )
+        .execute(&self.db_pool)
+        .await
+        .unwrap();
+        uuid
+    }
+
     pub async fn cleanup_test_db(&self) -> Result<(), sqlx::Error> {
         remove_file(&format!("{}.db", self.db_path)).await?;
         Ok(())
@@ -199,6 +1239,12 @@ impl TestApp {
 }
 
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+/// Like `spawn_app`, with a chance to tweak the configuration before the
+/// application is built.
+pub async fn spawn_app_with(customise: impl FnOnce(&mut Settings)) -> TestApp {
     // The first time `initialize` is invoked the code in `TRACING` is executed.
     // All other invocations will instead skip execution.
     LazyLock::force(&TRACING);
@@ -206,6 +1252,8 @@ pub async fn spawn_app() -> TestApp {
     fs::create_dir_all("scripts/a_place_for_test_dbs_to_spawn_in_it,supposed_to_be_empty_cuz_tests_terminate_after_success_execution/").expect("Failed to create directory");
 
     let email_server = MockServer::start().await;
+    // only reached by tests turning `turnstile_test_mode` off
+    let turnstile_server = MockServer::start().await;
 
     let configuration = {
         let mut configuration = get_configuration().expect("Failed to read configuration");
@@ -217,11 +1265,26 @@ pub async fn spawn_app() -> TestApp {
         configuration.database.busy_timeout = 5;
         configuration.database.foreign_keys = true;
         configuration.database.auto_vacuum = "NONE".to_string();
+        configuration.database.vacuum_enabled = false;
         configuration.database.page_size = 4096;
         configuration.database.cache_size = "-10000".to_string();
         configuration.database.mmap_size = "0".to_string();
         configuration.database.temp_store = "MEMORY".to_string();
+        // a tiny pool makes connection leaks show up as acquire timeouts
+        configuration.database.max_connections = 2;
+        configuration.database.min_connections = 0;
+        configuration.database.acquire_timeout_secs = 5;
         configuration.email_client.base_url = email_server.uri();
+        configuration.turnstile.base_url = turnstile_server.uri();
+        // every Turnstile token is accepted without a call to the mock server
+        configuration.application.turnstile_test_mode = true;
+        // every test talks to the application from 127.0.0.1, the tests
+        // sending `X-Forwarded-For` play the proxy
+        configuration.application.trusted_proxies = vec!["127.0.0.0/8".to_string()];
+        // every test talks to the application from 127.0.0.1
+        configuration.rate_limit.strict.capacity = 1_000;
+        configuration.rate_limit.default.capacity = 1_000;
+        customise(&mut configuration);
         configuration
     };
 
@@ -233,7 +1296,7 @@ pub async fn spawn_app() -> TestApp {
         .await
         .expect("Failed to run migrations");
 
-    let application = Application::build(configuration.clone())
+    let application = Application::build(configuration.clone(), Some(TRACING.clone()), None)
         .await
         .expect("Failed to build application");
 
@@ -242,35 +1305,49 @@ pub async fn spawn_app() -> TestApp {
     let application_port = application.port();
 
     let address = format!("http://{}:{}", application_host, application.port());
+    let shutdown_token = application.shutdown_token();
 
     tokio::spawn(async move { application.run_until_stopped().await.unwrap() });
 
+    let cookie_jar = Arc::new(reqwest::cookie::Jar::default());
     let client = reqwest::Client::builder()
         .redirect(reqwest::redirect::Policy::none())
-        .cookie_store(true)
+        .cookie_provider(cookie_jar.clone())
         .build()
         .unwrap();
 
+    let email_send_timeout = configuration.email_client.send_timeout();
     let test_app = TestApp {
         address,
         port: application_port,
-        db_pool: db_pool.clone(),
+        db_pool: SqliteInstrumentedPool::new(db_pool),
         db_path,
         email_server,
+        turnstile_server,
         test_user: TestUser::generate(),
         api_client: client,
-        email_client: configuration.email_client.client(),
+        cookie_jar,
+        email_client: configuration
+            .email_client
+            .client(Arc::new(configuration.http_client.client())),
+        max_retries: configuration.issue_delivery.max_retries,
+        email_send_timeout,
+        base_url: configuration.application.base_url,
+        hmac_secret: HmacSecret(configuration.application.hmac_secret),
+        shutdown_token,
     };
 
-    test_app.test_user.store(&db_pool).await;
+    test_app.test_user.store(&test_app.db_pool).await;
 
     test_app
 }
 
 pub struct TestUser {
-    uuid: Uuid,
+    pub uuid: Uuid,
     pub username: String,
+    pub email: String,
     pub password: String,
+    role: &'static str,
 }
 
 impl TestUser {
@@ -278,7 +1355,16 @@ impl TestUser {
         Self {
             uuid: Uuid::new_v4(),
             username: Uuid::new_v4().to_string(),
+            email: format!("{}@example.com", Uuid::new_v4()),
             password: Uuid::new_v4().to_string(),
+            role: "admin",
+        }
+    }
+
+    pub fn generate_editor() -> Self {
+        Self {
+            role: "editor",
+            ..Self::generate()
         }
     }
 
@@ -290,7 +1376,7 @@ impl TestUser {
         .await;
     }
 
-    async fn store(&self, pool: &SqlitePool) {
+    pub async fn store(&self, pool: &SqliteInstrumentedPool) {
         let salt = SaltString::generate(&mut rand_core::OsRng);
 
         let password_hash = Argon2::new(
@@ -307,11 +1393,13 @@ impl TestUser {
         let hashed_password = password_hash;
 
         sqlx::query!(
-            "INSERT INTO users (uuid, username, password_hash)
-            VALUES ($1, $2, $3)",
+            "INSERT INTO users (uuid, username, email, password_hash, role)
+            VALUES ($1, $2, $3, $4, $5)",
             uuid,
             username,
+            self.email,
             hashed_password,
+            self.role,
         )
         .execute(pool)
         .await
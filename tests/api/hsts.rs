@@ -0,0 +1,74 @@
+use newzletter::configuration::Environment;
+
+use crate::helpers::{spawn_app, spawn_app_with};
+
+#[tokio::test]
+async fn production_responses_enforce_https() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.environment = Environment::Production).await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(
+        response.headers()["Strict-Transport-Security"],
+        "max-age=31536000; includeSubDomains"
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn production_error_responses_enforce_https_too() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.application.environment = Environment::Production;
+        c.application.hsts_max_age_seconds = 600;
+    })
+    .await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/this-page-does-not-exist", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(
+        response.headers()["Strict-Transport-Security"],
+        "max-age=600; includeSubDomains"
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn local_responses_do_not_enforce_https() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(response
+        .headers()
+        .get("Strict-Transport-Security")
+        .is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
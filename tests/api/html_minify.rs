@@ -0,0 +1,54 @@
+use newzletter::configuration::Environment;
+
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+
+async fn get_login(app: &TestApp) -> reqwest::Response {
+    app.api_client
+        .get(&format!("{}/login", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn production_pages_are_minified() {
+    // Arrange
+    let local_app = spawn_app().await;
+    let production_app =
+        spawn_app_with(|c| c.application.environment = Environment::Production).await;
+
+    // Act
+    let local_html = local_app.get_login_html().await;
+    let response = get_login(&production_app).await;
+
+    // Assert
+    let content_length = response.headers().get("Content-Length").cloned();
+    let minified_html = response.text().await.unwrap();
+    assert!(
+        minified_html.len() < local_html.len(),
+        "{} bytes minified, {} bytes as rendered",
+        minified_html.len(),
+        local_html.len()
+    );
+    assert!(!minified_html.contains("<!--"));
+    if let Some(content_length) = content_length {
+        assert_eq!(content_length, minified_html.len().to_string().as_str());
+    }
+
+    local_app.cleanup_test_db().await.unwrap();
+    production_app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn local_pages_are_sent_as_rendered() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let html = app.get_login_html().await;
+
+    // Assert
+    assert!(html.contains("<!--"));
+
+    app.cleanup_test_db().await.unwrap();
+}
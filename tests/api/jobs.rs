@@ -0,0 +1,39 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_list_the_jobs() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_jobs().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_scheduled_jobs_are_listed() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_jobs().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let jobs: Vec<serde_json::Value> = response.json().await.unwrap();
+    let cleanup = jobs
+        .iter()
+        .find(|job| job["name"] == "idempotency_cleanup")
+        .expect("The idempotency cleanup is not scheduled");
+    assert_eq!(cleanup["interval_seconds"], 3600);
+    // it only runs an hour after startup
+    assert!(cleanup["last_run_at"].is_null());
+    // the test configuration disables vacuuming and doesn't use WAL
+    assert!(!jobs.iter().any(|job| job["name"] == "incremental_vacuum"));
+    assert!(!jobs.iter().any(|job| job["name"] == "wal_checkpoint"));
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,73 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestUser};
+
+// every test sets the same filter, the subscriber of the test suite is shared
+const FILTER: &str = "newzletter=debug";
+
+#[tokio::test]
+async fn an_admin_can_change_the_log_filter() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_log_level(&serde_json::json!({ "filter": FILTER }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 204);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invalid_log_filter_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_log_level(&serde_json::json!({ "filter": "newzletter=loudest" }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_change_the_log_filter() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app
+        .post_log_level(&serde_json::json!({ "filter": FILTER }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_change_the_log_filter() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_log_level(&serde_json::json!({ "filter": FILTER }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
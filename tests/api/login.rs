@@ -1,4 +1,4 @@
-use crate::helpers::{assert_is_redirect_to, spawn_app};
+use crate::helpers::{assert_is_redirect_to, spawn_app, spawn_app_with, TestApp};
 
 #[tokio::test]
 async fn an_error_flash_message_is_set_on_failure() {
@@ -25,3 +25,68 @@ async fn an_error_flash_message_is_set_on_failure() {
 
     app.cleanup_test_db().await.unwrap()
 }
+
+async fn fail_to_log_in(app: &TestApp) -> reqwest::Response {
+    app.post_login(&serde_json::json!({
+        "username": &app.test_user.username,
+        "password": "wrong-password"
+    }))
+    .await
+}
+
+#[tokio::test]
+async fn logins_are_refused_after_too_many_failed_attempts() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.login_max_attempts = 3).await;
+    for _ in 0..3 {
+        assert_is_redirect_to(&fail_to_log_in(&app).await, "/login");
+    }
+
+    // Act - even the right password is refused now
+    let response = app
+        .post_login(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+    let retry_after: u64 = response.headers()["Retry-After"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > 0 && retry_after <= 15 * 60);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn a_successful_login_resets_the_failed_attempts() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.login_max_attempts = 3).await;
+    for _ in 0..2 {
+        fail_to_log_in(&app).await;
+    }
+
+    // Act
+    let response = app
+        .post_login(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    app.post_logout().await;
+
+    // Assert - the counter starts over
+    for _ in 0..2 {
+        assert_is_redirect_to(&fail_to_log_in(&app).await, "/login");
+    }
+    let response = fail_to_log_in(&app).await;
+    assert_is_redirect_to(&response, "/login");
+    assert_eq!(fail_to_log_in(&app).await.status().as_u16(), 429);
+
+    app.cleanup_test_db().await.unwrap()
+}
@@ -1,8 +1,56 @@
+mod access_control;
 mod admin_dashboard;
+mod admin_health;
+mod admin_json_responses;
+mod allowed_hosts;
+mod api_keys;
+mod archive;
+mod audit_log;
+mod blog;
+mod broadcast;
 mod change_password;
+mod compression;
+mod csp;
+mod csrf;
+mod dead_letter;
+mod delivery_pause;
+mod email_preview;
+mod feed;
 mod health_check;
 mod helpers;
+mod hsts;
+mod html_minify;
+mod jobs;
+mod log_level;
 mod login;
+mod metrics;
 mod newsletter;
+mod newsletter_delete;
+mod rate_limit;
+mod register;
+mod request_id;
+mod reset_password;
+mod session_cookie;
+mod shutdown;
+mod subscribe_widget;
+mod subscribers_bulk_status;
+mod subscribers_count;
+mod subscribers_delete;
+mod subscribers_export;
+mod subscribers_import;
+mod subscribers_list;
+mod subscribers_search;
+mod subscribers_status;
 mod subscriptions;
+mod subscriptions_check_email;
 mod subscriptions_confirm;
+mod subscriptions_manage;
+mod subscriptions_status;
+mod subscriptions_unsubscribe;
+mod system;
+mod tags;
+mod telemetry;
+mod templates;
+mod test_send;
+mod two_factor;
+mod unsubscribe_reasons;
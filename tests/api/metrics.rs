@@ -0,0 +1,45 @@
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn metrics_are_exposed_in_the_prometheus_format() {
+    // Arrange
+    let app = spawn_app().await;
+    // generate at least one request for the http counter
+    app.get_login_html().await;
+
+    // Act
+    let response = app.get_metrics().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .headers()
+        .get("content-type")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    let body = response.text().await.unwrap();
+    assert!(body.contains("newzletter_http_requests_total"));
+    assert!(body.contains("newzletter_delivery_queue_depth"));
+    assert!(body.contains(r#"newzletter_subscriptions_total{status="confirmed"}"#));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscription_statuses_without_subscribers_are_reported_as_zero() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let body = app.get_metrics().await.text().await.unwrap();
+
+    // Assert
+    for status in ["pending", "confirmed", "unsubscribed"] {
+        let line = format!(r#"newzletter_subscriptions_total{{status="{}"}} 0"#, status);
+        assert!(body.contains(&line), "missing `{}` in:\n{}", line, body);
+    }
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -1,66 +1,16 @@
-use crate::helpers::{assert_is_redirect_to, spawn_app, ConfirmationLinks, FormData, TestApp};
+use crate::helpers::{assert_is_redirect_to, spawn_app, spawn_app_with, TestApp};
+use newzletter::idempotency::cleanup_expired_idempotency_keys;
+use newzletter::issue_delivery_queue::enqueue_due_scheduled_issues;
+use newzletter::issue_delivery_worker::{try_execute_task, ExecutionOutcome};
 use std::time::Duration;
 use wiremock::matchers::{any, method, path};
 use wiremock::{Mock, ResponseTemplate};
 
-async fn create_unconfirmed_subscriber(app: &TestApp) -> ConfirmationLinks {
-    create_unconfirmed_subscriber_with_email(app, format!("{}@example.com", uuid::Uuid::new_v4()))
-        .await
-}
-
-async fn create_unconfirmed_subscriber_with_email(
-    app: &TestApp,
-    email: String,
-) -> ConfirmationLinks {
-    let body = FormData {
-        name: Some("abood".to_string()),
-        email: Some(email),
-        cf_turnstile_response: Some("test-token".to_string()),
-    };
-
-    let _mock_guard = Mock::given(path("/email"))
-        .and(method("POST"))
-        .respond_with(ResponseTemplate::new(200))
-        .named("Create unconfirmed subscriber")
-        .expect(1)
-        .mount_as_scoped(&app.email_server)
-        .await;
-    app.post_subscriptions(&body)
-        .await
-        .error_for_status()
-        .unwrap();
-
-    let email_request = &app
-        .email_server
-        .received_requests()
-        .await
-        .unwrap()
-        .pop()
-        .unwrap();
-    app.get_confirmation_links(email_request)
-}
-
-async fn create_confirmed_subscriber(app: &TestApp) {
-    create_confirmed_subscriber_with_email(app, format!("{}@example.com", uuid::Uuid::new_v4()))
-        .await;
-}
-
-async fn create_confirmed_subscriber_with_email(app: &TestApp, email: String) {
-    let confirmation_link = create_unconfirmed_subscriber_with_email(app, email)
-        .await
-        .html;
-    reqwest::get(confirmation_link)
-        .await
-        .unwrap()
-        .error_for_status()
-        .unwrap();
-}
-
 #[tokio::test]
 async fn newsletters_are_not_delivered_to_unconfirmed_subscribers() {
     // Arrange
     let app = spawn_app().await;
-    create_unconfirmed_subscriber(&app).await;
+    app.create_unconfirmed_subscriber().await;
     app.test_user.login(&app).await;
 
     Mock::given(any())
@@ -92,7 +42,7 @@ async fn newsletters_are_not_delivered_to_unconfirmed_subscribers() {
 async fn newsletters_are_delivered_to_confirmed_subscribers() {
     // Arrange
     let app = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    app.create_confirmed_subscriber().await;
     app.test_user.login(&app).await;
 
     Mock::given(path("/email"))
@@ -125,8 +75,10 @@ async fn newsletters_are_delivered_to_confirmed_subscribers() {
 async fn newsletters_are_delivered_to_all_confirmed_subscribers() {
     // Arrange
     let app = spawn_app().await;
-    create_confirmed_subscriber_with_email(&app, "first@example.com".to_string()).await;
-    create_confirmed_subscriber_with_email(&app, "second@example.com".to_string()).await;
+    app.create_confirmed_subscriber_with_email("first@example.com")
+        .await;
+    app.create_confirmed_subscriber_with_email("second@example.com")
+        .await;
     app.test_user.login(&app).await;
 
     Mock::given(path("/email"))
@@ -152,11 +104,58 @@ async fn newsletters_are_delivered_to_all_confirmed_subscribers() {
     app.cleanup_test_db().await.unwrap()
 }
 
+#[tokio::test]
+async fn the_newsletters_page_lists_the_issues_with_their_delivery_counts() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    let publish = |title: &str| {
+        serde_json::json!({
+            "title": title,
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        })
+    };
+    app.post_publish_newsletter(&publish("First issue")).await;
+    app.dispatch_all_pending_emails().await;
+    app.create_confirmed_subscriber().await;
+    app.post_publish_newsletter(&publish("Second issue")).await;
+    app.dispatch_all_pending_emails().await;
+
+    // Act
+    let html_page = app.get_publish_newsletter_html().await;
+
+    // Assert
+    let row_of = |title: &str| {
+        let start = html_page.find(&format!("<td>{}</td>", title)).unwrap();
+        let end = start + html_page[start..].find("</tr>").unwrap();
+        html_page[start..end].to_string()
+    };
+    let first = row_of("First issue");
+    let second = row_of("Second issue");
+    assert!(first.contains(r#"<td data-column="sent">2</td>"#));
+    assert!(second.contains(r#"<td data-column="sent">3</td>"#));
+    for row in [first, second] {
+        assert!(row.contains(r#"<td data-column="failed">0</td>"#));
+        assert!(row.contains(r#"<td data-column="pending">0</td>"#));
+    }
+
+    app.cleanup_test_db().await.unwrap()
+}
+
 #[tokio::test]
 async fn newsletter_titles_do_not_need_to_be_unique() {
     // Arrange
     let app = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    app.create_confirmed_subscriber().await;
     app.test_user.login(&app).await;
 
     Mock::given(path("/email"))
@@ -234,7 +233,7 @@ async fn you_must_be_logged_in_to_publish_a_newsletter() {
 async fn newsletter_creation_is_idempotent() {
     // Arrange
     let app = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    app.create_confirmed_subscriber().await;
     app.test_user.login(&app).await;
     Mock::given(path("/email"))
         .and(method("POST"))
@@ -267,11 +266,81 @@ async fn newsletter_creation_is_idempotent() {
     // Mock verifies on Drop that we have sent the newsletter email **once**
 }
 
+#[tokio::test]
+async fn submitting_the_form_again_resumes_a_lost_delivery() {
+    // Arrange
+    let app = spawn_app().await;
+    let emails: Vec<String> = (0..4)
+        .map(|i| format!("subscriber{}@example.com", i))
+        .collect();
+    for email in &emails {
+        app.create_confirmed_subscriber_with_email(email).await;
+    }
+    app.test_user.login(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    // half of the subscribers get the issue before the worker crashes...
+    for _ in 0..emails.len() / 2 {
+        try_execute_task(
+            &app.db_pool,
+            &app.email_client,
+            &app.base_url,
+            &app.hmac_secret,
+            app.max_retries,
+            app.email_send_timeout,
+        )
+        .await
+        .unwrap();
+    }
+    // ...and the rest of the queue is lost
+    sqlx::query!("DELETE FROM issue_delivery_queue")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let received_requests = app.email_server.received_requests().await.unwrap();
+    let newsletters: Vec<serde_json::Value> = received_requests
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .filter(|email: &serde_json::Value| email["Subject"] == "Newsletter title")
+        .collect();
+    for email in &emails {
+        let n_received = newsletters
+            .iter()
+            .filter(|newsletter| newsletter["To"] == email.as_str())
+            .count();
+        assert_eq!(
+            n_received, 1,
+            "{} got the issue {} times",
+            email, n_received
+        );
+    }
+
+    app.cleanup_test_db().await.unwrap()
+}
+
 #[tokio::test]
 async fn concurrent_form_submission_is_handled_gracefully() {
     // Arrange
     let app = spawn_app().await;
-    create_confirmed_subscriber(&app).await;
+    app.create_confirmed_subscriber().await;
     app.test_user.login(&app).await;
 
     Mock::given(path("/email"))
@@ -305,3 +374,1283 @@ async fn concurrent_form_submission_is_handled_gracefully() {
     app.cleanup_test_db().await.unwrap();
     // Mock verifies on Drop that we have sent the newsletter email **once**
 }
+
+#[tokio::test]
+async fn failed_deliveries_are_rescheduled_with_a_backoff() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+
+    // Act
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let queued = sqlx::query!("SELECT n_retries, next_attempt_at FROM issue_delivery_queue")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch the rescheduled delivery task.");
+    assert_eq!(queued.n_retries, 1);
+    assert!(queued.next_attempt_at.is_some());
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn deliveries_to_an_unresponsive_provider_time_out_and_are_retried() {
+    // Arrange
+    let app = spawn_app_with(|c| c.email_client.email_send_timeout_seconds = 1).await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+
+    // Act
+    let started = std::time::Instant::now();
+    let outcome = try_execute_task(
+        &app.db_pool,
+        &app.email_client,
+        &app.base_url,
+        &app.hmac_secret,
+        app.max_retries,
+        app.email_send_timeout,
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(matches!(
+        outcome,
+        ExecutionOutcome::TaskFailed { retries_remaining } if retries_remaining == app.max_retries - 1
+    ));
+    let queued = sqlx::query!("SELECT n_retries FROM issue_delivery_queue")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch the rescheduled delivery task.");
+    assert_eq!(queued.n_retries, 1);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn deliveries_are_dead_lettered_after_max_retries() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    let max_retries = 2;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        // the first attempt and then every retry
+        .expect(u64::from(max_retries) + 1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+
+    // Act
+    let mut outcomes = Vec::new();
+    for _ in 0..10 {
+        let outcome = try_execute_task(
+            &app.db_pool,
+            &app.email_client,
+            &app.base_url,
+            &app.hmac_secret,
+            max_retries,
+            app.email_send_timeout,
+        )
+        .await
+        .unwrap();
+        if matches!(outcome, ExecutionOutcome::EmptyQueue) {
+            break;
+        }
+        outcomes.push(outcome);
+        // skip the backoff
+        sqlx::query!("UPDATE issue_delivery_queue SET next_attempt_at = NULL")
+            .execute(&app.db_pool)
+            .await
+            .unwrap();
+    }
+
+    // Assert
+    assert_eq!(outcomes.len(), usize::from(max_retries) + 1);
+    assert!(matches!(
+        outcomes[0],
+        ExecutionOutcome::TaskFailed { retries_remaining } if retries_remaining == max_retries - 1
+    ));
+    let n_queued = sqlx::query!(r#"SELECT COUNT(*) as "count!: i64" FROM issue_delivery_queue"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(n_queued, 0);
+    let dead_letter = sqlx::query!("SELECT n_retries FROM issue_delivery_dead_letter")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch the dead lettered delivery task.");
+    assert_eq!(dead_letter.n_retries, i64::from(max_retries) + 1);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn newsletters_contain_a_working_unsubscribe_link() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+
+    // Act - Part 1 - Deliver the newsletter
+    app.dispatch_all_pending_emails().await;
+
+    // Act - Part 2 - Follow the unsubscribe link
+    let newsletter_request = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let unsubscribe_links = app.get_confirmation_links(&newsletter_request);
+    assert_eq!(unsubscribe_links.html, unsubscribe_links.plain_text);
+    assert_eq!(unsubscribe_links.html.path(), "/subscriptions/unsubscribe");
+    let body: serde_json::Value = serde_json::from_slice(&newsletter_request.body).unwrap();
+    let list_unsubscribe = body["Headers"][0]["Value"].as_str().unwrap();
+    assert_eq!(body["Headers"][0]["Name"], "List-Unsubscribe");
+    // the link in the body has been rewritten to point at the test port
+    assert!(list_unsubscribe.ends_with(&format!("?{}>", unsubscribe_links.html.query().unwrap())));
+    assert_eq!(body["Headers"][1]["Value"], "List-Unsubscribe=One-Click");
+    let token = unsubscribe_links
+        .html
+        .query_pairs()
+        .find(|(name, _)| name == "token")
+        .unwrap()
+        .1
+        .into_owned();
+    let page = app
+        .api_client
+        .get(unsubscribe_links.html)
+        .send()
+        .await
+        .unwrap();
+    let response = app.post_unsubscribe_one_click(&token).await;
+
+    // Assert
+    assert_eq!(page.status().as_u16(), 200);
+    assert_eq!(response.status().as_u16(), 200);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "unsubscribed");
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn delivery_progress_reports_all_emails_as_sent_once_dispatched() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber_with_email("first@example.com")
+        .await;
+    app.create_confirmed_subscriber_with_email("second@example.com")
+        .await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+    let issue_id = sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid;
+
+    // Act - Part 1 - Check progress before dispatching
+    let progress: serde_json::Value = app
+        .get_newsletter_progress(&issue_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(progress["total_queued"], 2);
+    assert_eq!(progress["pending"], 2);
+    assert_eq!(progress["sent"], 0);
+
+    // Act - Part 2 - Dispatch and check again
+    app.dispatch_all_pending_emails().await;
+    let response = app.get_newsletter_progress(&issue_id).await;
+    let etag = response.headers().get("ETag").unwrap().clone();
+    let progress: serde_json::Value = response.json().await.unwrap();
+
+    // Assert
+    assert_eq!(progress["sent"], progress["total_queued"]);
+    assert_eq!(progress["sent"], 2);
+    assert_eq!(progress["failed"], 0);
+    assert_eq!(progress["pending"], 0);
+
+    // Polling again with the same ETag doesn't send the body again
+    let response = app
+        .api_client
+        .get(&format!(
+            "{}/admin/newsletters/{}/progress",
+            &app.address, issue_id
+        ))
+        .header("If-None-Match", etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 304);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+/// Read the `data:` lines of a server-sent events response until the server
+/// closes it.
+async fn read_progress_events(
+    mut response: reqwest::Response,
+    on_first_event: impl std::future::Future<Output = ()>,
+) -> Vec<serde_json::Value> {
+    let mut on_first_event = Some(on_first_event);
+    let mut buffer = String::new();
+    let mut events = Vec::new();
+    while let Some(chunk) = response.chunk().await.unwrap() {
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        // events are separated by a blank line
+        while let Some(end) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..end + 2).collect();
+            for data in event.lines().filter_map(|l| l.strip_prefix("data:")) {
+                events.push(serde_json::from_str(data.trim()).unwrap());
+            }
+            if let Some(callback) = on_first_event.take() {
+                callback.await;
+            }
+        }
+    }
+    events
+}
+
+#[tokio::test]
+async fn the_delivery_progress_stream_ends_once_everything_is_delivered() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber_with_email("first@example.com")
+        .await;
+    app.create_confirmed_subscriber_with_email("second@example.com")
+        .await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+
+    app.post_publish_newsletter(&serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    }))
+    .await;
+    let issue_id = sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid;
+
+    // Act
+    let response = app.get_newsletter_progress_stream(&issue_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["Content-Type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let events = tokio::time::timeout(
+        Duration::from_secs(10),
+        read_progress_events(response, app.dispatch_all_pending_emails()),
+    )
+    .await
+    .expect("The progress stream did not end");
+
+    // Assert
+    let first = events.first().unwrap();
+    assert_eq!(first["pending"], 2);
+    assert_eq!(first["total"], 2);
+    let last = events.last().unwrap();
+    assert_eq!(last["pending"], 0);
+    assert_eq!(last["sent"], 2);
+    assert_eq!(last["failed"], 0);
+    assert_eq!(last["total"], 2);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_stream_the_delivery_progress() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .get_newsletter_progress_stream(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_the_delivery_progress() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .get_newsletter_progress(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn an_expired_idempotency_key_does_not_return_the_saved_response() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string()
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // Act - Part 1 - Pretend the key was stored a long time ago
+    sqlx::query!("UPDATE idempotency SET created_at = '2000-01-01 00:00:00 UTC'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    // Act - Part 2 - Submit newsletter form **again**
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // Assert
+    let n_issues = sqlx::query!(r#"SELECT COUNT(*) as "count!: i64" FROM newsletter_issues"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(n_issues, 2);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn expired_idempotency_keys_are_cleaned_up() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let expired_key = uuid::Uuid::new_v4().to_string();
+    let fresh_key = uuid::Uuid::new_v4().to_string();
+    for idempotency_key in [&expired_key, &fresh_key] {
+        app.post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": idempotency_key
+        }))
+        .await;
+    }
+    sqlx::query!(
+        "UPDATE idempotency SET created_at = '2000-01-01 00:00:00 UTC' WHERE idempotency_key = $1",
+        expired_key
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let n_deleted = cleanup_expired_idempotency_keys(&app.db_pool, 24)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(n_deleted, 1);
+    let remaining = sqlx::query!("SELECT idempotency_key FROM idempotency")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].idempotency_key, fresh_key);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+fn scheduled_newsletter_request_body(scheduled_for: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": "Scheduled newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        "scheduled_for": scheduled_for,
+    })
+}
+
+#[tokio::test]
+async fn scheduled_newsletters_are_delivered_once_they_are_due() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    // Act - Part 1 - Schedule the issue
+    let scheduled_for = (chrono::Utc::now() + chrono::Duration::seconds(1))
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string();
+    let response = app
+        .post_publish_newsletter(&scheduled_newsletter_request_body(&scheduled_for))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    let html_page = app.get_publish_newsletter_html().await;
+    assert!(html_page.contains("<p><i>The newsletter issue has been scheduled!</i></p>"));
+
+    // Act - Part 2 - Nothing is sent before the issue is due
+    let scheduled: serde_json::Value = app.get_scheduled_newsletters().await.json().await.unwrap();
+    assert_eq!(scheduled.as_array().unwrap().len(), 1);
+    assert_eq!(scheduled[0]["title"], "Scheduled newsletter title");
+    {
+        let _mock_guard = Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount_as_scoped(&app.email_server)
+            .await;
+        assert_eq!(enqueue_due_scheduled_issues(&app.db_pool).await.unwrap(), 0);
+        app.dispatch_all_pending_emails().await;
+    }
+
+    // Act - Part 3 - The issue is enqueued and delivered once it is due
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    assert_eq!(enqueue_due_scheduled_issues(&app.db_pool).await.unwrap(), 1);
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let scheduled: serde_json::Value = app.get_scheduled_newsletters().await.json().await.unwrap();
+    assert!(scheduled.as_array().unwrap().is_empty());
+    let saved = sqlx::query!("SELECT status FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "queued");
+    // a second poll doesn't enqueue the same issue again
+    assert_eq!(enqueue_due_scheduled_issues(&app.db_pool).await.unwrap(), 0);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn a_scheduled_newsletter_can_be_cancelled() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    app.post_publish_newsletter(&scheduled_newsletter_request_body("2000-01-01T00:00:00Z"))
+        .await;
+    let scheduled: serde_json::Value = app.get_scheduled_newsletters().await.json().await.unwrap();
+    let issue_id = scheduled[0]["issue_id"].as_str().unwrap().to_string();
+
+    // Act
+    let response = app.delete_scheduled_newsletter(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 204);
+    let scheduled: serde_json::Value = app.get_scheduled_newsletters().await.json().await.unwrap();
+    assert!(scheduled.as_array().unwrap().is_empty());
+    assert_eq!(enqueue_due_scheduled_issues(&app.db_pool).await.unwrap(), 0);
+    app.dispatch_all_pending_emails().await;
+    // cancelling twice is not possible
+    let response = app.delete_scheduled_newsletter(&issue_id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn an_invalid_schedule_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&scheduled_newsletter_request_body("next tuesday"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_scheduled_newsletters() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let list_response = app.get_scheduled_newsletters().await;
+    let cancel_response = app
+        .delete_scheduled_newsletter(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&list_response, "/login");
+    assert_is_redirect_to(&cancel_response, "/login");
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn deliveries_are_recorded_per_subscriber_and_paginated() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber_with_email("first@example.com")
+        .await;
+    app.create_confirmed_subscriber_with_email("second@example.com")
+        .await;
+    app.create_confirmed_subscriber_with_email("third@example.com")
+        .await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(3)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+    let issue_id = sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid;
+
+    // Act
+    app.dispatch_all_pending_emails().await;
+    let first_page: serde_json::Value = app
+        .get_newsletter_deliveries(&issue_id, 1, 2)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let second_page: serde_json::Value = app
+        .get_newsletter_deliveries(&issue_id, 2, 2)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(first_page["total"], 3);
+    let first_deliveries = first_page["deliveries"].as_array().unwrap();
+    assert_eq!(first_deliveries.len(), 2);
+    assert_eq!(first_deliveries[0]["subscriber_email"], "first@example.com");
+    assert_eq!(first_deliveries[0]["status"], "delivered");
+    assert!(first_deliveries[0]["delivered_at"].is_string());
+    assert!(first_deliveries[0]["failure_reason"].is_null());
+    let second_deliveries = second_page["deliveries"].as_array().unwrap();
+    assert_eq!(second_deliveries.len(), 1);
+    assert_eq!(
+        second_deliveries[0]["subscriber_email"],
+        "third@example.com"
+    );
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn permanently_failed_deliveries_are_recorded_with_their_reason() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(400))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    app.post_publish_newsletter(&newsletter_request_body).await;
+    let issue_id = sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid;
+
+    // Act
+    app.dispatch_all_pending_emails().await;
+    let page: serde_json::Value = app
+        .get_newsletter_deliveries(&issue_id, 1, 10)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["deliveries"][0]["status"], "failed");
+    assert!(page["deliveries"][0]["delivered_at"].is_null());
+    assert!(page["deliveries"][0]["failure_reason"].is_string());
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn deliveries_of_an_unknown_issue_are_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .get_newsletter_deliveries(&uuid::Uuid::new_v4().to_string(), 1, 10)
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn an_invalid_deliveries_page_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .get_newsletter_deliveries(&uuid::Uuid::new_v4().to_string(), 0, 10)
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn markdown_newsletters_are_rendered_to_html_and_plain_text() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "",
+        "markdown_content": "# Hello\n\nSome **bold** text",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let saved =
+        sqlx::query!("SELECT text_content, html_content, markdown_content FROM newsletter_issues")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        saved.html_content,
+        "<h1>Hello</h1>\n<p>Some <strong>bold</strong> text</p>\n"
+    );
+    assert_eq!(saved.text_content, "Hello\n\nSome bold text");
+    assert_eq!(
+        saved.markdown_content.as_deref(),
+        Some("# Hello\n\nSome **bold** text")
+    );
+    let email_request = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert!(body["HtmlBody"]
+        .as_str()
+        .unwrap()
+        .contains("<strong>bold</strong>"));
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn newsletter_body_must_be_either_markdown_or_html() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let test_cases = vec![
+        (
+            serde_json::json!({
+                "title": "Newsletter title",
+                "text_content": "Newsletter body as plain text",
+                "html_content": "<p>Newsletter body as HTML</p>",
+                "markdown_content": "Newsletter body as Markdown",
+                "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            }),
+            "both HTML and Markdown",
+        ),
+        (
+            serde_json::json!({
+                "title": "Newsletter title",
+                "text_content": "Newsletter body as plain text",
+                "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            }),
+            "neither HTML nor Markdown",
+        ),
+        (
+            serde_json::json!({
+                "title": "Newsletter title",
+                "text_content": "",
+                "html_content": "<p>Newsletter body as HTML</p>",
+                "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            }),
+            "HTML without plain text",
+        ),
+    ];
+
+    for (invalid_body, error_message) in test_cases {
+        // Act
+        let response = app.post_publish_newsletter(&invalid_body).await;
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request when the payload had {}.",
+            error_message
+        );
+    }
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+async fn publish_newsletter_and_get_its_id(app: &TestApp) -> String {
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    });
+    let response = app.post_publish_newsletter(&newsletter_request_body).await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    sqlx::query!("SELECT newsletter_issue_uuid FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .newsletter_issue_uuid
+}
+
+fn edited_newsletter_body() -> serde_json::Value {
+    serde_json::json!({
+        "title": "Fixed newsletter title",
+        "text_content": "Fixed newsletter body as plain text",
+        "html_content": "<p>Fixed newsletter body as HTML</p>",
+    })
+}
+
+#[tokio::test]
+async fn the_edit_form_is_filled_with_the_issue_content() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    let issue_id = publish_newsletter_and_get_its_id(&app).await;
+
+    // Act
+    let response = app.get_edit_newsletter_issue(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains(r#"value="Newsletter title""#));
+    assert!(html.contains("Newsletter body as plain text"));
+    assert!(html.contains("Newsletter body as HTML"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn edits_reach_the_subscribers_still_in_the_queue() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber_with_email("first@example.com")
+        .await;
+    app.create_confirmed_subscriber_with_email("second@example.com")
+        .await;
+    app.test_user.login(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+    let issue_id = publish_newsletter_and_get_its_id(&app).await;
+    // deliver to one of the subscribers before the fix
+    try_execute_task(
+        &app.db_pool,
+        &app.email_client,
+        &app.base_url,
+        &app.hmac_secret,
+        app.max_retries,
+        app.email_send_timeout,
+    )
+    .await
+    .unwrap();
+
+    // Act
+    let response = app
+        .post_edit_newsletter_issue(&issue_id, &edited_newsletter_body())
+        .await;
+    assert_is_redirect_to(&response, &format!("/admin/newsletters/{}/edit", issue_id));
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let subjects: Vec<String> = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice::<serde_json::Value>(&r.body).unwrap())
+        .map(|body| body["Subject"].as_str().unwrap().to_string())
+        // leave the confirmation emails out
+        .filter(|subject| subject.ends_with("title"))
+        .collect();
+    assert_eq!(subjects, ["Newsletter title", "Fixed newsletter title"]);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_issue_that_has_been_delivered_cannot_be_edited() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    let issue_id = publish_newsletter_and_get_its_id(&app).await;
+    app.dispatch_all_pending_emails().await;
+
+    // Act
+    let response = app
+        .post_edit_newsletter_issue(&issue_id, &edited_newsletter_body())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+    assert_eq!(
+        app.get_edit_newsletter_issue(&issue_id)
+            .await
+            .status()
+            .as_u16(),
+        409
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editing_an_unknown_issue_is_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_edit_newsletter_issue(&uuid::Uuid::new_v4().to_string(), &edited_newsletter_body())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_edit_a_newsletter() {
+    // Arrange
+    let app = spawn_app().await;
+    let issue_id = uuid::Uuid::new_v4().to_string();
+
+    // Act
+    let form = app.get_edit_newsletter_issue(&issue_id).await;
+    let response = app
+        .post_edit_newsletter_issue(&issue_id, &edited_newsletter_body())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&form, "/login");
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_duplicated_issue_is_a_draft_copy_of_the_original() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    let issue_id = publish_newsletter_and_get_its_id(&app).await;
+    sqlx::query!("INSERT INTO tags (name, created_at) VALUES ('rust', '2026-01-01 00:00:00 UTC')")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "INSERT INTO newsletter_issue_tags (newsletter_issue_uuid, tag) VALUES ($1, 'rust')",
+        issue_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let response = app.post_duplicate_newsletter_issue(&issue_id).await;
+
+    // Assert
+    let copy = sqlx::query!(
+        r#"
+        SELECT newsletter_issue_uuid, title, text_content, html_content, status, slug
+        FROM newsletter_issues
+        WHERE newsletter_issue_uuid != $1
+        "#,
+        issue_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_is_redirect_to(
+        &response,
+        &format!("/admin/newsletters/{}/edit", copy.newsletter_issue_uuid),
+    );
+    assert_eq!(copy.title, "Copy of Newsletter title");
+    assert_eq!(copy.text_content, "Newsletter body as plain text");
+    assert_eq!(copy.html_content, "<p>Newsletter body as HTML</p>");
+    assert_eq!(copy.status, "draft");
+    assert!(copy.slug.is_none());
+    let tags = sqlx::query!(
+        "SELECT tag FROM newsletter_issue_tags WHERE newsletter_issue_uuid = $1",
+        copy.newsletter_issue_uuid
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].tag, "rust");
+
+    // the draft can be edited, but isn't published
+    let form = app
+        .get_edit_newsletter_issue(&copy.newsletter_issue_uuid)
+        .await;
+    assert_eq!(form.status().as_u16(), 200);
+    let archive = app.get_archive().await.text().await.unwrap();
+    assert!(!archive.contains("Copy of Newsletter title"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn duplicating_an_unknown_issue_is_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_duplicate_newsletter_issue(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    let n_issues = sqlx::query!(r#"SELECT COUNT(*) AS "count!: i64" FROM newsletter_issues"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(n_issues, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_duplicate_a_newsletter() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_duplicate_newsletter_issue(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn newsletters_are_sent_with_the_chosen_reply_to_address() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "reply_to_email": "editor@example.com",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let saved = sqlx::query!("SELECT reply_to_email FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.reply_to_email.as_deref(), Some("editor@example.com"));
+    let email_request = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(body["ReplyTo"], "editor@example.com");
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn replies_go_to_the_sender_when_no_reply_to_address_is_chosen() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "reply_to_email": "",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let email_request = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(body["ReplyTo"], body["From"]);
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn an_invalid_reply_to_address_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "reply_to_email": "not-an-email",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    let issues = sqlx::query!("SELECT title FROM newsletter_issues")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(issues.is_empty());
+
+    app.cleanup_test_db().await.unwrap()
+}
+
+#[tokio::test]
+async fn newsletter_list_shows_word_count_and_reading_time() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let text_content = vec!["word"; 450].join(" ");
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": text_content,
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+
+    // Assert
+    let issues = app.get_newsletters_json().await;
+    assert_eq!(issues[0]["word_count"], 450);
+    assert_eq!(issues[0]["reading_time_minutes"], 2);
+
+    let html_page = app.get_publish_newsletter_html().await;
+    assert!(html_page.contains("450 words, 2 min read"));
+
+    app.cleanup_test_db().await.unwrap()
+}
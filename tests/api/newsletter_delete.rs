@@ -0,0 +1,132 @@
+use crate::helpers::spawn_app;
+use newzletter::issue_delivery_worker::{try_execute_task, ExecutionOutcome};
+use wiremock::matchers::any;
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn deleted_issues_are_taken_out_of_the_archive_and_the_feed() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = app
+        .publish_issue("Deleted news", "<p>Newsletter body as HTML</p>")
+        .await;
+
+    // Act
+    let response = app.delete_newsletter_issue(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 204);
+    let archive = app.get_archive().await.text().await.unwrap();
+    assert!(!archive.contains("Deleted news"));
+    assert_eq!(
+        app.get_archive_issue("deleted-news")
+            .await
+            .status()
+            .as_u16(),
+        404
+    );
+    let feed = app.get_feed().await.text().await.unwrap();
+    assert!(!feed.contains("Deleted news"));
+    // the row is kept for the deliveries that refer to it
+    let deleted_at = sqlx::query_scalar!(
+        "SELECT deleted_at FROM newsletter_issues WHERE newsletter_issue_uuid = $1",
+        issue_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert!(deleted_at.is_some());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn deleted_issues_are_not_dispatched_by_the_worker() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+    let issue_id = app
+        .publish_issue("Deleted news", "<p>Newsletter body as HTML</p>")
+        .await;
+
+    // Act
+    let response = app.delete_newsletter_issue(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 204);
+    app.dispatch_all_pending_emails().await;
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn tasks_left_in_the_queue_of_a_deleted_issue_are_skipped() {
+    // Arrange
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.test_user.login(&app).await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+    let issue_id = app
+        .publish_issue("Deleted news", "<p>Newsletter body as HTML</p>")
+        .await;
+    // deleted while the worker already holds one of its tasks
+    sqlx::query!(
+        "UPDATE newsletter_issues SET deleted_at = 'now' WHERE newsletter_issue_uuid = $1",
+        issue_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let outcome = try_execute_task(
+        &app.db_pool,
+        &app.email_client,
+        &app.base_url,
+        &app.hmac_secret,
+        app.max_retries,
+        app.email_send_timeout,
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert!(matches!(outcome, ExecutionOutcome::TaskSkipped));
+    let delivered = sqlx::query_scalar!("SELECT COUNT(*) FROM newsletter_deliveries")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(delivered, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn deleting_an_issue_twice_returns_404() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = app
+        .publish_issue("Deleted news", "<p>Newsletter body as HTML</p>")
+        .await;
+    app.delete_newsletter_issue(&issue_id).await;
+
+    // Act
+    let response = app.delete_newsletter_issue(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
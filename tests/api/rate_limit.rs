@@ -0,0 +1,224 @@
+use crate::helpers::{spawn_app_with, FormData};
+
+#[tokio::test]
+async fn logging_in_too_often_is_rejected_with_a_429() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.strict.capacity = 5;
+        c.rate_limit.strict.refill_per_minute = 5;
+    })
+    .await;
+    let login_body = serde_json::json!({
+        "username": "random-username",
+        "password": "random-password"
+    });
+    for _ in 0..5 {
+        let response = app.post_login(&login_body).await;
+        assert_eq!(response.status().as_u16(), 303);
+    }
+
+    // Act
+    let response = app.post_login(&login_body).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+    let retry_after: u64 = response.headers()["Retry-After"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=12).contains(&retry_after));
+    // the login form itself is not subject to the strict limit
+    assert!(app.get_login_html().await.contains("Login to Newzletter"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribing_too_often_is_rejected_with_a_429() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.strict.capacity = 5;
+        c.rate_limit.strict.refill_per_minute = 5;
+    })
+    .await;
+    let body = FormData {
+        name: Some("".to_string()),
+        email: Some("ursula_le_guin@gmail.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+    for _ in 0..5 {
+        let response = app.post_subscriptions(&body).await;
+        assert_ne!(response.status().as_u16(), 429);
+    }
+
+    // Act
+    let response = app.post_subscriptions(&body).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+    assert!(response.headers().contains_key("Retry-After"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn resending_confirmations_too_often_is_rejected_with_a_429() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.strict.capacity = 5;
+        c.rate_limit.strict.refill_per_minute = 5;
+    })
+    .await;
+    // a different address every time, so the per address limit doesn't kick in first
+    for i in 0..5 {
+        let response = app
+            .post_resend_confirmation(&format!("ursula{i}@example.com"))
+            .await;
+        assert_ne!(response.status().as_u16(), 429);
+    }
+
+    // Act
+    let response = app.post_resend_confirmation("le_guin@example.com").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+    assert!(response.headers().contains_key("Retry-After"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn requesting_password_resets_too_often_is_rejected_with_a_429() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.strict.capacity = 5;
+        c.rate_limit.strict.refill_per_minute = 5;
+    })
+    .await;
+    for _ in 0..5 {
+        let response = app.post_reset_password("nobody@example.com").await;
+        assert_ne!(response.status().as_u16(), 429);
+    }
+
+    // Act
+    let response = app.post_reset_password("nobody@example.com").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+    // the form itself is not subject to the strict limit
+    assert_eq!(app.get_reset_password().await.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn every_route_is_subject_to_the_default_limit() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.default.capacity = 3;
+        c.rate_limit.default.refill_per_minute = 60;
+    })
+    .await;
+    let health_check = || {
+        app.api_client
+            .get(format!("{}/health_check", &app.address))
+            .send()
+    };
+    for _ in 0..3 {
+        assert_eq!(health_check().await.unwrap().status().as_u16(), 200);
+    }
+
+    // Act
+    let response = health_check().await.unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+    assert_eq!(response.headers()["Retry-After"], "1");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn each_client_address_has_its_own_bucket() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.default.capacity = 1;
+    })
+    .await;
+    let health_check = |client_ip: &'static str| {
+        app.api_client
+            .get(format!("{}/health_check", &app.address))
+            .header("X-Forwarded-For", client_ip)
+            .send()
+    };
+    assert_eq!(
+        health_check("203.0.113.1").await.unwrap().status().as_u16(),
+        200
+    );
+    assert_eq!(
+        health_check("203.0.113.1").await.unwrap().status().as_u16(),
+        429
+    );
+
+    // Act
+    let response = health_check("203.0.113.2").await.unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn forwarded_clients_subscribe_from_their_own_bucket() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.strict.capacity = 1;
+        c.rate_limit.strict.refill_per_minute = 1;
+    })
+    .await;
+    let body = FormData {
+        name: Some("".to_string()),
+        email: Some("ursula_le_guin@gmail.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+    let response = app.post_subscriptions_with_ip(&body, "203.0.113.1").await;
+    assert_ne!(response.status().as_u16(), 429);
+    let response = app.post_subscriptions_with_ip(&body, "203.0.113.1").await;
+    assert_eq!(response.status().as_u16(), 429);
+
+    // Act
+    let response = app.post_subscriptions_with_ip(&body, "203.0.113.2").await;
+
+    // Assert
+    assert_ne!(response.status().as_u16(), 429);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_forwarded_address_of_an_untrusted_peer_is_ignored() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.application.trusted_proxies = vec![];
+        c.rate_limit.strict.capacity = 1;
+        c.rate_limit.strict.refill_per_minute = 1;
+    })
+    .await;
+    let body = FormData {
+        name: Some("".to_string()),
+        email: Some("ursula_le_guin@gmail.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+    let response = app.post_subscriptions_with_ip(&body, "203.0.113.1").await;
+    assert_ne!(response.status().as_u16(), 429);
+
+    // Act
+    let response = app.post_subscriptions_with_ip(&body, "203.0.113.2").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,190 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp, TestUser};
+
+/// Invite a new user as the test admin, then log out, and return the token.
+async fn create_invite(app: &TestApp) -> String {
+    app.test_user.login(app).await;
+    let response = app.post_invite_user().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let invite: serde_json::Value = response.json().await.unwrap();
+    let invite_url = reqwest::Url::parse(invite["invite_url"].as_str().unwrap()).unwrap();
+    assert_eq!(invite_url.path(), "/register");
+    app.post_logout().await;
+
+    invite_url
+        .query_pairs()
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+fn registration_form(token: &str, username: &str, password: &str) -> serde_json::Value {
+    serde_json::json!({
+        "token": token,
+        "username": username,
+        "password": password,
+        "password_check": password,
+    })
+}
+
+#[tokio::test]
+async fn an_invited_user_can_register_and_log_in() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = create_invite(&app).await;
+    let username = uuid::Uuid::new_v4().to_string();
+    let password = uuid::Uuid::new_v4().to_string();
+
+    // Act - Part 1 - Open the invite link
+    let response = app.get_register(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act - Part 2 - Register, which logs the new user in
+    let response = app
+        .post_register(&registration_form(&token, &username, &password))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    let html_page = app.get_admin_dashboard_html().await;
+    assert!(html_page.contains(&format!("Welcome {}", username)));
+
+    // Act - Part 3 - Log out and log back in with the new account
+    app.post_logout().await;
+    let response = app
+        .post_login(&serde_json::json!({
+            "username": &username,
+            "password": &password,
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    let response = app.get_admin_dashboard().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invite_can_only_be_used_once() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = create_invite(&app).await;
+    let response = app
+        .post_register(&registration_form(&token, "first", "a-password"))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    app.post_logout().await;
+
+    // Act
+    let form_response = app.get_register(&token).await;
+    let response = app
+        .post_register(&registration_form(&token, "second", "a-password"))
+        .await;
+
+    // Assert
+    assert_eq!(form_response.status().as_u16(), 410);
+    assert_eq!(response.status().as_u16(), 410);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_expired_invite_is_rejected_with_a_410() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = create_invite(&app).await;
+    sqlx::query!(
+        "UPDATE user_invites SET expires_at = '2000-01-01 00:00:00 UTC' WHERE token = $1",
+        token
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let form_response = app.get_register(&token).await;
+    let response = app
+        .post_register(&registration_form(&token, "latecomer", "a-password"))
+        .await;
+
+    // Assert
+    assert_eq!(form_response.status().as_u16(), 410);
+    assert_eq!(response.status().as_u16(), 410);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unknown_invite_is_rejected_with_a_404() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_register("not-a-real-token").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn registering_with_mismatched_passwords_keeps_the_invite_usable() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = create_invite(&app).await;
+
+    // Act
+    let response = app
+        .post_register(&serde_json::json!({
+            "token": &token,
+            "username": "mismatch",
+            "password": "a-password",
+            "password_check": "another-password",
+        }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, &format!("/register?token={}", token));
+    let html_page = app.get_register(&token).await.text().await.unwrap();
+    assert!(html_page.contains("You entered two different passwords"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn registering_with_a_taken_username_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = create_invite(&app).await;
+
+    // Act
+    let response = app
+        .post_register(&registration_form(
+            &token,
+            &app.test_user.username,
+            "a-password",
+        ))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, &format!("/register?token={}", token));
+    let html_page = app.get_register(&token).await.text().await.unwrap();
+    assert!(html_page.contains("This username is already taken."));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_invite_users() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app.post_invite_user().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
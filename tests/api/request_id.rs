@@ -0,0 +1,75 @@
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn the_request_id_of_the_client_is_echoed_back() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .header("X-Request-ID", "upstream-request-42")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(
+        response.headers()["X-Request-ID"].to_str().unwrap(),
+        "upstream-request-42"
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn every_response_gets_a_request_id() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let first = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    // errors are what clients need to report the most
+    let second = app
+        .api_client
+        .get(&format!("{}/does-not-exist", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let first_id = first.headers()["X-Request-ID"].to_str().unwrap();
+    let second_id = second.headers()["X-Request-ID"].to_str().unwrap();
+    assert!(uuid::Uuid::try_parse(first_id).is_ok());
+    assert!(uuid::Uuid::try_parse(second_id).is_ok());
+    assert_ne!(first_id, second_id);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unreasonable_request_ids_are_replaced() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .header("X-Request-ID", "a".repeat(1000))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let request_id = response.headers()["X-Request-ID"].to_str().unwrap();
+    assert!(uuid::Uuid::try_parse(request_id).is_ok());
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,264 @@
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+/// Ask for a reset link for the test user and return the token it carries.
+async fn request_reset_token(app: &TestApp) -> String {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let response = app.post_reset_password(&app.test_user.email).await;
+    assert_is_redirect_to(&response, "/reset-password");
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let links = app.get_confirmation_links(email_request);
+    assert_eq!(links.html.path(), "/reset-password/confirm");
+    links
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+fn new_password_form(token: &str, new_password: &str) -> serde_json::Value {
+    serde_json::json!({
+        "token": token,
+        "new_password": new_password,
+        "new_password_check": new_password,
+    })
+}
+
+#[tokio::test]
+async fn the_login_page_links_to_the_reset_password_form() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let login_page = app.get_login_html().await;
+    let response = app.get_reset_password().await;
+
+    // Assert
+    assert!(login_page.contains(r#"href="/reset-password""#));
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn no_email_is_sent_for_an_unknown_address() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app.post_reset_password("nobody@example.com").await;
+
+    // Assert - the answer is the same whether the account exists or not
+    assert_is_redirect_to(&response, "/reset-password");
+    let html_page = app.get_reset_password().await.text().await.unwrap();
+    assert!(html_page.contains("a reset link is on its way"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invalid_email_is_rejected_with_a_400() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.post_reset_password("definitely-not-an-email").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_reset_link_lets_the_user_choose_a_new_password() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = request_reset_token(&app).await;
+    let new_password = uuid::Uuid::new_v4().to_string();
+
+    // Act - Part 1 - Open the reset link
+    let response = app.get_reset_password_confirm(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act - Part 2 - Choose a new password
+    let response = app
+        .post_reset_password_confirm(&new_password_form(&token, &new_password))
+        .await;
+    assert_is_redirect_to(&response, "/login");
+    let html_page = app.get_login_html().await;
+    assert!(html_page.contains("Your password has been reset"));
+
+    // Act - Part 3 - The old password no longer works
+    let response = app
+        .post_login(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &app.test_user.password,
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/login");
+
+    // Act - Part 4 - The new one does
+    let response = app
+        .post_login(&serde_json::json!({
+            "username": &app.test_user.username,
+            "password": &new_password,
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn resetting_the_password_logs_the_user_out_everywhere() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    assert_eq!(app.get_admin_dashboard().await.status().as_u16(), 200);
+    let token = request_reset_token(&app).await;
+
+    // Act - reset from another browser
+    let other_client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let csrf_token = other_client
+        .get(&format!("{}/reset-password/confirm", &app.address))
+        .query(&[("token", &token)])
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .cookies()
+        .find(|cookie| cookie.name() == "csrf_token")
+        .expect("The `csrf_token` cookie was not set.")
+        .value()
+        .to_string();
+    let response = other_client
+        .post(&format!("{}/reset-password/confirm", &app.address))
+        .header("X-CSRF-Token", csrf_token)
+        .form(&new_password_form(&token, "a-brand-new-password"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_is_redirect_to(&response, "/login");
+
+    // Assert
+    let response = app.get_admin_dashboard().await;
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_reset_token_can_only_be_used_once() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = request_reset_token(&app).await;
+    let response = app
+        .post_reset_password_confirm(&new_password_form(&token, "first-new-password"))
+        .await;
+    assert_is_redirect_to(&response, "/login");
+
+    // Act
+    let form_response = app.get_reset_password_confirm(&token).await;
+    let response = app
+        .post_reset_password_confirm(&new_password_form(&token, "second-new-password"))
+        .await;
+
+    // Assert
+    assert_eq!(form_response.status().as_u16(), 410);
+    assert_eq!(response.status().as_u16(), 410);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_expired_reset_token_is_rejected_with_a_410() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = request_reset_token(&app).await;
+    sqlx::query!(
+        "UPDATE password_reset_tokens SET expires_at = '2000-01-01 00:00:00 UTC' WHERE token = $1",
+        token
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let form_response = app.get_reset_password_confirm(&token).await;
+    let response = app
+        .post_reset_password_confirm(&new_password_form(&token, "a-new-password"))
+        .await;
+
+    // Assert
+    assert_eq!(form_response.status().as_u16(), 410);
+    assert_eq!(response.status().as_u16(), 410);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unknown_reset_token_is_rejected_with_a_404() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_reset_password_confirm("not-a-real-token").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn mismatched_new_passwords_keep_the_token_usable() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = request_reset_token(&app).await;
+
+    // Act
+    let response = app
+        .post_reset_password_confirm(&serde_json::json!({
+            "token": &token,
+            "new_password": "a-new-password",
+            "new_password_check": "another-new-password",
+        }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(
+        &response,
+        &format!("/reset-password/confirm?token={}", token),
+    );
+    let html_page = app
+        .get_reset_password_confirm(&token)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("You entered two different new passwords"));
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,60 @@
+use newzletter::configuration::Environment;
+
+use crate::helpers::{spawn_app, spawn_app_with};
+
+fn session_cookie(response: &reqwest::Response) -> String {
+    response
+        .headers()
+        .get_all("Set-Cookie")
+        .iter()
+        .map(|h| h.to_str().unwrap().to_string())
+        .find(|cookie| cookie.starts_with("id="))
+        .expect("No session cookie was set.")
+}
+
+#[tokio::test]
+async fn production_session_cookies_are_secure_and_strict() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.application.environment = Environment::Production;
+        c.session.secure = true;
+        c.session.same_site = "strict".to_string();
+    })
+    .await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/login", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let cookie = session_cookie(&response);
+    assert!(cookie.contains("; Secure"), "{}", cookie);
+    assert!(cookie.contains("SameSite=Strict"), "{}", cookie);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn local_session_cookies_work_over_plain_http() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/login", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let cookie = session_cookie(&response);
+    assert!(!cookie.contains("Secure"), "{}", cookie);
+    assert!(cookie.contains("SameSite=Lax"), "{}", cookie);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn in_flight_requests_complete_during_a_graceful_shutdown() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_subscriber(
+        "ursula",
+        "ursula@example.com",
+        "pending_confirmation",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    // resending the confirmation email is slow because the email server is slow
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(2)))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let slow_request = {
+        let client = app.api_client.clone();
+        let url = format!("{}/subscriptions/resend-confirmation", &app.address);
+        let csrf_token = app.csrf_token().await;
+        tokio::spawn(async move {
+            client
+                .post(url)
+                .header("X-CSRF-Token", csrf_token)
+                .form(&[("email", "ursula@example.com")])
+                .send()
+                .await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    app.shutdown_token.cancel();
+
+    // Assert
+    let response = slow_request
+        .await
+        .unwrap()
+        .expect("The in-flight request was dropped");
+    assert_is_redirect_to(&response, "/?subscribed=true");
+    // new connections are refused once the server has shut down
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(app
+        .api_client
+        .get(&format!("{}/health_check", &app.address))
+        .send()
+        .await
+        .is_err());
+
+    app.cleanup_test_db().await.unwrap();
+}
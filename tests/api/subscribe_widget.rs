@@ -0,0 +1,170 @@
+use newzletter::configuration::Environment;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, spawn_app_with};
+
+#[tokio::test]
+async fn the_widget_can_be_embedded_by_other_sites() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/subscribe-widget", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let policy = response.headers()["Content-Security-Policy"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(policy.contains("frame-ancestors *"), "{}", policy);
+    let html = response.text().await.unwrap();
+    assert!(html.contains(r#"action="/subscribe-widget""#));
+    assert!(html.contains("window.parent.postMessage"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_widget_script_runs_under_the_production_policy() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.environment = Environment::Production).await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/subscribe-widget", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let policy = response.headers()["Content-Security-Policy"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(policy.ends_with("; frame-ancestors *"), "{}", policy);
+    let nonce = policy
+        .split("'nonce-")
+        .nth(1)
+        .and_then(|rest| rest.split('\'').next())
+        .unwrap()
+        .to_string();
+    let html = response.text().await.unwrap();
+    assert!(html.contains(&format!(r#"<script nonce="{}">"#, nonce)));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn other_pages_are_not_embeddable_anywhere() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/login", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let policy = response.headers()["Content-Security-Policy"]
+        .to_str()
+        .unwrap();
+    assert!(!policy.contains("frame-ancestors"), "{}", policy);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_embed_code_points_to_the_widget_of_this_deployment() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(&format!("{}/subscribe-widget/embed-code", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let snippet = response.text().await.unwrap();
+    assert!(snippet.starts_with(&format!(
+        r#"<iframe src="{}/subscribe-widget""#,
+        app.base_url.trim_end_matches('/')
+    )));
+    assert!(snippet.ends_with("</iframe>"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_widget_can_subscribe_without_a_session() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act - no CSRF token, as from inside another site
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .post(&format!("{}/subscribe-widget", &app.address))
+        .form(&[
+            ("name", "abood"),
+            ("email", "3la_el_7doood@yahoo.com"),
+            ("cf-turnstile-response", "test-token"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_is_redirect_to(&response, "/?subscribed=true");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribing_outside_the_widget_still_needs_a_csrf_token() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .post(&format!("{}/subscriptions", &app.address))
+        .form(&[
+            ("name", "abood"),
+            ("email", "3la_el_7doood@yahoo.com"),
+            ("cf-turnstile-response", "test-token"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
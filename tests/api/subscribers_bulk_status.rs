@@ -0,0 +1,240 @@
+use uuid::Uuid;
+
+use crate::helpers::{spawn_app, TestApp, TestUser};
+
+async fn stored_status(app: &TestApp, uuid: &str) -> String {
+    sqlx::query!("SELECT status FROM subscriptions WHERE uuid = $1", uuid)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn every_listed_subscriber_is_updated() {
+    // Arrange
+    let app = spawn_app().await;
+    let ursula = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "pending_confirmation",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    let octavia = app
+        .insert_subscriber(
+            "octavia",
+            "octavia@example.com",
+            "pending_confirmation",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": [&ursula, &octavia],
+            "status": "confirmed",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let outcome: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(outcome["updated"], 2);
+    assert_eq!(outcome["not_found"], serde_json::json!([]));
+    assert_eq!(stored_status(&app, &ursula).await, "confirmed");
+    assert_eq!(stored_status(&app, &octavia).await, "confirmed");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_subscribers_are_reported_without_failing_the_batch() {
+    // Arrange
+    let app = spawn_app().await;
+    let ursula = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    let unknown = Uuid::new_v4().to_string();
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": [&ursula, &unknown],
+            "status": "unsubscribed",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let outcome: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(outcome["updated"], 1);
+    assert_eq!(outcome["not_found"], serde_json::json!([unknown]));
+    assert_eq!(stored_status(&app, &ursula).await, "unsubscribed");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn more_than_1000_subscribers_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let uuids: Vec<String> = (0..1001).map(|_| Uuid::new_v4().to_string()).collect();
+
+    // Act
+    let response = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": uuids,
+            "status": "confirmed",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_empty_list_updates_nothing() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": [],
+            "status": "confirmed",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let outcome: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(outcome["updated"], 0);
+    assert_eq!(outcome["not_found"], serde_json::json!([]));
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unsubscribed_subscribers_are_only_confirmed_when_forced() {
+    // Arrange
+    let app = spawn_app().await;
+    let ursula = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "unsubscribed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let outcome: serde_json::Value = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": [&ursula],
+            "status": "confirmed",
+        }))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(outcome["updated"], 0);
+    assert_eq!(outcome["skipped"], serde_json::json!([&ursula]));
+    assert_eq!(stored_status(&app, &ursula).await, "unsubscribed");
+
+    let outcome: serde_json::Value = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": [&ursula],
+            "status": "confirmed",
+            "force": true,
+        }))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(outcome["updated"], 1);
+    assert_eq!(stored_status(&app, &ursula).await, "confirmed");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_change_statuses_in_bulk() {
+    // Arrange
+    let app = spawn_app().await;
+    let ursula = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "pending_confirmation",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app
+        .post_bulk_subscriber_status(&serde_json::json!({
+            "uuids": [&ursula],
+            "status": "confirmed",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(stored_status(&app, &ursula).await, "pending_confirmation");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_body_that_is_not_json_is_rejected_with_a_415() {
+    // Arrange
+    let app = spawn_app().await;
+    let ursula = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "pending_confirmation",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    app.test_user.login(&app).await;
+    let body = serde_json::json!({ "uuids": [&ursula], "status": "confirmed" });
+
+    // Act
+    let response = app
+        .api_client
+        .post(format!("{}/admin/subscribers/bulk-status", &app.address))
+        .header("Content-Type", "text/plain")
+        .header("X-CSRF-Token", app.csrf_token().await)
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 415);
+    let error: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        error,
+        serde_json::json!({
+            "error": "content_type_required",
+            "expected": "application/json",
+        })
+    );
+    assert_eq!(stored_status(&app, &ursula).await, "pending_confirmation");
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,151 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestUser};
+
+#[tokio::test]
+async fn subscribers_are_counted_by_status() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_subscriber(
+        "ursula",
+        "ursula@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "viktor",
+        "viktor@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "wanda",
+        "wanda@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "xavier",
+        "xavier@example.com",
+        "pending_confirmation",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "yara",
+        "yara@example.com",
+        "unsubscribed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "zeno",
+        "zeno@example.com",
+        "unsubscribed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_subscriber_counts().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let counts: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        counts,
+        serde_json::json!({
+            "confirmed": 3,
+            "pending": 1,
+            "unsubscribed": 2,
+            "total": 6,
+        })
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn statuses_without_subscribers_are_counted_as_zero() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_subscriber_counts().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let counts: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        counts,
+        serde_json::json!({
+            "confirmed": 0,
+            "pending": 0,
+            "unsubscribed": 0,
+            "total": 0,
+        })
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscriber_counts_are_cached_for_a_minute() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_subscriber_counts().await;
+
+    // Assert
+    assert_eq!(
+        response.headers()["cache-control"].to_str().unwrap(),
+        "private, max-age=60"
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_can_see_subscriber_counts() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_subscriber(
+        "ursula",
+        "ursula@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app.get_subscriber_counts().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let counts: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(counts["confirmed"], 1);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_subscriber_counts() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_subscriber_counts().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
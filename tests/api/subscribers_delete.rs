@@ -0,0 +1,132 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp, TestUser};
+
+async fn deliver_a_newsletter(app: &TestApp) {
+    app.post_publish_newsletter(&serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": uuid::Uuid::new_v4().to_string(),
+    }))
+    .await;
+    app.dispatch_all_pending_emails().await;
+}
+
+#[tokio::test]
+async fn deleting_a_subscriber_erases_their_personal_data() {
+    // Arrange
+    let app = spawn_app().await;
+    let ursula = app
+        .create_confirmed_subscriber_with_email("ursula@example.com")
+        .await;
+    let viktor = app
+        .create_confirmed_subscriber_with_email("viktor@example.com")
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    app.test_user.login(&app).await;
+    deliver_a_newsletter(&app).await;
+
+    // Act
+    // both leave '[redacted]' deliveries behind for the same issue
+    let first_response = app.delete_subscriber(&ursula).await;
+    let second_response = app.delete_subscriber(&viktor).await;
+
+    // Assert
+    assert_eq!(first_response.status().as_u16(), 204);
+    assert_eq!(second_response.status().as_u16(), 204);
+    let n_subscribers = sqlx::query_scalar!("SELECT COUNT(*) FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(n_subscribers, 0);
+    let n_tokens = sqlx::query_scalar!("SELECT COUNT(*) FROM subscription_tokens")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(n_tokens, 0);
+    let deliveries = sqlx::query!("SELECT subscriber_email, status FROM newsletter_deliveries")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(deliveries.len(), 2);
+    for delivery in deliveries {
+        assert_eq!(delivery.subscriber_email, "[redacted]");
+        assert_eq!(delivery.status, "delivered");
+    }
+    let deletions =
+        sqlx::query!("SELECT uuid, deleted_by_user_uuid FROM subscriber_deletions ORDER BY id")
+            .fetch_all(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(deletions.len(), 2);
+    assert_eq!(deletions[0].uuid, ursula);
+    assert_eq!(
+        deletions[0].deleted_by_user_uuid,
+        app.test_user.uuid.to_string()
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn deleting_an_unknown_subscriber_is_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .delete_subscriber(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    let n_deletions = sqlx::query_scalar!("SELECT COUNT(*) FROM subscriber_deletions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(n_deletions, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_delete_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app
+        .delete_subscriber(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_delete_a_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .delete_subscriber(&uuid::Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,103 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+async fn insert_subscribers(app: &TestApp) {
+    app.insert_subscriber(
+        "ursula",
+        "ursula@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "le guin, ursula",
+        "le_guin@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "pending",
+        "pending@example.com",
+        "pending_confirmation",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "gone",
+        "gone@example.com",
+        "unsubscribed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_export_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_subscribers_export(None).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn all_subscribers_are_exported_as_csv() {
+    // Arrange
+    let app = spawn_app().await;
+    insert_subscribers(&app).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_subscribers_export(None).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["Content-Type"],
+        "text/csv; charset=utf-8"
+    );
+    let content_disposition = response.headers()["Content-Disposition"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    assert!(content_disposition.starts_with("attachment; filename=\"subscribers-"));
+    assert!(content_disposition.ends_with(".csv\""));
+
+    let csv = response.text().await.unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines[0], "uuid,name,email,status,subscribed_at");
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].ends_with(",ursula,ursula@example.com,confirmed,2026-01-01 00:00:00 UTC"));
+    // names with a comma are quoted
+    assert!(lines[2].contains(",\"le guin, ursula\",le_guin@example.com,"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_export_can_be_filtered_by_status() {
+    // Arrange
+    let app = spawn_app().await;
+    insert_subscribers(&app).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let csv = app
+        .get_subscribers_export(Some("confirmed"))
+        .await
+        .text()
+        .await
+        .unwrap();
+
+    // Assert
+    let rows: Vec<_> = csv.lines().skip(1).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|row| row.contains(",confirmed,")));
+
+    app.cleanup_test_db().await.unwrap();
+}
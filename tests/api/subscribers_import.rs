@@ -0,0 +1,141 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+async fn subscriber_statuses(app: &TestApp) -> Vec<(String, String)> {
+    sqlx::query!("SELECT email, status FROM subscriptions ORDER BY email")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.email, r.status))
+        .collect()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_import_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_subscribers_import("name,email\nursula,ursula@example.com\n")
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    assert!(subscriber_statuses(&app).await.is_empty());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_well_formed_csv_is_imported_as_confirmed_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_subscribers_import(
+            "name,email\nursula,ursula@example.com\n\"le guin, ursula\",le_guin@example.com\n",
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "imported": 2,
+            "skipped_duplicates": 0,
+            "validation_errors": [],
+        })
+    );
+    assert_eq!(
+        subscriber_statuses(&app).await,
+        vec![
+            ("le_guin@example.com".to_string(), "confirmed".to_string()),
+            ("ursula@example.com".to_string(), "confirmed".to_string()),
+        ]
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn duplicate_emails_are_skipped() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_subscribers_import("name,email\nursula,ursula@example.com\n")
+        .await;
+
+    // Act
+    let response = app
+        .post_subscribers_import(
+            "name,email\nursula,ursula@example.com\nnew one,new@example.com\nnew again,new@example.com\n",
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["skipped_duplicates"], 2);
+    assert_eq!(report["validation_errors"], serde_json::json!([]));
+    assert_eq!(subscriber_statuses(&app).await.len(), 2);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn rows_with_invalid_emails_are_reported_and_the_rest_imported() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_subscribers_import(
+            "name,email\nursula,ursula@example.com\nbroken,not-an-email\nempty,\n",
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["skipped_duplicates"], 0);
+    let errors = report["validation_errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0]["row"], 3);
+    assert!(errors[0]["reason"]
+        .as_str()
+        .unwrap()
+        .contains("not-an-email"));
+    assert_eq!(errors[1]["row"], 4);
+    assert_eq!(
+        subscriber_statuses(&app).await,
+        vec![("ursula@example.com".to_string(), "confirmed".to_string())]
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_csv_without_the_expected_columns_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_subscribers_import("full_name,address\nursula,ursula@example.com\n")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(subscriber_statuses(&app).await.is_empty());
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,180 @@
+use uuid::Uuid;
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+/// Five subscribers, `a` joined first and `e` last, `c` never confirmed.
+async fn insert_subscribers(app: &TestApp) {
+    app.insert_subscriber("a", "a@example.com", "confirmed", "2026-01-01 00:00:00 UTC")
+        .await;
+    app.insert_subscriber("b", "b@example.com", "confirmed", "2026-01-02 00:00:00 UTC")
+        .await;
+    app.insert_subscriber(
+        "c",
+        "c@example.com",
+        "pending_confirmation",
+        "2026-01-03 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber("d", "d@example.com", "confirmed", "2026-01-04 00:00:00 UTC")
+        .await;
+    app.insert_subscriber(
+        "e",
+        "e@example.com",
+        "unsubscribed",
+        "2026-01-05 00:00:00 UTC",
+    )
+    .await;
+}
+
+/// The names on a page and the cursor to the next one.
+async fn get_page(app: &TestApp, query: &[(&str, &str)]) -> (Vec<String>, Option<String>) {
+    let response = app.get_admin_subscribers(query).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let page: serde_json::Value = response.json().await.unwrap();
+    let names = page["subscribers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect();
+    let next_cursor = page["next_cursor"].as_str().map(str::to_string);
+    (names, next_cursor)
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_list_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_subscribers(&[]).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribers_are_listed_newest_first_by_default() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+
+    // Act
+    let response = app.get_admin_subscribers(&[]).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let page: serde_json::Value = response.json().await.unwrap();
+    let first = &page["subscribers"][0];
+    assert_eq!(first["name"], "e");
+    assert_eq!(first["email"], "e@example.com");
+    assert_eq!(first["status"], "unsubscribed");
+    assert_eq!(first["subscribed_at"], "2026-01-05 00:00:00 UTC");
+    assert!(first["uuid"].is_string());
+    assert_eq!(page["subscribers"].as_array().unwrap().len(), 5);
+    assert!(page["next_cursor"].is_null());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn following_the_cursor_walks_through_every_subscriber_once() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+
+    // Act
+    let (first, cursor) = get_page(&app, &[("limit", "2")]).await;
+    let cursor = cursor.unwrap();
+    let (second, cursor) = get_page(&app, &[("limit", "2"), ("after", &cursor)]).await;
+    let cursor = cursor.unwrap();
+    let (third, cursor) = get_page(&app, &[("limit", "2"), ("after", &cursor)]).await;
+
+    // Assert
+    assert_eq!(first, vec!["e", "d"]);
+    assert_eq!(second, vec!["c", "b"]);
+    assert_eq!(third, vec!["a"]);
+    assert!(cursor.is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn pages_follow_the_requested_sort_and_order() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+    let query = [("sort", "email"), ("order", "asc"), ("limit", "3")];
+
+    // Act
+    let (first, cursor) = get_page(&app, &query).await;
+    let cursor = cursor.unwrap();
+    let mut next_query = query.to_vec();
+    next_query.push(("after", &cursor));
+    let (second, cursor) = get_page(&app, &next_query).await;
+
+    // Assert
+    assert_eq!(first, vec!["a", "b", "c"]);
+    assert_eq!(second, vec!["d", "e"]);
+    assert!(cursor.is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn pages_only_contain_subscribers_with_the_requested_status() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+
+    // Act
+    let (first, cursor) = get_page(&app, &[("status", "confirmed"), ("limit", "2")]).await;
+    let cursor = cursor.unwrap();
+    let (second, cursor) = get_page(
+        &app,
+        &[("status", "confirmed"), ("limit", "2"), ("after", &cursor)],
+    )
+    .await;
+
+    // Assert
+    assert_eq!(first, vec!["d", "b"]);
+    assert_eq!(second, vec!["a"]);
+    assert!(cursor.is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_parameters_are_rejected_with_a_400() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+    let unknown_cursor = Uuid::new_v4().to_string();
+
+    for (query, expected_message) in [
+        (vec![("sort", "status")], "not a sortable field"),
+        (vec![("order", "sideways")], "not a sort order"),
+        (vec![("status", "banned")], "not a subscription status"),
+        (vec![("limit", "0")], "`limit` must be between"),
+        (
+            vec![("after", unknown_cursor.as_str())],
+            "does not match any subscriber",
+        ),
+    ] {
+        // Act
+        let response = app.get_admin_subscribers(&query).await;
+
+        // Assert
+        assert_eq!(response.status().as_u16(), 400, "{:?}", query);
+        assert!(response.text().await.unwrap().contains(expected_message));
+    }
+
+    app.cleanup_test_db().await.unwrap();
+}
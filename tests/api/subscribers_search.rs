@@ -0,0 +1,197 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+async fn insert_subscribers(app: &TestApp) {
+    app.insert_subscriber(
+        "Abdelrahman Omar",
+        "abood@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "Mohamed Salah",
+        "mo@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "Ahmed Hassan",
+        "ahmed@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "Youssef Kamal",
+        "youssef@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+    app.insert_subscriber(
+        "Karim Adel",
+        "karim@example.org",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+}
+
+/// The names of the subscribers found for `q`.
+async fn search(app: &TestApp, q: &str) -> Vec<String> {
+    let response = app.get_subscribers_search(&[("q", q)]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let subscribers: serde_json::Value = response.json().await.unwrap();
+    subscribers
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn you_must_be_an_admin_to_search_subscribers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_subscribers_search(&[("q", "abood")]).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_unique_substring_finds_exactly_one_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+
+    // Act
+    let names = search(&app, "ssef").await;
+
+    // Assert
+    assert_eq!(names, vec!["Youssef Kamal"]);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn emails_are_searched_too() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+
+    // Act
+    let names = search(&app, "example.org").await;
+
+    // Assert
+    assert_eq!(names, vec!["Karim Adel"]);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn deleted_subscribers_are_no_longer_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+    let uuid = app
+        .insert_subscriber(
+            "Leaving Soon",
+            "leaving@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    assert_eq!(search(&app, "Leaving").await, vec!["Leaving Soon"]);
+
+    // Act
+    let response = app.delete_subscriber(&uuid).await;
+    assert!(response.status().is_success());
+
+    // Assert
+    assert!(search(&app, "Leaving").await.is_empty());
+    let indexed = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!: i64" FROM subscriptions_fts WHERE uuid = $1"#,
+        uuid
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(indexed, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn renamed_subscribers_are_found_by_their_new_name() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let uuid = app
+        .insert_subscriber(
+            "Old Name",
+            "renamed@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+
+    // Act
+    sqlx::query!(
+        "UPDATE subscriptions SET name = 'Brand New' WHERE uuid = $1",
+        uuid
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Assert
+    assert_eq!(search(&app, "Brand").await, vec!["Brand New"]);
+    assert!(search(&app, "Old Name").await.is_empty());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn short_or_invalid_searches_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act & Assert
+    for query in [
+        vec![("q", "ab")],
+        vec![("q", "abood"), ("limit", "0")],
+        vec![("q", "abood"), ("limit", "101")],
+    ] {
+        let response = app.get_subscribers_search(&query).await;
+        assert_eq!(response.status().as_u16(), 400, "{:?}", query);
+    }
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn query_syntax_is_searched_as_plain_text() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    insert_subscribers(&app).await;
+
+    // Act
+    let names = search(&app, "mo OR ahmed*").await;
+
+    // Assert
+    assert!(names.is_empty());
+
+    app.cleanup_test_db().await.unwrap();
+}
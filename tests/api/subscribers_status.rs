@@ -0,0 +1,148 @@
+use uuid::Uuid;
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+async fn stored_status(app: &TestApp) -> String {
+    sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn an_admin_can_unsubscribe_a_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+    let uuid = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .patch_subscriber_status(&uuid, &serde_json::json!({ "status": "unsubscribed" }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let subscriber: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(subscriber["uuid"], uuid.as_str());
+    assert_eq!(subscriber["email"], "ursula@example.com");
+    assert_eq!(subscriber["status"], "unsubscribed");
+    assert_eq!(stored_status(&app).await, "unsubscribed");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invalid_status_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    let uuid = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .patch_subscriber_status(&uuid, &serde_json::json!({ "status": "banned" }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(stored_status(&app).await, "confirmed");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unknown_subscriber_is_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .patch_subscriber_status(
+            &Uuid::new_v4().to_string(),
+            &serde_json::json!({ "status": "confirmed" }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn confirming_an_unsubscribed_subscriber_must_be_forced() {
+    // Arrange
+    let app = spawn_app().await;
+    let uuid = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "unsubscribed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+    app.test_user.login(&app).await;
+
+    // Act - Part 1 - Without force
+    let response = app
+        .patch_subscriber_status(&uuid, &serde_json::json!({ "status": "confirmed" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+    assert_eq!(stored_status(&app).await, "unsubscribed");
+
+    // Act - Part 2 - Forced
+    let response = app
+        .patch_subscriber_status(
+            &uuid,
+            &serde_json::json!({ "status": "confirmed", "force": true }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(stored_status(&app).await, "confirmed");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_change_a_subscriber_status() {
+    // Arrange
+    let app = spawn_app().await;
+    let uuid = app
+        .insert_subscriber(
+            "ursula",
+            "ursula@example.com",
+            "confirmed",
+            "2026-01-01 00:00:00 UTC",
+        )
+        .await;
+
+    // Act
+    let response = app
+        .patch_subscriber_status(&uuid, &serde_json::json!({ "status": "unsubscribed" }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    assert_eq!(stored_status(&app).await, "confirmed");
+
+    app.cleanup_test_db().await.unwrap();
+}
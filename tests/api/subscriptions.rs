@@ -4,7 +4,12 @@ use wiremock::{
     Mock, ResponseTemplate,
 };
 
-use crate::helpers::{spawn_app, FormData};
+use newzletter::domain::NewSubscriber;
+use newzletter::routes::{
+    cleanup_stale_pending_subscriptions, insert_subscriber, store_token, SubscriptionSource,
+};
+
+use crate::helpers::{spawn_app, spawn_app_with, FormData, TestApp};
 
 #[tokio::test]
 async fn subscribe_returns_a_303_for_valid_form_data() {
@@ -178,9 +183,14 @@ async fn subscribe_returns_a_400_when_fields_are_present_but_invalid() {
             "The API did not return a 303 redirect when the payload was {}.",
             description
         );
-        assert_eq!(
-            response.headers().get("Location").unwrap(),
-            "/?error=validation",
+        let location = response
+            .headers()
+            .get("Location")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(
+            location.starts_with("/?error=validation&field="),
             "The API did not redirect to /?error=validation when the payload was {}.",
             description
         );
@@ -189,6 +199,114 @@ async fn subscribe_returns_a_400_when_fields_are_present_but_invalid() {
     app.cleanup_test_db().await.unwrap();
 }
 
+/// The field and the reason of a validation error redirect.
+fn validation_error(response: &reqwest::Response) -> (String, String) {
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let location = response
+        .headers()
+        .get("Location")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let url = reqwest::Url::parse("http://localhost")
+        .unwrap()
+        .join(location)
+        .unwrap();
+    let param = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+            .unwrap()
+    };
+    assert_eq!(param("error"), "validation");
+    (param("field"), param("reason"))
+}
+
+#[tokio::test]
+async fn subscribe_explains_why_a_name_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    let test_cases = [
+        ("", "The name can't be empty."),
+        ("   ", "The name can't be empty."),
+        ("a", "The name must be at least 2 characters long."),
+        (" b ", "The name must be at least 2 characters long."),
+    ];
+
+    for (name, expected_reason) in test_cases {
+        // Act
+        let response = app
+            .post_subscriptions(&FormData {
+                name: Some(name.to_string()),
+                email: Some("ursula_le_guin@gmail.com".to_string()),
+                cf_turnstile_response: Some("test-token".to_string()),
+            })
+            .await;
+
+        // Assert
+        let (field, reason) = validation_error(&response);
+        assert_eq!(field, "name", "Wrong field for the name {:?}.", name);
+        assert_eq!(
+            reason, expected_reason,
+            "Wrong reason for the name {:?}.",
+            name
+        );
+    }
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_rejects_names_with_forbidden_characters() {
+    // Arrange
+    let app = spawn_app().await;
+
+    for forbidden in ['<', '>', '"', '\'', ';', '(', ')', '{', '}', '\\', '/'] {
+        // Act
+        let response = app
+            .post_subscriptions(&FormData {
+                name: Some(format!("ursula {} le guin", forbidden)),
+                email: Some("ursula_le_guin@gmail.com".to_string()),
+                cf_turnstile_response: Some("test-token".to_string()),
+            })
+            .await;
+
+        // Assert
+        let (field, reason) = validation_error(&response);
+        assert_eq!(field, "name", "Wrong field for {:?}.", forbidden);
+        assert!(
+            reason.starts_with("The name can't contain"),
+            "Wrong reason for {:?}: {}",
+            forbidden,
+            reason
+        );
+    }
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_explains_why_an_email_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_subscriptions(&FormData {
+            name: Some("ursula".to_string()),
+            email: Some("definitely-not-an-email".to_string()),
+            cf_turnstile_response: Some("test-token".to_string()),
+        })
+        .await;
+
+    // Assert
+    let (field, reason) = validation_error(&response);
+    assert_eq!(field, "email");
+    assert!(reason.contains("is not a valid subscriber email"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
 #[tokio::test]
 async fn subscribe_sends_a_confirmation_email_for_valid_data() {
     // Arrange
@@ -249,3 +367,468 @@ async fn subscribe_sends_a_confirmation_email_with_a_link() {
 
     app.cleanup_test_db().await.unwrap();
 }
+
+#[tokio::test]
+async fn turnstile_is_not_called_in_test_mode() {
+    // Arrange
+    let app = spawn_app().await;
+    let fake_user_form_data = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("".to_string()),
+    };
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    Mock::given(path("/turnstile/v0/siteverify"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.turnstile_server)
+        .await;
+
+    // Act
+    let response = app.post_subscriptions(&fake_user_form_data).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()["Location"], "/?subscribed=true");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_is_rejected_when_the_turnstile_token_is_invalid() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.turnstile_test_mode = false).await;
+    let fake_user_form_data = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("a-bot-token".to_string()),
+    };
+
+    Mock::given(path("/turnstile/v0/siteverify"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": false,
+            "error-codes": ["invalid-input-response"]
+        })))
+        .expect(1)
+        .mount(&app.turnstile_server)
+        .await;
+    Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app.post_subscriptions(&fake_user_form_data).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()["Location"], "/?error=captcha");
+    let saved = sqlx::query!("SELECT email FROM subscriptions")
+        .fetch_optional(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(saved.is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscriptions_filling_in_the_honeypot_pretend_to_succeed() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.turnstile_test_mode = false).await;
+    Mock::given(path("/turnstile/v0/siteverify"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.turnstile_server)
+        .await;
+    Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_subscriptions_with_query(
+            &[],
+            &serde_json::json!({
+                "name": "abood",
+                "email": "3la_el_7doood@yahoo.com",
+                "cf-turnstile-response": "a-bot-token",
+                "website": "https://spam.example.com",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()["Location"], "/?subscribed=true");
+    let saved = sqlx::query!("SELECT email FROM subscriptions")
+        .fetch_optional(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(saved.is_none());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_empty_honeypot_does_not_stop_the_subscription() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_subscriptions_with_query(
+            &[],
+            &serde_json::json!({
+                "name": "abood",
+                "email": "3la_el_7doood@yahoo.com",
+                "cf-turnstile-response": "test-token",
+                "website": "",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.headers()["Location"], "/?subscribed=true");
+    let saved = sqlx::query!("SELECT email FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.email, "3la_el_7doood@yahoo.com");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_persists_the_subscription_source() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_subscriptions_with_query(
+            &[
+                ("utm_source", "twitter"),
+                ("utm_medium", "social"),
+                ("utm_campaign", "launch"),
+            ],
+            &serde_json::json!({
+                "name": "abood",
+                "email": "3la_el_7doood@yahoo.com",
+                "cf-turnstile-response": "test-token",
+                "source_url": "https://example.com/blog/astro-rust/",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let saved =
+        sqlx::query!("SELECT source_url, utm_source, utm_medium, utm_campaign FROM subscriptions")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        saved.source_url.as_deref(),
+        Some("https://example.com/blog/astro-rust/")
+    );
+    assert_eq!(saved.utm_source.as_deref(), Some("twitter"));
+    assert_eq!(saved.utm_medium.as_deref(), Some("social"));
+    assert_eq!(saved.utm_campaign.as_deref(), Some("launch"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscription_source_form_fields_win_over_query_parameters() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    app.post_subscriptions_with_query(
+        &[("utm_source", "query"), ("utm_medium", "email")],
+        &serde_json::json!({
+            "name": "abood",
+            "email": "3la_el_7doood@yahoo.com",
+            "cf-turnstile-response": "test-token",
+            "source_url": "  ",
+            "utm_source": "form",
+        }),
+    )
+    .await;
+
+    // Assert
+    let saved =
+        sqlx::query!("SELECT source_url, utm_source, utm_medium, utm_campaign FROM subscriptions")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(saved.source_url, None);
+    assert_eq!(saved.utm_source.as_deref(), Some("form"));
+    assert_eq!(saved.utm_medium.as_deref(), Some("email"));
+    assert_eq!(saved.utm_campaign, None);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_subscription_source_is_listed_to_admins() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    app.post_subscriptions_with_query(
+        &[("utm_campaign", "launch")],
+        &serde_json::json!({
+            "name": "abood",
+            "email": "3la_el_7doood@yahoo.com",
+            "cf-turnstile-response": "test-token",
+        }),
+    )
+    .await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_admin_subscribers(&[]).await;
+
+    // Assert
+    let page: serde_json::Value = response.json().await.unwrap();
+    let subscriber = &page["subscribers"][0];
+    assert_eq!(subscriber["utm_campaign"], "launch");
+    assert!(subscriber["utm_source"].is_null());
+    assert!(subscriber["source_url"].is_null());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_returns_a_415_for_a_json_body() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{"name": "abood", "email": "3la_el_7doood@yahoo.com"}"#;
+
+    // Act
+    let response = app.post_subscriptions_raw(body, "application/json").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_returns_a_413_for_a_body_over_the_limit() {
+    // Arrange
+    let app = spawn_app_with(|c| c.application.max_request_body_bytes = 1024).await;
+    let body = format!(
+        "name={}&email=3la_el_7doood%40yahoo.com&cf-turnstile-response=test-token",
+        "a".repeat(2048)
+    );
+
+    // Act
+    let response = app
+        .post_subscriptions_raw(&body, "application/x-www-form-urlencoded")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_returns_a_422_for_an_empty_body() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_subscriptions_raw("", "application/x-www-form-urlencoded")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+async fn post_subscriptions_accepting_json(app: &TestApp, body: &FormData) -> reqwest::Response {
+    app.api_client
+        .post(&format!("{}/subscriptions", &app.address))
+        .header("Accept", "application/json")
+        .header("X-CSRF-Token", app.csrf_token().await)
+        .form(body)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn json_clients_get_validation_errors_as_a_400() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = FormData {
+        name: Some("hamada".to_string()),
+        email: Some("definitely-not-(blitzcrank)-an-email".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+
+    // Act
+    let response = post_subscriptions_accepting_json(&app, &body).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(error["error"], "validation");
+    assert_eq!(error["field"], "email");
+    assert!(!error["message"].as_str().unwrap().is_empty());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn json_clients_get_unexpected_errors_as_a_500() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+    sqlx::query!("ALTER TABLE subscriptions RENAME TO broken_subscriptions;")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = post_subscriptions_accepting_json(&app, &body).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let error: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(error, serde_json::json!({ "error": "server" }));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_stored_subscriber_is_returned_by_the_insert() {
+    // Arrange
+    let app = spawn_app().await;
+    let new_subscriber = NewSubscriber::builder()
+        .name("abood")
+        .email("3la_el_7doood@yahoo.com")
+        .build()
+        .unwrap();
+    let mut transaction = app.db_pool.begin().await.unwrap();
+
+    // Act
+    let stored = insert_subscriber(
+        &mut transaction,
+        &new_subscriber,
+        &SubscriptionSource::default(),
+    )
+    .await
+    .unwrap();
+    let token = store_token(&mut transaction, stored.uuid, "a-confirmation-token")
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    // Assert
+    let saved = sqlx::query!("SELECT uuid, name, email, subscribed_at FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.uuid, stored.uuid.to_string());
+    assert_eq!(saved.name, stored.name);
+    assert_eq!(saved.email, stored.email);
+    assert_eq!(saved.subscribed_at, stored.subscribed_at);
+    let saved_token =
+        sqlx::query!("SELECT subscription_token, subscriber_id FROM subscription_tokens")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(saved_token.subscription_token, token);
+    assert_eq!(token, "a-confirmation-token");
+    assert_eq!(saved_token.subscriber_id, stored.uuid.to_string());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn stale_pending_subscriptions_are_cleaned_up() {
+    // Arrange
+    let app = spawn_app().await;
+    let now = chrono::Utc::now().to_string();
+    let subscribers = [
+        ("stale", "pending_confirmation", "2000-01-01 00:00:00 UTC"),
+        ("confirmed", "confirmed", "2000-01-01 00:00:00 UTC"),
+        ("fresh", "pending_confirmation", now.as_str()),
+    ];
+    for (name, status, subscribed_at) in subscribers {
+        let email = format!("{name}@example.com");
+        let token = format!("{name}-token");
+        let uuid = app
+            .insert_subscriber(name, &email, status, subscribed_at)
+            .await;
+        sqlx::query!(
+            "INSERT INTO subscription_tokens (subscription_token, subscriber_id, token_expires_at)
+            VALUES ($1, $2, '2000-01-02 00:00:00 UTC')",
+            token,
+            uuid
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+
+    // Act
+    let n_deleted = cleanup_stale_pending_subscriptions(&app.db_pool, 7)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(n_deleted, 1);
+    let remaining = sqlx::query_scalar!("SELECT name FROM subscriptions ORDER BY name")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, ["confirmed", "fresh"]);
+    let tokens =
+        sqlx::query_scalar!("SELECT subscription_token FROM subscription_tokens ORDER BY 1")
+            .fetch_all(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(tokens, ["confirmed-token", "fresh-token"]);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use crate::helpers::{spawn_app, spawn_app_with, TestApp};
+
+async fn check_email(app: &TestApp, email: &str) -> serde_json::Value {
+    let response = app.get_check_email(email).await;
+    assert_eq!(response.status().as_u16(), 200);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn a_confirmed_subscriber_is_not_available() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_subscriber(
+        "abood",
+        "confirmed@example.com",
+        "confirmed",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+
+    // Act
+    let body = check_email(&app, "confirmed@example.com").await;
+
+    // Assert
+    assert_eq!(
+        body,
+        serde_json::json!({ "available": false, "status": "confirmed" })
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_pending_subscriber_is_not_available() {
+    // Arrange
+    let app = spawn_app().await;
+    app.insert_subscriber(
+        "abood",
+        "pending@example.com",
+        "pending_confirmation",
+        "2026-01-01 00:00:00 UTC",
+    )
+    .await;
+
+    // Act
+    let body = check_email(&app, "pending@example.com").await;
+
+    // Assert
+    assert_eq!(
+        body,
+        serde_json::json!({ "available": false, "status": "pending_confirmation" })
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unknown_email_is_available() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let started = Instant::now();
+    let body = check_email(&app, "nobody@example.com").await;
+
+    // Assert
+    assert_eq!(body, serde_json::json!({ "available": true }));
+    // slowed down on purpose, against enumeration
+    assert!(started.elapsed() >= Duration::from_millis(500));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invalid_email_looks_like_an_available_one() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act & Assert
+    for email in ["not-an-email", "", "@example.com"] {
+        let body = check_email(&app, email).await;
+        assert_eq!(body, serde_json::json!({ "available": true }), "{}", email);
+    }
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn checking_too_many_emails_is_rejected_with_a_429() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.rate_limit.check_email.capacity = 2;
+        c.rate_limit.check_email.refill_per_minute = 2;
+    })
+    .await;
+    for _ in 0..2 {
+        check_email(&app, "nobody@example.com").await;
+    }
+
+    // Act
+    let response = app.get_check_email("nobody@example.com").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+
+    app.cleanup_test_db().await.unwrap();
+}
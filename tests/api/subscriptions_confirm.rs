@@ -4,7 +4,7 @@ use wiremock::{
     Mock, ResponseTemplate,
 };
 
-use crate::helpers::{spawn_app, FormData};
+use crate::helpers::{assert_is_redirect_to, spawn_app, FormData, TestApp};
 
 #[tokio::test]
 async fn confirmations_without_token_are_rejected_with_a_400() {
@@ -50,7 +50,7 @@ async fn the_link_returned_by_subscribe_returns_a_200_if_called() {
 }
 
 #[tokio::test]
-async fn clicking_on_the_confirmation_link_confirms_a_subscriber() {
+async fn opening_the_confirmation_link_only_shows_the_confirmation_form() {
     // Arrange
     let app = spawn_app().await;
     let body = FormData {
@@ -68,15 +68,63 @@ async fn clicking_on_the_confirmation_link_confirms_a_subscriber() {
     app.post_subscriptions(&body).await;
     let email_request = &app.email_server.received_requests().await.unwrap()[0];
     let confirmation_links = app.get_confirmation_links(email_request);
+    let subscription_token = confirmation_links
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .unwrap()
+        .1
+        .into_owned();
 
     // Act
-    reqwest::get(confirmation_links.html)
+    let html_page = reqwest::get(confirmation_links.html)
         .await
         .unwrap()
-        .error_for_status()
+        .text()
+        .await
         .unwrap();
 
     // Assert
+    assert!(html_page.contains("Confirm my subscription"));
+    assert!(html_page.contains(r#"action="/subscriptions/confirm""#));
+    assert!(html_page.contains(&format!(r#"value="{}""#, subscription_token)));
+    // a link scanner opening the link must not subscribe anyone
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "pending_confirmation");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn clicking_on_the_confirmation_link_confirms_a_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(&body).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+
+    // Act
+    let response = app.confirm_subscription(confirmation_links.html).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains("Email Confirmed!"));
+
     let saved = sqlx::query!("SELECT uuid, email, name, status FROM subscriptions")
         .fetch_one(&app.db_pool)
         .await
@@ -88,3 +136,190 @@ async fn clicking_on_the_confirmation_link_confirms_a_subscriber() {
 
     app.cleanup_test_db().await.unwrap();
 }
+
+#[tokio::test]
+async fn confirming_with_an_unknown_token_is_rejected_with_a_401() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.post_subscriptions_confirm("not-a-real-token").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn confirming_without_a_token_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .api_client
+        .post(&format!("{}/subscriptions/confirm", app.address))
+        .form(&[("name", "abood")])
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(response.status().is_client_error());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+async fn subscribe_and_expire_the_confirmation_link(app: &TestApp) -> reqwest::Url {
+    let body = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(&body).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+
+    sqlx::query!("UPDATE subscription_tokens SET token_expires_at = '2000-01-01 00:00:00 UTC'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    confirmation_links.html
+}
+
+#[tokio::test]
+async fn expired_confirmation_links_are_rejected_with_a_410() {
+    // Arrange
+    let app = spawn_app().await;
+    let confirmation_link = subscribe_and_expire_the_confirmation_link(&app).await;
+
+    // Act
+    let response = reqwest::get(confirmation_link).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::GONE);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("/subscriptions/resend-confirmation"));
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "pending_confirmation");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn confirming_with_an_expired_token_is_rejected_with_a_410() {
+    // Arrange
+    let app = spawn_app().await;
+    let confirmation_link = subscribe_and_expire_the_confirmation_link(&app).await;
+    let subscription_token = confirmation_link
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .unwrap()
+        .1
+        .into_owned();
+
+    // Act
+    let response = app.post_subscriptions_confirm(&subscription_token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::GONE);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "pending_confirmation");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_resent_confirmation_link_confirms_the_subscriber() {
+    // Arrange
+    let app = spawn_app().await;
+    let expired_link = subscribe_and_expire_the_confirmation_link(&app).await;
+
+    // Act - Part 1 - Ask for a new link
+    let response = app
+        .post_resend_confirmation("3la_el_7doood@yahoo.com")
+        .await;
+    assert_is_redirect_to(&response, "/?subscribed=true");
+
+    // Act - Part 2 - Follow the new link
+    let email_requests = app.email_server.received_requests().await.unwrap();
+    assert_eq!(email_requests.len(), 2);
+    let new_link = app.get_confirmation_links(&email_requests[1]).html;
+    assert_ne!(new_link, expired_link);
+    let response = app.confirm_subscription(new_link).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "confirmed");
+    // the old token has been replaced
+    let response = reqwest::get(expired_link).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn resending_to_an_unknown_address_does_not_send_an_email() {
+    // Arrange
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app.post_resend_confirmation("nobody@example.com").await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/?subscribed=true");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn resending_the_confirmation_is_rate_limited_per_email_address() {
+    // Arrange
+    let app = spawn_app().await;
+    subscribe_and_expire_the_confirmation_link(&app).await;
+
+    // Act
+    for _ in 0..3 {
+        let response = app
+            .post_resend_confirmation("3la_el_7doood@yahoo.com")
+            .await;
+        assert_is_redirect_to(&response, "/?subscribed=true");
+    }
+    let response = app
+        .post_resend_confirmation("3la_el_7doood@yahoo.com")
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    // the initial email plus three resends
+    assert_eq!(app.email_server.received_requests().await.unwrap().len(), 4);
+
+    app.cleanup_test_db().await.unwrap();
+}
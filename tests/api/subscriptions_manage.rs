@@ -0,0 +1,163 @@
+use reqwest::StatusCode;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, FormData, TestApp};
+
+/// Subscribe and return the subscription token sent in the confirmation
+/// email, the subscriber is left pending.
+async fn subscribe_and_get_token(app: &TestApp) -> String {
+    let body = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(&body).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    app.get_confirmation_links(email_request)
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn the_manage_page_shows_the_subscriber_details() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = subscribe_and_get_token(&app).await;
+
+    // Act
+    let response = app.get_manage_subscription(&token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("abood"));
+    assert!(html_page.contains("3la_el_7doood@yahoo.com"));
+    assert!(html_page.contains("Pending confirmation"));
+    assert!(html_page.contains("/subscriptions/unsubscribe?token="));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_tokens_are_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_manage_subscription("not-a-token").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unsubscribed_subscribers_have_nothing_to_manage() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = subscribe_and_get_token(&app).await;
+    sqlx::query("UPDATE subscriptions SET status = 'unsubscribed'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = app.get_manage_subscription(&token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribers_can_change_their_name() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = subscribe_and_get_token(&app).await;
+
+    // Act
+    let response = app
+        .post_manage_subscription(&serde_json::json!({
+            "token": &token,
+            "name": "Abdelrahman",
+        }))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, &format!("/subscriptions/manage?token={}", token));
+    let html_page = app
+        .get_manage_subscription(&token)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("Your name has been updated."));
+    let saved = sqlx::query!("SELECT name FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.name, "Abdelrahman");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_names_are_reported_on_the_page() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = subscribe_and_get_token(&app).await;
+
+    // Act
+    let response = app
+        .post_manage_subscription(&serde_json::json!({
+            "token": &token,
+            "name": "a",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("The name must be at least 2 characters long."));
+    let saved = sqlx::query!("SELECT name FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.name, "abood");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn names_cannot_be_changed_with_an_unknown_token() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_manage_subscription(&serde_json::json!({
+            "token": "not-a-token",
+            "name": "Abdelrahman",
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,105 @@
+use reqwest::StatusCode;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{spawn_app, FormData, TestApp};
+
+/// Subscribe and return the subscription token sent in the confirmation email.
+async fn subscribe_and_get_token(app: &TestApp) -> String {
+    let body = FormData {
+        name: Some("abood".to_string()),
+        email: Some("3la_el_7doood@yahoo.com".to_string()),
+        cf_turnstile_response: Some("test-token".to_string()),
+    };
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.post_subscriptions(&body).await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+    app.confirm_subscription(confirmation_links.html.clone())
+        .await
+        .error_for_status()
+        .unwrap();
+
+    confirmation_links
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn the_status_page_shows_the_subscriber_details() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = subscribe_and_get_token(&app).await;
+
+    // Act
+    let response = app.get_subscription_status(&token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let html_page = response.text().await.unwrap();
+    assert!(html_page.contains("abood"));
+    assert!(html_page.contains("3la_el_7doood@yahoo.com"));
+    assert!(html_page.contains("Confirmed"));
+    assert!(html_page.contains("/subscriptions/unsubscribe?token="));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_status_page_returns_a_404_for_an_unknown_token() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_subscription_status("not-a-real-token").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_unsubscribe_form_on_the_status_page_unsubscribes() {
+    // Arrange
+    let app = spawn_app().await;
+    let token = subscribe_and_get_token(&app).await;
+    let html_page = app
+        .get_subscription_status(&token)
+        .await
+        .text()
+        .await
+        .unwrap();
+    let unsubscribe_token = html_page
+        .split("/subscriptions/unsubscribe?token=")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap()
+        .to_string();
+
+    // Act
+    let response = app.post_unsubscribe_one_click(&unsubscribe_token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let html_page = app
+        .get_subscription_status(&token)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("Unsubscribed"));
+
+    app.cleanup_test_db().await.unwrap();
+}
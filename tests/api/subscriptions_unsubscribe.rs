@@ -0,0 +1,226 @@
+use newzletter::domain::generate_unsubscribe_token;
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn stored_reasons(app: &TestApp) -> Vec<(Option<String>, Option<String>)> {
+    sqlx::query!("SELECT reason, other_text FROM unsubscribe_events")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| (event.reason, event.other_text))
+        .collect()
+}
+
+#[tokio::test]
+async fn the_unsubscribe_link_shows_the_survey_without_unsubscribing() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    let response = app.get_unsubscribe(&token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = response.text().await.unwrap();
+    assert!(html.contains(&format!(
+        "action=\"/subscriptions/unsubscribe?token={}\"",
+        token
+    )));
+    assert!(html.contains("name=\"reason\""));
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "confirmed");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unsubscribing_twice_with_the_same_token_is_a_no_op() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    let first_response = app.post_unsubscribe_one_click(&token).await;
+    let second_response = app.post_unsubscribe_one_click(&token).await;
+
+    // Assert
+    assert_eq!(first_response.status(), StatusCode::OK);
+    assert_eq!(second_response.status(), StatusCode::OK);
+    // only the first one actually unsubscribed
+    assert_eq!(stored_reasons(&app).await, vec![(None, None)]);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unsubscribing_with_a_tampered_token_is_rejected_with_a_400() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+    let (_, rest) = token.split_once('.').unwrap();
+    let tampered_token = format!("{}.{}", Uuid::new_v4(), rest);
+
+    // Act
+    let response = app.get_unsubscribe(&tampered_token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "confirmed");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unsubscribing_without_a_token_is_rejected_with_a_400() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = reqwest::get(&format!("{}/subscriptions/unsubscribe", app.address))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn one_click_unsubscribe_marks_the_subscriber_as_unsubscribed() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    let response = app.post_unsubscribe_one_click(&token).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "unsubscribed");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_survey_reason_is_stored_when_unsubscribing() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    let response = app
+        .post_unsubscribe_survey(&token, &[("reason", "too_frequent")])
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        stored_reasons(&app).await,
+        vec![(Some("too_frequent".to_string()), None)]
+    );
+    let event = sqlx::query!("SELECT subscriber_uuid FROM unsubscribe_events")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(event.subscriber_uuid, subscriber_id.to_string());
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_other_reason_keeps_its_explanation() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    app.post_unsubscribe_survey(
+        &token,
+        &[
+            ("reason", "other"),
+            ("other_text", "  Moving to a cabin.  "),
+        ],
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        stored_reasons(&app).await,
+        vec![(
+            Some("other".to_string()),
+            Some("Moving to a cabin.".to_string())
+        )]
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn a_blank_survey_still_unsubscribes() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    let response = app
+        .post_unsubscribe_survey(&token, &[("other_text", "")])
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "unsubscribed");
+    assert_eq!(stored_reasons(&app).await, vec![(None, None)]);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_unknown_reason_still_unsubscribes() {
+    // Arrange
+    let app = spawn_app().await;
+    let subscriber_id = Uuid::parse_str(&app.create_confirmed_subscriber().await).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+
+    // Act
+    let response = app
+        .post_unsubscribe_survey(&token, &[("reason", "bored")])
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.status, "unsubscribed");
+    assert_eq!(stored_reasons(&app).await, vec![(None, None)]);
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,47 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_the_system_page() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_system().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_system_page_shows_the_runtime_configuration() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_admin_system().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains("SQLite"));
+    assert!(html.contains(env!("CARGO_PKG_VERSION")));
+    assert!(html.contains("local"));
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_system_page_does_not_leak_secrets() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let html = app.get_admin_system().await.text().await.unwrap();
+
+    // Assert
+    assert!(!html.contains("redis://"));
+    assert!(!html.contains(".db"));
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,207 @@
+use uuid::Uuid;
+use wiremock::matchers::{any, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app_with, TestApp};
+
+async fn spawn_app_with_tags() -> TestApp {
+    spawn_app_with(|c| c.application.newsletter_tags = vec!["rust".into(), "web".into()]).await
+}
+
+async fn insert_confirmed_subscriber(app: &TestApp, email: &str, tags: &[&str]) -> String {
+    let uuid = app
+        .insert_subscriber("ursula", email, "confirmed", "2026-01-01 00:00:00 UTC")
+        .await;
+    for tag in tags {
+        sqlx::query!(
+            "INSERT INTO subscriber_tags (subscriber_uuid, tag) VALUES ($1, $2)",
+            uuid,
+            tag
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+    uuid
+}
+
+async fn queued_emails(app: &TestApp) -> Vec<String> {
+    let mut emails = sqlx::query_scalar!("SELECT subscriber_email FROM issue_delivery_queue")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    emails.sort();
+    emails
+}
+
+fn newsletter(tags: Option<&str>) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "title": "Newsletter title",
+        "text_content": "Newsletter body as plain text",
+        "html_content": "<p>Newsletter body as HTML</p>",
+        "idempotency_key": Uuid::new_v4().to_string(),
+    });
+    if let Some(tags) = tags {
+        body["tags"] = tags.into();
+    }
+    body
+}
+
+#[tokio::test]
+async fn tagged_issues_only_go_to_subscribers_with_a_matching_tag() {
+    // Arrange
+    let app = spawn_app_with_tags().await;
+    insert_confirmed_subscriber(&app, "rustacean@example.com", &["rust"]).await;
+    insert_confirmed_subscriber(&app, "both@example.com", &["rust", "web"]).await;
+    insert_confirmed_subscriber(&app, "webdev@example.com", &["web"]).await;
+    insert_confirmed_subscriber(&app, "untagged@example.com", &[]).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.post_publish_newsletter(&newsletter(Some("rust"))).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    assert_eq!(
+        queued_emails(&app).await,
+        vec!["both@example.com", "rustacean@example.com"]
+    );
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn untagged_issues_go_to_every_confirmed_subscriber() {
+    // Arrange
+    let app = spawn_app_with_tags().await;
+    insert_confirmed_subscriber(&app, "rustacean@example.com", &["rust"]).await;
+    insert_confirmed_subscriber(&app, "untagged@example.com", &[]).await;
+    app.test_user.login(&app).await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app.post_publish_newsletter(&newsletter(None)).await;
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    // the mock checks that both subscribers got the issue
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_tags_are_rejected() {
+    // Arrange
+    let app = spawn_app_with_tags().await;
+    insert_confirmed_subscriber(&app, "rustacean@example.com", &["rust"]).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.post_publish_newsletter(&newsletter(Some("rsut"))).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(queued_emails(&app).await.is_empty());
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_tags_are_listed_with_their_subscriber_counts() {
+    // Arrange
+    let app = spawn_app_with_tags().await;
+    insert_confirmed_subscriber(&app, "rustacean@example.com", &["rust"]).await;
+    insert_confirmed_subscriber(&app, "both@example.com", &["rust", "web"]).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_tags().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let tags: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        tags,
+        serde_json::json!([
+            { "name": "rust", "subscribers": 2 },
+            { "name": "web", "subscribers": 1 },
+        ])
+    );
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribers_can_pick_tags_when_signing_up() {
+    // Arrange
+    let app = spawn_app_with_tags().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    assert!(app
+        .get_home_html()
+        .await
+        .contains(r#"name="tags" value="web""#));
+
+    // Act
+    let response = app
+        .post_subscriptions_raw(
+            "name=ursula&email=ursula%40example.com&cf-turnstile-response=token&tags=rust&tags=web",
+            "application/x-www-form-urlencoded",
+        )
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/?subscribed=true");
+    let tags = sqlx::query_scalar!("SELECT tag FROM subscriber_tags ORDER BY tag")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(tags, vec!["rust", "web"]);
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribers_can_change_their_tags() {
+    // Arrange
+    let app = spawn_app_with_tags().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    app.post_subscriptions_raw(
+        "name=ursula&email=ursula%40example.com&cf-turnstile-response=token&tags=rust",
+        "application/x-www-form-urlencoded",
+    )
+    .await;
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let token = app
+        .get_confirmation_links(email_request)
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "subscription_token")
+        .map(|(_, value)| value.into_owned())
+        .unwrap();
+
+    // Act
+    let response = app
+        .post_manage_subscription_tags(&format!("token={}&tags=web", token))
+        .await;
+
+    // Assert
+    assert_is_redirect_to(&response, &format!("/subscriptions/manage?token={}", token));
+    let html_page = app
+        .get_manage_subscription(&token)
+        .await
+        .text()
+        .await
+        .unwrap();
+    assert!(html_page.contains("Your topics have been updated."));
+    assert!(html_page.contains(r#"value="web" class="checkbox" checked"#));
+    assert!(!html_page.contains(r#"value="rust" class="checkbox" checked"#));
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,273 @@
+use std::sync::{Arc, Mutex};
+
+use newzletter::{
+    idempotency::{get_saved_response, IdempotencyKey},
+    issue_delivery_worker::try_execute_task,
+    telemetry::{get_otel_subscriber, get_subscriber},
+};
+use tracing::Instrument;
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::helpers::spawn_app;
+
+fn emit_a_span(subscriber: impl tracing::Subscriber + Send + Sync) {
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info_span!("test_span").in_scope(|| tracing::info!("Hello from the span"));
+    });
+}
+
+#[tokio::test]
+async fn the_subscriber_works_without_an_otlp_endpoint() {
+    let (subscriber, _, _) =
+        get_otel_subscriber("test".into(), "info".into(), std::io::sink, None).unwrap();
+
+    emit_a_span(subscriber);
+}
+
+#[tokio::test]
+async fn the_subscriber_works_with_an_otlp_endpoint() {
+    // nothing listens there, the exporter only connects once it has spans to send
+    let (subscriber, _, _) = get_otel_subscriber(
+        "test".into(),
+        "info".into(),
+        std::io::sink,
+        Some("http://127.0.0.1:4317"),
+    )
+    .unwrap();
+
+    emit_a_span(subscriber);
+}
+
+/// Collects what the subscriber writes, in place of stdout.
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test]
+async fn database_calls_record_their_latency() {
+    // Arrange
+    let app = spawn_app().await;
+    // the subscriber `TEST_LOG` turns on, writing to a buffer instead of stdout
+    let logs = CapturedLogs::default();
+    let (subscriber, _) = get_subscriber("test".into(), "info".into(), logs.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // Act
+    get_saved_response(
+        &app.db_pool,
+        &IdempotencyKey::try_from(uuid::Uuid::new_v4().to_string()).unwrap(),
+        app.test_user.uuid,
+    )
+    .await
+    .unwrap();
+    try_execute_task(
+        &app.db_pool,
+        &app.email_client,
+        &app.base_url,
+        &app.hmac_secret,
+        app.max_retries,
+        app.email_send_timeout,
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let spans: Vec<serde_json::Value> = logs
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .filter(|line: &serde_json::Value| line["msg"].as_str().unwrap().ends_with("- END]"))
+        .collect();
+    let span = |name: &str| {
+        spans
+            .iter()
+            .find(|span| span["msg"].as_str().unwrap().contains(name))
+            .unwrap_or_else(|| panic!("The `{}` span was not closed in:\n{}", name, logs))
+    };
+    assert!(span("GET_SAVED_RESPONSE")["db_query_duration_ms"].is_u64());
+    let dequeue_task = span("DEQUEUE_TASK");
+    assert!(dequeue_task["db_query_duration_ms"].is_u64());
+    // the queue is empty
+    assert_eq!(dequeue_task["db_rows_affected"], 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn queries_on_the_instrumented_pool_record_their_duration() {
+    // Arrange
+    let app = spawn_app().await;
+    let logs = CapturedLogs::default();
+    let (subscriber, _) = get_subscriber("test".into(), "newzletter=debug".into(), logs.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // Act
+    async {
+        sqlx::query("SELECT username FROM users")
+            .fetch_all(&app.db_pool)
+            .await
+    }
+    .instrument(tracing::info_span!(
+        target: "newzletter",
+        "list_users",
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_affected = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty,
+    ))
+    .await
+    .unwrap();
+
+    // Assert
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = logs
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let span = lines
+        .iter()
+        .find(|line| line["msg"] == "[LIST_USERS - END]")
+        .unwrap_or_else(|| panic!("The span was not closed in:\n{}", logs));
+    assert!(span["db_query_duration_ms"].is_u64());
+    // only the test user
+    assert_eq!(span["db_rows_returned"], 1);
+    assert_eq!(span["db_rows_affected"], 0);
+    assert!(lines.iter().any(
+        |line| line["msg"] == "[LIST_USERS - EVENT] Ran a database query"
+            && line["db_query_duration_ms"].is_u64()
+    ));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn queries_in_a_transaction_record_their_duration() {
+    // Arrange
+    let app = spawn_app().await;
+    let logs = CapturedLogs::default();
+    let (subscriber, _) = get_subscriber("test".into(), "newzletter=debug".into(), logs.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+    let username = app.test_user.username.clone();
+
+    // Act
+    async {
+        let mut transaction = app.db_pool.begin().await?;
+        sqlx::query("UPDATE users SET username = username WHERE username = $1")
+            .bind(&username)
+            .execute(&mut transaction)
+            .await?;
+        transaction.commit().await
+    }
+    .instrument(tracing::info_span!(
+        target: "newzletter",
+        "touch_user",
+        db_query_duration_ms = tracing::field::Empty,
+        db_rows_affected = tracing::field::Empty,
+        db_rows_returned = tracing::field::Empty,
+    ))
+    .await
+    .unwrap();
+
+    // Assert
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let span = logs
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|line| line["msg"] == "[TOUCH_USER - END]")
+        .unwrap_or_else(|| panic!("The span was not closed in:\n{}", logs));
+    assert!(span["db_query_duration_ms"].is_u64());
+    assert_eq!(span["db_rows_affected"], 1);
+    assert_eq!(span["db_rows_returned"], 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn requests_are_logged_with_their_user_agent() {
+    // Arrange
+    let app = spawn_app().await;
+    let logs = CapturedLogs::default();
+    let (subscriber, _) = get_subscriber("test".into(), "newzletter=debug".into(), logs.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // Act
+    app.api_client
+        .get(&format!("{}/health_check", &app.address))
+        .header("User-Agent", "newzletter-monitor/1.0")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = logs
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let incoming = lines
+        .iter()
+        .find(|line| line["msg"] == "[HTTP_REQUEST - EVENT] Incoming request")
+        .unwrap_or_else(|| panic!("The request was not logged in:\n{}", logs));
+    assert_eq!(incoming["user_agent"], "newzletter-monitor/1.0");
+    assert_eq!(incoming["uri"], "/health_check");
+    let span = lines
+        .iter()
+        .find(|line| line["msg"] == "[HTTP_REQUEST - END]")
+        .unwrap_or_else(|| panic!("The request span was not closed in:\n{}", logs));
+    assert_eq!(span["user_agent"], "newzletter-monitor/1.0");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[test]
+fn the_log_filter_can_be_changed_at_runtime() {
+    // Arrange
+    let logs = CapturedLogs::default();
+    let (subscriber, log_filter) = get_subscriber("test".into(), "info".into(), logs.clone());
+
+    // Act
+    tracing::subscriber::with_default(subscriber, || {
+        log_filter.set("newzletter=debug").unwrap();
+        tracing::debug!(target: "newzletter", "Debug events are logged");
+    });
+
+    // Assert
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(logs.contains("Debug events are logged"));
+}
+
+#[test]
+fn changing_a_log_filter_leaves_the_other_subscribers_alone() {
+    // Arrange
+    let logs = CapturedLogs::default();
+    let (subscriber, _) = get_subscriber("test".into(), "info".into(), logs.clone());
+    let (_other_subscriber, other_log_filter) =
+        get_subscriber("other".into(), "info".into(), std::io::sink);
+
+    // Act
+    tracing::subscriber::with_default(subscriber, || {
+        other_log_filter.set("newzletter=debug").unwrap();
+        tracing::debug!(target: "newzletter", "Debug events are logged");
+    });
+
+    // Assert
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(!logs.contains("Debug events are logged"));
+}
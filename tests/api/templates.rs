@@ -0,0 +1,174 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+fn branded_template() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Branded",
+        "header_html": "<header>Newzletter</header>",
+        "footer_html": "<footer><a href=\"{UNSUBSCRIBE_URL}\">Leave</a></footer>",
+    })
+}
+
+async fn create_template(app: &TestApp) -> String {
+    let response = app.post_template(&branded_template()).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let template: serde_json::Value = response.json().await.unwrap();
+    template["uuid"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_manage_templates() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.post_template(&branded_template()).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn templates_can_be_created_updated_and_deleted() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act - Part 1 - Create
+    let template_id = create_template(&app).await;
+
+    // Assert - Part 1
+    let templates = app.get_templates().await;
+    assert_eq!(templates.as_array().unwrap().len(), 1);
+    assert_eq!(templates[0]["name"], "Branded");
+
+    // Act - Part 2 - Update
+    let response = app
+        .put_template(
+            &template_id,
+            &serde_json::json!({
+                "name": "Plain",
+                "header_html": "<header>Plain</header>",
+            }),
+        )
+        .await;
+
+    // Assert - Part 2
+    assert_eq!(response.status().as_u16(), 200);
+    let templates = app.get_templates().await;
+    assert_eq!(templates[0]["name"], "Plain");
+    assert_eq!(templates[0]["footer_html"], "");
+
+    // Act - Part 3 - Delete
+    let response = app.delete_template(&template_id).await;
+
+    // Assert - Part 3
+    assert_eq!(response.status().as_u16(), 204);
+    assert_eq!(app.get_templates().await, serde_json::json!([]));
+    let response = app.delete_template(&template_id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn template_names_are_unique() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    create_template(&app).await;
+
+    // Act
+    let response = app.post_template(&branded_template()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_selected_template_wraps_the_html_body() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let template_id = create_template(&app).await;
+    app.insert_subscriber("reader", "reader@example.com", "confirmed", "now")
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "template_uuid": template_id,
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/newsletters");
+    app.dispatch_all_pending_emails().await;
+
+    // Assert
+    let saved = sqlx::query!("SELECT text_content, html_content FROM newsletter_issues")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        saved.html_content,
+        "<header>Newzletter</header><p>Newsletter body as HTML</p>\
+        <footer><a href=\"{UNSUBSCRIBE_URL}\">Leave</a></footer>"
+    );
+    assert_eq!(saved.text_content, "Newsletter body as plain text");
+    let email_request = app
+        .email_server
+        .received_requests()
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let html_body = body["HtmlBody"].as_str().unwrap();
+    assert!(!html_body.contains("{UNSUBSCRIBE_URL}"));
+    assert!(html_body.contains(&format!(
+        "<footer><a href=\"{}/subscriptions/unsubscribe?token=",
+        app.base_url
+    )));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn publishing_with_an_unknown_template_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app
+        .post_publish_newsletter(&serde_json::json!({
+            "title": "Newsletter title",
+            "text_content": "Newsletter body as plain text",
+            "html_content": "<p>Newsletter body as HTML</p>",
+            "idempotency_key": uuid::Uuid::new_v4().to_string(),
+            "template_uuid": uuid::Uuid::new_v4().to_string(),
+        }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    let issues = sqlx::query!("SELECT title FROM newsletter_issues")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(issues.is_empty());
+    app.cleanup_test_db().await.unwrap();
+}
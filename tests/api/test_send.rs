@@ -0,0 +1,133 @@
+use wiremock::{
+    matchers::{any, method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[tokio::test]
+async fn a_test_copy_is_sent_to_the_logged_in_user() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = app
+        .publish_issue("Newsletter title", "<p>Newsletter body as HTML</p>")
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app.post_test_send(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["sent_to"], app.test_user.email);
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let email: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!(email["To"], app.test_user.email);
+    assert_eq!(email["Subject"], "[TEST] Newsletter title");
+    assert!(email["HtmlBody"]
+        .as_str()
+        .unwrap()
+        .contains("<p>Newsletter body as HTML</p>"));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sends_leave_the_delivery_tables_alone() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = app
+        .publish_issue("Newsletter title", "<p>Newsletter body as HTML</p>")
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    app.post_test_send(&issue_id).await;
+
+    // Assert
+    let queued = sqlx::query_scalar!("SELECT COUNT(*) FROM issue_delivery_queue")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    let delivered = sqlx::query_scalar!("SELECT COUNT(*) FROM newsletter_deliveries")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(queued, 0);
+    assert_eq!(delivered, 0);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn each_issue_can_only_be_test_sent_ten_times() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let issue_id = app
+        .publish_issue("Newsletter title", "<p>Newsletter body as HTML</p>")
+        .await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(10)
+        .mount(&app.email_server)
+        .await;
+    for _ in 0..10 {
+        assert_eq!(app.post_test_send(&issue_id).await.status().as_u16(), 200);
+    }
+
+    // Act
+    let response = app.post_test_send(&issue_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sends_of_an_unknown_issue_are_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    // Act
+    let response = app.post_test_send(&uuid::Uuid::new_v4().to_string()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_send_a_test_copy() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.post_test_send(&uuid::Uuid::new_v4().to_string()).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
@@ -0,0 +1,183 @@
+use totp_rs::TOTP;
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+
+async fn log_in(app: &TestApp) -> reqwest::Response {
+    app.post_login(&serde_json::json!({
+        "username": &app.test_user.username,
+        "password": &app.test_user.password
+    }))
+    .await
+}
+
+/// Enrolls the logged in test user, returning what their authenticator app
+/// would generate the codes with.
+async fn enroll(app: &TestApp) -> TOTP {
+    let response = app.post_enable_two_factor().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let enrollment: serde_json::Value = response.json().await.unwrap();
+    let totp = TOTP::from_url(enrollment["otpauth_url"].as_str().unwrap()).unwrap();
+    assert_eq!(totp.get_secret_base32(), enrollment["secret"]);
+
+    let response = app
+        .post_verify_two_factor(&serde_json::json!({
+            "code": totp.generate_current().unwrap()
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    totp
+}
+
+#[tokio::test]
+async fn enabling_returns_an_otpauth_url_for_the_authenticator_app() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.post_enable_two_factor().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let enrollment: serde_json::Value = response.json().await.unwrap();
+    let otpauth_url = enrollment["otpauth_url"].as_str().unwrap();
+    assert!(otpauth_url.starts_with("otpauth://totp/Newzletter:"));
+    assert!(otpauth_url.contains(&format!(
+        "secret={}",
+        enrollment["secret"].as_str().unwrap()
+    )));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_secret_is_stored_encrypted() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let enrollment: serde_json::Value = app.post_enable_two_factor().await.json().await.unwrap();
+
+    // Assert
+    let user_id = app.test_user.uuid.to_string();
+    let stored = sqlx::query!("SELECT totp_secret FROM users WHERE uuid = $1", user_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    let stored = stored.totp_secret.unwrap();
+    assert!(!stored.contains(enrollment["secret"].as_str().unwrap()));
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn logins_skip_the_second_factor_until_a_code_has_been_verified() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_enable_two_factor().await;
+    app.post_logout().await;
+
+    // Act
+    let response = log_in(&app).await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/admin/dashboard");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn an_invalid_code_does_not_enable_two_factor_authentication() {
+    // Arrange
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    app.post_enable_two_factor().await;
+
+    // Act
+    let response = app
+        .post_admin_form_as_json("/2fa/verify", &serde_json::json!({ "code": "000000" }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    app.post_logout().await;
+    assert_is_redirect_to(&log_in(&app).await, "/admin/dashboard");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn two_factor_authentication_can_be_enrolled_used_at_login_and_disabled() {
+    // Arrange - enroll
+    let app = spawn_app().await;
+    app.test_user.login(&app).await;
+    let totp = enroll(&app).await;
+    app.post_logout().await;
+
+    // Act - Part 1 - the password alone doesn't log in
+    let response = log_in(&app).await;
+    assert_is_redirect_to(&response, "/login/2fa");
+    assert_is_redirect_to(&app.get_admin_dashboard().await, "/login");
+
+    // Act - Part 2 - neither does a wrong code
+    let response = app.get_login_two_factor().await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("Two-factor authentication"));
+    let response = app
+        .post_login_two_factor(&serde_json::json!({ "code": "000000" }))
+        .await;
+    assert_is_redirect_to(&response, "/login/2fa");
+    let html_page = app.get_login_two_factor().await.text().await.unwrap();
+    assert!(html_page.contains("<p><i>The code is invalid or has expired.</i></p>"));
+    assert_is_redirect_to(&app.get_admin_dashboard().await, "/login");
+
+    // Act - Part 3 - the right code does
+    let response = app
+        .post_login_two_factor(&serde_json::json!({
+            "code": totp.generate_current().unwrap()
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    assert_eq!(app.get_admin_dashboard().await.status().as_u16(), 200);
+
+    // Act - Part 4 - disabling asks for the password
+    let response = app
+        .post_admin_form_as_json(
+            "/2fa/disable",
+            &serde_json::json!({ "current_password": "wrong-password" }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app
+        .post_disable_two_factor(&serde_json::json!({
+            "current_password": &app.test_user.password
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+
+    // Assert - the password is enough again
+    app.post_logout().await;
+    assert_is_redirect_to(&log_in(&app).await, "/admin/dashboard");
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn the_second_factor_form_is_only_shown_after_the_password() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_login_two_factor().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}
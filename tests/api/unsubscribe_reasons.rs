@@ -0,0 +1,76 @@
+use newzletter::domain::generate_unsubscribe_token;
+use uuid::Uuid;
+
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp, TestUser};
+
+async fn unsubscribe(app: &TestApp, name: &str, survey: &[(&str, &str)]) {
+    let email = format!("{}@example.com", name);
+    let subscriber_id = app
+        .insert_subscriber(name, &email, "confirmed", "2026-01-01 00:00:00 UTC")
+        .await;
+    let subscriber_id = Uuid::parse_str(&subscriber_id).unwrap();
+    let token = generate_unsubscribe_token(subscriber_id, &app.hmac_secret);
+    let response = app.post_unsubscribe_survey(&token, survey).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn unsubscribe_reasons_are_counted() {
+    // Arrange
+    let app = spawn_app().await;
+    unsubscribe(&app, "ursula", &[("reason", "too_frequent")]).await;
+    unsubscribe(&app, "viktor", &[("reason", "too_frequent")]).await;
+    unsubscribe(&app, "wanda", &[("reason", "other"), ("other_text", "Meh")]).await;
+    unsubscribe(&app, "xavier", &[]).await;
+    app.test_user.login(&app).await;
+
+    // Act
+    let response = app.get_unsubscribe_reasons().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let counts: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        counts,
+        serde_json::json!({
+            "too_frequent": 2,
+            "not_relevant": 0,
+            "never_subscribed": 0,
+            "other": 1,
+            "unspecified": 1,
+        })
+    );
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn editors_cannot_see_unsubscribe_reasons() {
+    // Arrange
+    let app = spawn_app().await;
+    let editor = TestUser::generate_editor();
+    editor.store(&app.db_pool).await;
+    editor.login(&app).await;
+
+    // Act
+    let response = app.get_unsubscribe_reasons().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.cleanup_test_db().await.unwrap();
+}
+
+#[tokio::test]
+async fn you_must_be_logged_in_to_see_unsubscribe_reasons() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_unsubscribe_reasons().await;
+
+    // Assert
+    assert_is_redirect_to(&response, "/login");
+
+    app.cleanup_test_db().await.unwrap();
+}